@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use pulldown_cmark::{CowStr, Event, Parser, Tag};
+
+/// Accessible names of the emoji the built-in output uses. Variation-selector forms come first
+/// so they are matched whole.
+const EMOJI_NAMES: [(&str, &str); 26] = [
+    ("⚠️", "warning"),
+    ("♻️", "recycling"),
+    ("⚠", "warning"),
+    ("♻", "recycling"),
+    ("📅", "calendar"),
+    ("📊", "bar chart"),
+    ("🎯", "target"),
+    ("✅", "check mark"),
+    ("📝", "memo"),
+    ("👥", "people"),
+    ("🚆", "train"),
+    ("🚦", "traffic light"),
+    ("📈", "increasing chart"),
+    ("✨", "sparkles"),
+    ("🐛", "bug"),
+    ("📚", "books"),
+    ("⚡", "lightning"),
+    ("📦", "package"),
+    ("👷", "construction worker"),
+    ("🔧", "wrench"),
+    ("💄", "lipstick"),
+    ("💥", "collision"),
+    ("🟢", "green circle"),
+    ("🟡", "yellow circle"),
+    ("🔴", "red circle"),
+    ("🚀", "rocket"),
+];
+
+/// Converts the notes' Markdown to accessible HTML, possibly in several pieces: every heading
+/// below the page title gets an id and opens a `<section>` labelled by it, which stays open
+/// until the next heading of the same or a higher level, and emoji get accessible names.
+/// Component headings get `component-<name>` ids, so a table of contents can link to them.
+pub struct AccessibleHtml {
+    components: Vec<String>,
+    used_ids: HashMap<String, usize>,
+    /// Levels of the sections currently open, outermost first
+    open_sections: Vec<usize>,
+}
+
+impl AccessibleHtml {
+    pub fn new(components: &[String]) -> Self {
+        Self {
+            components: components.to_vec(),
+            used_ids: HashMap::new(),
+            open_sections: Vec::new(),
+        }
+    }
+
+    /// Id of the heading of component `repository`, where it is first rendered.
+    pub fn component_id(repository: &str) -> String {
+        format!("component-{}", slug(repository))
+    }
+
+    /// Convert `markdown` and append it to `html`.
+    pub fn push(&mut self, html: &mut String, markdown: &str) {
+        let mut events = Vec::new();
+        let mut heading: Option<(usize, Vec<Event>)> = None;
+
+        for event in Parser::new(markdown) {
+            match event {
+                Event::Start(Tag::Heading(level, ..)) => heading = Some((level as usize, Vec::new())),
+                Event::End(Tag::Heading(..)) => {
+                    if let Some((level, inner)) = heading.take() {
+                        self.push_heading(&mut events, level, inner);
+                    }
+                }
+                Event::Text(text) => {
+                    let target = match &mut heading {
+                        Some((_, inner)) => inner,
+                        None => &mut events,
+                    };
+                    push_text(target, text);
+                }
+                event => match &mut heading {
+                    Some((_, inner)) => inner.push(event),
+                    None => events.push(event),
+                },
+            }
+        }
+
+        pulldown_cmark::html::push_html(html, events.into_iter());
+    }
+
+    /// Close the sections still open; call after the last piece.
+    pub fn finish(&mut self, html: &mut String) {
+        for _ in self.open_sections.drain(..) {
+            html.push_str("</section>\n");
+        }
+    }
+
+    fn push_heading<'a>(&mut self, events: &mut Vec<Event<'a>>, level: usize, inner: Vec<Event<'a>>) {
+        let text: String = inner.iter()
+            .filter_map(|event| match event {
+                Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+                _ => None,
+            })
+            .collect();
+        let id = self.heading_id(&text);
+
+        // The h1 is the page title; everything below it is sectioned
+        if level > 1 {
+            while self.open_sections.last().is_some_and(|&open| open >= level) {
+                self.open_sections.pop();
+                events.push(Event::Html("</section>\n".into()));
+            }
+            events.push(Event::Html(format!("<section aria-labelledby=\"{}\">\n", id).into()));
+            self.open_sections.push(level);
+        }
+        events.push(Event::Html(format!("<h{} id=\"{}\">", level, id).into()));
+        events.extend(inner);
+        events.push(Event::Html(format!("</h{}>\n", level).into()));
+    }
+
+    /// A component's own id the first time its heading appears, otherwise an id from the
+    /// heading text, numbered when it repeats.
+    fn heading_id(&mut self, text: &str) -> String {
+        let name = text.trim();
+        let name = name.split_once(" (renamed from").map_or(name, |(name, _)| name.trim());
+        let base = if self.components.iter().any(|component| component == name) {
+            Self::component_id(name)
+        } else {
+            match slug(text) {
+                slug if slug.is_empty() => "section".to_string(),
+                slug => slug,
+            }
+        };
+
+        let count = self.used_ids.entry(base.clone()).or_insert(0);
+        *count += 1;
+        match *count {
+            1 => base,
+            n => format!("{}-{}", base, n),
+        }
+    }
+}
+
+/// Push `text`, with each known emoji wrapped in an image role carrying its name.
+fn push_text<'a>(events: &mut Vec<Event<'a>>, text: CowStr<'a>) {
+    let mut rest: &str = &text;
+    let mut plain = String::new();
+    let mut wrapped = false;
+    while let Some(c) = rest.chars().next() {
+        match EMOJI_NAMES.iter().find(|(emoji, _)| rest.starts_with(emoji)) {
+            Some((emoji, name)) => {
+                if !plain.is_empty() {
+                    events.push(Event::Text(std::mem::take(&mut plain).into()));
+                }
+                events.push(Event::Html(format!("<span role=\"img\" aria-label=\"{}\">{}</span>", name, emoji).into()));
+                rest = &rest[emoji.len()..];
+                wrapped = true;
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !wrapped {
+        events.push(Event::Text(text));
+    } else if !plain.is_empty() {
+        events.push(Event::Text(plain.into()));
+    }
+}
+
+/// Lowercase letters and digits of `text`, other runs replaced by single dashes.
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
+use crate::aggregator::branding::Branding;
+use crate::aggregator::changelog_generator::{ChangelogGenerator, OutputFormat};
+use crate::aggregator::entry_format::EntryFormats;
+use crate::aggregator::locale::Locale;
+use crate::aggregator::release_fetcher::{AggregatedRelease, AggregatorConfig};
+use crate::aggregator::session::Session;
+use crate::config::types::{Config, RepoSettings};
+use crate::github::client::GitHubClient;
+use crate::github::types::loose_version;
+use crate::provider::connection::Connection;
+use crate::provider::ReleaseProvider;
+
+/// Index of the documents written by `backfill`, newest version first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackfillIndex {
+    pub entries: Vec<BackfillEntry>,
+}
+
+/// One historical version and the document its notes were written to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillEntry {
+    pub version: String,
+    pub date: DateTime<Utc>,
+    /// File name of the version's document, relative to the index
+    pub file: String,
+    pub total_repos: usize,
+    pub updated_repos: usize,
+    pub total_commits: usize,
+}
+
+impl BackfillIndex {
+    pub fn push(&mut self, release: &AggregatedRelease, file: String) {
+        self.entries.push(BackfillEntry {
+            version: release.version.clone(),
+            date: release.date,
+            file,
+            total_repos: release.summary.total_repos,
+            updated_repos: release.summary.updated_repos,
+            total_commits: release.summary.total_commits,
+        });
+    }
+
+    /// Order the entries newest version first, the way a release-notes site lists them.
+    pub fn sort(&mut self) {
+        self.entries.sort_by(|a, b| {
+            loose_version(&b.version)
+                .cmp(&loose_version(&a.version))
+                .then_with(|| b.version.cmp(&a.version))
+        });
+    }
+}
+
+/// Versions with a release in any of `repos` whose tag matches the glob `pattern`, lowest
+/// first. Tags of repositories with a tag scheme are matched as the version they stand for.
+pub async fn matching_versions(
+    client: &impl ReleaseProvider,
+    repos: &[String],
+    repo_settings: &HashMap<String, RepoSettings>,
+    pattern: &str,
+) -> Result<Vec<String>> {
+    let glob = glob::Pattern::new(pattern).with_context(|| format!("Invalid --all-matching pattern {}", pattern))?;
+    let mut versions = BTreeSet::new();
+    for repo in repos {
+        let settings = repo_settings.get(repo);
+        for release in client.list_all_releases(repo).await? {
+            let version = match settings {
+                Some(settings) => settings.version_of(&release.tag_name),
+                None => Some(release.tag_name),
+            };
+            versions.extend(version.filter(|version| glob.matches(version)));
+        }
+    }
+    let mut versions: Vec<String> = versions.into_iter().collect();
+    versions.sort_by(|a, b| loose_version(a).cmp(&loose_version(b)).then_with(|| a.cmp(b)));
+    Ok(versions)
+}
+
+/// What `backfill` writes, with the command line's defaults already applied.
+pub struct BackfillOptions {
+    pub output: PathBuf,
+    /// Repositories to cover; the configured ones when empty
+    pub repos: Vec<String>,
+    /// Glob the versions to write must match
+    pub matching: String,
+    pub format: OutputFormat,
+    pub template: Option<PathBuf>,
+    pub locale: Option<Locale>,
+    pub branding: Branding,
+    pub entry_formats: EntryFormats,
+    /// Keep the documents an earlier run wrote instead of generating them again
+    pub skip_existing: bool,
+    pub include_prs: bool,
+    pub categorize: bool,
+    pub deterministic: bool,
+    pub generated_at: Option<DateTime<Utc>>,
+}
+
+/// Write a document for every released version matching `options.matching`, plus an index of
+/// them, into `options.output`.
+pub async fn run(
+    connection: &Connection,
+    github: GitHubClient,
+    file_config: Option<&Config>,
+    base: AggregatorConfig,
+    options: BackfillOptions,
+) -> Result<()> {
+    let BackfillOptions {
+        output, repos, matching, format, template, locale, branding, entry_formats, skip_existing, include_prs, categorize, deterministic, generated_at,
+    } = options;
+    let config = AggregatorConfig {
+        include_prs,
+        include_issues: false,
+        categorize_commits: categorize,
+        template_path: template.clone(),
+        ..base
+    };
+    let session = Session::open(connection, github, file_config, repos, config).await?;
+
+    let versions = session.matching_versions(&matching).await?;
+    if versions.is_empty() {
+        anyhow::bail!("No release tag matches {} in the selected repositories", matching);
+    }
+    std::fs::create_dir_all(&output).with_context(|| format!("Failed to create {}", output.display()))?;
+    let index_path = output.join("index.json");
+    let mut previous_index = if skip_existing && index_path.is_file() {
+        serde_json::from_str::<BackfillIndex>(&std::fs::read_to_string(&index_path)?)
+            .with_context(|| format!("Failed to parse {}", index_path.display()))?
+    } else {
+        BackfillIndex::default()
+    };
+    println!("Backfilling {} versions into {}", versions.len(), output.display());
+
+    let mut generator = ChangelogGenerator::new(format, template)?
+        .with_entry_formats(entry_formats)
+        .with_links(session.aggregator.client().web_links())
+        .with_branding(branding)?;
+    if let Some(locale) = locale {
+        generator = generator.with_locale(locale);
+    }
+
+    let mut index = BackfillIndex::default();
+    for version in versions {
+        let file = format!("{}.{}", version.replace('/', "-"), format.extension());
+        let path = output.join(&file);
+        if skip_existing && path.is_file() {
+            if let Some(pos) = previous_index.entries.iter().position(|entry| entry.version == version) {
+                index.entries.push(previous_index.entries.swap_remove(pos));
+                println!("- {}: already written", version);
+                continue;
+            }
+        }
+
+        let release = shipped_release(&session, &version, deterministic, generated_at).await?;
+        let file_handle = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = std::io::BufWriter::new(file_handle);
+        generator.write(&release, &mut writer)?;
+        writer.flush()?;
+        index.push(&release, file);
+        println!("✓ {}: written to {}", version, path.display());
+    }
+
+    index.sort();
+    std::fs::write(&index_path, serde_json::to_string_pretty(&index)?)
+        .with_context(|| format!("Failed to write {}", index_path.display()))?;
+    if !matches!(format, OutputFormat::Json) {
+        let page = output.join(format!("index.{}", format.extension()));
+        std::fs::write(&page, generator.generate_index(&index)?)
+            .with_context(|| format!("Failed to write {}", page.display()))?;
+    }
+    println!("Index written to {}", output.join(format!("index.{}", format.extension())).display());
+    Ok(())
+}
+
+/// Aggregate a historical `version`, dated by when it shipped rather than when it was
+/// backfilled unless `generated_at` pins the date.
+pub async fn shipped_release(
+    session: &Session,
+    version: &str,
+    deterministic: bool,
+    generated_at: Option<DateTime<Utc>>,
+) -> Result<AggregatedRelease> {
+    let mut release = session.aggregate(version).await?;
+    release.pin(deterministic, generated_at);
+    if generated_at.is_none() {
+        if let Some(date) = release.latest_release_date() {
+            release.date = date;
+        }
+    }
+    Ok(release)
+}
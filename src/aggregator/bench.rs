@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::provider::replay::{Recording, ReplayProvider};
+use super::changelog_generator::{ChangelogGenerator, OutputFormat};
+use super::release_fetcher::{AggregatorConfig, ReleaseAggregator};
+
+/// File the baseline is kept in, inside a fixtures directory.
+pub const BASELINE_FILE: &str = "baseline.json";
+
+/// Slowdowns smaller than this are noise on small fixtures, whatever the percentage.
+const MIN_SLOWDOWN: Duration = Duration::from_millis(1);
+
+/// What one `bench --replay` measured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub version: String,
+    pub components: usize,
+    pub commits: usize,
+    pub iterations: usize,
+    /// Median time to aggregate the train from the recording
+    #[serde(rename = "aggregate_ms", with = "millis")]
+    pub aggregate: Duration,
+    /// Median time to render the aggregated train as Markdown and HTML
+    #[serde(rename = "render_ms", with = "millis")]
+    pub render: Duration,
+    /// Provider calls one aggregation makes, by method
+    pub calls: BTreeMap<String, u64>,
+    pub measured_at: DateTime<Utc>,
+}
+
+/// Aggregate and render the recorded train `iterations` times, reporting median times and
+/// the calls each aggregation made.
+pub async fn run(recording: Arc<Recording>, config: AggregatorConfig, iterations: usize) -> Result<BenchResult> {
+    let iterations = iterations.max(1);
+    let mut aggregate = Vec::with_capacity(iterations);
+    let mut render = Vec::with_capacity(iterations);
+    let mut calls = BTreeMap::new();
+    let mut counts = (0, 0);
+
+    for _ in 0..iterations {
+        let aggregator = ReleaseAggregator::new(ReplayProvider::new(recording.clone()), config.clone());
+        let start = Instant::now();
+        let release = aggregator.aggregate(&recording.version, recording.repos.clone()).await?;
+        aggregate.push(start.elapsed());
+        calls = aggregator.client().calls();
+
+        let start = Instant::now();
+        for format in [OutputFormat::Markdown, OutputFormat::Html] {
+            ChangelogGenerator::new(format, None)?.write(&release, &mut std::io::sink())?;
+        }
+        render.push(start.elapsed());
+        counts = (release.components.len(), release.summary.total_commits);
+    }
+
+    Ok(BenchResult {
+        version: recording.version.clone(),
+        components: counts.0,
+        commits: counts.1,
+        iterations,
+        aggregate: median(aggregate),
+        render: median(render),
+        calls,
+        measured_at: Utc::now(),
+    })
+}
+
+fn median(mut durations: Vec<Duration>) -> Duration {
+    durations.sort();
+    durations[durations.len() / 2]
+}
+
+impl BenchResult {
+    pub fn api_calls(&self) -> u64 {
+        self.calls.values().sum()
+    }
+
+    /// The baseline saved in `dir`, if there is one.
+    pub fn load_baseline(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(BASELINE_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Some(serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?))
+    }
+
+    pub fn save_baseline(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(BASELINE_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Ways this run is worse than `baseline`: more provider calls than before, or a phase
+    /// more than `tolerance` percent slower.
+    pub fn regressions(&self, baseline: &BenchResult, tolerance: f64) -> Vec<String> {
+        let mut regressions = Vec::new();
+        if self.api_calls() > baseline.api_calls() {
+            regressions.push(format!("API calls rose from {} to {}", baseline.api_calls(), self.api_calls()));
+        }
+        for (phase, current, before) in [("aggregate", self.aggregate, baseline.aggregate), ("render", self.render, baseline.render)] {
+            let change = percent_change(before, current);
+            if change > tolerance && current.saturating_sub(before) >= MIN_SLOWDOWN {
+                regressions.push(format!("{} took {:.1}% longer (tolerance {}%)", phase, change, tolerance));
+            }
+        }
+        regressions
+    }
+
+    /// Times and call counts next to the baseline's, with each method's calls below.
+    pub fn to_table(&self, baseline: Option<&BenchResult>) -> String {
+        let mut output = format!(
+            "Benchmark {}: {} components, {} commits, median of {} runs\n\n",
+            self.version, self.components, self.commits, self.iterations
+        );
+        let ms = |d: Duration| format!("{:.1} ms", d.as_secs_f64() * 1000.0);
+        let mut rows = vec![["METRIC".to_string(), "BASELINE".to_string(), "CURRENT".to_string(), "CHANGE".to_string()]];
+        for (metric, current, before) in [
+            ("aggregate", self.aggregate, baseline.map(|b| b.aggregate)),
+            ("render", self.render, baseline.map(|b| b.render)),
+        ] {
+            rows.push([
+                metric.to_string(),
+                before.map(ms).unwrap_or_else(|| "-".to_string()),
+                ms(current),
+                before.map(|before| format!("{:+.1}%", percent_change(before, current))).unwrap_or_default(),
+            ]);
+        }
+        let calls = |result: &BenchResult, method: Option<&str>| match method {
+            Some(method) => result.calls.get(method).copied().unwrap_or(0),
+            None => result.api_calls(),
+        };
+        let methods = self.calls.keys()
+            .chain(baseline.iter().flat_map(|b| b.calls.keys()))
+            .collect::<std::collections::BTreeSet<_>>();
+        for method in std::iter::once(None).chain(methods.into_iter().map(|m| Some(m.as_str()))) {
+            let current = calls(self, method);
+            let before = baseline.map(|b| calls(b, method));
+            rows.push([
+                method.map(|m| format!("  {}", m)).unwrap_or_else(|| "api calls".to_string()),
+                before.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string()),
+                current.to_string(),
+                before.map(|b| format!("{:+}", current as i64 - b as i64)).unwrap_or_default(),
+            ]);
+        }
+
+        let mut widths = [0; 4];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for row in &rows {
+            let cells: Vec<String> = row.iter().zip(widths)
+                .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+                .collect();
+            output.push_str(cells.join("  ").trim_end());
+            output.push('\n');
+        }
+        if let Some(baseline) = baseline {
+            output.push_str(&format!("\nBaseline measured {}\n", baseline.measured_at.format("%Y-%m-%d %H:%M UTC")));
+        }
+        output
+    }
+}
+
+fn percent_change(before: Duration, current: Duration) -> f64 {
+    if before.is_zero() {
+        return 0.0;
+    }
+    (current.as_secs_f64() - before.as_secs_f64()) / before.as_secs_f64() * 100.0
+}
+
+/// Durations as fractional milliseconds, which read better in a baseline file than seconds and
+/// nanoseconds.
+mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let ms = f64::deserialize(deserializer)?;
+        Ok(Duration::from_secs_f64(ms.max(0.0) / 1000.0))
+    }
+}
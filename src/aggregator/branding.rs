@@ -0,0 +1,35 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Corporate styling for HTML output: extra CSS, a logo, and header/footer templates.
+#[derive(Debug, Clone, Default)]
+pub struct Branding {
+    /// Stylesheet appended after the built-in styles, so its rules win
+    pub css: Option<String>,
+    /// Image URL or path, used as-is for the logo's `src`
+    pub logo: Option<String>,
+    /// Handlebars source rendered above the notes, with the same data as the notes template
+    pub header: Option<String>,
+    /// Handlebars source rendered below the notes
+    pub footer: Option<String>,
+    /// Image shown in link previews (`og:image`); should be an absolute URL
+    pub social_image: Option<String>,
+    /// Site name shown in link previews (`og:site_name`)
+    pub site_name: Option<String>,
+}
+
+impl Branding {
+    pub fn load(css: Option<&Path>, logo: Option<String>, header: Option<&Path>, footer: Option<&Path>) -> Result<Self> {
+        Ok(Self {
+            css: css.map(read).transpose()?,
+            logo,
+            header: header.map(read).transpose()?,
+            footer: footer.map(read).transpose()?,
+            ..Default::default()
+        })
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read branding file {}", path.display()))
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crate::config::types::RepoSettings;
+use crate::github::client::GitHubClient;
+use crate::github::types::{CommitInfo, DateSource, PreviousStrategy};
+
+/// Replays the lookups a `generate` run makes so their responses land in the ETag store. A
+/// later run then gets `304 Not Modified` for everything that hasn't changed, which is fast
+/// and costs no rate limit.
+pub struct CacheWarmer<'a> {
+    client: &'a GitHubClient,
+    date_source: DateSource,
+    previous_strategy: PreviousStrategy,
+    include_prs: bool,
+}
+
+/// What was fetched for one repository.
+#[derive(Debug, Default)]
+pub struct WarmedRepository {
+    pub releases: usize,
+    pub commits: usize,
+    /// Commits on the default branch since the latest release, which the next release will ship
+    pub unreleased_commits: usize,
+}
+
+impl<'a> CacheWarmer<'a> {
+    pub fn new(client: &'a GitHubClient, date_source: DateSource, previous_strategy: PreviousStrategy, include_prs: bool) -> Self {
+        Self { client, date_source, previous_strategy, include_prs }
+    }
+
+    /// Fetch every release of `repo` dated at or after `since` with its commit range, plus the
+    /// commits waiting on the default branch. Ranges start at the previous release under the
+    /// repository's tag scheme, as aggregation will read them.
+    pub async fn warm(&self, repo: &str, settings: Option<&RepoSettings>, since: DateTime<Utc>) -> Result<WarmedRepository> {
+        let mut warmed = WarmedRepository::default();
+        let head = self.client.get_repository(repo).await?
+            .and_then(|info| info.default_branch)
+            .unwrap_or_else(|| "HEAD".to_string());
+
+        let recent: Vec<_> = self.client.list_all_releases(repo).await?
+            .into_iter()
+            .filter(|r| self.date_source.resolve(r.created_at, r.published_at).is_some_and(|date| date >= since))
+            .collect();
+        for listed in recent {
+            let Some(release) = self.client.get_release(repo, &listed.tag_name).await? else {
+                continue;
+            };
+            self.client.resolve_ref_sha(repo, &release.tag_name).await?;
+
+            let previous = self.client.get_previous_release(repo, &release, settings, self.date_source, self.previous_strategy).await?;
+            let commits = match &previous {
+                Some(previous) => self.client.get_commits_between(repo, &previous.tag_name, &release.tag_name).await?,
+                None => self.client.get_all_commits_until(repo, &release.tag_name).await?,
+            };
+            warmed.releases += 1;
+            warmed.commits += commits.len();
+            self.warm_pull_requests(repo, &commits).await?;
+        }
+
+        let commits = match self.client.get_latest_release(repo).await? {
+            Some(latest) => self.client.get_commits_between(repo, &latest.tag_name, &head).await?,
+            None => self.client.get_all_commits_until(repo, &head).await?,
+        };
+        warmed.unreleased_commits = commits.len();
+        self.warm_pull_requests(repo, &commits).await?;
+
+        Ok(warmed)
+    }
+
+    /// Only the per-commit REST fallback of the pull request lookup is cacheable; batched GraphQL
+    /// queries aren't, but they cost a request per 50 commits anyway.
+    async fn warm_pull_requests(&self, repo: &str, commits: &[CommitInfo]) -> Result<()> {
+        if self.include_prs {
+            let shas = commits.iter().map(|c| c.sha.clone()).collect();
+            self.client.get_pull_requests_for_commits(repo, shas).await?;
+        }
+        Ok(())
+    }
+}
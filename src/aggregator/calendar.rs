@@ -0,0 +1,146 @@
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use crate::aggregator::release_fetcher::AggregatedRelease;
+use crate::config::types::CalendarConfig;
+
+/// Calendar title when `calendar.name` isn't set.
+pub const DEFAULT_CALENDAR_NAME: &str = "Release trains";
+
+/// Longest content line allowed by RFC 5545, in octets, before it must be folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// One release train on the calendar, either shipped or planned.
+#[derive(Debug, Clone)]
+pub struct TrainEvent {
+    pub version: String,
+    pub date: NaiveDate,
+    /// Planned in `calendar.scheduled` rather than generated
+    pub scheduled: bool,
+    pub description: String,
+    /// Where the train's notes are published
+    pub url: Option<String>,
+}
+
+impl TrainEvent {
+    /// Identifier that stays the same when a scheduled train is generated, so subscribers see
+    /// the planned event turn into the shipped one instead of a duplicate.
+    pub fn uid(&self) -> String {
+        format!("train-{}@release-inator", self.version)
+    }
+
+    pub fn summary(&self) -> String {
+        if self.scheduled {
+            format!("Release {} (scheduled)", self.version)
+        } else {
+            format!("Release {}", self.version)
+        }
+    }
+}
+
+/// Events for the stored trains plus the scheduled ones not generated yet, oldest first.
+/// Sub-trains ship with their parent and get no event of their own.
+pub fn train_events(trains: &[AggregatedRelease], config: &CalendarConfig) -> Vec<TrainEvent> {
+    let notes_url = |version: &str| config.notes_url.as_ref().map(|url| url.replace("{version}", version));
+
+    let mut events: Vec<TrainEvent> = trains.iter()
+        .map(|train| TrainEvent {
+            version: train.version.clone(),
+            date: train.date.date_naive(),
+            scheduled: false,
+            description: format!(
+                "{} of {} components updated, {} commits from {} contributors",
+                train.summary.updated_repos,
+                train.summary.total_repos,
+                train.summary.total_commits,
+                train.summary.contributors.len(),
+            ),
+            url: notes_url(&train.version),
+        })
+        .collect();
+    for planned in &config.scheduled {
+        if events.iter().any(|event| event.version == planned.version) {
+            continue;
+        }
+        events.push(TrainEvent {
+            version: planned.version.clone(),
+            date: planned.date,
+            scheduled: true,
+            description: planned.description.clone().unwrap_or_else(|| "Scheduled release train".to_string()),
+            url: notes_url(&planned.version),
+        });
+    }
+    events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.version.cmp(&b.version)));
+    events
+}
+
+/// Render `events` as an iCalendar feed of all-day events. `stamp` is written as every event's
+/// DTSTAMP, the time the feed was generated.
+pub fn to_ics(events: &[TrainEvent], name: &str, stamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//release-inator//Release trains//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+    for event in events {
+        let mut description = event.description.clone();
+        if let Some(url) = &event.url {
+            description.push_str(&format!("\nRelease notes: {}", url));
+        }
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid()));
+        lines.push(format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", next_day(event.date).format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary())));
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+        if let Some(url) = &event.url {
+            lines.push(format!("URL:{}", url));
+        }
+        lines.push(format!("STATUS:{}", if event.scheduled { "TENTATIVE" } else { "CONFIRMED" }));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// The exclusive end of an all-day event on `date`.
+pub fn next_day(date: NaiveDate) -> NaiveDate {
+    date.checked_add_days(Days::new(1)).unwrap_or(date)
+}
+
+/// Escape a TEXT value: backslashes, semicolons, commas and newlines.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line longer than 75 octets onto continuation lines starting with a space,
+/// without splitting a UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts toward the continuation line's length
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
@@ -1,11 +1,23 @@
-use anyhow::Result;
-use handlebars::Handlebars;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use handlebars::{html_escape, Handlebars};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::path::PathBuf;
-use std::collections::HashMap;
-use super::release_fetcher::{AggregatedRelease, ComponentStatus};
-use super::commit_analyzer::{CommitType, EnrichedCommit};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use super::release_fetcher::{AggregatedRelease, ComponentRelease, ComponentStatus, DownloadStats};
+use super::commit_analyzer::{CommitAnalyzer, CommitType, EnrichedCommit};
+use super::branding::Branding;
+use super::accessibility::AccessibleHtml;
+use super::backfill::BackfillIndex;
+use super::entry_format::EntryFormats;
+use super::locale::{self, Locale};
+use super::risk::RiskScore;
+use super::rollout::{format_minutes, RolloutPlan, RolloutWave};
+use super::scaffold::Scaffold;
+use crate::config::types::SectionPosition;
+use crate::github::links::{RepoLinks, WebLinks};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -28,9 +40,114 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+impl OutputFormat {
+    /// File extension of documents in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "md",
+            OutputFormat::Json => "json",
+            OutputFormat::Html => "html",
+        }
+    }
+}
+
+const DEFAULT_TEMPLATE: &str = include_str!("../../templates/default.md.hbs");
+const COMPONENTS_TEMPLATE: &str = include_str!("../../templates/components.md.hbs");
+const COMPONENT_TEMPLATE: &str = include_str!("../../templates/component.md.hbs");
+const SINGLE_TEMPLATE: &str = include_str!("../../templates/single.md.hbs");
+const SHORT_TEMPLATE: &str = include_str!("../../templates/short.md.hbs");
+
+/// Search form of a `site`'s front page. Matches entries of `search.json` (a `SearchIndex`)
+/// by message, repository, author or release and links each to its component page. Raw HTML
+/// without blank lines, so Markdown passes it through as one block.
+const SITE_SEARCH_BOX: &str = r#"<form role="search" onsubmit="return false">
+<label for="site-search">Search changes</label>
+<input id="site-search" type="search" autocomplete="off">
+</form>
+<ul id="site-search-results" aria-live="polite"></ul>
+<script>
+fetch('search.json').then(response => response.json()).then(index => {
+  const input = document.getElementById('site-search');
+  const results = document.getElementById('site-search-results');
+  const page = name => encodeURIComponent(name.replace(/\//g, '-'));
+  input.addEventListener('input', () => {
+    const query = input.value.trim().toLowerCase();
+    results.replaceChildren();
+    if (!query) return;
+    index.documents
+      .filter(doc => [doc.message, doc.repository, doc.author, doc.release].some(field => field && field.toLowerCase().includes(query)))
+      .slice(0, 50)
+      .forEach(doc => {
+        const item = document.createElement('li');
+        const link = document.createElement('a');
+        link.href = page(doc.release) + '/' + page(doc.repository) + '.html';
+        link.textContent = doc.message;
+        item.append(link, ' — ' + doc.repository + ' ' + doc.release);
+        results.append(item);
+      });
+  });
+});
+</script>"#;
+
+/// Most commits listed as highlights in the short summary.
+const MAX_HIGHLIGHTS: usize = 5;
+
+/// Built-in templates, available to custom ones as `{{> default/<name>}}`.
+const BUILT_IN_PARTIALS: [(&str, &str); 4] = [
+    ("release", DEFAULT_TEMPLATE),
+    ("components", COMPONENTS_TEMPLATE),
+    ("component", COMPONENT_TEMPLATE),
+    ("single", SINGLE_TEMPLATE),
+];
+
+/// Starts the line that stands in for a component in the streamed layout, followed by its index.
+const COMPONENT_MARKER: &str = "\u{0}component:";
+
+/// Type-erased per-component data builder, so the same layout data can hold full components or
+/// stand-ins for streaming.
+type ComponentData<'r, 'f> = &'f mut dyn FnMut(&'r ComponentRelease, &str) -> serde_json::Value;
+
 pub struct ChangelogGenerator {
     template_engine: Handlebars<'static>,
+    /// The built-in template with each component replaced by a marker line, rendered first when
+    /// streaming so components can be rendered one at a time; `None` with a custom template
+    layout_engine: Option<Handlebars<'static>>,
     format: OutputFormat,
+    entry_formats: EntryFormats,
+    branding: Branding,
+    links: Option<WebLinks>,
+    rollout: Option<RolloutPlan>,
+    scaffold: Scaffold,
+    locale: Option<Locale>,
+    /// Label and target of a link at the bottom of `write_split`'s index, e.g. up to a site's
+    /// release list
+    index_link: Option<(String, String)>,
+}
+
+/// Register the `format_number` and `format_date` helpers, which write their argument for
+/// `locale` (unchanged without one).
+fn register_locale_helpers(engine: &mut Handlebars<'static>, locale: Option<Locale>) {
+    for (name, format) in [
+        ("format_number", locale::format_number as fn(Option<Locale>, &str) -> String),
+        ("format_date", locale::format_date),
+    ] {
+        engine.register_helper(
+            name,
+            Box::new(move |h: &handlebars::Helper,
+                           _: &Handlebars,
+                           _: &handlebars::Context,
+                           _: &mut handlebars::RenderContext,
+                           out: &mut dyn handlebars::Output| -> handlebars::HelperResult {
+                let text = match h.param(0).map(|p| p.value()) {
+                    Some(serde_json::Value::String(text)) => text.clone(),
+                    Some(serde_json::Value::Null) | None => return Ok(()),
+                    Some(value) => value.to_string(),
+                };
+                out.write(&handlebars::html_escape(&format(locale, &text)))?;
+                Ok(())
+            }),
+        );
+    }
 }
 
 impl ChangelogGenerator {
@@ -55,10 +172,33 @@ impl ChangelogGenerator {
             }),
         );
 
+        register_locale_helpers(&mut template_engine, None);
+
+        // Component partial is shared by the default template and available to custom ones
+        template_engine.register_partial("component", COMPONENT_TEMPLATE)?;
+        template_engine.register_partial("components", COMPONENTS_TEMPLATE)?;
+
+        // Built-in templates under `default/`, so custom ones can include them and override
+        // their `{{#> block}}`s with inline partials of the same name
+        for (name, template) in BUILT_IN_PARTIALS {
+            template_engine.register_partial(&format!("default/{}", name), template)?;
+        }
+
+        // The short summary has its own template, replaced with `with_short_template`
+        template_engine.register_template_string("short", SHORT_TEMPLATE)?;
+
         // Register default template if no custom one provided
+        let mut layout_engine = None;
         if template_path.is_none() {
-            let default_template = include_str!("../../templates/default.md.hbs");
-            template_engine.register_template_string("default", default_template)?;
+            template_engine.register_template_string("default", DEFAULT_TEMPLATE)?;
+            template_engine.register_template_string("single", SINGLE_TEMPLATE)?;
+
+            let mut layout = Handlebars::new();
+            register_locale_helpers(&mut layout, None);
+            layout.register_partial("components", COMPONENTS_TEMPLATE)?;
+            layout.register_partial("component", format!("{}{{{{stream_index}}}}\n", COMPONENT_MARKER))?;
+            layout.register_template_string("default", DEFAULT_TEMPLATE)?;
+            layout_engine = Some(layout);
         } else {
             let template_content = std::fs::read_to_string(template_path.as_ref().unwrap())?;
             template_engine.register_template_string("custom", &template_content)?;
@@ -66,21 +206,280 @@ impl ChangelogGenerator {
 
         Ok(Self {
             template_engine,
+            layout_engine,
             format,
+            entry_formats: EntryFormats::default(),
+            branding: Branding::default(),
+            links: None,
+            rollout: None,
+            scaffold: Scaffold::default(),
+            locale: None,
+            index_link: None,
         })
     }
 
+    /// Use per-category bullet formats for commit entries.
+    pub fn with_entry_formats(mut self, entry_formats: EntryFormats) -> Self {
+        self.entry_formats = entry_formats;
+        self
+    }
+
+    /// Link commits, pull requests, issues and component headers to their pages on GitHub.
+    pub fn with_links(mut self, links: WebLinks) -> Self {
+        self.links = Some(links);
+        self
+    }
+
+    /// Append a rollout plan section to Markdown and HTML output.
+    pub fn with_rollout(mut self, plan: RolloutPlan) -> Self {
+        self.rollout = Some(plan);
+        self
+    }
+
+    /// Add the announcement's fixed sections to Markdown and HTML output, above the summary or
+    /// after everything else as each section's `position` says.
+    pub fn with_scaffold(mut self, scaffold: Scaffold) -> Self {
+        self.scaffold = scaffold;
+        self
+    }
+
+    /// Write numbers and dates for `locale`, in the built-in output and through the
+    /// `format_number` and `format_date` template helpers.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        register_locale_helpers(&mut self.template_engine, Some(locale));
+        if let Some(layout) = &mut self.layout_engine {
+            register_locale_helpers(layout, Some(locale));
+        }
+        self.locale = Some(locale);
+        self
+    }
+
+    /// End the index written by `write_split` with a link to `href`.
+    pub fn with_index_link(mut self, label: &str, href: &str) -> Self {
+        self.index_link = Some((label.to_string(), href.to_string()));
+        self
+    }
+
+    /// Render the short summary with the Handlebars template at `path` instead of the built-in one.
+    pub fn with_short_template(mut self, path: &Path) -> Result<Self> {
+        let template = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read short template {}", path.display()))?;
+        self.template_engine.register_template_string("short", template)
+            .with_context(|| format!("Invalid short template {}", path.display()))?;
+        Ok(self)
+    }
+
+    fn local_date(&self, date: &DateTime<Utc>) -> String {
+        match self.locale {
+            Some(locale) => locale.format_date(date.date_naive()),
+            None => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    fn local_number(&self, number: impl ToString) -> String {
+        locale::format_number(self.locale, &number.to_string())
+    }
+
+    fn repo_links(&self, repo: &str) -> Option<RepoLinks> {
+        self.links.as_ref().map(|links| links.repo(repo))
+    }
+
+    /// Apply corporate branding to HTML output.
+    pub fn with_branding(mut self, branding: Branding) -> Result<Self> {
+        if let Some(header) = &branding.header {
+            self.template_engine.register_template_string("branding_header", header)?;
+        }
+        if let Some(footer) = &branding.footer {
+            self.template_engine.register_template_string("branding_footer", footer)?;
+        }
+        self.branding = branding;
+        Ok(self)
+    }
+
     pub fn generate(&self, release: &AggregatedRelease) -> Result<String> {
+        let mut output = Vec::new();
+        self.write(release, &mut output)?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    /// Write the notes to `out` as they are rendered. With the built-in template each component
+    /// is rendered (and for HTML, converted) on its own and written before the next, so memory
+    /// use doesn't grow with the size of the train. Custom templates see the whole train, so
+    /// they're rendered in one go.
+    pub fn write(&self, release: &AggregatedRelease, out: &mut dyn Write) -> Result<()> {
         match self.format {
-            OutputFormat::Markdown => self.generate_markdown(release),
-            OutputFormat::Json => self.generate_json(release),
-            OutputFormat::Html => self.generate_html(release),
+            OutputFormat::Markdown => self.write_markdown(release, &mut |markdown| Ok(out.write_all(markdown.as_bytes())?)),
+            OutputFormat::Json => Ok(serde_json::to_writer_pretty(out, release)?),
+            OutputFormat::Html => self.write_html(release, out),
+        }
+    }
+
+    /// Write the notes into `dir` as `index.md` (or `index.html`) with the summary and a link
+    /// per component, plus one file per component named after its repository that links back
+    /// to the index. Components repeated in sub-trains get numbered file names. Returns the
+    /// paths written, index first. Only the built-in Markdown and HTML templates can be split.
+    pub fn write_split(&self, release: &AggregatedRelease, dir: &Path) -> Result<Vec<PathBuf>> {
+        let Some(layout_engine) = self.layout_engine.as_ref().filter(|_| !matches!(self.format, OutputFormat::Json)) else {
+            anyhow::bail!("Only Markdown and HTML notes with the built-in template can be written one file per component");
+        };
+        let extension = self.format.extension();
+        let page_data = if self.template_engine.has_template("branding_header") || self.template_engine.has_template("branding_footer") {
+            self.train_data(release, "##")
+        } else {
+            serde_json::Value::Null
+        };
+        let write_page = |path: &Path, title: &str, description: &str, markdown: &str| -> Result<()> {
+            let content = match self.format {
+                OutputFormat::Html => self.wrap_html(title, description, markdown, &page_data)?,
+                _ => markdown.to_string(),
+            };
+            std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+        };
+
+        let mut streamed = Vec::new();
+        let data = self.markdown_data(release, &mut |component, heading| {
+            streamed.push((component, heading.to_string()));
+            json!({ "stream_index": streamed.len() - 1 })
+        });
+        let layout = layout_engine.render("default", &data)?;
+        drop(data);
+
+        let index_file = format!("index.{}", extension);
+        let mut index = String::new();
+        let mut written = vec![dir.join(&index_file)];
+        let mut used_names = std::collections::HashSet::new();
+        let mut rest = layout.as_str();
+        while let Some(start) = rest.find(COMPONENT_MARKER) {
+            index.push_str(&rest[..start]);
+            let marker = &rest[start + COMPONENT_MARKER.len()..];
+            let end = marker.find('\n').unwrap_or(marker.len());
+            let (component, _) = marker[..end].parse::<usize>().ok()
+                .and_then(|index| streamed.get(index))
+                .ok_or_else(|| anyhow::anyhow!("Malformed component marker in the rendered layout"))?;
+            rest = marker.get(end + 1..).unwrap_or_default();
+
+            let name = component.repository.replace('/', "-");
+            let file = (1..)
+                .map(|n| match n {
+                    1 => format!("{}.{}", name, extension),
+                    n => format!("{}-{}.{}", name, n, extension),
+                })
+                .find(|file| used_names.insert(file.clone()))
+                .unwrap_or_default();
+            let status = match &component.status {
+                ComponentStatus::Released { current_version, commits, .. } => {
+                    format!("`{}`, {} commits", current_version, self.local_number(commits.len()))
+                }
+                ComponentStatus::NoRelease { .. } => "no changes".to_string(),
+                ComponentStatus::NotYetReleased { .. } => "not yet released".to_string(),
+            };
+            index.push_str(&format!("- [{}]({}) — {}\n", component.repository, file, status));
+
+            let mut page = self.template_engine.render("component", &self.component_data(component, "#"))?;
+            page.push_str(&format!("\n[← Release {}]({})\n", release.version, index_file));
+            let path = dir.join(&file);
+            write_page(&path, &format!("{} — Release {}", component.repository, release.version), &status, &page)?;
+            written.push(path);
+        }
+        index.push_str(rest);
+        if let Some((label, href)) = &self.index_link {
+            index.push_str(&format!("\n[{}]({})\n", label, href));
+        }
+
+        let description = format!(
+            "{} of {} repositories updated, {} commits from {} contributors",
+            release.summary.updated_repos,
+            release.summary.total_repos,
+            release.summary.total_commits,
+            release.summary.contributors.len()
+        );
+        write_page(&written[0], &format!("Release {}", release.version), &description, &index)?;
+        Ok(written)
+    }
+
+    /// Render the notes as Markdown and pass them to `sink` in pieces: the layout between
+    /// components, then each component.
+    fn write_markdown(&self, release: &AggregatedRelease, sink: &mut dyn FnMut(&str) -> Result<()>) -> Result<()> {
+        if self.template_engine.has_template("custom") {
+            let data = self.markdown_data(release, &mut |component, heading| self.component_data(component, heading));
+            return sink(&self.template_engine.render("custom", &data)?);
         }
+        let Some(layout_engine) = &self.layout_engine else {
+            // Fallback to simple markdown
+            return sink(&self.generate_simple_markdown(release));
+        };
+
+        // Components are only referenced by the layout; their data is built as each is rendered
+        let mut streamed = Vec::new();
+        let data = self.markdown_data(release, &mut |component, heading| {
+            streamed.push((component, heading.to_string()));
+            json!({ "stream_index": streamed.len() - 1 })
+        });
+        let layout = layout_engine.render("default", &data)?;
+        drop(data);
+
+        let mut rest = layout.as_str();
+        while let Some(start) = rest.find(COMPONENT_MARKER) {
+            sink(&rest[..start])?;
+            let marker = &rest[start + COMPONENT_MARKER.len()..];
+            let end = marker.find('\n').unwrap_or(marker.len());
+            let (component, heading) = marker[..end].parse::<usize>().ok()
+                .and_then(|index| streamed.get(index))
+                .ok_or_else(|| anyhow::anyhow!("Malformed component marker in the rendered layout"))?;
+            sink(&self.template_engine.render("component", &self.component_data(component, heading))?)?;
+            rest = marker.get(end + 1..).unwrap_or_default();
+        }
+        sink(rest)
     }
 
-    fn generate_markdown(&self, release: &AggregatedRelease) -> Result<String> {
+    /// Data for the release template, with each component's data built by `component_data`.
+    fn markdown_data<'r>(&self, release: &'r AggregatedRelease, component_data: ComponentData<'r, '_>) -> serde_json::Value {
         // Convert to JSON for template rendering
-        let mut data = json!({
+        let mut data = self.train_data_with(release, "##", component_data);
+        data["sub_trains"] = json!(release.sub_trains.iter()
+            .map(|sub_train| self.train_data_with(sub_train, "###", component_data))
+            .collect::<Vec<_>>());
+        data["rollout"] = json!(self.rollout.iter()
+            .flat_map(|plan| &plan.waves)
+            .map(|wave| json!({
+                "wave": wave.wave,
+                "components": Self::wave_components(wave),
+                "risk": wave.risk,
+                "badge": wave.risk.badge(),
+                "soak": format_minutes(wave.soak_minutes),
+            }))
+            .collect::<Vec<_>>());
+        data["scaffold_top"] = self.scaffold_data(SectionPosition::Top);
+        data["scaffold_bottom"] = self.scaffold_data(SectionPosition::Bottom);
+        data
+    }
+
+    fn train_data(&self, release: &AggregatedRelease, base_heading: &str) -> serde_json::Value {
+        self.train_data_with(release, base_heading, &mut |component, heading| self.component_data(component, heading))
+    }
+
+    fn train_data_with<'r>(&self, release: &'r AggregatedRelease, base_heading: &str, component_data: ComponentData<'r, '_>) -> serde_json::Value {
+        let heading = if release.sections.is_empty() {
+            base_heading.to_string()
+        } else {
+            format!("{}#", base_heading)
+        };
+        let components: Vec<serde_json::Value> = release.components.iter()
+            .map(|component| component_data(component, &heading))
+            .collect();
+
+        let sections: Vec<serde_json::Value> = release.sections.iter()
+            .map(|section| json!({
+                "name": section.name,
+                "heading": base_heading,
+                "components": section.repositories.iter()
+                    .filter_map(|repo| release.components.iter().find(|c| &c.repository == repo))
+                    .map(|component| component_data(component, &heading))
+                    .collect::<Vec<_>>(),
+            }))
+            .collect();
+
+        json!({
             "version": release.version,
             "date": release.date.format("%Y-%m-%d").to_string(),
             "summary": {
@@ -88,208 +487,784 @@ impl ChangelogGenerator {
                 "updated_repos": release.summary.updated_repos,
                 "total_commits": release.summary.total_commits,
                 "contributors": release.summary.contributors.len(),
+                "skipped_archived": release.summary.skipped_archived.iter()
+                    .map(|r| format!("`{}`", r))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                "retired": Self::retired(release),
             },
-            "components": Vec::<serde_json::Value>::new(),
-        });
+            "risk": Self::risk(release).into_iter()
+                .map(|(repository, version, risk)| json!({
+                    "repository": repository,
+                    "version": version,
+                    "score": risk.score,
+                    "level": risk.level,
+                    "badge": risk.level.badge(),
+                    "factors": risk.factors.join(", "),
+                }))
+                .collect::<Vec<_>>(),
+            "components": components,
+            "sections": sections,
+            "adoption": Self::adoption(release).into_iter()
+                .map(|(repository, downloads)| json!({
+                    "repository": repository,
+                    "version": downloads.version,
+                    "total": downloads.total,
+                    "assets": downloads.assets,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
 
-        // Process components
-        if let Some(components) = data.get_mut("components") {
-            if let Some(components_array) = components.as_array_mut() {
-                for component in &release.components {
-                    let comp_data = match &component.status {
-                        ComponentStatus::Released {
-                            current_version,
-                            previous_version,
-                            release_date,
-                            commits,
-                            release_notes,
-                            stats,
-                        } => {
-                            let grouped_commits = self.group_commits_by_type(commits);
-                            json!({
-                                "repository": component.repository,
-                                "status": "Released",
-                                "current_version": current_version,
-                                "previous_version": previous_version,
-                                "release_date": release_date.format("%Y-%m-%d").to_string(),
-                                "commits": commits.iter().map(|c| json!({
-                                    "sha": &c.sha[..7],
-                                    "message": c.message,
-                                    "author": c.author,
-                                    "pr_number": c.pr_number,
-                                    "issues": c.issues,
-                                })).collect::<Vec<_>>(),
-                                "grouped_commits": grouped_commits,
-                                "release_notes": release_notes,
-                                "stats": {
-                                    "commit_count": stats.commit_count,
-                                    "contributors": stats.contributors,
-                                    "breaking_changes": stats.breaking_changes,
-                                    "features": stats.features,
-                                    "fixes": stats.fixes,
-                                }
-                            })
-                        }
-                        ComponentStatus::NoRelease {
-                            latest_version,
-                            latest_date,
-                        } => {
-                            json!({
-                                "repository": component.repository,
-                                "status": "NoRelease",
-                                "latest_version": latest_version,
-                                "latest_date": latest_date.map(|d| d.format("%Y-%m-%d").to_string()),
-                            })
-                        }
-                    };
-                    components_array.push(comp_data);
-                }
-            }
+    fn scaffold_data(&self, position: SectionPosition) -> serde_json::Value {
+        json!(self.scaffold.rendered(position).into_iter()
+            .map(|(title, content)| json!({ "title": title, "content": content }))
+            .collect::<Vec<_>>())
+    }
+
+    fn push_scaffold(&self, output: &mut String, position: SectionPosition) {
+        for (title, content) in self.scaffold.rendered(position) {
+            output.push_str(&format!("## {}
+
+{}
+
+", title, content));
         }
+    }
 
-        // Use template or fallback to simple format
-        if self.template_engine.has_template("custom") {
-            Ok(self.template_engine.render("custom", &data)?)
-        } else if self.template_engine.has_template("default") {
-            Ok(self.template_engine.render("default", &data)?)
-        } else {
-            // Fallback to simple markdown
-            Ok(self.generate_simple_markdown(release))
+    /// Components whose previous release has download counts, for the adoption appendix.
+    fn adoption(release: &AggregatedRelease) -> Vec<(&str, &DownloadStats)> {
+        release.components.iter()
+            .filter_map(|component| match &component.status {
+                ComponentStatus::Released { previous_downloads: Some(downloads), .. } => {
+                    Some((component.repository.as_str(), downloads))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// A wave's components as `repo` `version` pairs, noting what each waits for.
+    fn wave_components(wave: &RolloutWave) -> String {
+        wave.components.iter()
+            .map(|c| match c.depends_on.is_empty() {
+                true => format!("{} `{}`", c.repository, c.version),
+                false => format!("{} `{}` (after {})", c.repository, c.version, c.depends_on.join(", ")),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Retired components with the last train they were part of, e.g. `` `billing` (after v1.3) ``.
+    fn retired(release: &AggregatedRelease) -> String {
+        release.summary.retired.iter()
+            .map(|r| format!("`{}` (after {})", r.repository, r.retired_after))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Released components with a risk score, for the summary table.
+    fn risk(release: &AggregatedRelease) -> Vec<(&str, &str, &RiskScore)> {
+        release.components.iter()
+            .filter_map(|component| match &component.status {
+                ComponentStatus::Released { current_version, risk: Some(risk), .. } => {
+                    Some((component.repository.as_str(), current_version.as_str(), risk))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn component_data(&self, component: &ComponentRelease, heading: &str) -> serde_json::Value {
+        let links = self.repo_links(&component.repository);
+        let repository_url = links.as_ref().map(|l| l.url().to_string());
+        match &component.status {
+            ComponentStatus::Released {
+                current_version,
+                previous_version,
+                release_date,
+                created_at,
+                published_at,
+                target_sha,
+                commits,
+                release_notes,
+                stats,
+                releases,
+                branch,
+                risk,
+                is_latest,
+                known_issues,
+                ..
+            } => {
+                let grouped_commits = self.group_commits_by_type(commits);
+                json!({
+                    "repository": component.repository,
+                    "repository_url": repository_url,
+                    "renamed_from": component.renamed_from,
+                    "heading": heading,
+                    "status": "Released",
+                    "current_version": current_version,
+                    "previous_version": previous_version,
+                    "compare_url": previous_version.as_ref().zip(links.as_ref())
+                        .map(|(prev, l)| l.compare(prev, current_version)),
+                    "release_date": release_date.map(|d| d.format("%Y-%m-%d").to_string()),
+                    "created_at": created_at.map(|d| d.to_rfc3339()),
+                    "published_at": published_at.map(|d| d.to_rfc3339()),
+                    "target_sha": target_sha,
+                    "branch": branch,
+                    "commits": commits.iter().map(|c| self.commit_data(c, links.as_ref())).collect::<Vec<_>>(),
+                    "grouped_commits": grouped_commits,
+                    "categories": self.categories_data(commits, links.as_ref()),
+                    "breaking_commits": commits.iter()
+                        .filter(|c| c.breaking)
+                        .map(|c| self.commit_data(c, links.as_ref()))
+                        .collect::<Vec<_>>(),
+                    "closed_issues": CommitAnalyzer::closed_issues(commits),
+                    "known_issues": known_issues,
+                    "release_notes": release_notes,
+                    "releases": releases.iter().map(|r| json!({
+                        "version": r.version,
+                        "date": r.date.map(|d| d.format("%Y-%m-%d").to_string()),
+                        "commit_count": r.commit_count,
+                        "release_notes": r.release_notes,
+                    })).collect::<Vec<_>>(),
+                    "stats": {
+                        "commit_count": stats.commit_count,
+                        "contributors": stats.contributors,
+                        "breaking_changes": stats.breaking_changes,
+                        "features": stats.features,
+                        "fixes": stats.fixes,
+                    },
+                    "risk": risk,
+                    "is_latest": is_latest,
+                })
+            }
+            ComponentStatus::NoRelease {
+                latest_version,
+                latest_date,
+            } => {
+                json!({
+                    "repository": component.repository,
+                    "repository_url": repository_url,
+                    "renamed_from": component.renamed_from,
+                    "heading": heading,
+                    "status": "NoRelease",
+                    "latest_version": latest_version,
+                    "latest_date": latest_date.map(|d| d.format("%Y-%m-%d").to_string()),
+                })
+            }
+            ComponentStatus::NotYetReleased { empty } => {
+                json!({
+                    "repository": component.repository,
+                    "repository_url": repository_url,
+                    "renamed_from": component.renamed_from,
+                    "heading": heading,
+                    "status": "NotYetReleased",
+                    "empty": empty,
+                })
+            }
         }
     }
 
     fn generate_simple_markdown(&self, release: &AggregatedRelease) -> String {
         let mut output = String::new();
+        self.push_simple_header(&mut output, release);
+        
+        self.push_simple_components(&mut output, release, "##");
+
+        for sub_train in &release.sub_trains {
+            output.push_str(&format!("## 🚆 {}\n\n", sub_train.version));
+            output.push_str(&format!("- **Updated Repositories:** {}/{}\n", self.local_number(sub_train.summary.updated_repos), self.local_number(sub_train.summary.total_repos)));
+            output.push_str(&format!("- **Total Commits:** {}\n", self.local_number(sub_train.summary.total_commits)));
+            output.push_str(&format!("- **Contributors:** {}\n\n", self.local_number(sub_train.summary.contributors.len())));
+            self.push_simple_components(&mut output, sub_train, "###");
+        }
+
+        if let Some(plan) = &self.rollout {
+            output.push_str("## 🚦 Rollout Plan\n\n");
+            output.push_str("| Wave | Components | Risk | Soak |\n");
+            output.push_str("|---|---|---|---|\n");
+            for wave in &plan.waves {
+                output.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    wave.wave, Self::wave_components(wave), wave.risk.badge(), format_minutes(wave.soak_minutes)
+                ));
+            }
+            output.push('\n');
+        }
+
+        let adoption = Self::adoption(release);
+        if !adoption.is_empty() {
+            output.push_str("## 📈 Adoption\n\n");
+            output.push_str("| Component | Version | Downloads to date |\n");
+            output.push_str("|---|---|---|\n");
+            for (repository, downloads) in adoption {
+                output.push_str(&format!("| {} | `{}` | {} |\n", repository, downloads.version, self.local_number(downloads.total)));
+            }
+            output.push('\n');
+        }
+        self.push_scaffold(&mut output, SectionPosition::Bottom);
         
+        output
+    }
+
+    fn push_simple_header(&self, output: &mut String, release: &AggregatedRelease) {
         output.push_str(&format!("# Release {}\n\n", release.version));
-        output.push_str(&format!("📅 **Date:** {}\n\n", release.date.format("%Y-%m-%d")));
+        output.push_str(&format!("📅 **Date:** {}\n\n", self.local_date(&release.date)));
+        self.push_scaffold(output, SectionPosition::Top);
         
         output.push_str("## 📊 Summary\n\n");
-        output.push_str(&format!("- **Total Repositories:** {}\n", release.summary.total_repos));
-        output.push_str(&format!("- **Updated Repositories:** {}\n", release.summary.updated_repos));
-        output.push_str(&format!("- **Total Commits:** {}\n", release.summary.total_commits));
-        output.push_str(&format!("- **Contributors:** {}\n\n", release.summary.contributors.len()));
+        output.push_str(&format!("- **Total Repositories:** {}\n", self.local_number(release.summary.total_repos)));
+        output.push_str(&format!("- **Updated Repositories:** {}\n", self.local_number(release.summary.updated_repos)));
+        output.push_str(&format!("- **Total Commits:** {}\n", self.local_number(release.summary.total_commits)));
+        output.push_str(&format!("- **Contributors:** {}\n", self.local_number(release.summary.contributors.len())));
+        if !release.summary.skipped_archived.is_empty() {
+            let skipped: Vec<String> = release.summary.skipped_archived.iter().map(|r| format!("`{}`", r)).collect();
+            output.push_str(&format!("- **Skipped (archived):** {}\n", skipped.join(", ")));
+        }
+        if !release.summary.retired.is_empty() {
+            output.push_str(&format!("- **Retired:** {}\n", Self::retired(release)));
+        }
+        output.push('\n');
+
+        let risk = Self::risk(release);
+        if !risk.is_empty() {
+            output.push_str("| Component | Version | Risk | Factors |\n");
+            output.push_str("|---|---|---|---|\n");
+            for (repository, version, risk) in risk {
+                output.push_str(&format!(
+                    "| {} | `{}` | {} ({}) | {} |\n",
+                    repository, version, risk.level.badge(), risk.score, risk.factors.join(", ")
+                ));
+            }
+            output.push('\n');
+        }
         
         output.push_str("---\n\n");
-        
+    }
+
+    /// One-paragraph Markdown summary for chat channels: the counts and up to `MAX_HIGHLIGHTS`
+    /// commits, breaking changes first and then features. Always Markdown, whatever the format
+    /// of the full notes.
+    pub fn generate_short(&self, release: &AggregatedRelease) -> Result<String> {
+        let released: Vec<(&str, &EnrichedCommit)> = release.components.iter()
+            .filter_map(|component| match &component.status {
+                ComponentStatus::Released { commits, .. } => Some((component.repository.as_str(), commits)),
+                _ => None,
+            })
+            .flat_map(|(repository, commits)| commits.iter().map(move |commit| (repository, commit)))
+            .collect();
+
+        let breaking = released.iter().filter(|(_, commit)| commit.breaking);
+        let features = released.iter()
+            .filter(|(_, commit)| !commit.breaking && commit.commit_type == Some(CommitType::Feature));
+        let highlights: Vec<serde_json::Value> = breaking.chain(features)
+            .take(MAX_HIGHLIGHTS)
+            .map(|(repository, commit)| {
+                let links = self.repo_links(repository);
+                json!({
+                    "repository": repository,
+                    "message": commit.message.lines().next().unwrap_or_default().trim_end_matches('.'),
+                    "sha": &commit.sha[..7.min(commit.sha.len())],
+                    "url": links.as_ref().filter(|_| !commit.sha.is_empty()).map(|l| l.commit(&commit.sha)),
+                    "breaking": commit.breaking,
+                })
+            })
+            .collect();
+
+        let data = json!({
+            "version": release.version,
+            "date": release.date.format("%Y-%m-%d").to_string(),
+            "summary": {
+                "total_repos": release.summary.total_repos,
+                "updated_repos": release.summary.updated_repos,
+                "total_commits": release.summary.total_commits,
+                "contributors": release.summary.contributors.len(),
+            },
+            "breaking_changes": released.iter().filter(|(_, commit)| commit.breaking).count(),
+            "highlights": highlights,
+        });
+
+        let mut short = self.template_engine.render("short", &data)?;
+        if !short.ends_with('\n') {
+            short.push('\n');
+        }
+        Ok(short)
+    }
+
+    /// Index page of a backfill, listing each version with a link to its document.
+    pub fn generate_index(&self, index: &BackfillIndex) -> Result<String> {
+        let markdown = self.index_markdown(index, "");
+        match self.format {
+            OutputFormat::Markdown => Ok(markdown),
+            OutputFormat::Json => Ok(serde_json::to_string_pretty(index)?),
+            OutputFormat::Html => {
+                let description = format!("Release notes of {} versions", index.entries.len());
+                self.wrap_html("Release History", &description, &markdown, &json!({}))
+            }
+        }
+    }
+
+    /// Front page of a `site`: the versions of `index` under a box searching the site's
+    /// `search.json`. Always HTML.
+    pub fn generate_site_index(&self, index: &BackfillIndex) -> Result<String> {
+        let markdown = self.index_markdown(index, SITE_SEARCH_BOX);
+        let description = format!("Release notes of {} versions", index.entries.len());
+        self.wrap_html("Release History", &description, &markdown, &json!({}))
+    }
+
+    /// The release history as a list (the HTML conversion doesn't render tables), after
+    /// `before_list`, which may be raw HTML.
+    fn index_markdown(&self, index: &BackfillIndex, before_list: &str) -> String {
+        let mut markdown = String::from("# Release History\n\n");
+        if !before_list.is_empty() {
+            markdown.push_str(before_list);
+            markdown.push_str("\n\n");
+        }
+        for entry in &index.entries {
+            markdown.push_str(&format!(
+                "- [{}]({}) — {} · {}/{} repositories updated · {} commits\n",
+                entry.version,
+                entry.file,
+                self.local_date(&entry.date),
+                self.local_number(entry.updated_repos),
+                self.local_number(entry.total_repos),
+                self.local_number(entry.total_commits)
+            ));
+        }
+        markdown
+    }
+
+    /// Compact Markdown for size-limited destinations such as GitHub release bodies: each released
+    /// component's changes are collapsed into a `<details>` block and component release notes are
+    /// left out.
+    pub fn generate_collapsed(&self, release: &AggregatedRelease) -> String {
+        let mut output = String::new();
+        self.push_simple_header(&mut output, release);
+
+        let mut unchanged = Vec::new();
         for component in &release.components {
-            output.push_str(&format!("## {}\n\n", component.repository));
-            
-            match &component.status {
-                ComponentStatus::Released {
-                    current_version,
-                    previous_version,
-                    release_date,
-                    commits,
-                    release_notes,
-                    stats,
-                } => {
-                    output.push_str(&format!("**Version:** `{}`  \n", current_version));
-                    if let Some(prev) = previous_version {
-                        output.push_str(&format!("**Previous:** `{}`  \n", prev));
-                    } else {
-                        output.push_str("**Previous:** *Initial Release*  \n");
+            let ComponentStatus::Released { current_version, commits, .. } = &component.status else {
+                unchanged.push(format!("`{}`", component.repository));
+                continue;
+            };
+
+            output.push_str(&format!(
+                "<details>\n<summary><strong>{}</strong> <code>{}</code> — {} commit(s)</summary>\n\n",
+                component.repository,
+                current_version,
+                self.local_number(commits.len())
+            ));
+
+            let links = self.repo_links(&component.repository);
+            for (category, type_commits) in group_commits(commits) {
+                output.push_str(&format!("#### {}\n\n", category));
+                for commit in type_commits {
+                    output.push_str(&self.entry_formats.format(commit, links.as_ref()));
+                    output.push('\n');
+                }
+                output.push('\n');
+            }
+
+            output.push_str("</details>\n\n");
+        }
+
+        if !unchanged.is_empty() {
+            output.push_str(&format!("**Unchanged:** {}\n", unchanged.join(", ")));
+        }
+        if !self.scaffold.rendered(SectionPosition::Bottom).is_empty() {
+            output.push('\n');
+            self.push_scaffold(&mut output, SectionPosition::Bottom);
+        }
+
+        output
+    }
+
+    fn push_simple_components(&self, output: &mut String, release: &AggregatedRelease, base_heading: &str) {
+        if release.sections.is_empty() {
+            for component in &release.components {
+                self.push_simple_component(output, component, base_heading);
+            }
+        } else {
+            let heading = format!("{}#", base_heading);
+            for section in &release.sections {
+                output.push_str(&format!("{} {}\n\n", base_heading, section.name));
+                for repo in &section.repositories {
+                    if let Some(component) = release.components.iter().find(|c| &c.repository == repo) {
+                        self.push_simple_component(output, component, &heading);
                     }
-                    output.push_str(&format!("**Release Date:** {}  \n", release_date.format("%Y-%m-%d")));
-                    output.push_str(&format!("**Commits:** {}  \n\n", stats.commit_count));
+                }
+            }
+        }
+    }
+
+    fn push_simple_component(&self, output: &mut String, component: &ComponentRelease, heading: &str) {
+        let links = self.repo_links(&component.repository);
+        let compare_url = match &component.status {
+            ComponentStatus::Released { current_version, previous_version: Some(prev), .. } => {
+                links.as_ref().map(|l| l.compare(prev, current_version))
+            }
+            _ => None,
+        };
+        let title = match compare_url {
+            Some(url) => format!("[{}]({})", component.repository, url),
+            None => component.repository.clone(),
+        };
+        match &component.renamed_from {
+            Some(old) => output.push_str(&format!("{} {} *(renamed from `{}`)*\n\n", heading, title, old)),
+            None => output.push_str(&format!("{} {}\n\n", heading, title)),
+        }
+        
+        match &component.status {
+            ComponentStatus::Released {
+                current_version,
+                previous_version,
+                release_date,
+                commits,
+                release_notes,
+                stats,
+                releases,
+                branch,
+                known_issues,
+                ..
+            } => {
+                match branch {
+                    Some(branch) => output.push_str(&format!("**Version:** `{}` (head of `{}`)  \n", current_version, branch)),
+                    None => output.push_str(&format!("**Version:** `{}`  \n", current_version)),
+                }
+                if let Some(prev) = previous_version {
+                    output.push_str(&format!("**Previous:** `{}`  \n", prev));
+                } else {
+                    output.push_str("**Previous:** *Initial Release*  \n");
+                }
+                match release_date {
+                    Some(date) => output.push_str(&format!("**Release Date:** {}  \n", self.local_date(date))),
+                    None => output.push_str("**Release Date:** *Unknown*  \n"),
+                }
+                output.push_str(&format!("**Commits:** {}  \n", self.local_number(stats.commit_count)));
+                if !releases.is_empty() {
+                    let listed: Vec<String> = releases.iter()
+                        .map(|r| match r.date {
+                            Some(date) => format!("`{}` ({})", r.version, self.local_date(&date)),
+                            None => format!("`{}`", r.version),
+                        })
+                        .collect();
+                    output.push_str(&format!("**Releases:** {}  \n", listed.join(", ")));
+                }
+                output.push('\n');
+                
+                if !commits.is_empty() {
+                    output.push_str(&format!("{}# 🎯 Changes\n\n", heading));
                     
-                    if !commits.is_empty() {
-                        output.push_str("### 🎯 Changes\n\n");
-                        
-                        let grouped = self.group_commits_by_type(commits);
-                        if !grouped.is_empty() {
-                            for (commit_type, type_commits) in grouped {
-                                output.push_str(&format!("#### {}\n", commit_type));
-                                for commit in type_commits {
-                                    output.push_str(&format!("- {} ([`{}`])\n", 
-                                        commit.message, 
-                                        &commit.sha[..7]
-                                    ));
-                                }
-                                output.push_str("\n");
-                            }
-                        } else {
-                            for commit in commits {
-                                output.push_str(&format!("- {} ([`{}`])\n", 
-                                    commit.message, 
-                                    &commit.sha[..7]
-                                ));
+                    if commits.iter().any(EnrichedCommit::is_categorized) {
+                        for (category, type_commits) in group_commits(commits) {
+                            output.push_str(&format!("{}## {}\n", heading, category));
+                            for commit in type_commits {
+                                output.push_str(&self.entry_formats.format(commit, links.as_ref()));
+                                output.push('\n');
                             }
                             output.push_str("\n");
                         }
-                    }
-                    
-                    if let Some(notes) = release_notes {
-                        output.push_str("### 📝 Release Notes\n\n");
-                        output.push_str(notes);
-                        output.push_str("\n\n");
-                    }
-                    
-                    if !stats.contributors.is_empty() {
-                        output.push_str("### 👥 Contributors\n");
-                        for contributor in &stats.contributors {
-                            output.push_str(&format!("- @{}\n", contributor));
+                    } else {
+                        for commit in commits {
+                            output.push_str(&self.entry_formats.format(commit, links.as_ref()));
+                            output.push('\n');
                         }
                         output.push_str("\n");
                     }
                 }
-                ComponentStatus::NoRelease {
-                    latest_version,
-                    latest_date,
-                } => {
-                    output.push_str("*No changes in this release*\n\n");
-                    if let Some(latest) = latest_version {
-                        output.push_str(&format!("Latest version: `{}`", latest));
-                        if let Some(date) = latest_date {
-                            output.push_str(&format!(" ({})", date.format("%Y-%m-%d")));
+
+                let closed_issues = CommitAnalyzer::closed_issues(commits);
+                if !closed_issues.is_empty() {
+                    output.push_str(&format!("{}# ✅ Closed Issues\n\n", heading));
+                    for issue in closed_issues {
+                        output.push_str(&format!("- [#{}]({}) {}\n", issue.number, issue.url, issue.title));
+                    }
+                    output.push('\n');
+                }
+
+                if !known_issues.is_empty() {
+                    output.push_str(&format!("{}# ⚠️ Known Issues\n\n", heading));
+                    for issue in known_issues {
+                        output.push_str(&format!("- [#{}]({}) {}", issue.number, issue.url, issue.title));
+                        if let Some(workaround) = &issue.workaround {
+                            output.push_str(&format!(" — *Workaround:* {}", workaround));
                         }
+                        output.push('\n');
+                    }
+                    output.push('\n');
+                }
+                
+                if let Some(notes) = release_notes {
+                    output.push_str(&format!("{}# 📝 Release Notes\n\n", heading));
+                    output.push_str(notes);
+                    output.push_str("\n\n");
+                }
+                for entry in releases {
+                    if let Some(notes) = &entry.release_notes {
+                        output.push_str(&format!("{}# 📝 {}\n\n", heading, entry.version));
+                        output.push_str(notes);
                         output.push_str("\n\n");
                     }
                 }
+                
+                if !stats.contributors.is_empty() {
+                    output.push_str(&format!("{}# 👥 Contributors\n", heading));
+                    for contributor in &stats.contributors {
+                        output.push_str(&format!("- @{}\n", contributor));
+                    }
+                    output.push_str("\n");
+                }
+            }
+            ComponentStatus::NoRelease {
+                latest_version,
+                latest_date,
+            } => {
+                output.push_str("*No changes in this release*\n\n");
+                if let Some(latest) = latest_version {
+                    output.push_str(&format!("Latest version: `{}`", latest));
+                    if let Some(date) = latest_date {
+                        output.push_str(&format!(" ({})", self.local_date(date)));
+                    }
+                    output.push_str("\n\n");
+                }
+            }
+            ComponentStatus::NotYetReleased { empty } => {
+                output.push_str("*Not yet released*");
+                if *empty {
+                    output.push_str(" (empty repository)");
+                }
+                output.push_str("\n\n");
             }
-            
-            output.push_str("---\n\n");
         }
         
-        output
+        output.push_str("---\n\n");
     }
 
-    fn generate_json(&self, release: &AggregatedRelease) -> Result<String> {
-        Ok(serde_json::to_string_pretty(release)?)
+    fn write_html(&self, release: &AggregatedRelease, out: &mut dyn Write) -> Result<()> {
+        // Only header and footer templates use the whole train's data
+        let data = if self.template_engine.has_template("branding_header") || self.template_engine.has_template("branding_footer") {
+            self.train_data(release, "##")
+        } else {
+            serde_json::Value::Null
+        };
+        let summary = &release.summary;
+        let description = format!(
+            "{} of {} repositories updated, {} commits from {} contributors",
+            summary.updated_repos,
+            summary.total_repos,
+            summary.total_commits,
+            summary.contributors.len()
+        );
+        let components: Vec<String> = release.components.iter().map(|c| c.repository.clone()).collect();
+        let (head, tail) = self.html_page(&format!("Release {}", release.version), &description, &data, &components)?;
+        drop(data);
+
+        out.write_all(head.as_bytes())?;
+        // Each piece ends between blocks, so converting them one at a time gives the same HTML;
+        // sections opened in one piece are closed in a later one
+        let mut converter = AccessibleHtml::new(&components);
+        let mut html = String::new();
+        self.write_markdown(release, &mut |markdown| {
+            html.clear();
+            converter.push(&mut html, markdown);
+            Ok(out.write_all(html.as_bytes())?)
+        })?;
+        html.clear();
+        converter.finish(&mut html);
+        out.write_all(html.as_bytes())?;
+        out.write_all(tail.as_bytes())?;
+        Ok(())
     }
 
-    fn generate_html(&self, release: &AggregatedRelease) -> Result<String> {
-        // Convert markdown to HTML
-        let markdown = self.generate_markdown(release)?;
-        let parser = pulldown_cmark::Parser::new(&markdown);
+    fn wrap_html(&self, title: &str, description: &str, markdown: &str, data: &serde_json::Value) -> Result<String> {
+        let mut converter = AccessibleHtml::new(&[]);
         let mut html = String::new();
-        pulldown_cmark::html::push_html(&mut html, parser);
+        converter.push(&mut html, markdown);
+        converter.finish(&mut html);
+
+        let (head, tail) = self.html_page(title, description, data, &[])?;
+        Ok(format!("{}{}{}", head, html, tail))
+    }
+
+    /// The HTML page around the notes, split where the notes go, with a skip link and, when
+    /// `components` are given, a navigation landmark linking to each.
+    fn html_page(&self, title: &str, description: &str, data: &serde_json::Value, components: &[String]) -> Result<(String, String)> {
+        let brand_css = self.branding.css.as_deref().unwrap_or_default();
+        let mut header = String::new();
+        if let Some(logo) = &self.branding.logo {
+            // Without a site name the logo is decorative
+            let alt = self.branding.site_name.as_deref().unwrap_or_default();
+            header.push_str(&format!("<img class=\"brand-logo\" src=\"{}\" alt=\"{}\">\n", logo, html_escape(alt)));
+        }
+        if self.template_engine.has_template("branding_header") {
+            header.push_str(&self.template_engine.render("branding_header", data)?);
+        }
+        let footer = if self.template_engine.has_template("branding_footer") {
+            self.template_engine.render("branding_footer", data)?
+        } else {
+            String::new()
+        };
+        let meta = self.meta_tags(title, description);
+        let nav = if components.is_empty() {
+            String::new()
+        } else {
+            let links: Vec<String> = components.iter()
+                .map(|repository| format!(
+                    "<li><a href=\"#{}\">{}</a></li>",
+                    AccessibleHtml::component_id(repository),
+                    html_escape(repository)
+                ))
+                .collect();
+            format!("<nav aria-label=\"Components\"><ul>{}</ul></nav>", links.join(""))
+        };
         
         // Wrap in basic HTML structure
-        Ok(format!(
-            r#"<!DOCTYPE html>
-<html lang="en">
+        let head = format!(
+            r##"<!DOCTYPE html>
+<html lang="{}">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Release {}</title>
+    <title>{}</title>
+{}
     <style>
-        body {{ font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; max-width: 900px; margin: 0 auto; padding: 20px; }}
-        h1, h2, h3 {{ border-bottom: 1px solid #e1e4e8; padding-bottom: 0.3em; }}
-        code {{ background: #f6f8fa; padding: 2px 4px; border-radius: 3px; }}
+        body {{ font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; max-width: 900px; margin: 0 auto; padding: 20px; color: #1f2328; background: #ffffff; line-height: 1.5; }}
+        h1, h2, h3 {{ border-bottom: 1px solid #d1d9e0; padding-bottom: 0.3em; }}
+        a {{ color: #0550ae; text-decoration: underline; }}
+        a:focus-visible {{ outline: 3px solid #0969da; outline-offset: 2px; }}
+        code {{ background: #eff1f3; color: #1f2328; padding: 2px 4px; border-radius: 3px; }}
+        nav ul {{ display: flex; flex-wrap: wrap; gap: 0.25em 1em; list-style: none; padding: 0; }}
+        footer {{ color: #59636e; }}
+        .skip-link {{ position: absolute; left: -10000px; }}
+        .skip-link:focus {{ left: 8px; top: 8px; padding: 8px 12px; background: #1f2328; color: #ffffff; z-index: 1; }}
+        .brand-logo {{ max-height: 48px; }}
+{}
     </style>
 </head>
 <body>
+    <a class="skip-link" href="#content">Skip to the release notes</a>
+    <header>{}</header>
     {}
+    <main id="content">
+    "##,
+            self.locale.map_or("en", |l| l.tag()),
+            html_escape(title),
+            meta,
+            brand_css,
+            header,
+            nav
+        );
+        let tail = format!(
+            r#"
+    </main>
+    <footer>{}</footer>
 </body>
 </html>"#,
-            release.version,
-            html
-        ))
+            footer
+        );
+        Ok((head, tail))
     }
 
-    fn group_commits_by_type<'a>(&self, commits: &'a [EnrichedCommit]) -> HashMap<CommitType, Vec<&'a EnrichedCommit>> {
-        let mut grouped: HashMap<CommitType, Vec<&'a EnrichedCommit>> = HashMap::new();
+    /// Description and Open Graph / Twitter card tags so links to the page unfurl with a preview.
+    fn meta_tags(&self, title: &str, description: &str) -> String {
+        let mut tags = vec![
+            ("name", "description", description),
+            ("property", "og:type", "article"),
+            ("property", "og:title", title),
+            ("property", "og:description", description),
+        ];
+        if let Some(site_name) = &self.branding.site_name {
+            tags.push(("property", "og:site_name", site_name));
+        }
+        if let Some(image) = &self.branding.social_image {
+            tags.push(("property", "og:image", image));
+            tags.push(("name", "twitter:card", "summary_large_image"));
+        } else {
+            tags.push(("name", "twitter:card", "summary"));
+        }
+
+        tags.iter()
+            .map(|(attr, key, value)| format!("    <meta {}=\"{}\" content=\"{}\">", attr, key, html_escape(value)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Render a conventional changelog for one repository, without the aggregate summary layout.
+    pub fn generate_single(&self, component: &ComponentRelease) -> Result<String> {
+        match self.format {
+            OutputFormat::Json => Ok(serde_json::to_string_pretty(component)?),
+            OutputFormat::Markdown => self.generate_single_markdown(component),
+            OutputFormat::Html => {
+                let markdown = self.generate_single_markdown(component)?;
+                let data = self.component_data(component, "#");
+                let description = match &component.status {
+                    ComponentStatus::Released { current_version, stats, .. } => format!(
+                        "{} {}: {} commits from {} contributors",
+                        component.repository,
+                        current_version,
+                        stats.commit_count,
+                        stats.contributors.len()
+                    ),
+                    _ => format!("{} release notes", component.repository),
+                };
+                self.wrap_html(&component.repository, &description, &markdown, &data)
+            }
+        }
+    }
+
+    fn generate_single_markdown(&self, component: &ComponentRelease) -> Result<String> {
+        let data = self.component_data(component, "#");
+
+        let template = if self.template_engine.has_template("custom") { "custom" } else { "single" };
+        Ok(self.template_engine.render(template, &data)?)
+    }
+
+    /// Categorized bullet list used to prefill a component's draft release.
+    pub fn generate_draft_body(&self, commits: &[EnrichedCommit]) -> String {
+        let mut output = String::from("<!-- Prefilled from unreleased commits; edit before publishing -->\n\n");
+
+        for (category, type_commits) in group_commits(commits) {
+            output.push_str(&format!("## {}\n\n", category));
+            for commit in type_commits {
+                // Drafts live in the commits' own repository, where GitHub links SHAs and #refs itself
+                output.push_str(&self.entry_formats.format(commit, None));
+                output.push('\n');
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn commit_data(&self, commit: &EnrichedCommit, links: Option<&RepoLinks>) -> serde_json::Value {
+        json!({
+            "entry": self.entry_formats.format(commit, links),
+            "sha": &commit.sha[..7.min(commit.sha.len())],
+            "url": links.filter(|_| !commit.sha.is_empty()).map(|l| l.commit(&commit.sha)),
+            "pr_url": commit.pr_number.zip(links).map(|(n, l)| l.pull(n)),
+            "message": commit.message,
+            "author": commit.author,
+            "pr_number": commit.pr_number,
+            "issues": commit.issues,
+            "resolved_issues": commit.resolved_issues,
+            "breaking": commit.breaking,
+        })
+    }
+
+    /// Commits grouped by label section and type in a stable order, with uncategorized commits last.
+    fn categories_data(&self, commits: &[EnrichedCommit], links: Option<&RepoLinks>) -> Vec<serde_json::Value> {
+        group_commits(commits).into_iter()
+            .map(|(category, type_commits)| json!({
+                "title": category.to_string(),
+                "commits": type_commits.iter().map(|c| self.commit_data(c, links)).collect::<Vec<_>>(),
+            }))
+            .collect()
+    }
+
+    /// Commits grouped by type alone, for templates that use `grouped_commits`.
+    fn group_commits_by_type<'a>(&self, commits: &'a [EnrichedCommit]) -> BTreeMap<CommitType, Vec<&'a EnrichedCommit>> {
+        let mut grouped: BTreeMap<CommitType, Vec<&'a EnrichedCommit>> = BTreeMap::new();
         
         for commit in commits {
             if let Some(ref commit_type) = commit.commit_type {
@@ -301,4 +1276,33 @@ impl ChangelogGenerator {
         
         grouped
     }
-}
\ No newline at end of file
+}
+
+/// Heading a commit is listed under. Sections named by pull request labels come before the
+/// commit types, and uncategorized commits fall under `CommitType::Other`, which sorts last.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Category {
+    Section(String),
+    Type(CommitType),
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Category::Section(title) => write!(f, "{}", title),
+            Category::Type(commit_type) => write!(f, "{}", commit_type),
+        }
+    }
+}
+
+fn group_commits(commits: &[EnrichedCommit]) -> BTreeMap<Category, Vec<&EnrichedCommit>> {
+    let mut grouped: BTreeMap<Category, Vec<&EnrichedCommit>> = BTreeMap::new();
+    for commit in commits {
+        let category = match (&commit.section, &commit.commit_type) {
+            (Some(section), _) => Category::Section(section.clone()),
+            (None, commit_type) => Category::Type(commit_type.clone().unwrap_or(CommitType::Other)),
+        };
+        grouped.entry(category).or_default().push(commit);
+    }
+    grouped
+}
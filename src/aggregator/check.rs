@@ -0,0 +1,482 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crate::github::types::Release;
+use serde::{Deserialize, Serialize};
+use crate::config::types::{RefMode, RegistryKind, RepoSettings};
+use crate::github::client::GitHubClient;
+use crate::github::types::{loose_version, tag_version, DateSource, PreviousStrategy};
+use crate::integrations::oci::OciClient;
+use crate::integrations::registry::RegistryClient;
+use crate::state::store::short_sha;
+
+/// Outcome of `check`: whether each repository has released the train's version.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckReport {
+    pub version: String,
+    pub repositories: Vec<RepositoryCheck>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// The release and every configured artifact were found
+    Found,
+    /// The release, branch or an artifact is missing
+    NotFound,
+    /// Archived repository, skipped without `--include-archived`
+    Archived,
+    /// Component retired by an earlier train, skipped
+    Retired,
+}
+
+/// What `check` found for one repository. Only the checks that apply to it are filled in: a
+/// branch-deployed component has `branch` instead of a release, and a registry-only one has
+/// neither.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepositoryCheck {
+    pub repository: String,
+    pub status: CheckStatus,
+    /// Tag the version maps to in this repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<ReleaseFound>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<BranchCheck>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<RegistryCheck>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageCheck>,
+    /// Expected release assets, one per configured pattern
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<AssetCheck>,
+    /// Last train a retired component was part of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retired_after: Option<String>,
+    /// Tags that moved since an earlier check
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Consistency rules the release breaks; these warn without failing the check
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<RuleViolation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseFound {
+    /// The release's own tag, which can differ from `tag` in its `v` prefix
+    pub tag: String,
+    /// Commit the tag points at
+    #[serde(default)]
+    pub sha: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub prerelease: bool,
+    pub draft: bool,
+}
+
+/// Consistency rules applied to each release `check` finds. Each can be turned off with
+/// `--disable-rule` or `check.disabled_rules`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckRule {
+    /// The release is marked as a prerelease, though the version isn't one
+    Prerelease,
+    /// The release is still a draft
+    Draft,
+    /// The tag points at an ancestor of the previous release's commit
+    StaleTag,
+    /// The version is lower than the repository's latest release
+    Downgrade,
+}
+
+impl CheckRule {
+    pub const ALL: [CheckRule; 4] = [CheckRule::Prerelease, CheckRule::Draft, CheckRule::StaleTag, CheckRule::Downgrade];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CheckRule::Prerelease => "prerelease",
+            CheckRule::Draft => "draft",
+            CheckRule::StaleTag => "stale-tag",
+            CheckRule::Downgrade => "downgrade",
+        }
+    }
+}
+
+impl std::str::FromStr for CheckRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase();
+        CheckRule::ALL.into_iter()
+            .find(|rule| rule.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = CheckRule::ALL.iter().map(CheckRule::name).collect();
+                format!("Unknown check rule: {} (expected one of {})", s, names.join(", "))
+            })
+    }
+}
+
+impl std::fmt::Display for CheckRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuleViolation {
+    pub rule: CheckRule,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BranchCheck {
+    pub name: String,
+    /// Head of the branch; `None` if the branch doesn't exist
+    pub sha: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegistryCheck {
+    pub registry: RegistryKind,
+    pub package: String,
+    pub found: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageCheck {
+    pub image: String,
+    /// Manifest digest of the version's tag; `None` if the tag doesn't exist
+    pub digest: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetCheck {
+    pub pattern: String,
+    /// Names of the uploaded assets matching `pattern`; empty if it is missing
+    pub matched: Vec<String>,
+}
+
+impl RepositoryCheck {
+    pub fn new(repository: &str) -> Self {
+        Self {
+            repository: repository.to_string(),
+            status: CheckStatus::Found,
+            tag: None,
+            release: None,
+            branch: None,
+            registry: None,
+            image: None,
+            assets: vec![],
+            retired_after: None,
+            warnings: vec![],
+            violations: vec![],
+        }
+    }
+
+    pub fn archived(repository: &str) -> Self {
+        Self { status: CheckStatus::Archived, ..Self::new(repository) }
+    }
+
+    pub fn retired(repository: &str, retired_after: &str) -> Self {
+        Self { status: CheckStatus::Retired, retired_after: Some(retired_after.to_string()), ..Self::new(repository) }
+    }
+
+    /// Set `status` from the checks that were run.
+    pub fn finish(mut self) -> Self {
+        let missing = (self.tag.is_some() && self.release.is_none())
+            || self.branch.as_ref().is_some_and(|b| b.sha.is_none())
+            || self.registry.as_ref().is_some_and(|r| !r.found)
+            || self.image.as_ref().is_some_and(|i| i.digest.is_none())
+            || self.assets.iter().any(|a| a.matched.is_empty());
+        if missing {
+            self.status = CheckStatus::NotFound;
+        }
+        self
+    }
+
+    /// Status column: ✓ when everything was found, ✗ when something is missing.
+    fn status_cell(&self) -> String {
+        match self.status {
+            CheckStatus::Found => "✓ released".to_string(),
+            CheckStatus::NotFound => "✗ missing".to_string(),
+            CheckStatus::Archived => "- archived".to_string(),
+            CheckStatus::Retired => "- retired".to_string(),
+        }
+    }
+
+    /// Tag column: the release's tag (with the tag asked for if it differs), or the branch head.
+    fn tag_cell(&self) -> String {
+        if let Some(branch) = &self.branch {
+            return match &branch.sha {
+                Some(sha) => format!("{}@{}", branch.name, short_sha(sha)),
+                None => format!("{} (no branch)", branch.name),
+            };
+        }
+        match (&self.tag, &self.release) {
+            (Some(tag), Some(release)) if release.tag != *tag => format!("{} (as {})", tag, release.tag),
+            (Some(tag), _) => tag.clone(),
+            (None, _) => "-".to_string(),
+        }
+    }
+
+    fn date_cell(&self) -> String {
+        self.release.as_ref()
+            .and_then(|release| release.published_at)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    fn flags_cell(&self) -> String {
+        let Some(release) = &self.release else {
+            return String::new();
+        };
+        let flags: Vec<&str> = [(release.draft, "draft"), (release.prerelease, "prerelease")].into_iter()
+            .filter_map(|(set, flag)| set.then_some(flag))
+            .collect();
+        flags.join(", ")
+    }
+
+    /// Registry, image, asset and retirement details.
+    fn notes_cell(&self, version: &str) -> String {
+        let mark = |found: bool| if found { "✓" } else { "✗" };
+        let mut notes = Vec::new();
+        if let Some(after) = &self.retired_after {
+            notes.push(format!("after {}", after));
+        }
+        if let Some(registry) = &self.registry {
+            notes.push(format!("{} {} {} on {}", mark(registry.found), registry.package, version, registry.registry));
+        }
+        if let Some(image) = &self.image {
+            notes.push(format!("{} image {}:{}", mark(image.digest.is_some()), image.image, version));
+        }
+        if !self.assets.is_empty() {
+            let missing: Vec<&str> = self.assets.iter()
+                .filter(|a| a.matched.is_empty())
+                .map(|a| a.pattern.as_str())
+                .collect();
+            if missing.is_empty() {
+                let count: usize = self.assets.iter().map(|a| a.matched.len()).sum();
+                notes.push(format!("✓ {} asset{}", count, if count == 1 { "" } else { "s" }));
+            } else {
+                notes.push(format!("✗ no asset {}", missing.join(", ")));
+            }
+        }
+        notes.join("; ")
+    }
+}
+
+/// Checks a repository's release of a version, along with its package and image when the
+/// repository's settings name them.
+pub struct ReleaseChecker<'a> {
+    client: &'a GitHubClient,
+    registries: &'a RegistryClient,
+    /// Set when container images should be checked too
+    images: Option<&'a OciClient>,
+    /// Whether releases are checked for their configured assets
+    assets: bool,
+    include_archived: bool,
+    disabled_rules: Vec<CheckRule>,
+    /// How the previous release is found for the `stale-tag` rule
+    date_source: DateSource,
+    strategy: PreviousStrategy,
+}
+
+impl<'a> ReleaseChecker<'a> {
+    pub fn new(client: &'a GitHubClient, registries: &'a RegistryClient, images: Option<&'a OciClient>, include_archived: bool) -> Self {
+        Self {
+            client,
+            registries,
+            images,
+            assets: false,
+            include_archived,
+            disabled_rules: vec![],
+            date_source: DateSource::default(),
+            strategy: PreviousStrategy::default(),
+        }
+    }
+
+    /// Skip these rules; every rule applies by default.
+    pub fn with_disabled_rules(mut self, rules: Vec<CheckRule>) -> Self {
+        self.disabled_rules = rules;
+        self
+    }
+
+    /// Check that each release has an uploaded asset matching every pattern in its
+    /// repository's `assets` setting.
+    pub fn with_assets(mut self, assets: bool) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    /// Find the previous release the way `generate` does.
+    pub fn with_previous_release(mut self, date_source: DateSource, strategy: PreviousStrategy) -> Self {
+        self.date_source = date_source;
+        self.strategy = strategy;
+        self
+    }
+
+    fn applies(&self, rule: CheckRule) -> bool {
+        !self.disabled_rules.contains(&rule)
+    }
+
+    pub async fn check(&self, repo: &str, settings: &RepoSettings, version: &str) -> Result<RepositoryCheck> {
+        if settings.retired_in(version) {
+            return Ok(RepositoryCheck::retired(repo, settings.retired_after.as_deref().unwrap_or_default()));
+        }
+        let info = self.client.get_repository(repo).await?;
+        if !self.include_archived && info.as_ref().is_some_and(|info| info.archived) {
+            return Ok(RepositoryCheck::archived(repo));
+        }
+
+        let mut check = RepositoryCheck::new(repo);
+        if settings.ref_mode == RefMode::Branch {
+            let branch = settings.branch.clone()
+                .or_else(|| info.and_then(|info| info.default_branch))
+                .unwrap_or_else(|| "HEAD".to_string());
+            let sha = self.client.resolve_ref_sha(repo, &branch).await?;
+            check.branch = Some(BranchCheck { name: branch, sha });
+        } else if !(settings.registry_only && settings.registry.is_some()) {
+            let tag = settings.tag_for(version);
+            if let Some(release) = self.client.get_release(repo, &tag).await? {
+                check.violations = self.violations(repo, settings, version, &release).await?;
+                if self.assets {
+                    check.assets = expected_assets(&release, &settings.asset_patterns(version));
+                }
+                check.release = Some(ReleaseFound {
+                    sha: self.client.resolve_ref_sha(repo, &release.tag_name).await?,
+                    tag: release.tag_name,
+                    published_at: release.published_at,
+                    prerelease: release.prerelease,
+                    draft: release.draft,
+                });
+            }
+            check.tag = Some(tag);
+        }
+
+        if let Some(registry) = settings.registry {
+            let package = settings.package.clone().unwrap_or_else(|| repo.to_string());
+            let found = self.registries.version_exists(registry, &package, version).await?;
+            check.registry = Some(RegistryCheck { registry, package, found });
+        }
+        if let (Some(images), Some(image)) = (self.images, &settings.image) {
+            let digest = images.manifest_digest(image, version).await?;
+            check.image = Some(ImageCheck { image: image.clone(), digest });
+        }
+        Ok(check.finish())
+    }
+
+    /// The rules `release` breaks. A prerelease is expected when the version is one itself
+    /// (`v2.0.0-rc.1`).
+    async fn violations(&self, repo: &str, settings: &RepoSettings, version: &str, release: &Release) -> Result<Vec<RuleViolation>> {
+        let tag = &release.tag_name;
+        let mut violations = Vec::new();
+        let mut violate = |rule: CheckRule, message: String| violations.push(RuleViolation { rule, message });
+
+        if self.applies(CheckRule::Draft) && release.draft {
+            violate(CheckRule::Draft, format!("{}: {} is still a draft", repo, tag));
+        }
+        let prerelease_version = [version, tag.as_str()].into_iter()
+            .any(|v| tag_version(v).is_some_and(|v| !v.pre.is_empty()));
+        if self.applies(CheckRule::Prerelease) && release.prerelease && !prerelease_version {
+            violate(CheckRule::Prerelease, format!("{}: {} is marked as a prerelease", repo, tag));
+        }
+
+        if self.applies(CheckRule::StaleTag) {
+            if let Some(previous) = self.client.get_previous_release(repo, release, Some(settings), self.date_source, self.strategy).await? {
+                let comparison = self.client.compare(repo, &previous.tag_name, tag).await?;
+                if comparison.status == "behind" {
+                    violate(CheckRule::StaleTag, format!(
+                        "{}: {} points at a commit {} behind the previous release {}",
+                        repo, tag, comparison.behind_by, previous.tag_name
+                    ));
+                }
+            }
+        }
+
+        if self.applies(CheckRule::Downgrade) {
+            if let Some(latest) = self.client.get_latest_release(repo).await? {
+                if let (Some(latest_version), Some(current)) = (loose_version(&latest.tag_name), loose_version(tag)) {
+                    if latest_version > current {
+                        violate(CheckRule::Downgrade, format!("{}: {} is lower than the latest release {}", repo, tag, latest.tag_name));
+                    }
+                }
+            }
+        }
+        Ok(violations)
+    }
+}
+
+/// Match `release`'s assets against each pattern. Assets whose upload didn't finish
+/// (any state but `uploaded`) don't count, and invalid patterns match nothing.
+fn expected_assets(release: &Release, patterns: &[String]) -> Vec<AssetCheck> {
+    patterns.iter()
+        .map(|pattern| {
+            let glob = glob::Pattern::new(pattern).ok();
+            let matched = release.assets.iter()
+                .filter(|asset| asset.state == "uploaded")
+                .filter(|asset| glob.as_ref().is_some_and(|glob| glob.matches(&asset.name)))
+                .map(|asset| asset.name.clone())
+                .collect();
+            AssetCheck { pattern: pattern.clone(), matched }
+        })
+        .collect()
+}
+
+impl CheckReport {
+    /// Whether every repository that wasn't skipped has the version.
+    pub fn is_complete(&self) -> bool {
+        !self.repositories.iter().any(|r| r.status == CheckStatus::NotFound)
+    }
+
+    /// Repositories that were checked, and how many of them have the version.
+    pub fn counts(&self) -> (usize, usize) {
+        let checked: Vec<&RepositoryCheck> = self.repositories.iter()
+            .filter(|r| matches!(r.status, CheckStatus::Found | CheckStatus::NotFound))
+            .collect();
+        let released = checked.iter().filter(|r| r.status == CheckStatus::Found).count();
+        (released, checked.len())
+    }
+
+    /// An aligned table with a row per repository, moved tags and broken rules flagged by ⚠
+    /// below it, and a `17/20 released` summary line.
+    pub fn to_table(&self) -> String {
+        let header = ["REPOSITORY", "STATUS", "TAG", "RELEASED", "FLAGS", "NOTES"].map(String::from);
+        let rows: Vec<[String; 6]> = self.repositories.iter()
+            .map(|r| [r.repository.clone(), r.status_cell(), r.tag_cell(), r.date_cell(), r.flags_cell(), r.notes_cell(&self.version)])
+            .collect();
+
+        let mut widths = header.clone().map(|cell| cell.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut output = String::new();
+        for row in std::iter::once(&header).chain(&rows) {
+            let cells: Vec<String> = row.iter().zip(widths)
+                .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+                .collect();
+            output.push_str(cells.join("  ").trim_end());
+            output.push('\n');
+        }
+
+        let warnings: Vec<String> = self.repositories.iter()
+            .flat_map(|r| r.warnings.iter().cloned().chain(r.violations.iter().map(|v| format!("{} [{}]", v.message, v.rule))))
+            .collect();
+        if !warnings.is_empty() {
+            output.push('\n');
+            for warning in warnings {
+                output.push_str(&format!("⚠ {}\n", warning));
+            }
+        }
+
+        let (released, checked) = self.counts();
+        let skipped = self.repositories.len() - checked;
+        output.push_str(&format!("\n{}/{} released", released, checked));
+        if skipped > 0 {
+            output.push_str(&format!(", {} skipped", skipped));
+        }
+        output.push('\n');
+        output
+    }
+}
@@ -1,8 +1,12 @@
-use crate::github::types::CommitInfo;
+use crate::config::types::{ParserKind, RepoSettings};
+use crate::github::types::{CommitAuthor, CommitInfo, Label, PullRequest};
+use anyhow::{Context, Result};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum CommitType {
     Feature,
     Fix,
@@ -35,6 +39,140 @@ impl fmt::Display for CommitType {
     }
 }
 
+impl CommitType {
+    /// Map a free-form type keyword (`feat`, `BUGFIX`, `Docs`, ...) to a commit type.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword.trim().to_lowercase().as_str() {
+            "feat" | "feature" | "features" => Some(CommitType::Feature),
+            "fix" | "bugfix" | "bug" | "hotfix" => Some(CommitType::Fix),
+            "docs" | "doc" | "documentation" => Some(CommitType::Documentation),
+            "perf" | "performance" => Some(CommitType::Performance),
+            "refactor" | "refactoring" => Some(CommitType::Refactor),
+            "test" | "tests" => Some(CommitType::Test),
+            "build" => Some(CommitType::Build),
+            "ci" | "cd" => Some(CommitType::CI),
+            "chore" => Some(CommitType::Chore),
+            "style" => Some(CommitType::Style),
+            _ => None,
+        }
+    }
+
+    /// Map a pull request label to a commit type. Accepts type keywords plus GitHub's default
+    /// labels (`enhancement`, `bug`, `documentation`, `dependencies`), optionally namespaced
+    /// as in `type: feature` or `kind/bug`.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let label = label.trim().to_lowercase();
+        let name = label.rsplit([':', '/']).next().unwrap_or(&label).trim();
+        match name {
+            "enhancement" => Some(CommitType::Feature),
+            "dependencies" => Some(CommitType::Build),
+            _ => Self::from_keyword(name),
+        }
+    }
+}
+
+/// Labels that mark a pull request as a breaking change.
+const BREAKING_LABELS: &[&str] = &["breaking", "breaking-change", "breaking change"];
+
+/// How a repository's commit messages encode their type.
+#[derive(Debug, Clone, Default)]
+pub enum CommitParser {
+    /// `feat(scope): message`
+    #[default]
+    Conventional,
+    /// `[FEATURE] message`, `[BUGFIX] message`
+    BracketTags,
+    /// Named groups `type` and `message` (optional `breaking`) on the first line
+    CustomRegex(Regex),
+    /// `✨ message` or `:sparkles: message`
+    Gitmoji,
+}
+
+impl CommitParser {
+    pub fn from_settings(settings: &RepoSettings) -> Result<Self> {
+        match settings.parser {
+            ParserKind::Conventional => Ok(CommitParser::Conventional),
+            ParserKind::BracketTags => Ok(CommitParser::BracketTags),
+            ParserKind::Gitmoji => Ok(CommitParser::Gitmoji),
+            ParserKind::CustomRegex => {
+                let pattern = settings.parser_pattern.as_deref()
+                    .context("parser = \"custom-regex\" requires a parser_pattern")?;
+                let regex = Regex::new(pattern)
+                    .with_context(|| format!("Invalid parser_pattern: {}", pattern))?;
+                Ok(CommitParser::CustomRegex(regex))
+            }
+        }
+    }
+}
+
+/// Gitmoji emoji and shortcode pairs mapped to the closest commit type.
+const GITMOJI: &[(&str, &str, CommitType)] = &[
+    ("✨", ":sparkles:", CommitType::Feature),
+    ("🎉", ":tada:", CommitType::Feature),
+    ("🚩", ":triangular_flag_on_post:", CommitType::Feature),
+    ("🐛", ":bug:", CommitType::Fix),
+    ("🚑", ":ambulance:", CommitType::Fix),
+    ("🩹", ":adhesive_bandage:", CommitType::Fix),
+    ("🔒", ":lock:", CommitType::Fix),
+    ("✏", ":pencil2:", CommitType::Fix),
+    ("📝", ":memo:", CommitType::Documentation),
+    ("💡", ":bulb:", CommitType::Documentation),
+    ("📄", ":page_facing_up:", CommitType::Documentation),
+    ("⚡", ":zap:", CommitType::Performance),
+    ("♻", ":recycle:", CommitType::Refactor),
+    ("🚚", ":truck:", CommitType::Refactor),
+    ("🏗", ":building_construction:", CommitType::Refactor),
+    ("🔥", ":fire:", CommitType::Refactor),
+    ("✅", ":white_check_mark:", CommitType::Test),
+    ("🧪", ":test_tube:", CommitType::Test),
+    ("📦", ":package:", CommitType::Build),
+    ("➕", ":heavy_plus_sign:", CommitType::Build),
+    ("➖", ":heavy_minus_sign:", CommitType::Build),
+    ("⬆", ":arrow_up:", CommitType::Build),
+    ("⬇", ":arrow_down:", CommitType::Build),
+    ("📌", ":pushpin:", CommitType::Build),
+    ("👷", ":construction_worker:", CommitType::CI),
+    ("💚", ":green_heart:", CommitType::CI),
+    ("🔧", ":wrench:", CommitType::Chore),
+    ("🔨", ":hammer:", CommitType::Chore),
+    ("🙈", ":see_no_evil:", CommitType::Chore),
+    ("🔖", ":bookmark:", CommitType::Chore),
+    ("🎨", ":art:", CommitType::Style),
+    ("💄", ":lipstick:", CommitType::Style),
+    ("🚨", ":rotating_light:", CommitType::Style),
+];
+
+const GITMOJI_BREAKING: (&str, &str) = ("💥", ":boom:");
+
+/// Ordering of commits within each category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitSort {
+    /// Keep the order returned by the API
+    #[default]
+    Api,
+    /// Newest first
+    Date,
+    Scope,
+    Pr,
+    Alphabetical,
+}
+
+impl std::str::FromStr for CommitSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "api" | "none" => Ok(CommitSort::Api),
+            "date" => Ok(CommitSort::Date),
+            "scope" => Ok(CommitSort::Scope),
+            "pr" | "pr_number" => Ok(CommitSort::Pr),
+            "alphabetical" | "alpha" => Ok(CommitSort::Alphabetical),
+            _ => Err(format!("Unknown commit sort: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnrichedCommit {
     pub sha: String,
@@ -42,38 +180,183 @@ pub struct EnrichedCommit {
     pub author: String,
     pub date: chrono::DateTime<chrono::Utc>,
     pub commit_type: Option<CommitType>,
+    /// Section title from a pull request label mapped under `[labels]`; takes precedence over
+    /// `commit_type` when entries are grouped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Conventional commit scope, e.g. `api` in `feat(api): ...`
+    #[serde(default)]
+    pub scope: Option<String>,
     pub breaking: bool,
     pub pr_number: Option<u64>,
     pub issues: Vec<u64>,
+    /// Issue tracker keys such as `API-123`
+    #[serde(default)]
+    pub issue_keys: Vec<String>,
+    pub verified: bool,
+    /// `issues` as resolved from GitHub, when issues were requested; pull requests and
+    /// unreadable numbers are left out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_issues: Vec<IssueDetails>,
+}
+
+/// An issue referenced by a commit, with its title and state at aggregation time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueDetails {
+    pub number: u64,
+    pub title: String,
+    /// `open` or `closed`
+    pub state: String,
+    pub url: String,
+}
+
+impl EnrichedCommit {
+    /// Whether the entry is listed under a label section or a commit type.
+    pub fn is_categorized(&self) -> bool {
+        self.section.is_some() || self.commit_type.is_some()
+    }
 }
 
 pub struct CommitAnalyzer;
 
 impl CommitAnalyzer {
     pub fn analyze_commits(commits: Vec<CommitInfo>) -> Vec<EnrichedCommit> {
+        Self::analyze_commits_with(commits, &CommitParser::Conventional)
+    }
+
+    pub fn analyze_commits_with(commits: Vec<CommitInfo>, parser: &CommitParser) -> Vec<EnrichedCommit> {
         commits
             .into_iter()
-            .map(|commit| Self::analyze_single_commit(commit))
+            .map(|commit| Self::analyze_single_commit(commit, parser))
             .collect()
     }
 
-    fn analyze_single_commit(commit: CommitInfo) -> EnrichedCommit {
-        let (commit_type, breaking) = Self::parse_commit_message(&commit.message);
+    /// One entry per merged pull request, for squash-merge repositories where pull requests and
+    /// commits are 1:1. The title is the message, categorized by its labels (see `apply_labels`)
+    /// and otherwise parsed like a commit message; the body is searched for issues.
+    pub fn analyze_pull_requests(
+        prs: Vec<PullRequest>,
+        parser: &CommitParser,
+        label_sections: &BTreeMap<String, String>,
+    ) -> Vec<EnrichedCommit> {
+        prs.into_iter()
+            .map(|pr| {
+                let login = pr.user.map(|u| u.login).unwrap_or_default();
+                let info = CommitInfo {
+                    sha: pr.merge_commit_sha.unwrap_or_default(),
+                    message: match &pr.body {
+                        Some(body) => format!("{}\n\n{}", pr.title, body),
+                        None => pr.title.clone(),
+                    },
+                    author: CommitAuthor { name: login.clone(), email: String::new(), username: Some(login) },
+                    date: pr.merged_at.unwrap_or_default(),
+                    verified: false,
+                };
+                let mut entry = Self::analyze_single_commit(info, parser);
+                Self::apply_labels(&mut entry, &pr.labels, label_sections, true);
+                entry.pr_number = Some(pr.number);
+                entry.issues.retain(|&n| n != pr.number);
+                entry
+            })
+            .collect()
+    }
+
+    /// Categorize an entry by its pull request's labels, ahead of whatever its message says. The
+    /// first label with a title in `label_sections` (keys compared case-insensitively) sets the
+    /// section. With `by_type`, the first type label (see `CommitType::from_label`) also sets the
+    /// commit type, and a breaking label marks the entry breaking.
+    pub fn apply_labels(entry: &mut EnrichedCommit, labels: &[Label], label_sections: &BTreeMap<String, String>, by_type: bool) {
+        let section = labels.iter().find_map(|label| {
+            label_sections.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(label.name.trim()))
+                .map(|(_, title)| title.clone())
+        });
+        if section.is_some() {
+            entry.section = section;
+        }
+        if !by_type {
+            return;
+        }
+        if let Some(label_type) = labels.iter().find_map(|l| CommitType::from_label(&l.name)) {
+            entry.commit_type = Some(label_type);
+        }
+        entry.breaking |= labels.iter().any(|l| BREAKING_LABELS.contains(&l.name.to_lowercase().as_str()));
+    }
+
+    /// `text` without control characters other than newlines and tabs (a CRLF becomes a plain
+    /// newline), which legacy history sometimes carries and which garble rendered output. Terminal
+    /// color codes such as `ESC[31m` are dropped whole.
+    pub fn sanitize_text(text: &str) -> String {
+        let mut sanitized = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\u{1b}' && chars.peek() == Some(&'[') {
+                // Parameters and intermediates, up to and including the final byte
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) && c != '[' {
+                        break;
+                    }
+                }
+            } else if c == '\n' || c == '\t' || !c.is_control() {
+                sanitized.push(c);
+            }
+        }
+        sanitized
+    }
+
+    fn analyze_single_commit(mut commit: CommitInfo, parser: &CommitParser) -> EnrichedCommit {
+        commit.message = Self::sanitize_text(&commit.message);
+        commit.author.name = Self::sanitize_text(&commit.author.name);
+        let parsed = match parser {
+            CommitParser::Conventional => None,
+            CommitParser::BracketTags => Some(Self::parse_bracket_tags(&commit.message)),
+            CommitParser::CustomRegex(regex) => Some(Self::parse_custom_regex(&commit.message, regex)),
+            CommitParser::Gitmoji => Some(Self::parse_gitmoji(&commit.message)),
+        };
+        // Tagged repositories still get the odd `fix: ...` commit; read those the conventional way
+        let conventional = match &parsed {
+            None => true,
+            Some((commit_type, _, _)) => commit_type.is_none()
+                && matches!(parser, CommitParser::BracketTags | CommitParser::Gitmoji)
+                && Self::extract_type(&commit.message).is_some(),
+        };
+        let (commit_type, breaking, message) = match parsed {
+            Some(parsed) if !conventional => parsed,
+            _ => Self::parse_conventional(&commit.message),
+        };
+        let scope = match parser {
+            CommitParser::CustomRegex(regex) => regex
+                .captures(commit.message.lines().next().unwrap_or(""))
+                .and_then(|caps| caps.name("scope").map(|m| m.as_str().to_string())),
+            _ if conventional => Self::extract_scope(&commit.message),
+            _ => None,
+        };
         let issues = Self::extract_issues(&commit.message);
+        let issue_keys = Self::extract_issue_keys(&commit.message);
         let pr_number = Self::extract_pr_number(&commit.message);
 
         EnrichedCommit {
             sha: commit.sha.clone(),
-            message: Self::clean_message(&commit.message),
+            message,
             author: commit.author.username.unwrap_or(commit.author.name),
             date: commit.date,
             commit_type,
+            section: None,
+            scope,
             breaking,
             pr_number,
             issues,
+            issue_keys,
+            verified: commit.verified,
+            resolved_issues: Vec::new(),
         }
     }
 
+    fn parse_conventional(message: &str) -> (Option<CommitType>, bool, String) {
+        let (commit_type, breaking) = Self::parse_commit_message(message);
+        (commit_type, breaking, Self::clean_message(message))
+    }
+
     fn parse_commit_message(message: &str) -> (Option<CommitType>, bool) {
         let lower = message.to_lowercase();
         let first_line = lower.lines().next().unwrap_or("");
@@ -109,6 +392,83 @@ impl CommitAnalyzer {
         (commit_type, breaking)
     }
 
+    /// Parse leading `[TAG]` groups, e.g. `[FEATURE][BREAKING] Add export`.
+    fn parse_bracket_tags(message: &str) -> (Option<CommitType>, bool, String) {
+        let mut rest = message.lines().next().unwrap_or("").trim();
+        let mut commit_type = None;
+        let mut breaking = message.contains("BREAKING CHANGE");
+
+        while let Some(tag_end) = rest.strip_prefix('[').and_then(|r| r.find(']')) {
+            let tag = &rest[1..tag_end + 1];
+            if tag.eq_ignore_ascii_case("breaking") {
+                breaking = true;
+            } else if commit_type.is_none() {
+                commit_type = CommitType::from_keyword(tag);
+            }
+            rest = rest[tag_end + 2..].trim_start();
+        }
+
+        (commit_type, breaking, Self::capitalize(rest))
+    }
+
+    /// Parse leading gitmoji, written either as emoji or `:shortcode:`.
+    fn parse_gitmoji(message: &str) -> (Option<CommitType>, bool, String) {
+        // Variation selectors are optional in practice (⚡ vs ⚡️), so match without them
+        let first_line = message.lines().next().unwrap_or("").replace('\u{FE0F}', "");
+        let mut rest = first_line.trim();
+        let mut commit_type = None;
+        let mut breaking = message.contains("BREAKING CHANGE");
+
+        loop {
+            if let Some(r) = rest.strip_prefix(GITMOJI_BREAKING.0).or_else(|| rest.strip_prefix(GITMOJI_BREAKING.1)) {
+                breaking = true;
+                rest = r.trim_start();
+                continue;
+            }
+
+            let matched = GITMOJI.iter().find_map(|(emoji, shortcode, mapped)| {
+                rest.strip_prefix(emoji)
+                    .or_else(|| rest.strip_prefix(shortcode))
+                    .map(|r| (r, mapped))
+            });
+
+            match matched {
+                Some((r, mapped)) => {
+                    if commit_type.is_none() {
+                        commit_type = Some(mapped.clone());
+                    }
+                    rest = r.trim_start();
+                }
+                None => break,
+            }
+        }
+
+        (commit_type, breaking, Self::capitalize(rest))
+    }
+
+    fn parse_custom_regex(message: &str, regex: &Regex) -> (Option<CommitType>, bool, String) {
+        let first_line = message.lines().next().unwrap_or("");
+        let breaking = message.contains("BREAKING CHANGE");
+
+        match regex.captures(first_line) {
+            Some(caps) => {
+                let commit_type = caps.name("type").and_then(|m| CommitType::from_keyword(m.as_str()));
+                let breaking = breaking || caps.name("breaking").map_or(false, |m| !m.as_str().is_empty());
+                let cleaned = caps.name("message").map_or(first_line, |m| m.as_str()).trim();
+                (commit_type, breaking, Self::capitalize(cleaned))
+            }
+            None => (None, breaking, Self::capitalize(first_line.trim())),
+        }
+    }
+
+    fn capitalize(message: &str) -> String {
+        let mut chars = message.chars();
+        match chars.next() {
+            None => String::new(),
+            Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        }
+    }
+
     fn clean_message(message: &str) -> String {
         let first_line = message.lines().next().unwrap_or("");
         
@@ -137,11 +497,7 @@ impl CommitAnalyzer {
             .trim();
 
         // Capitalize first letter
-        let mut chars = cleaned.chars();
-        match chars.next() {
-            None => String::new(),
-            Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
-        }
+        Self::capitalize(cleaned)
     }
 
     fn extract_issues(message: &str) -> Vec<u64> {
@@ -163,6 +519,68 @@ impl CommitAnalyzer {
         issues
     }
 
+    /// Type of a conventional commit header such as `fix(api)!: ...`; `None` for other messages.
+    fn extract_type(message: &str) -> Option<CommitType> {
+        let re = regex::Regex::new(r"^([A-Za-z]+)(?:\([^)]*\))?!?:").unwrap();
+        re.captures(message.lines().next().unwrap_or(""))
+            .and_then(|cap| CommitType::from_keyword(&cap[1]))
+    }
+
+    fn extract_scope(message: &str) -> Option<String> {
+        let re = regex::Regex::new(r"^[A-Za-z]+\(([^)]+)\)!?:").unwrap();
+        re.captures(message.lines().next().unwrap_or(""))
+            .map(|cap| cap[1].trim().to_string())
+    }
+
+    /// Closed issues resolved on `commits`, each once, by number.
+    pub fn closed_issues(commits: &[EnrichedCommit]) -> Vec<&IssueDetails> {
+        let mut closed: Vec<&IssueDetails> = commits.iter()
+            .flat_map(|c| &c.resolved_issues)
+            .filter(|issue| issue.state == "closed")
+            .collect();
+        closed.sort_by_key(|issue| issue.number);
+        closed.dedup_by_key(|issue| issue.number);
+        closed
+    }
+
+    /// Order commits in place; every strategy falls back to date and SHA so output is stable.
+    pub fn sort_commits(commits: &mut [EnrichedCommit], sort: CommitSort) {
+        let newest_first = |a: &EnrichedCommit, b: &EnrichedCommit| {
+            b.date.cmp(&a.date).then_with(|| a.sha.cmp(&b.sha))
+        };
+
+        match sort {
+            CommitSort::Api => {}
+            CommitSort::Date => commits.sort_by(newest_first),
+            CommitSort::Scope => commits.sort_by(|a, b| {
+                // Unscoped commits sort after scoped ones
+                (a.scope.is_none(), &a.scope).cmp(&(b.scope.is_none(), &b.scope))
+                    .then_with(|| newest_first(a, b))
+            }),
+            CommitSort::Pr => commits.sort_by(|a, b| {
+                (a.pr_number.is_none(), a.pr_number).cmp(&(b.pr_number.is_none(), b.pr_number))
+                    .then_with(|| newest_first(a, b))
+            }),
+            CommitSort::Alphabetical => commits.sort_by(|a, b| {
+                a.message.to_lowercase().cmp(&b.message.to_lowercase())
+                    .then_with(|| newest_first(a, b))
+            }),
+        }
+    }
+
+    fn extract_issue_keys(message: &str) -> Vec<String> {
+        // Look for Jira-style keys like API-123
+        let re = regex::Regex::new(r"\b([A-Z][A-Z0-9]+-\d+)\b").unwrap();
+
+        let mut keys: Vec<String> = re.captures_iter(message)
+            .map(|cap| cap[1].to_string())
+            .collect();
+
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
     fn extract_pr_number(message: &str) -> Option<u64> {
         // Look for patterns like (#123) at the end of commit messages
         let re = regex::Regex::new(r"\(#(\d+)\)").unwrap();
@@ -189,4 +607,135 @@ impl CommitAnalyzer {
         
         grouped
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::{CommitAnalyzer, CommitParser, CommitType, EnrichedCommit};
+    use crate::aggregator::AggregatorConfig;
+    use crate::config::types::Config;
+    use crate::config::validate::validate;
+    use crate::github::types::{CommitAuthor, CommitInfo};
+    use regex::Regex;
+
+    fn analyze(message: &str, parser: &CommitParser) -> EnrichedCommit {
+        let commit = CommitInfo {
+            sha: "abc1234".to_string(),
+            message: message.to_string(),
+            author: CommitAuthor { name: "Ada".to_string(), email: "ada@example.com".to_string(), username: None },
+            date: chrono::DateTime::UNIX_EPOCH,
+            verified: false,
+        };
+        CommitAnalyzer::analyze_commits_with(vec![commit], parser).remove(0)
+    }
+
+    #[test]
+    fn gitmoji_reads_emoji_and_shortcodes() {
+        assert_eq!(
+            CommitAnalyzer::parse_gitmoji("✨ add dark mode"),
+            (Some(CommitType::Feature), false, "Add dark mode".to_string())
+        );
+        assert_eq!(
+            CommitAnalyzer::parse_gitmoji(":bug: fix the login redirect"),
+            (Some(CommitType::Fix), false, "Fix the login redirect".to_string())
+        );
+        // With and without the variation selector
+        assert_eq!(CommitAnalyzer::parse_gitmoji("♻️ split the router").0, Some(CommitType::Refactor));
+        assert_eq!(CommitAnalyzer::parse_gitmoji("♻ split the router").0, Some(CommitType::Refactor));
+        assert_eq!(
+            CommitAnalyzer::parse_gitmoji("💥 :sparkles: drop the v1 API"),
+            (Some(CommitType::Feature), true, "Drop the v1 API".to_string())
+        );
+    }
+
+    #[test]
+    fn gitmoji_leaves_unknown_emoji_uncategorized() {
+        assert_eq!(
+            CommitAnalyzer::parse_gitmoji("🦄 make it magical"),
+            (None, false, "🦄 make it magical".to_string())
+        );
+        assert_eq!(CommitAnalyzer::parse_gitmoji(":unicorn: make it magical").0, None);
+    }
+
+    #[test]
+    fn bracket_tags_read_type_and_breaking_in_any_case_and_order() {
+        assert_eq!(
+            CommitAnalyzer::parse_bracket_tags("[FEATURE] add export"),
+            (Some(CommitType::Feature), false, "Add export".to_string())
+        );
+        assert_eq!(
+            CommitAnalyzer::parse_bracket_tags("[BugFix] handle empty files"),
+            (Some(CommitType::Fix), false, "Handle empty files".to_string())
+        );
+        assert_eq!(
+            CommitAnalyzer::parse_bracket_tags("[BREAKING][feature] remove the v1 API"),
+            (Some(CommitType::Feature), true, "Remove the v1 API".to_string())
+        );
+        // The first tag naming a type wins
+        assert_eq!(
+            CommitAnalyzer::parse_bracket_tags("[docs] [Breaking] [test] rewrite the guide"),
+            (Some(CommitType::Documentation), true, "Rewrite the guide".to_string())
+        );
+    }
+
+    #[test]
+    fn untagged_messages_fall_back_to_conventional_parsing() {
+        for parser in [CommitParser::Gitmoji, CommitParser::BracketTags] {
+            let entry = analyze("fix: handle empty files", &parser);
+            assert_eq!(entry.commit_type, Some(CommitType::Fix));
+            assert_eq!(entry.message, "Handle empty files");
+
+            let entry = analyze("feat(api)!: rename the endpoints", &parser);
+            assert_eq!((entry.commit_type, entry.breaking), (Some(CommitType::Feature), true));
+            assert_eq!(entry.scope.as_deref(), Some("api"));
+
+            let entry = analyze("Update the readme", &parser);
+            assert_eq!(entry.commit_type, None);
+            assert_eq!(entry.message, "Update the readme");
+        }
+    }
+
+    #[test]
+    fn custom_regex_maps_named_groups_to_type_and_scope() {
+        let parser = CommitParser::CustomRegex(
+            Regex::new(r"^(?P<type>[A-Z]+)(?P<breaking>!?)-\d+ \((?P<scope>[a-z]+)\): (?P<message>.*)$").unwrap(),
+        );
+        let entry = analyze("BUGFIX-42 (billing): round invoice totals", &parser);
+        assert_eq!(entry.commit_type, Some(CommitType::Fix));
+        assert_eq!(entry.scope.as_deref(), Some("billing"));
+        assert_eq!(entry.message, "Round invoice totals");
+        assert!(!entry.breaking);
+
+        let entry = analyze("FEATURE!-7 (api): drop the v1 endpoints", &parser);
+        assert_eq!((entry.commit_type, entry.breaking), (Some(CommitType::Feature), true));
+
+        // A type the pattern captures but no category knows stays uncategorized
+        assert_eq!(analyze("SPIKE-3 (api): try gRPC", &parser).commit_type, None);
+    }
+
+    #[test]
+    fn custom_regex_passes_unmatched_messages_through() {
+        let parser = CommitParser::CustomRegex(Regex::new(r"^(?P<type>[A-Z]+)-\d+: (?P<message>.*)$").unwrap());
+        let entry = analyze("fix: not the team's convention\n\nBREAKING CHANGE: still noticed", &parser);
+        assert_eq!(entry.commit_type, None);
+        assert_eq!(entry.scope, None);
+        assert_eq!(entry.message, "Fix: not the team's convention");
+        assert!(entry.breaking);
+    }
+
+    #[test]
+    fn invalid_custom_regex_is_rejected_when_the_config_loads() {
+        let config: Config = toml::from_str(
+            r#"
+            [repos.settings.billing]
+            parser = "custom-regex"
+            parser_pattern = '^(?P<type>[A-Z]+-\d+: (?P<message>.*)$'
+            "#,
+        ).unwrap();
+        let error = AggregatorConfig::from_config(&config).unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid parser_pattern"), "{:#}", error);
+        assert!(validate(&config).iter().any(|problem| problem.starts_with("repos.settings.billing: Invalid parser_pattern")));
+
+        let config: Config = toml::from_str("[repos.settings.billing]\nparser = \"custom-regex\"\n").unwrap();
+        assert!(AggregatorConfig::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use crate::github::links::RepoLinks;
+use super::commit_analyzer::{CommitType, EnrichedCommit};
+
+/// Built-in bullet format, matching the default template's historical output.
+pub const DEFAULT_ENTRY_FORMAT: &str = "- {message} ([`{sha}`]({sha_url})) ({pr_link})";
+
+/// Built-in bullet format for `--mode prs`, where each entry is a pull request.
+pub const PR_ENTRY_FORMAT: &str = "- {message} ({pr_link}) — @{author}";
+
+/// Built-in bullet format for `--mode fragments`, where entries have no commit or author.
+pub const FRAGMENT_ENTRY_FORMAT: &str = "- {message} ({issues})";
+
+/// Per-category bullet formats with `{placeholder}` substitution.
+///
+/// Supported placeholders: `{title}`/`{message}`, `{sha}`, `{sha_url}`, `{author}`,
+/// `{pr_number}`, `{pr_url}`, `{pr_link}`, and `{issues}`. When the repository's links are known,
+/// `{pr_link}` and `{issues}` are Markdown links. Empty groups such as `()` left by missing
+/// values are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct EntryFormats {
+    default: Option<String>,
+    uncategorized: Option<String>,
+    by_type: HashMap<CommitType, String>,
+}
+
+impl EntryFormats {
+    /// Build from config keys: `default`, `other`, or a commit type keyword such as `feat`.
+    pub fn from_config(formats: &HashMap<String, String>) -> Result<Self> {
+        let mut entry_formats = EntryFormats::default();
+
+        for (key, format) in formats {
+            match key.as_str() {
+                "default" => entry_formats.default = Some(format.clone()),
+                "other" => entry_formats.uncategorized = Some(format.clone()),
+                _ => match CommitType::from_keyword(key) {
+                    Some(commit_type) => {
+                        entry_formats.by_type.insert(commit_type, format.clone());
+                    }
+                    None => bail!("Unknown entry format category: {}", key),
+                },
+            }
+        }
+
+        Ok(entry_formats)
+    }
+
+    /// Use `format` in place of the built-in default, unless a `default` was configured.
+    pub fn with_fallback(mut self, format: &str) -> Self {
+        self.default.get_or_insert_with(|| format.to_string());
+        self
+    }
+
+    fn format_for(&self, commit: &EnrichedCommit) -> &str {
+        let specific = match &commit.commit_type {
+            Some(commit_type) => self.by_type.get(commit_type),
+            None => self.uncategorized.as_ref(),
+        };
+
+        specific
+            .or(self.default.as_ref())
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_ENTRY_FORMAT)
+    }
+
+    /// Format `commit`'s bullet, linking it to pages in the repository behind `links` if given.
+    pub fn format(&self, commit: &EnrichedCommit, links: Option<&RepoLinks>) -> String {
+        let reference = |number: u64, url: Option<String>| match url {
+            Some(url) => format!("[#{}]({})", number, url),
+            None => format!("#{}", number),
+        };
+        let issues = commit.issues.iter()
+            .map(|&i| reference(i, links.map(|l| l.issue(i))))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let pr_url = commit.pr_number.zip(links).map(|(n, l)| l.pull(n));
+        let message = match links {
+            Some(links) => Self::link_references(&commit.message, links),
+            None => commit.message.clone(),
+        };
+
+        let entry = self.format_for(commit)
+            .replace("{title}", &message)
+            .replace("{message}", &message)
+            .replace("{sha_url}", &links.filter(|_| !commit.sha.is_empty()).map(|l| l.commit(&commit.sha)).unwrap_or_default())
+            .replace("{sha}", &commit.sha[..7.min(commit.sha.len())])
+            .replace("{author}", &commit.author)
+            .replace("{pr_number}", &commit.pr_number.map(|n| n.to_string()).unwrap_or_default())
+            .replace("{pr_url}", pr_url.as_deref().unwrap_or_default())
+            .replace("{pr_link}", &commit.pr_number.map(|n| reference(n, pr_url.clone())).unwrap_or_default())
+            .replace("{issues}", &issues);
+
+        Self::tidy(&entry)
+    }
+
+    /// Turn `#123` references in a commit message into links, so they point at the commit's
+    /// repository rather than wherever the notes end up being published.
+    fn link_references(message: &str, links: &RepoLinks) -> String {
+        let re = regex::Regex::new(r"(^|[\s(\[])#(\d+)\b").unwrap();
+        re.replace_all(message, |caps: &regex::Captures| {
+            let number: u64 = caps[2].parse().unwrap_or_default();
+            format!("{}[#{}]({})", &caps[1], &caps[2], links.issue(number))
+        })
+        .into_owned()
+    }
+
+    /// Drop empty brackets and doubled spaces left behind by missing values.
+    fn tidy(entry: &str) -> String {
+        let mut tidied = entry.replace("()", "").replace("[]", "");
+        while tidied.contains("  ") {
+            tidied = tidied.replace("  ", " ");
+        }
+        tidied.trim_end().to_string()
+    }
+}
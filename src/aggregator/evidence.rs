@@ -0,0 +1,200 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use super::release_fetcher::{AggregatedRelease, ComponentStatus, ReleaseAggregator};
+
+/// Audit bundle describing how a release train was produced and published.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvidenceBundle {
+    pub version: String,
+    pub org: String,
+    pub generated_at: DateTime<Utc>,
+    pub notes_sha256: String,
+    pub components: Vec<ComponentEvidence>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComponentEvidence {
+    pub repository: String,
+    pub released: bool,
+    pub tag: Option<String>,
+    pub previous_tag: Option<String>,
+    pub published_by: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub target_commitish: Option<String>,
+    pub release_url: Option<String>,
+    pub diff_url: Option<String>,
+    pub release_notes_sha256: Option<String>,
+    pub approvals: Vec<PullRequestApproval>,
+    pub commit_verification: VerificationStats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PullRequestApproval {
+    pub pr_number: u64,
+    pub approvers: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VerificationStats {
+    pub total: usize,
+    pub verified: usize,
+    pub unverified: usize,
+}
+
+pub struct EvidenceCollector;
+
+impl EvidenceCollector {
+    pub async fn collect(
+        aggregator: &ReleaseAggregator,
+        release: &AggregatedRelease,
+        notes: &str,
+    ) -> Result<EvidenceBundle> {
+        let client = aggregator.client();
+        let org = client.org().to_string();
+        let mut components = Vec::new();
+
+        for component in &release.components {
+            let repo = &component.repository;
+
+            let evidence = match &component.status {
+                ComponentStatus::Released {
+                    current_version,
+                    previous_version,
+                    commits,
+                    release_notes,
+                    ..
+                } => {
+                    // Re-fetch the release object for publisher and target details
+                    let gh_release = client.get_release(repo, current_version).await?;
+
+                    let mut pr_numbers: Vec<u64> = commits.iter().filter_map(|c| c.pr_number).collect();
+                    pr_numbers.sort_unstable();
+                    pr_numbers.dedup();
+
+                    let mut approvals = Vec::new();
+                    for pr_number in pr_numbers {
+                        let approvers = client.get_pull_request_approvals(repo, pr_number).await?;
+                        approvals.push(PullRequestApproval { pr_number, approvers });
+                    }
+
+                    let verified = commits.iter().filter(|c| c.verified).count();
+                    let diff_url = match previous_version {
+                        Some(prev) => format!("{}/compare/{}...{}", client.repo_url(repo), prev, current_version),
+                        None => format!("{}/commits/{}", client.repo_url(repo), current_version),
+                    };
+
+                    ComponentEvidence {
+                        repository: repo.clone(),
+                        released: true,
+                        tag: Some(current_version.clone()),
+                        previous_tag: previous_version.clone(),
+                        published_by: gh_release.as_ref().and_then(|r| r.author.clone()),
+                        published_at: gh_release.as_ref().and_then(|r| r.published_at),
+                        target_commitish: gh_release.as_ref().and_then(|r| r.target_commitish.clone()),
+                        release_url: gh_release.as_ref().and_then(|r| r.html_url.clone()),
+                        diff_url: Some(diff_url),
+                        release_notes_sha256: release_notes.as_deref().map(sha256_hex),
+                        approvals,
+                        commit_verification: VerificationStats {
+                            total: commits.len(),
+                            verified,
+                            unverified: commits.len() - verified,
+                        },
+                    }
+                }
+                ComponentStatus::NoRelease { .. } | ComponentStatus::NotYetReleased { .. } => ComponentEvidence {
+                    repository: repo.clone(),
+                    released: false,
+                    tag: None,
+                    previous_tag: None,
+                    published_by: None,
+                    published_at: None,
+                    target_commitish: None,
+                    release_url: None,
+                    diff_url: None,
+                    release_notes_sha256: None,
+                    approvals: vec![],
+                    commit_verification: VerificationStats::default(),
+                },
+            };
+
+            components.push(evidence);
+        }
+
+        Ok(EvidenceBundle {
+            version: release.version.clone(),
+            org,
+            generated_at: Utc::now(),
+            notes_sha256: sha256_hex(notes),
+            components,
+        })
+    }
+}
+
+impl EvidenceBundle {
+    pub fn to_markdown(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("# Release Evidence: {}\n\n", self.version));
+        output.push_str(&format!("- **Organization:** {}\n", self.org));
+        output.push_str(&format!("- **Generated:** {}\n", self.generated_at.to_rfc3339()));
+        output.push_str(&format!("- **Release notes SHA-256:** `{}`\n\n", self.notes_sha256));
+
+        output.push_str("| Repository | Tag | Published by | Published at | Approved PRs | Verified commits |\n");
+        output.push_str("|---|---|---|---|---|---|\n");
+        for component in &self.components {
+            let approved = component.approvals.iter().filter(|a| !a.approvers.is_empty()).count();
+            output.push_str(&format!(
+                "| {} | {} | {} | {} | {}/{} | {}/{} |\n",
+                component.repository,
+                component.tag.as_deref().unwrap_or("—"),
+                component.published_by.as_deref().unwrap_or("—"),
+                component.published_at.map(|d| d.to_rfc3339()).unwrap_or_else(|| "—".to_string()),
+                approved,
+                component.approvals.len(),
+                component.commit_verification.verified,
+                component.commit_verification.total,
+            ));
+        }
+        output.push('\n');
+
+        for component in self.components.iter().filter(|c| c.released) {
+            output.push_str(&format!("## {}\n\n", component.repository));
+            if let Some(url) = &component.release_url {
+                output.push_str(&format!("- **Release:** {}\n", url));
+            }
+            if let Some(url) = &component.diff_url {
+                output.push_str(&format!("- **Diff:** {}\n", url));
+            }
+            if let Some(sha) = &component.target_commitish {
+                output.push_str(&format!("- **Target:** `{}`\n", sha));
+            }
+            if let Some(hash) = &component.release_notes_sha256 {
+                output.push_str(&format!("- **Release body SHA-256:** `{}`\n", hash));
+            }
+            output.push('\n');
+
+            if !component.approvals.is_empty() {
+                output.push_str("| Pull Request | Approvers |\n");
+                output.push_str("|---|---|\n");
+                for approval in &component.approvals {
+                    let approvers = if approval.approvers.is_empty() {
+                        "*none*".to_string()
+                    } else {
+                        approval.approvers.iter().map(|a| format!("@{}", a)).collect::<Vec<_>>().join(", ")
+                    };
+                    output.push_str(&format!("| #{} | {} |\n", approval.pr_number, approvers));
+                }
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+}
+
+fn sha256_hex(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
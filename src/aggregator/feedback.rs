@@ -0,0 +1,110 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::github::client::GitHubClient;
+use crate::github::types::{ReactionRollup, ReleaseReactions};
+
+/// How published trains were received: the reactions on each umbrella release, for judging
+/// how far an announcement reached.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EngagementReport {
+    /// Repository holding the umbrella releases
+    pub repository: String,
+    pub collected_at: DateTime<Utc>,
+    /// Newest first
+    pub trains: Vec<TrainEngagement>,
+    /// Versions asked for that have no umbrella release
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrainEngagement {
+    pub version: String,
+    pub url: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub reactions: ReactionRollup,
+}
+
+impl From<ReleaseReactions> for TrainEngagement {
+    fn from(release: ReleaseReactions) -> Self {
+        Self {
+            version: release.tag_name,
+            url: release.html_url,
+            published_at: release.published_at,
+            reactions: release.reactions.unwrap_or_default(),
+        }
+    }
+}
+
+impl EngagementReport {
+    /// Reactions on the umbrella releases of `versions` in `repository`, or on its `limit` most
+    /// recently published ones when no versions are given. Drafts have no audience yet, so
+    /// they are left out of the latter.
+    pub async fn collect(client: &GitHubClient, repository: &str, versions: &[String], limit: usize) -> Result<Self> {
+        let mut trains = Vec::new();
+        let mut missing = Vec::new();
+
+        if versions.is_empty() {
+            trains = client.list_release_reactions(repository, limit).await?
+                .into_iter()
+                .filter(|release| !release.draft)
+                .map(TrainEngagement::from)
+                .collect();
+        } else {
+            for version in versions {
+                match client.get_release_reactions(repository, version).await? {
+                    Some(release) => trains.push(TrainEngagement::from(release)),
+                    None => missing.push(version.clone()),
+                }
+            }
+            trains.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+        }
+
+        Ok(Self {
+            repository: repository.to_string(),
+            collected_at: Utc::now(),
+            trains,
+            missing,
+        })
+    }
+
+    /// Reactions across every train in the report.
+    pub fn total(&self) -> u64 {
+        self.trains.iter().map(|t| t.reactions.total_count).sum()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut output = format!("# Release Feedback: {}\n\n", self.repository);
+        if self.trains.is_empty() {
+            output.push_str("No published trains found.\n");
+        } else {
+            output.push_str("| Train | Published | 👍 | 👎 | 😄 | 🎉 | 😕 | ❤️ | 🚀 | 👀 | Total |\n");
+            output.push_str("|-------|-----------|----|----|----|----|----|----|----|----|-------|\n");
+            for train in &self.trains {
+                let r = &train.reactions;
+                output.push_str(&format!(
+                    "| [{}]({}) | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                    train.version,
+                    train.url,
+                    train.published_at.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "—".to_string()),
+                    r.plus_one, r.minus_one, r.laugh, r.hooray, r.confused, r.heart, r.rocket, r.eyes,
+                    r.total_count
+                ));
+            }
+
+            let average = self.total() as f64 / self.trains.len() as f64;
+            output.push_str(&format!(
+                "\n**{} reactions** across {} trains ({:.1} per train)\n",
+                self.total(),
+                self.trains.len(),
+                average
+            ));
+        }
+
+        if !self.missing.is_empty() {
+            output.push_str(&format!("\nNo umbrella release for: {}\n", self.missing.join(", ")));
+        }
+        output
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use super::commit_analyzer::{CommitType, EnrichedCommit};
+
+/// Directory changelog fragments are read from unless a repository sets `fragments_dir`.
+pub const DEFAULT_FRAGMENTS_DIR: &str = "changelog.d";
+
+/// A changelog fragment: one Markdown file per change, named after the issue it closes and the
+/// kind of change, as towncrier lays them out (`123.feature.md`, `+cleanup.misc.md` for changes
+/// without an issue, `123.fix.1.md` for a second fragment on the same issue).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub issue: Option<u64>,
+    /// The type from the file name, e.g. `feature`
+    pub kind: String,
+    pub text: String,
+}
+
+impl Fragment {
+    /// Fragment stored at `path`; `None` for files that aren't fragments (a README or template
+    /// kept in the same directory).
+    pub fn parse(path: &str, text: &str) -> Option<Self> {
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let stem = file.strip_suffix(".md")?;
+        let mut parts = stem.split('.');
+        let name = parts.next()?;
+        let kind = parts.next()?.to_lowercase();
+        // An optional counter, and nothing after it
+        if parts.next().is_some_and(|n| !n.chars().all(|c| c.is_ascii_digit())) || parts.next().is_some() {
+            return None;
+        }
+        let issue = match name.strip_prefix('+') {
+            Some(_) => None,
+            None => Some(name.parse().ok()?),
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(Self { issue, kind, text: text.to_string() })
+    }
+
+    /// Commit type of the fragment's kind; towncrier's `removal` and `breaking` kinds mark
+    /// breaking changes, and unknown kinds are listed with the other changes.
+    fn commit_type(&self) -> (CommitType, bool) {
+        match self.kind.as_str() {
+            "removal" | "breaking" => (CommitType::Other, true),
+            "misc" | "other" => (CommitType::Other, false),
+            kind => (CommitType::from_keyword(kind).unwrap_or(CommitType::Other), false),
+        }
+    }
+
+    /// The fragment as a changelog entry dated `date`. Fragments have no commit or author, so
+    /// those are left empty; multi-line fragments are joined into one line.
+    pub fn into_entry(self, date: DateTime<Utc>) -> EnrichedCommit {
+        let (commit_type, breaking) = self.commit_type();
+        let message = self.text.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+        EnrichedCommit {
+            sha: String::new(),
+            message,
+            author: String::new(),
+            date,
+            commit_type: Some(commit_type),
+            section: None,
+            scope: None,
+            breaking,
+            pr_number: None,
+            issues: self.issue.into_iter().collect(),
+            issue_keys: vec![],
+            verified: false,
+            resolved_issues: vec![],
+        }
+    }
+}
+
+/// Whether `path` lies directly in directory `dir`.
+pub fn in_directory(path: &str, dir: &str) -> bool {
+    path.strip_prefix(dir.trim_end_matches('/'))
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|file| !file.is_empty() && !file.contains('/'))
+}
@@ -0,0 +1,234 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use std::io::Write;
+use std::path::PathBuf;
+use crate::aggregator::branding::Branding;
+use crate::aggregator::changelog_generator::{ChangelogGenerator, OutputFormat};
+use crate::aggregator::entry_format::{EntryFormats, FRAGMENT_ENTRY_FORMAT, PR_ENTRY_FORMAT};
+use crate::aggregator::locale::Locale;
+use crate::aggregator::release_fetcher::{AggregatedRelease, AggregatorConfig, ChangelogMode};
+use crate::aggregator::rollout::RolloutPlan;
+use crate::aggregator::scaffold::Scaffold;
+use crate::aggregator::search_index::SearchIndex;
+use crate::aggregator::session::Session;
+use crate::config::types::Config;
+use crate::export;
+use crate::github::client::GitHubClient;
+use crate::provider::connection::Connection;
+use crate::state::store::{self, StateStore};
+
+/// What `generate` writes, with the command line's defaults already applied.
+pub struct GenerateOptions {
+    /// The train's version; without one, `since` names a date range and otherwise the train is
+    /// "Unreleased"
+    pub version: Option<String>,
+    /// Versions to write one document each for, into the `output` directory
+    pub versions: Vec<String>,
+    /// Glob picking the released versions to write one document each for, instead of `versions`
+    pub all_matching: Option<String>,
+    /// Version to compare against instead of each component's previous release
+    pub from: Option<String>,
+    pub unreleased: bool,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// Repositories to cover; the configured ones when empty
+    pub repos: Vec<String>,
+    /// File, or directory for split or batched output; stdout when `None`
+    pub output: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub locale: Option<Locale>,
+    pub template: Option<PathBuf>,
+    /// Where to write the short summary; `{version}` is replaced by each version
+    pub short_output: Option<PathBuf>,
+    pub short_template: Option<PathBuf>,
+    pub branding: Branding,
+    pub entry_formats: EntryFormats,
+    /// Markdown file filling in the scaffold sections, overriding `scaffold.file`
+    pub sections_file: Option<PathBuf>,
+    /// Scaffold sections to fill in, each `TITLE=TEXT`
+    pub sections: Vec<String>,
+    pub mode: ChangelogMode,
+    pub include_prs: bool,
+    pub include_issues: bool,
+    /// Label of the known issues to list
+    pub known_issue_label: Option<String>,
+    pub categorize: bool,
+    /// Saved trains to nest under each generated one
+    pub include_train: Vec<PathBuf>,
+    pub download_stats: bool,
+    pub risk: bool,
+    pub rollout_plan: Option<PathBuf>,
+    pub search_index: Option<PathBuf>,
+    pub export_sqlite: Option<PathBuf>,
+    pub export_parquet: Option<PathBuf>,
+    pub deterministic: bool,
+    pub generated_at: Option<DateTime<Utc>>,
+}
+
+/// Aggregate the train (or each of the batch of trains) `options` asks for, record it in the
+/// state file and write its notes along with the exports and indexes requested.
+pub async fn run(
+    connection: &Connection,
+    github: GitHubClient,
+    file_config: Option<&Config>,
+    base: AggregatorConfig,
+    options: GenerateOptions,
+) -> Result<()> {
+    let config = AggregatorConfig {
+        include_prs: options.include_prs,
+        include_issues: options.include_issues,
+        categorize_commits: options.categorize,
+        template_path: options.template.clone(),
+        download_stats: options.download_stats,
+        ..base
+    };
+    let mut session = Session::open(connection, github, file_config, options.repos.clone(), config).await?;
+
+    let date_range = options.since.map(|since| (since, options.until.unwrap_or_else(|| Utc::now().date_naive())));
+    // A batch is generated with one client, so later versions reuse what earlier ones fetched
+    let batch = match &options.all_matching {
+        Some(pattern) => Some(session.matching_versions(pattern).await?),
+        None => (!options.versions.is_empty()).then(|| options.versions.clone()),
+    };
+    let batched = batch.is_some();
+    let runs: Vec<(String, Option<PathBuf>)> = match batch {
+        Some(versions) => {
+            if versions.is_empty() {
+                anyhow::bail!("No release tag matches the --all-matching pattern");
+            }
+            let dir = options.output.clone()
+                .ok_or_else(|| anyhow::anyhow!("Pass --output DIR for the documents of --versions or --all-matching"))?;
+            std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+            versions.into_iter()
+                .map(|version| {
+                    let path = dir.join(format!("{}.{}", version.replace('/', "-"), options.format.extension()));
+                    (version, Some(path))
+                })
+                .collect()
+        }
+        None => {
+            let version = match (&options.version, date_range) {
+                (Some(version), _) => version.clone(),
+                (None, Some((since, until))) => format!("{}..{}", since, until),
+                (None, None) => "Unreleased".to_string(),
+            };
+            vec![(version, options.output.clone())]
+        }
+    };
+    if batched && options.short_output.as_ref().is_some_and(|path| !path.to_string_lossy().contains("{version}")) {
+        anyhow::bail!("--short-output needs a {{version}} placeholder with --versions or --all-matching");
+    }
+
+    let branches = StateStore::open(session.state())?.branches;
+    session.aggregator = session.aggregator
+        .with_branch_history(branches)
+        .with_mode(options.mode)
+        .with_base_version(options.from.clone())
+        .with_unreleased(options.unreleased)
+        .with_risk(options.risk || options.rollout_plan.is_some())
+        .with_known_issues(options.known_issue_label.clone())
+        .with_date_range(date_range.map(|(since, until)| (
+            since.and_time(NaiveTime::MIN).and_utc(),
+            until.and_time(NaiveTime::MIN).and_utc() + chrono::Duration::days(1) - chrono::Duration::nanoseconds(1),
+        )));
+    let entry_formats = match options.mode {
+        ChangelogMode::Prs => options.entry_formats.clone().with_fallback(PR_ENTRY_FORMAT),
+        ChangelogMode::Fragments => options.entry_formats.clone().with_fallback(FRAGMENT_ENTRY_FORMAT),
+        ChangelogMode::Commits => options.entry_formats.clone(),
+    };
+    let scaffold_config = file_config.map(|c| c.scaffold.clone()).unwrap_or_default();
+
+    for (version, output) in runs {
+        let mut release = session.aggregate(&version).await?;
+        for path in &options.include_train {
+            release.add_sub_train(AggregatedRelease::load(path)?);
+        }
+        release.pin(options.deterministic, options.generated_at);
+
+        // Branch heads move on every merge, so only tags are tracked for drift. A date range
+        // isn't a train, so it doesn't mark branch-deployed heads as released either
+        if !options.unreleased {
+            store::record_release_shas(session.state(), &release, date_range.is_none())?;
+        }
+
+        if let Some(index_path) = &options.search_index {
+            let mut index = SearchIndex::load_or_default(index_path)?;
+            index.upsert_release(&release);
+            index.save(index_path)?;
+        }
+
+        if let Some(db_path) = &options.export_sqlite {
+            export::sqlite::export(db_path, &release)?;
+        }
+
+        if let Some(dir) = &options.export_parquet {
+            export::parquet::export(dir, &release)?;
+        }
+
+        let plan = match &options.rollout_plan {
+            Some(path) => {
+                let depends_on = file_config
+                    .map(|c| c.repos.settings.iter()
+                        .map(|(repo, settings)| (repo.clone(), settings.depends_on.clone()))
+                        .collect())
+                    .unwrap_or_default();
+                let rollout = file_config.map(|c| c.rollout.clone()).unwrap_or_default();
+                let plan = RolloutPlan::build(&release, &depends_on, &rollout)?;
+                std::fs::write(path, serde_json::to_string_pretty(&plan)?)
+                    .with_context(|| format!("Failed to write the rollout plan to {}", path.display()))?;
+                Some(plan)
+            }
+            None => None,
+        };
+
+        let scaffold = Scaffold::for_train(&scaffold_config, options.sections_file.as_deref(), &options.sections, &version)?;
+        let mut generator = ChangelogGenerator::new(options.format, options.template.clone())?
+            .with_entry_formats(entry_formats.clone())
+            .with_links(session.aggregator.client().web_links())
+            .with_branding(options.branding.clone())?
+            .with_scaffold(scaffold);
+        if let Some(plan) = plan {
+            generator = generator.with_rollout(plan);
+        }
+        if let Some(locale) = options.locale {
+            generator = generator.with_locale(locale);
+        }
+        if let Some(path) = &options.short_template {
+            generator = generator.with_short_template(path)?;
+        }
+        if let Some(path) = &options.short_output {
+            let path = PathBuf::from(path.to_string_lossy().replace("{version}", &version.replace('/', "-")));
+            std::fs::write(&path, generator.generate_short(&release)?)
+                .with_context(|| format!("Failed to write the short summary to {}", path.display()))?;
+            println!("✓ Short summary written to {}", path.display());
+        }
+        // A directory gets an index and one file per component
+        let split_dir = output.as_ref()
+            .filter(|path| !batched && (path.is_dir() || path.to_string_lossy().ends_with(std::path::MAIN_SEPARATOR)));
+        if let Some(dir) = split_dir {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+            let written = generator.write_split(&release, dir)?;
+            println!("Release notes written to {} ({} component files)", written[0].display(), written.len() - 1);
+            continue;
+        }
+        // Written as it's rendered, so a large train is never held in memory as a whole
+        if let Some(output_path) = output {
+            let file = std::fs::File::create(&output_path)
+                .with_context(|| format!("Failed to create {}", output_path.display()))?;
+            let mut writer = std::io::BufWriter::new(file);
+            generator.write(&release, &mut writer)?;
+            writer.flush()?;
+            if batched {
+                println!("✓ {}: written to {}", version, output_path.display());
+            } else {
+                println!("Release notes written successfully!");
+            }
+        } else {
+            let mut stdout = std::io::BufWriter::new(std::io::stdout().lock());
+            generator.write(&release, &mut stdout)?;
+            writeln!(stdout)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
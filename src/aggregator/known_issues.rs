@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use crate::github::types::Issue;
+
+/// Label of the open issues listed as a component's known issues unless another is configured.
+pub const DEFAULT_KNOWN_ISSUE_LABEL: &str = "known-issue";
+
+/// An open issue users of a component should know about, read again on every generation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownIssue {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    /// How to work around it, from the issue description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workaround: Option<String>,
+}
+
+impl From<Issue> for KnownIssue {
+    fn from(issue: Issue) -> Self {
+        Self {
+            number: issue.number,
+            title: issue.title,
+            url: issue.html_url,
+            workaround: issue.body.as_deref().and_then(workaround),
+        }
+    }
+}
+
+/// The workaround described in an issue body: the text under a `Workaround` heading (as issue
+/// forms produce) up to the next heading, or the paragraph starting `Workaround:`. Joined
+/// into one line so it fits a list entry; `None` when there is none, or it says `_No response_`.
+pub fn workaround(body: &str) -> Option<String> {
+    let mut lines = body.lines();
+    let mut text = Vec::new();
+    let mut under_heading = false;
+
+    for line in lines.by_ref() {
+        let line = line.trim();
+        if let Some(heading) = line.strip_prefix('#') {
+            if starts_with_workaround(heading.trim_start_matches('#')).is_some() {
+                under_heading = true;
+                break;
+            }
+        } else if let Some(rest) = starts_with_workaround(line) {
+            if let Some(first) = rest.strip_prefix(':') {
+                text.push(first.trim_start_matches(['*', '_']).trim());
+                break;
+            }
+        }
+    }
+    if !under_heading && text.is_empty() {
+        return None;
+    }
+
+    for line in lines.map(str::trim) {
+        if line.starts_with('#') || (line.is_empty() && !under_heading) {
+            break;
+        }
+        text.push(line);
+    }
+    let workaround = text.into_iter().filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+    match workaround.as_str() {
+        "" | "_No response_" => None,
+        _ => Some(workaround),
+    }
+}
+
+/// What follows `Workaround` (or `Workarounds`) at the start of `text`, ignoring case and
+/// Markdown emphasis; `None` if `text` doesn't start with it.
+fn starts_with_workaround(text: &str) -> Option<&str> {
+    let text = text.trim().trim_start_matches(['*', '_']);
+    let word = text.get(..10)?;
+    if !word.eq_ignore_ascii_case("workaround") {
+        return None;
+    }
+    let rest = &text[10..];
+    Some(rest.strip_prefix(['s', 'S']).unwrap_or(rest).trim_start_matches(['*', '_']))
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::github::client::GitHubClient;
+use super::release_fetcher::{AggregatedRelease, ComponentStatus};
+
+/// Team label used for findings whose author isn't on any org team.
+const UNOWNED: &str = "unowned";
+
+/// Problems in a train's source data that maintainers should fix before publishing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LintReport {
+    pub version: String,
+    pub uncategorized: Vec<UncategorizedCommit>,
+    /// Required `[[scaffold.sections]]` that haven't been filled in
+    #[serde(default)]
+    pub unfilled_sections: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UncategorizedCommit {
+    pub repository: String,
+    pub sha: String,
+    pub message: String,
+    /// PR author when the commit came from a pull request, otherwise the commit author
+    pub author: String,
+    pub pr_number: Option<u64>,
+    /// Org teams (`org/slug`) the author belongs to; only filled in when ownership lookup was requested
+    #[serde(default)]
+    pub teams: Vec<String>,
+}
+
+impl LintReport {
+    pub fn from_release(release: &AggregatedRelease) -> Self {
+        let mut uncategorized = Vec::new();
+
+        for component in &release.components {
+            let ComponentStatus::Released { commits, .. } = &component.status else {
+                continue;
+            };
+            for commit in commits.iter().filter(|c| !c.is_categorized()) {
+                uncategorized.push(UncategorizedCommit {
+                    repository: component.repository.clone(),
+                    sha: commit.sha.clone(),
+                    message: commit.message.lines().next().unwrap_or_default().to_string(),
+                    author: commit.author.clone(),
+                    pr_number: commit.pr_number,
+                    teams: vec![],
+                });
+            }
+        }
+
+        Self {
+            version: release.version.clone(),
+            uncategorized,
+            unfilled_sections: vec![],
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.uncategorized.is_empty() && self.unfilled_sections.is_empty()
+    }
+
+    /// Attribute each uncategorized commit to its PR author's org teams so nudges reach the owners.
+    pub async fn assign_owners(&mut self, client: &GitHubClient) -> Result<()> {
+        if self.uncategorized.is_empty() {
+            return Ok(());
+        }
+
+        let memberships = client.get_team_memberships().await?;
+        for finding in &mut self.uncategorized {
+            if let Some(pr_number) = finding.pr_number {
+                if let Some(author) = client.get_pull_request_author(&finding.repository, pr_number).await? {
+                    finding.author = author;
+                }
+            }
+            finding.teams = memberships.get(&finding.author).cloned().unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("# Lint: {}\n\n", self.version));
+        if self.is_clean() {
+            output.push_str("✓ No issues found\n");
+            return output;
+        }
+
+        if !self.unfilled_sections.is_empty() {
+            output.push_str(&format!(
+                "## Unfilled sections ({})\n\nFill these in with `--sections-file` or `--section` before publishing.\n\n",
+                self.unfilled_sections.len()
+            ));
+            for title in &self.unfilled_sections {
+                output.push_str(&format!("- {}\n", title));
+            }
+            output.push('\n');
+        }
+        if self.uncategorized.is_empty() {
+            return output;
+        }
+
+        output.push_str(&format!(
+            "## Uncategorized commits ({})\n\nPlease reword these with a recognised type (e.g. `feat:`, `fix:`) or label the PR.\n\n",
+            self.uncategorized.len()
+        ));
+
+        // Group by owning team when known so each team gets one list to work through
+        let mut by_owner: BTreeMap<String, Vec<&UncategorizedCommit>> = BTreeMap::new();
+        for finding in &self.uncategorized {
+            let owners: Vec<String> = if finding.teams.is_empty() {
+                vec![UNOWNED.to_string()]
+            } else {
+                finding.teams.iter().map(|t| format!("@{}", t)).collect()
+            };
+            for owner in owners {
+                by_owner.entry(owner).or_default().push(finding);
+            }
+        }
+
+        let grouped = by_owner.len() > 1 || !by_owner.contains_key(UNOWNED);
+        for (owner, findings) in by_owner {
+            if grouped {
+                output.push_str(&format!("### {}\n\n", owner));
+            }
+            for finding in findings {
+                let pr = finding.pr_number.map(|n| format!(" (#{})", n)).unwrap_or_default();
+                output.push_str(&format!(
+                    "- {} `{}` {}{} — @{}\n",
+                    finding.repository,
+                    &finding.sha[..7.min(finding.sha.len())],
+                    finding.message,
+                    pr,
+                    finding.author
+                ));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+}
@@ -0,0 +1,208 @@
+use chrono::{DateTime, Utc};
+use crate::github::types::Release;
+use serde::{Deserialize, Serialize};
+use crate::github::types::{loose_version, DateSource};
+
+/// How `list` prints releases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListFormat {
+    /// An aligned table with a row per release
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(ListFormat::Table),
+            "json" => Ok(ListFormat::Json),
+            "csv" => Ok(ListFormat::Csv),
+            _ => Err(format!("Unknown list format: {} (expected table, json or csv)", s)),
+        }
+    }
+}
+
+/// Order of each repository's releases in `list`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSort {
+    /// Newest first, by `--date-source`; unpublished drafts lead
+    #[default]
+    Date,
+    /// Highest version first; tags that aren't versions follow, newest first
+    Semver,
+}
+
+impl std::str::FromStr for ListSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "date" => Ok(ListSort::Date),
+            "semver" => Ok(ListSort::Semver),
+            _ => Err(format!("Unknown list sort: {} (expected date or semver)", s)),
+        }
+    }
+}
+
+/// Which releases `list` shows.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListFilter {
+    pub include_prereleases: bool,
+    pub exclude_drafts: bool,
+}
+
+impl ListFilter {
+    pub fn keeps(&self, release: &Release) -> bool {
+        (self.include_prereleases || !release.prerelease) && !(self.exclude_drafts && release.draft)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListedRelease {
+    pub repository: String,
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Date picked by `--date-source`; `None` for unpublished drafts
+    pub published_at: Option<DateTime<Utc>>,
+    /// Login of whoever published the release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub prerelease: bool,
+    pub draft: bool,
+}
+
+impl ListedRelease {
+    pub fn new(repository: &str, release: &Release, date_source: DateSource) -> Self {
+        Self {
+            repository: repository.to_string(),
+            tag: release.tag_name.clone(),
+            name: release.name.clone().filter(|name| !name.is_empty()),
+            published_at: date_source.resolve(release.created_at, release.published_at),
+            author: release.author.clone(),
+            prerelease: release.prerelease,
+            draft: release.draft,
+        }
+    }
+
+    fn date_cell(&self) -> String {
+        self.published_at
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    fn flags_cell(&self) -> String {
+        let flags: Vec<&str> = [(self.draft, "draft"), (self.prerelease, "prerelease")].into_iter()
+            .filter_map(|(set, flag)| set.then_some(flag))
+            .collect();
+        flags.join(", ")
+    }
+}
+
+/// Outcome of `list`: recent releases of each repository, in the order the repositories were given.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReleaseList {
+    pub releases: Vec<ListedRelease>,
+}
+
+impl ReleaseList {
+    /// Add one repository's releases, ordered by `sort`.
+    pub fn extend(&mut self, mut releases: Vec<ListedRelease>, sort: ListSort) {
+        match sort {
+            ListSort::Date => releases.sort_by(newest_first),
+            ListSort::Semver => releases.sort_by(|a, b| {
+                loose_version(&b.tag).cmp(&loose_version(&a.tag))
+                    .then_with(|| b.published_at.cmp(&a.published_at))
+            }),
+        }
+        self.releases.extend(releases);
+    }
+
+    /// Keep the `limit` newest releases across every repository, newest first, for an
+    /// org-wide activity feed.
+    pub fn keep_latest(&mut self, limit: usize) {
+        self.releases.sort_by(newest_first);
+        self.releases.truncate(limit);
+    }
+
+    /// An aligned table with a row per release and the repository on each row, so it can be
+    /// filtered with `grep`.
+    pub fn to_table(&self) -> String {
+        if self.releases.is_empty() {
+            return "No releases found\n".to_string();
+        }
+        let header = ["REPOSITORY", "TAG", "NAME", "PUBLISHED", "AUTHOR", "FLAGS"].map(String::from);
+        let rows: Vec<[String; 6]> = self.releases.iter()
+            .map(|r| [
+                r.repository.clone(),
+                r.tag.clone(),
+                r.name.clone().unwrap_or_default(),
+                r.date_cell(),
+                r.author.clone().unwrap_or_default(),
+                r.flags_cell(),
+            ])
+            .collect();
+
+        let mut widths = header.clone().map(|cell| cell.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut output = String::new();
+        for row in std::iter::once(&header).chain(&rows) {
+            let cells: Vec<String> = row.iter().zip(widths)
+                .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+                .collect();
+            output.push_str(cells.join("  ").trim_end());
+            output.push('\n');
+        }
+        output
+    }
+
+    /// CSV with a header row; dates are RFC 3339 and flags are `true`/`false` columns.
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("repository,tag,name,published_at,author,prerelease,draft\n");
+        for r in &self.releases {
+            let fields = [
+                r.repository.clone(),
+                r.tag.clone(),
+                r.name.clone().unwrap_or_default(),
+                r.published_at.map(|date| date.to_rfc3339()).unwrap_or_default(),
+                r.author.clone().unwrap_or_default(),
+                r.prerelease.to_string(),
+                r.draft.to_string(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            output.push_str(&fields.join(","));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Newest first by `published_at`; unpublished drafts lead.
+fn newest_first(a: &ListedRelease, b: &ListedRelease) -> std::cmp::Ordering {
+    match (a.published_at, b.published_at) {
+        (None, None) => std::cmp::Ordering::Equal,
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (Some(a), Some(b)) => b.cmp(&a),
+    }
+}
+
+/// Quote a CSV field when it holds a comma, quote or line break (RFC 4180).
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Locale numbers and dates in the notes are written for, via the `number` and `date` template
+/// helpers. Without one, numbers are written plainly and dates as `YYYY-MM-DD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// `1,234.5`, `2024-03-01`
+    En,
+    /// `1.234,5`, `01.03.2024`
+    De,
+    /// `1 234,5` (narrow no-break space), `01/03/2024`
+    Fr,
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    /// Accepts a language with or without a region, e.g. `de`, `de-DE`, `de_AT`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s.split(['-', '_']).next().unwrap_or_default();
+        match language.to_lowercase().as_str() {
+            "en" => Ok(Locale::En),
+            "de" => Ok(Locale::De),
+            "fr" => Ok(Locale::Fr),
+            _ => Err(format!("Unknown locale: {} (expected en, de or fr)", s)),
+        }
+    }
+}
+
+impl Locale {
+    /// Language tag for the HTML `lang` attribute.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Fr => "fr",
+        }
+    }
+
+    fn separators(&self) -> (&'static str, &'static str) {
+        match self {
+            Locale::En => (",", "."),
+            Locale::De => (".", ","),
+            Locale::Fr => ("\u{202f}", ","),
+        }
+    }
+
+    /// Group the digits of a number such as `1234.5` or `-98765` by thousands and swap in the
+    /// locale's decimal separator. Anything that isn't a plain number is returned unchanged.
+    pub fn format_number(&self, number: &str) -> String {
+        let (sign, unsigned) = match number.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", number),
+        };
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (unsigned, None),
+        };
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(integer) || fraction.is_some_and(|f| !is_digits(f)) {
+            return number.to_string();
+        }
+
+        let (thousands, decimal) = self.separators();
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push_str(thousands);
+            }
+            grouped.push(digit);
+        }
+        match fraction {
+            Some(fraction) => format!("{}{}{}{}", sign, grouped, decimal, fraction),
+            None => format!("{}{}", sign, grouped),
+        }
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        let pattern = match self {
+            Locale::En => "%Y-%m-%d",
+            Locale::De => "%d.%m.%Y",
+            Locale::Fr => "%d/%m/%Y",
+        };
+        date.format(pattern).to_string()
+    }
+}
+
+/// `number` with `locale`'s separators, or unchanged without one.
+pub fn format_number(locale: Option<Locale>, number: &str) -> String {
+    match locale {
+        Some(locale) => locale.format_number(number),
+        None => number.to_string(),
+    }
+}
+
+/// A `YYYY-MM-DD` or RFC 3339 date in `locale`'s format. Without a locale, or for text that
+/// isn't a date, the text is returned unchanged.
+pub fn format_date(locale: Option<Locale>, date: &str) -> String {
+    let Some(locale) = locale else {
+        return date.to_string();
+    };
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(date).ok().map(|d| d.date_naive()));
+    match parsed {
+        Some(parsed) => locale.format_date(parsed),
+        None => date.to_string(),
+    }
+}
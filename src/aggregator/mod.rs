@@ -1,6 +1,31 @@
-pub mod release_fetcher;
-pub mod commit_analyzer;
+pub mod accessibility;
+pub mod backfill;
+pub mod bench;
+pub mod branding;
+pub mod cache_warmer;
+pub mod calendar;
 pub mod changelog_generator;
+pub mod check;
+pub mod commit_analyzer;
+pub mod entry_format;
+pub mod evidence;
+pub mod feedback;
+pub mod fragments;
+pub mod generate;
+pub mod known_issues;
+pub mod lint;
+pub mod listing;
+pub mod locale;
+pub mod notes_diff;
+pub mod publisher;
+pub mod redaction;
+pub mod release_fetcher;
+pub mod risk;
+pub mod rollout;
+pub mod scaffold;
+pub mod search_index;
+pub mod session;
+pub mod site;
 
-pub use release_fetcher::{ReleaseAggregator, AggregatorConfig, AggregatedRelease};
-pub use commit_analyzer::CommitType;
\ No newline at end of file
+pub use commit_analyzer::CommitType;
+pub use release_fetcher::{AggregatedRelease, AggregatorConfig, ChangelogMode, ReleaseAggregator};
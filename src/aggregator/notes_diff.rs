@@ -0,0 +1,176 @@
+/// What changed between two renderings of a train's Markdown notes, entry by entry, so a
+/// regenerated document can be reviewed without reading both in full.
+#[derive(Debug, Default)]
+pub struct NotesDiff {
+    /// Groups with changes: the new document's order, then groups only the old one had
+    pub sections: Vec<SectionDiff>,
+}
+
+/// Entries added to and removed from one component (or other top-level heading).
+#[derive(Debug)]
+pub struct SectionDiff {
+    pub name: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl NotesDiff {
+    /// Compare the list entries of `old` and `new`, grouped by component. Entries count as
+    /// changed text, so an amended commit shows up as one removed and one added entry.
+    pub fn between(old: &str, new: &str) -> Self {
+        let old = entries_by_heading(old);
+        let new = entries_by_heading(new);
+
+        let mut sections = Vec::new();
+        for (name, entries) in &new {
+            let previous = old.iter().find(|(n, _)| n == name).map(|(_, e)| e.as_slice()).unwrap_or_default();
+            push_changes(&mut sections, name, previous, entries);
+        }
+        for (name, entries) in &old {
+            if !new.iter().any(|(n, _)| n == name) {
+                push_changes(&mut sections, name, entries, &[]);
+            }
+        }
+        Self { sections }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    pub fn added(&self) -> usize {
+        self.sections.iter().map(|s| s.added.len()).sum()
+    }
+
+    pub fn removed(&self) -> usize {
+        self.sections.iter().map(|s| s.removed.len()).sum()
+    }
+
+    /// A `diff` block per changed component.
+    pub fn to_markdown(&self, version: &str) -> String {
+        let mut output = format!("# Changes to {}\n\n", version);
+        if self.is_empty() {
+            output.push_str("No entries were added or removed.\n");
+            return output;
+        }
+
+        output.push_str(&format!(
+            "**{} added, {} removed** across {} component(s)\n\n",
+            self.added(),
+            self.removed(),
+            self.sections.len()
+        ));
+        for section in &self.sections {
+            output.push_str(&format!("## {}\n\n```diff\n", section.name));
+            for entry in &section.removed {
+                output.push_str(&format!("- {}\n", entry));
+            }
+            for entry in &section.added {
+                output.push_str(&format!("+ {}\n", entry));
+            }
+            output.push_str("```\n\n");
+        }
+        output
+    }
+}
+
+fn push_changes(sections: &mut Vec<SectionDiff>, name: &str, old: &[String], new: &[String]) {
+    let removed = without(old, new);
+    let added = without(new, old);
+    if !added.is_empty() || !removed.is_empty() {
+        sections.push(SectionDiff { name: name.to_string(), added, removed });
+    }
+}
+
+/// Entries of `entries` not matched by one in `other`, counting duplicates.
+fn without(entries: &[String], other: &[String]) -> Vec<String> {
+    let mut unmatched: Vec<&String> = other.iter().collect();
+    entries.iter()
+        .filter(|entry| match unmatched.iter().position(|o| o == entry) {
+            Some(i) => {
+                unmatched.swap_remove(i);
+                false
+            }
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// List entries grouped under the heading of the component they belong to, in document order.
+///
+/// Components are the headings directly followed by a `**Version:**` line, or the
+/// `<summary>` of a collapsed component. Headings at their level or above (the summary, named
+/// sections, unchanged components) start a group of their own; deeper ones (commit
+/// categories) are part of the component.
+fn entries_by_heading(notes: &str) -> Vec<(String, Vec<String>)> {
+    let component_level = component_heading_level(notes);
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    let mut current = String::new();
+
+    for line in notes.lines() {
+        let line = line.trim();
+        if let Some((level, title)) = heading(line) {
+            if level <= component_level {
+                current = title;
+            }
+            continue;
+        }
+        if let Some(title) = collapsed_title(line) {
+            current = title;
+            continue;
+        }
+        let Some(entry) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) else {
+            continue;
+        };
+        match groups.iter_mut().find(|(name, _)| *name == current) {
+            Some((_, entries)) => entries.push(entry.trim().to_string()),
+            None => groups.push((current.clone(), vec![entry.trim().to_string()])),
+        }
+    }
+    groups
+}
+
+/// Level of the headings that introduce components; 2 (`##`) when none can be told apart.
+fn component_heading_level(notes: &str) -> usize {
+    let mut level = None;
+    let mut last_heading = None;
+    for line in notes.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some((l, _)) = heading(line) {
+            last_heading = Some(l);
+        } else {
+            if line.starts_with("**Version:**") {
+                if let Some(l) = last_heading {
+                    level = Some(level.map_or(l, |level: usize| level.min(l)));
+                }
+            }
+            last_heading = None;
+        }
+    }
+    level.unwrap_or(2)
+}
+
+/// Level and title of a Markdown heading, with a linked title reduced to its text.
+fn heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let title = line.get(level..)?.strip_prefix(' ')?;
+    if level == 0 {
+        return None;
+    }
+    Some((level, plain_title(title)))
+}
+
+/// Title of a collapsed component: `<summary><strong>api</strong> ...`.
+fn collapsed_title(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("<summary><strong>")?;
+    rest.split_once("</strong>").map(|(title, _)| title.to_string())
+}
+
+/// `[api](https://...) *(renamed from `billing`)*` becomes `api`.
+fn plain_title(title: &str) -> String {
+    let title = title.split(" *(renamed from").next().unwrap_or(title).trim();
+    match title.strip_prefix('[').and_then(|t| t.split_once("](")) {
+        Some((text, _)) => text.to_string(),
+        None => title.to_string(),
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use octocrab::models;
+use crate::github::client::GitHubClient;
+use crate::github::types::{MakeLatest, ReleaseSource};
+use super::release_fetcher::{AggregatedRelease, ComponentStatus};
+
+/// GitHub rejects release bodies longer than this many characters.
+pub const MAX_RELEASE_BODY: usize = 125_000;
+
+const FOOTER_START: &str = "<!-- release-train:start -->";
+const FOOTER_END: &str = "<!-- release-train:end -->";
+
+/// How to publish notes that are too long for a release body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OversizeStrategy {
+    /// Re-render with each component collapsed into a compact `<details>` block, truncating
+    /// only if that still doesn't fit
+    #[default]
+    Details,
+    /// Cut the notes at the last component that fits
+    Truncate,
+}
+
+impl std::str::FromStr for OversizeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "details" => Ok(OversizeStrategy::Details),
+            "truncate" => Ok(OversizeStrategy::Truncate),
+            _ => Err(format!("Unknown oversize strategy: {}", s)),
+        }
+    }
+}
+
+/// Writes a train's results back to GitHub.
+pub struct Publisher<'a> {
+    client: &'a GitHubClient,
+}
+
+impl<'a> Publisher<'a> {
+    pub fn new(client: &'a GitHubClient) -> Self {
+        Self { client }
+    }
+
+    /// Create or update the umbrella release carrying the aggregated notes.
+    pub async fn publish_umbrella(
+        &self,
+        release_repo: &str,
+        version: &str,
+        notes: &str,
+        draft: bool,
+        make_latest: Option<MakeLatest>,
+    ) -> Result<models::repos::Release> {
+        self.client
+            .upsert_release(release_repo, version, &format!("Release {}", version), notes, draft, make_latest)
+            .await
+    }
+
+    /// Body of the umbrella release already published for `version`, which publishing again
+    /// will replace.
+    pub async fn published_notes(&self, release_repo: &str, version: &str) -> Result<Option<String>> {
+        let existing = self.client.get_github_release(release_repo, version).await?;
+        Ok(existing.map(|release| release.body.unwrap_or_default()))
+    }
+
+    /// Whether `tag` already has a release in `repo`, counting drafts (which have no tag yet and
+    /// are invisible to lookups by tag).
+    pub async fn has_release(&self, repo: &str, tag: &str) -> Result<bool> {
+        let releases = self.client.list_all_releases(repo).await?;
+        Ok(releases.iter().any(|r| r.tag_name == tag))
+    }
+
+    /// Create a draft release for `tag` so maintainers can edit the prefilled notes before publishing.
+    pub async fn prefill_draft(&self, repo: &str, tag: &str, body: &str) -> Result<models::repos::Release> {
+        self.client
+            .upsert_release(repo, tag, tag, body, true, None)
+            .await
+    }
+
+    /// Commit the full notes to the release repository so an oversize release body can link to them.
+    pub async fn store_full_notes(&self, release_repo: &str, version: &str, notes: &str) -> Result<String> {
+        let path = format!("release-notes/{}.md", version);
+        let url = self.client
+            .put_file(release_repo, &path, &format!("Add full release notes for {}", version), notes)
+            .await?;
+        Ok(url.unwrap_or_else(|| format!("{}/blob/HEAD/{}", self.client.repo_url(release_repo), path)))
+    }
+
+    /// Append a "part of release train" footer to every released component's GitHub release.
+    /// Returns the repositories whose release bodies were changed.
+    pub async fn backfill_train_links(&self, release: &AggregatedRelease, train_url: &str) -> Result<Vec<String>> {
+        let mut updated = Vec::new();
+
+        for component in &release.components {
+            let ComponentStatus::Released { current_version, .. } = &component.status else {
+                continue;
+            };
+            // A bare tag has no release body to link from
+            if self.client.release_source(&component.repository) == ReleaseSource::Tags {
+                continue;
+            }
+            let Some(component_release) = self.client.get_release(&component.repository, current_version).await? else {
+                continue;
+            };
+
+            let body = component_release.body.unwrap_or_default();
+            let new_body = with_train_footer(&body, &release.version, train_url);
+            if new_body != body {
+                let Some(github_release) = self.client.get_github_release(&component.repository, &component_release.tag_name).await? else {
+                    continue;
+                };
+                self.client
+                    .update_release_body(&component.repository, github_release.id.into_inner(), &new_body)
+                    .await?;
+                updated.push(component.repository.clone());
+            }
+        }
+
+        Ok(updated)
+    }
+}
+
+/// Replace any existing train footer with one pointing at `train_url`.
+fn with_train_footer(body: &str, version: &str, train_url: &str) -> String {
+    let base = match (body.find(FOOTER_START), body.find(FOOTER_END)) {
+        (Some(start), Some(end)) if end > start => {
+            format!("{}{}", &body[..start], &body[end + FOOTER_END.len()..])
+        }
+        _ => body.to_string(),
+    };
+
+    format!(
+        "{}\n\n{}\n---\n📦 Part of release train [{}]({})\n{}",
+        base.trim_end(),
+        FOOTER_START,
+        version,
+        train_url,
+        FOOTER_END
+    )
+}
+
+/// Cut `body` to fit in a release, preferring to stop after a complete component (`---` separator),
+/// and point readers at the full notes.
+pub fn truncate_body(body: &str, full_notes_url: &str) -> String {
+    let notice = format!(
+        "\n\n> ⚠️ These notes were truncated to fit GitHub's release size limit. [Read the full release notes]({}).\n",
+        full_notes_url
+    );
+    truncate_with_notice(body, MAX_RELEASE_BODY, &notice)
+}
+
+/// Cut `body` to at most `limit` characters including `notice`, which is appended when anything
+/// is cut. Stops after a complete component where possible, else at a line break.
+pub fn truncate_with_notice(body: &str, limit: usize, notice: &str) -> String {
+    if body.chars().count() <= limit {
+        return body.to_string();
+    }
+
+    let budget = limit.saturating_sub(notice.chars().count());
+
+    // Byte offset of the last character that fits
+    let end = body.char_indices().nth(budget).map(|(i, _)| i).unwrap_or(body.len());
+    let fitting = &body[..end];
+    let cut = fitting.rfind("\n---\n")
+        .or_else(|| fitting.rfind('\n'))
+        .unwrap_or(end);
+
+    format!("{}{}", body[..cut].trim_end(), notice)
+}
@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use crate::config::types::{matches_pattern, RedactionConfig};
+use super::commit_analyzer::{CommitType, EnrichedCommit};
+use super::release_fetcher::{AggregatedRelease, ComponentStatus, ReleaseStats, ReleaseSummary};
+
+const REDACTED: &str = "[redacted]";
+
+/// Produces the external view of a train: hidden repositories and commit types removed,
+/// sensitive text masked, and stats recomputed from what remains.
+#[derive(Debug)]
+pub struct Redactor {
+    hide_repos: Vec<String>,
+    hide_types: Vec<CommitType>,
+    hide_uncategorized: bool,
+    hide_authors: bool,
+    hide_issue_keys: bool,
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    pub fn from_config(config: &RedactionConfig) -> Result<Self> {
+        let hide_types = config.hide_commit_types.iter()
+            .map(|keyword| CommitType::from_keyword(keyword)
+                .ok_or_else(|| anyhow::anyhow!("Unknown commit type in redaction.hide_commit_types: {}", keyword)))
+            .collect::<Result<_>>()?;
+        let patterns = config.patterns.iter()
+            .map(|pattern| Regex::new(pattern)
+                .with_context(|| format!("Invalid pattern in redaction.patterns: {}", pattern)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            hide_repos: config.hide_repos.clone(),
+            hide_types,
+            hide_uncategorized: config.hide_uncategorized,
+            hide_authors: config.hide_authors,
+            hide_issue_keys: config.hide_issue_keys,
+            patterns,
+        })
+    }
+
+    /// Redacted copy of `release` and its sub-trains.
+    pub fn apply(&self, release: &AggregatedRelease) -> AggregatedRelease {
+        let components: Vec<_> = release.components.iter()
+            .filter(|c| !self.hides_repo(&c.repository))
+            .cloned()
+            .map(|mut component| {
+                if let ComponentStatus::Released { commits, release_notes, stats, releases, known_issues, .. } = &mut component.status {
+                    commits.retain(|c| self.shows_commit(c));
+                    for commit in commits.iter_mut() {
+                        self.redact_commit(commit);
+                    }
+                    *release_notes = release_notes.as_deref().map(|notes| self.redact_text(notes));
+                    for entry in releases.iter_mut() {
+                        entry.release_notes = entry.release_notes.as_deref().map(|notes| self.redact_text(notes));
+                    }
+                    for issue in known_issues.iter_mut() {
+                        issue.title = self.redact_text(&issue.title);
+                        issue.workaround = issue.workaround.as_deref().map(|text| self.redact_text(text));
+                    }
+                    *stats = ReleaseStats::from_commits(commits);
+                    if self.hide_authors {
+                        stats.contributors.clear();
+                    }
+                }
+                component
+            })
+            .collect();
+
+        let sections = release.sections.iter()
+            .cloned()
+            .map(|mut section| {
+                section.repositories.retain(|r| !self.hides_repo(r));
+                section
+            })
+            .filter(|section| !section.repositories.is_empty())
+            .collect();
+
+        let mut redacted = AggregatedRelease {
+            version: release.version.clone(),
+            date: release.date,
+            summary: ReleaseSummary::from_components(
+                &components,
+                release.summary.skipped_archived.iter()
+                    .filter(|r| !self.hides_repo(r))
+                    .cloned()
+                    .collect(),
+            ),
+            components,
+            sections,
+            sub_trains: Vec::new(),
+        };
+        redacted.summary.retired = release.summary.retired.iter()
+            .filter(|r| !self.hides_repo(&r.repository))
+            .cloned()
+            .collect();
+        for sub_train in &release.sub_trains {
+            redacted.add_sub_train(self.apply(sub_train));
+        }
+        redacted
+    }
+
+    fn hides_repo(&self, repo: &str) -> bool {
+        self.hide_repos.iter().any(|pattern| matches_pattern(pattern, repo))
+    }
+
+    fn shows_commit(&self, commit: &EnrichedCommit) -> bool {
+        match &commit.commit_type {
+            Some(commit_type) => !self.hide_types.contains(commit_type),
+            None => commit.section.is_some() || !self.hide_uncategorized,
+        }
+    }
+
+    fn redact_commit(&self, commit: &mut EnrichedCommit) {
+        commit.message = self.redact_text(&commit.message);
+        for issue in &mut commit.resolved_issues {
+            issue.title = self.redact_text(&issue.title);
+        }
+        if self.hide_authors {
+            commit.author.clear();
+        }
+        if self.hide_issue_keys {
+            commit.issue_keys.clear();
+        }
+    }
+
+    fn redact_text(&self, text: &str) -> String {
+        self.patterns.iter().fold(text.to_string(), |text, pattern| {
+            pattern.replace_all(&text, REDACTED).into_owned()
+        })
+    }
+}
@@ -1,49 +1,278 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::config::types::{Config, RefMode, RepoSettings, SectionConfig};
 use crate::github::client::GitHubClient;
-use super::commit_analyzer::{CommitAnalyzer, EnrichedCommit};
+use crate::provider::ReleaseProvider;
+use crate::github::types::{ChangedFile, CommitComparison, CommitInfo, DateSource, FileStatus, PreviousStrategy, Release};
+use crate::state::store::{range_key, short_sha, BranchHistory, CachedRange};
+use super::commit_analyzer::{CommitAnalyzer, CommitParser, CommitSort, EnrichedCommit, IssueDetails};
+use super::fragments::{self, Fragment, DEFAULT_FRAGMENTS_DIR};
+use super::known_issues::KnownIssue;
+use super::risk::{RiskInputs, RiskScore};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AggregatorConfig {
     pub include_prs: bool,
     pub include_issues: bool,
     pub categorize_commits: bool,
     pub template_path: Option<PathBuf>,
+    pub sections: Vec<SectionConfig>,
+    pub date_source: DateSource,
+    pub previous_strategy: PreviousStrategy,
+    /// Commit message parser per repository; repositories not listed use conventional commits
+    pub parsers: HashMap<String, CommitParser>,
+    /// Per-repository settings from the config, keyed by configured repository name
+    pub repo_settings: HashMap<String, RepoSettings>,
+    pub commit_sort: CommitSort,
+    /// Process archived repositories instead of skipping them
+    pub include_archived: bool,
+    /// Record asset download counts of each component's previous release
+    pub download_stats: bool,
+    /// Maximum number of repositories processed at once
+    pub concurrency: usize,
+    /// Section title per pull request label, applied wherever pull requests are attached
+    pub label_sections: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+pub const DEFAULT_CONCURRENCY: usize = 5;
+
+impl Default for AggregatorConfig {
+    /// Categorized commits from every repository, with nothing optional fetched.
+    fn default() -> Self {
+        Self {
+            include_prs: false,
+            include_issues: false,
+            categorize_commits: true,
+            template_path: None,
+            sections: vec![],
+            date_source: DateSource::default(),
+            previous_strategy: PreviousStrategy::default(),
+            parsers: HashMap::new(),
+            repo_settings: HashMap::new(),
+            commit_sort: CommitSort::default(),
+            include_archived: false,
+            download_stats: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            label_sections: BTreeMap::new(),
+        }
+    }
+}
+
+impl AggregatorConfig {
+    /// The settings `config` declares: its sections, label sections, per-repository settings
+    /// and commit parsers, commit sort and `[features]`. Everything else keeps its default.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut parsers = HashMap::new();
+        for (repo, settings) in &config.repos.settings {
+            let parser = CommitParser::from_settings(settings)
+                .with_context(|| format!("Invalid commit parser settings for {}", repo))?;
+            parsers.insert(repo.clone(), parser);
+        }
+        let commit_sort = match config.output.sort.as_deref() {
+            Some(sort) => sort.parse().map_err(anyhow::Error::msg)?,
+            None => CommitSort::default(),
+        };
+
+        Ok(Self {
+            include_prs: config.features.include_prs,
+            include_issues: config.features.include_issues,
+            categorize_commits: config.features.categorize_commits,
+            sections: config.sections.clone(),
+            parsers,
+            repo_settings: config.repos.settings.clone(),
+            commit_sort,
+            label_sections: config.labels.clone(),
+            ..Self::default()
+        })
+    }
+}
+
+/// Contributors checked for earlier commits per component when scoring risk.
+const MAX_CONTRIBUTOR_LOOKUPS: usize = 25;
+
+/// What a component's changelog entries are built from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangelogMode {
+    /// Every commit between the two releases
+    #[default]
+    Commits,
+    /// Pull requests merged between the two release dates, categorized by label
+    Prs,
+    /// Changelog fragment files (`changelog.d/*.md`) added between the two release tags
+    Fragments,
+}
+
+impl std::str::FromStr for ChangelogMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "commits" => Ok(ChangelogMode::Commits),
+            "prs" | "pull-requests" => Ok(ChangelogMode::Prs),
+            "fragments" => Ok(ChangelogMode::Fragments),
+            _ => Err(format!("Unknown changelog mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedRelease {
     pub version: String,
     pub date: DateTime<Utc>,
     pub components: Vec<ComponentRelease>,
     pub summary: ReleaseSummary,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<ReleaseSection>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_trains: Vec<AggregatedRelease>,
+}
+
+impl AggregatedRelease {
+    /// Load a previously generated JSON export so it can be nested in a parent train.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read release export {}", path.display()))?;
+        let release = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse release export {}", path.display()))?;
+        Ok(release)
+    }
+
+    /// Release date of the component released last, to date a train generated after the fact.
+    pub fn latest_release_date(&self) -> Option<DateTime<Utc>> {
+        self.components.iter()
+            .filter_map(|component| match &component.status {
+                ComponentStatus::Released { release_date, .. } => *release_date,
+                _ => None,
+            })
+            .max()
+    }
+
+    /// Sort every collection that doesn't already have a defined order, here and in sub-trains,
+    /// so identical data always serializes identically. Component and commit order are kept.
+    pub fn make_deterministic(&mut self) {
+        self.summary.contributors.sort();
+        self.summary.skipped_archived.sort();
+        for component in &mut self.components {
+            if let ComponentStatus::Released { commits, stats, .. } = &mut component.status {
+                stats.contributors.sort();
+                for commit in commits {
+                    commit.issues.sort_unstable();
+                    commit.issues.dedup();
+                    commit.issue_keys.sort();
+                    commit.issue_keys.dedup();
+                }
+            }
+        }
+        for sub_train in &mut self.sub_trains {
+            sub_train.make_deterministic();
+        }
+    }
+
+    /// Apply `--deterministic` and `--generated-at` to a freshly aggregated release: sort it
+    /// if `deterministic`, and date it `generated_at`, or the Unix epoch when deterministic.
+    pub fn pin(&mut self, deterministic: bool, generated_at: Option<DateTime<Utc>>) {
+        if deterministic {
+            self.make_deterministic();
+        }
+        if let Some(generated_at) = generated_at.or(deterministic.then_some(DateTime::UNIX_EPOCH)) {
+            self.date = generated_at;
+        }
+    }
+
+    /// Nest another train under this one, folding its totals into the parent summary.
+    pub fn add_sub_train(&mut self, sub_train: AggregatedRelease) {
+        self.summary.total_repos += sub_train.summary.total_repos;
+        self.summary.updated_repos += sub_train.summary.updated_repos;
+        self.summary.total_commits += sub_train.summary.total_commits;
+        self.summary.contributors.extend(sub_train.summary.contributors.iter().cloned());
+        self.summary.contributors.sort();
+        self.summary.contributors.dedup();
+        self.summary.skipped_archived.extend(sub_train.summary.skipped_archived.iter().cloned());
+        self.summary.retired.extend(sub_train.summary.retired.iter().cloned());
+
+        self.sub_trains.push(sub_train);
+    }
+}
+
+/// Named grouping of components; repositories are listed in render order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseSection {
+    pub name: String,
+    pub repositories: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComponentRelease {
     pub repository: String,
+    /// Name the repository was configured under, when GitHub reports it was renamed or transferred
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
     pub status: ComponentStatus,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ComponentStatus {
     Released {
         current_version: String,
         previous_version: Option<String>,
-        release_date: DateTime<Utc>,
+        /// Date chosen by the configured `DateSource`; `None` only if GitHub reports neither timestamp
+        release_date: Option<DateTime<Utc>>,
+        created_at: Option<DateTime<Utc>>,
+        published_at: Option<DateTime<Utc>>,
+        /// Commit SHA the release tag resolved to at aggregation time
+        #[serde(default)]
+        target_sha: Option<String>,
         commits: Vec<EnrichedCommit>,
         release_notes: Option<String>,
         stats: ReleaseStats,
+        /// Asset downloads of the previous release to date, when download stats were requested
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        previous_downloads: Option<DownloadStats>,
+        /// Every release in the window, oldest first, when a date range was aggregated; the
+        /// fields above then describe the newest, and `commits` covers them all
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        releases: Vec<ReleaseEntry>,
+        /// Branch the component deploys from, when its release is a branch head rather than a
+        /// tag; the versions are then short SHAs
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+        /// Rollout risk, when risk scoring was requested
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        risk: Option<RiskScore>,
+        /// Whether the release is the repository's latest release on GitHub, for releases
+        /// looked up by version
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        is_latest: Option<bool>,
+        /// Open issues labeled as known issues, when they were requested
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        known_issues: Vec<KnownIssue>,
     },
     NoRelease {
         latest_version: Option<String>,
         latest_date: Option<DateTime<Utc>>,
     },
+    /// The repository has never published a release (or has no commits at all yet)
+    NotYetReleased {
+        empty: bool,
+    },
+}
+
+/// One of several releases rolled up into a component.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseEntry {
+    pub version: String,
+    pub date: Option<DateTime<Utc>>,
+    pub commit_count: usize,
+    pub release_notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReleaseStats {
     pub commit_count: usize,
     pub contributors: Vec<String>,
@@ -52,159 +281,1106 @@ pub struct ReleaseStats {
     pub fixes: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl ReleaseStats {
+    pub fn from_commits(commits: &[EnrichedCommit]) -> Self {
+        // Changelog fragments have no author
+        let mut contributors: Vec<String> = commits.iter()
+            .filter(|c| !c.author.is_empty())
+            .map(|c| c.author.clone())
+            .collect();
+        contributors.sort();
+        contributors.dedup();
+
+        ReleaseStats {
+            commit_count: commits.len(),
+            contributors,
+            breaking_changes: commits.iter().filter(|c| c.breaking).count(),
+            features: commits.iter()
+                .filter(|c| matches!(c.commit_type, Some(super::commit_analyzer::CommitType::Feature)))
+                .count(),
+            fixes: commits.iter()
+                .filter(|c| matches!(c.commit_type, Some(super::commit_analyzer::CommitType::Fix)))
+                .count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadStats {
+    pub version: String,
+    pub total: u64,
+    pub assets: Vec<AssetDownloads>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetDownloads {
+    pub name: String,
+    pub downloads: u64,
+}
+
+impl DownloadStats {
+    /// Download counts for a release's assets; `None` if it has no assets to count.
+    pub fn from_release(release: &Release) -> Option<Self> {
+        if release.assets.is_empty() {
+            return None;
+        }
+
+        let assets: Vec<AssetDownloads> = release.assets.iter()
+            .map(|asset| AssetDownloads {
+                name: asset.name.clone(),
+                downloads: asset.download_count,
+            })
+            .collect();
+
+        Some(Self {
+            version: release.tag_name.clone(),
+            total: assets.iter().map(|a| a.downloads).sum(),
+            assets,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReleaseSummary {
     pub total_repos: usize,
     pub updated_repos: usize,
     pub total_commits: usize,
     pub contributors: Vec<String>,
+    /// Archived repositories that were requested but left out of the train
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_archived: Vec<String>,
+    /// Components left out because they were retired by an earlier train
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retired: Vec<RetiredComponent>,
+}
+
+/// A component retired from the trains after `retired_after`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetiredComponent {
+    pub repository: String,
+    pub retired_after: String,
 }
 
-pub struct ReleaseAggregator {
-    client: GitHubClient,
+impl ReleaseSummary {
+    /// Totals over `components`: released components count as updated, and contributors are
+    /// deduplicated across them.
+    pub fn from_components(components: &[ComponentRelease], skipped_archived: Vec<String>) -> Self {
+        let mut contributors = Vec::new();
+        let mut total_commits = 0;
+        let mut updated_repos = 0;
+        for component in components {
+            if let ComponentStatus::Released { commits, stats, .. } = &component.status {
+                total_commits += commits.len();
+                contributors.extend(stats.contributors.iter().cloned());
+                updated_repos += 1;
+            }
+        }
+        contributors.sort();
+        contributors.dedup();
+
+        ReleaseSummary {
+            total_repos: components.len(),
+            updated_repos,
+            total_commits,
+            contributors,
+            skipped_archived,
+            retired: Vec::new(),
+        }
+    }
+}
+
+/// Aggregates a train from `P`'s releases; `GitHubClient` unless a test or another backend
+/// provides them.
+pub struct ReleaseAggregator<P: ReleaseProvider = GitHubClient> {
+    client: P,
     config: AggregatorConfig,
+    /// Tag to compare against instead of each component's previous release
+    base_version: Option<String>,
+    /// Report each default branch against its latest release instead of looking up a version
+    unreleased: bool,
+    /// Roll up every release dated within this inclusive window instead of looking up a version
+    date_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Heads released by earlier trains, keyed by repository, for `ref_mode = "branch"`
+    branch_history: std::collections::BTreeMap<String, BranchHistory>,
+    mode: ChangelogMode,
+    /// Score each released component's rollout risk
+    risk: bool,
+    /// Label of the open issues listed as each released component's known issues
+    known_issue_label: Option<String>,
+    /// Commit ranges resolved by earlier runs and this one, keyed by `range_key`; `None`
+    /// compares every range again
+    range_cache: Option<Mutex<BTreeMap<String, CachedRange>>>,
 }
 
-impl ReleaseAggregator {
-    pub fn new(client: GitHubClient, config: AggregatorConfig) -> Self {
-        Self { client, config }
+impl<P: ReleaseProvider> ReleaseAggregator<P> {
+    pub fn new(client: P, config: AggregatorConfig) -> Self {
+        Self {
+            client,
+            config,
+            base_version: None,
+            unreleased: false,
+            date_range: None,
+            branch_history: Default::default(),
+            mode: ChangelogMode::default(),
+            risk: false,
+            known_issue_label: None,
+            range_cache: None,
+        }
+    }
+
+    /// Cover everything from `base_version` up to the aggregated version. Repositories without
+    /// a `base_version` release fall back to their previous release.
+    pub fn with_base_version(mut self, base_version: Option<String>) -> Self {
+        self.base_version = base_version;
+        self
+    }
+
+    /// Describe what each default branch would ship if released now. Components list the
+    /// branch as their version and the latest release as the previous one; repositories with
+    /// nothing new are reported unchanged.
+    pub fn with_unreleased(mut self, unreleased: bool) -> Self {
+        self.unreleased = unreleased;
+        self
+    }
+
+    /// Roll up every release dated within `since..=until` per repository, for periodic reports.
+    pub fn with_date_range(mut self, date_range: Option<(DateTime<Utc>, DateTime<Utc>)>) -> Self {
+        self.date_range = date_range;
+        self
+    }
+
+    /// Branch heads recorded by earlier trains (see `StateStore::branches`). Without them, a
+    /// branch-deployed component's first train covers the branch's whole history.
+    pub fn with_branch_history(mut self, branch_history: std::collections::BTreeMap<String, BranchHistory>) -> Self {
+        self.branch_history = branch_history;
+        self
+    }
+
+    /// Build entries from merged pull requests instead of commits. Applies to releases looked
+    /// up by version and to `--unreleased`; date ranges and branch-deployed components always
+    /// list commits.
+    pub fn with_mode(mut self, mode: ChangelogMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Score the rollout risk of each component released under the version, from its diff
+    /// size, breaking changes, migrations, new contributors and time since its previous release.
+    /// Finding new contributors costs a request per contributor.
+    pub fn with_risk(mut self, risk: bool) -> Self {
+        self.risk = risk;
+        self
+    }
+
+    /// List the open issues labeled `label` under each released component, as they are when
+    /// the notes are generated. Costs a request per component.
+    pub fn with_known_issues(mut self, label: Option<String>) -> Self {
+        self.known_issue_label = label;
+        self
+    }
+
+    /// Answer commit ranges from `ranges` (see `StateStore::ranges`) while both of their refs
+    /// still point at the same commits, and keep the ranges compared anew for `range_cache`.
+    /// Costs a request per ref to check it, instead of the pages of a comparison.
+    pub fn with_range_cache(mut self, ranges: BTreeMap<String, CachedRange>) -> Self {
+        self.range_cache = Some(Mutex::new(ranges));
+        self
+    }
+
+    /// The commit ranges to keep for the next run: those loaded with `with_range_cache` plus
+    /// the ones compared since.
+    pub fn range_cache(&self) -> BTreeMap<String, CachedRange> {
+        self.range_cache.as_ref()
+            .map(|cache| cache.lock().expect("range cache lock poisoned").clone())
+            .unwrap_or_default()
+    }
+
+    pub fn client(&self) -> &P {
+        &self.client
+    }
+
+    pub fn config(&self) -> &AggregatorConfig {
+        &self.config
+    }
+
+    /// Tag name of the logical `version` in `repo`, following its `tag_pattern` or `tag_prefix`.
+    pub fn tag_for(&self, repo: &str, version: &str) -> String {
+        match self.config.repo_settings.get(repo) {
+            Some(settings) => settings.tag_for(version),
+            None => version.to_string(),
+        }
     }
 
     pub async fn aggregate(&self, version: &str, repos: Vec<String>) -> Result<AggregatedRelease> {
+        // Retired components are left out before anything is fetched for them
+        let (retired, repos): (Vec<String>, Vec<String>) = repos.into_iter()
+            .partition(|repo| self.config.repo_settings.get(repo).is_some_and(|s| s.retired_in(version)));
+        let retired: Vec<RetiredComponent> = retired.into_iter()
+            .map(|repository| RetiredComponent {
+                retired_after: self.config.repo_settings[&repository].retired_after.clone().unwrap_or_default(),
+                repository,
+            })
+            .collect();
+
+        if let Err(e) = self.client.prefetch(&repos).await {
+            tracing::warn!("GraphQL prefetch failed, fetching over REST: {:#}", e);
+        }
+
         let mut components = Vec::new();
-        let mut all_contributors = Vec::new();
-        let mut total_commits = 0;
-        let mut updated_repos = 0;
+        let mut skipped_archived = Vec::new();
 
-        // Process each repository
-        for repo in &repos {
-            let component = self.process_repository(repo, version).await?;
-            
-            // Collect stats
-            match &component.status {
-                ComponentStatus::Released { commits, stats, .. } => {
-                    total_commits += commits.len();
-                    all_contributors.extend(stats.contributors.clone());
-                    updated_repos += 1;
-                }
-                _ => {}
-            }
-            
+        // Process repositories concurrently, then restore the requested order. The names are
+        // cloned so the future stays `Send` when spawned (borrowed items trip up its inference)
+        let mut results: Vec<(usize, Result<Option<ComponentRelease>>)> = stream::iter(repos.iter().cloned().enumerate())
+            .map(|(index, repo)| async move {
+                let component = match self.process_repository(&repo, version).await {
+                    Ok(Some(component)) => self.add_known_issues(component).await.map(Some),
+                    other => other,
+                };
+                (index, component)
+            })
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect()
+            .await;
+        results.sort_by_key(|(index, _)| *index);
+
+        for (repo, (_, result)) in repos.iter().zip(results) {
+            let Some(component) = result? else {
+                skipped_archived.push(repo.clone());
+                continue;
+            };
             components.push(component);
         }
 
-        // Deduplicate contributors
-        all_contributors.sort();
-        all_contributors.dedup();
+        let mut summary = ReleaseSummary::from_components(&components, skipped_archived);
+        summary.retired = retired;
 
-        let summary = ReleaseSummary {
-            total_repos: repos.len(),
-            updated_repos,
-            total_commits,
-            contributors: all_contributors,
-        };
+        let repos: Vec<String> = repos.into_iter()
+            .filter(|r| !summary.skipped_archived.contains(r))
+            .collect();
+        let sections = Self::assign_sections(&self.config.sections, &repos, &components);
 
         Ok(AggregatedRelease {
             version: version.to_string(),
             date: Utc::now(),
             components,
             summary,
+            sections,
+            sub_trains: vec![],
         })
     }
 
-    async fn process_repository(&self, repo: &str, version: &str) -> Result<ComponentRelease> {
-        // Try to get the release for this version
-        let release = self.client.get_release(repo, version).await?;
+    fn assign_sections(configured: &[SectionConfig], repos: &[String], components: &[ComponentRelease]) -> Vec<ReleaseSection> {
+        if configured.is_empty() {
+            return vec![];
+        }
+
+        // Sections are configured by the names passed in; components may carry a renamed name
+        let component_name = |configured_name: &String| -> String {
+            components.iter()
+                .find(|c| c.renamed_from.as_ref() == Some(configured_name))
+                .map(|c| c.repository.clone())
+                .unwrap_or_else(|| configured_name.clone())
+        };
+
+        let mut sections: Vec<ReleaseSection> = configured.iter()
+            .map(|section| ReleaseSection {
+                name: section.name.clone(),
+                repositories: section.repos.iter()
+                    .filter(|r| repos.contains(r))
+                    .map(component_name)
+                    .collect(),
+            })
+            .filter(|section| !section.repositories.is_empty())
+            .collect();
+
+        // Anything not claimed by a configured section is grouped at the end
+        let unassigned: Vec<String> = repos.iter()
+            .filter(|r| !configured.iter().any(|s| s.repos.contains(r)))
+            .map(component_name)
+            .collect();
+        if !unassigned.is_empty() {
+            sections.push(ReleaseSection {
+                name: "Other Components".to_string(),
+                repositories: unassigned,
+            });
+        }
+
+        sections
+    }
+
+    async fn add_known_issues(&self, mut component: ComponentRelease) -> Result<ComponentRelease> {
+        let Some(label) = &self.known_issue_label else {
+            return Ok(component);
+        };
+        if let ComponentStatus::Released { known_issues, .. } = &mut component.status {
+            *known_issues = self.client.list_open_issues(&component.repository, label).await?
+                .into_iter()
+                .map(KnownIssue::from)
+                .collect();
+        }
+        Ok(component)
+    }
+
+    /// Build the component for one repository; `None` means it is archived and was skipped.
+    async fn process_repository(&self, configured_repo: &str, version: &str) -> Result<Option<ComponentRelease>> {
+        // Resolve renames so API calls and links use the canonical name; repositories transferred
+        // out of the org are addressed as `owner/name` from here on
+        let info = self.client.get_repository(configured_repo).await?;
+        let canonical = match &info {
+            Some(info) => self.client.canonical_name(info),
+            None => configured_repo.to_string(),
+        };
+        if !self.config.include_archived && info.as_ref().is_some_and(|i| i.archived) {
+            tracing::info!("Skipping archived repository {}", configured_repo);
+            return Ok(None);
+        }
+        let renamed_from = (canonical != configured_repo).then(|| configured_repo.to_string());
+        if let Some(old) = &renamed_from {
+            tracing::info!("{} is now {}", old, canonical);
+        }
+        let repo = canonical.as_str();
+        let default_branch = info.as_ref().and_then(|info| info.default_branch.clone());
+
+        let settings = self.config.repo_settings.get(configured_repo);
+        if settings.is_some_and(|s| s.ref_mode == RefMode::Branch) {
+            let branch = settings.and_then(|s| s.branch.clone())
+                .or_else(|| info.and_then(|info| info.default_branch))
+                .unwrap_or_else(|| "HEAD".to_string());
+            return self.branch_component(configured_repo, repo, renamed_from, &branch, version).await.map(Some);
+        }
+        if self.unreleased {
+            let head = info.and_then(|info| info.default_branch).unwrap_or_else(|| "HEAD".to_string());
+            return self.unreleased_component(configured_repo, repo, &head, renamed_from).await.map(Some);
+        }
+        if let Some(date_range) = self.date_range {
+            return self.range_component(configured_repo, repo, renamed_from, date_range).await.map(Some);
+        }
+
+        // Try to get the release for this version, under the repository's tag scheme
+        let release = self.client.get_release(repo, &self.tag_for(configured_repo, version)).await?;
 
         if let Some(release) = release {
-            // Get the previous release to compare
-            let previous_release = self.client.get_previous_release(repo, &release).await?;
-            
-            let commits = if let Some(prev) = &previous_release {
-                // Get commits between releases
-                self.client.get_commits_between(repo, &prev.tag_name, &release.tag_name).await?
-            } else {
-                // First release - get all commits up to this point
-                self.client.get_all_commits_until(repo, &release.tag_name).await?
-            };
+            let target_sha = self.client.resolve_ref_sha(repo, &release.tag_name).await?;
 
-            // Analyze commits
-            let enriched_commits = if self.config.categorize_commits {
-                CommitAnalyzer::analyze_commits(commits)
-            } else {
-                commits.into_iter().map(|c| EnrichedCommit {
-                    sha: c.sha.clone(),
-                    message: c.message.clone(),
-                    author: c.author.username.unwrap_or(c.author.name),
-                    date: c.date,
-                    commit_type: None,
-                    breaking: false,
-                    pr_number: None,
-                    issues: vec![],
-                }).collect()
+            // Get the release to compare against: the requested base, else the previous release
+            let base_release = match &self.base_version {
+                Some(base) => {
+                    let found = self.client.get_release(repo, &self.tag_for(configured_repo, base)).await?;
+                    if found.is_none() {
+                        tracing::warn!("{}: no release {}; comparing against the previous release instead", repo, base);
+                    }
+                    found
+                }
+                None => None,
             };
-
-            // Get PR information if requested
-            let enriched_commits = if self.config.include_prs {
-                let shas = enriched_commits.iter().map(|c| c.sha.clone()).collect();
-                let prs = self.client.get_pull_requests_for_commits(repo, shas).await?;
-                
-                // Merge PR information into commits
-                enriched_commits.into_iter().map(|mut commit| {
-                    for pr in &prs {
-                        if let Some(ref merge_sha) = pr.merge_commit_sha {
-                            if merge_sha == &commit.sha {
-                                commit.pr_number = Some(pr.number);
-                            }
+            let previous_release = match base_release {
+                Some(base) => Some(base),
+                None => self.client.get_previous_release(repo, &release, settings, self.config.date_source, self.config.previous_strategy).await?,
+            };
+            
+            let mut changed_files = None;
+            let enriched_commits = match self.mode {
+                ChangelogMode::Commits => {
+                    let commits = if let Some(prev) = &previous_release {
+                        // Get commits between releases
+                        let changes = self.changes_between(repo, &prev.tag_name, &release.tag_name, target_sha.as_deref()).await?;
+                        changed_files = Some(changes.files);
+                        changes.commits
+                    } else {
+                        // First release - get all commits up to this point
+                        self.client.get_all_commits_until(repo, &release.tag_name).await?
+                    };
+                    self.enrich_commits(configured_repo, repo, commits).await?
+                }
+                ChangelogMode::Prs => {
+                    let since = previous_release.as_ref()
+                        .and_then(|r| self.config.date_source.resolve(r.created_at, r.published_at));
+                    // An undated release bounds nothing: every pull request merged since the previous one
+                    let until = self.config.date_source.resolve(release.created_at, release.published_at);
+                    self.pull_request_entries(configured_repo, repo, default_branch.as_deref(), since, until).await?
+                }
+                ChangelogMode::Fragments => {
+                    let dir = settings.and_then(|s| s.fragments_dir.as_deref()).unwrap_or(DEFAULT_FRAGMENTS_DIR);
+                    let paths: Vec<String> = if let Some(prev) = &previous_release {
+                        // Fragments added since the previous release; ones removed when it was
+                        // cut belong to it
+                        let changes = self.changes_between(repo, &prev.tag_name, &release.tag_name, target_sha.as_deref()).await?;
+                        let added = changes.files.iter()
+                            .filter(|f| f.status == FileStatus::Added && fragments::in_directory(&f.filename, dir))
+                            .map(|f| f.filename.clone())
+                            .collect();
+                        changed_files = Some(changes.files);
+                        added
+                    } else {
+                        // First release - every fragment in the tree
+                        self.client.list_directory(repo, dir, &release.tag_name).await?
+                    };
+                    match self.config.date_source.resolve(release.created_at, release.published_at) {
+                        Some(date) => self.fragment_entries(repo, &paths, &release.tag_name, date).await?,
+                        None => {
+                            tracing::warn!("{}: {} has no date to give its changelog fragments; skipping them", repo, release.tag_name);
+                            Vec::new()
                         }
                     }
-                    commit
-                }).collect()
+                }
+            };
+
+            let stats = ReleaseStats::from_commits(&enriched_commits);
+            let risk = if self.risk {
+                Some(self.score_risk(repo, &enriched_commits, changed_files.as_deref(), &release, previous_release.as_ref()).await?)
             } else {
-                enriched_commits
+                None
             };
 
-            // Calculate statistics
-            let mut contributors: Vec<String> = enriched_commits.iter()
-                .map(|c| c.author.clone())
-                .collect();
-            contributors.sort();
-            contributors.dedup();
-
-            let stats = ReleaseStats {
-                commit_count: enriched_commits.len(),
-                contributors: contributors.clone(),
-                breaking_changes: enriched_commits.iter().filter(|c| c.breaking).count(),
-                features: enriched_commits.iter()
-                    .filter(|c| matches!(c.commit_type, Some(super::commit_analyzer::CommitType::Feature)))
-                    .count(),
-                fixes: enriched_commits.iter()
-                    .filter(|c| matches!(c.commit_type, Some(super::commit_analyzer::CommitType::Fix)))
-                    .count(),
+            let is_latest = self.is_latest(repo, &release).await?;
+
+            let previous_downloads = if self.config.download_stats {
+                previous_release.as_ref().and_then(DownloadStats::from_release)
+            } else {
+                None
             };
 
-            Ok(ComponentRelease {
-                repository: repo.to_string(),
+            Ok(Some(ComponentRelease {
+                repository: canonical.clone(),
+                renamed_from,
                 status: ComponentStatus::Released {
                     current_version: release.tag_name.clone(),
                     previous_version: previous_release.map(|r| r.tag_name),
-                    release_date: release.created_at.unwrap_or_else(|| Utc::now()),
+                    release_date: self.config.date_source.resolve(release.created_at, release.published_at),
+                    created_at: release.created_at,
+                    published_at: release.published_at,
+                    target_sha,
                     commits: enriched_commits,
                     release_notes: release.body.clone(),
                     stats,
+                    previous_downloads,
+                    releases: Vec::new(),
+                    branch: None,
+                    risk,
+                    is_latest,
+                    known_issues: vec![],
                 },
-            })
+            }))
         } else {
             // No release for this version - get the latest release info
             let latest = self.client.get_latest_release(repo).await?;
 
-            Ok(ComponentRelease {
-                repository: repo.to_string(),
+            if latest.is_none() {
+                return Ok(Some(ComponentRelease {
+                    repository: canonical.clone(),
+                    renamed_from,
+                    status: ComponentStatus::NotYetReleased {
+                        empty: self.client.is_empty_repository(repo).await?,
+                    },
+                }));
+            }
+
+            Ok(Some(ComponentRelease {
+                repository: canonical.clone(),
+                renamed_from,
                 status: ComponentStatus::NoRelease {
                     latest_version: latest.as_ref().map(|r| r.tag_name.clone()),
-                    latest_date: latest.and_then(|r| r.created_at),
+                    latest_date: latest.and_then(|r| self.config.date_source.resolve(r.created_at, r.published_at)),
                 },
-            })
+            }))
+        }
+    }
+
+    /// Commits and changed files from `from` to `to`, from the range cache when both refs still
+    /// point where they did when the range was compared. `to_sha` saves resolving `to` again.
+    async fn changes_between(&self, repo: &str, from: &str, to: &str, to_sha: Option<&str>) -> Result<CommitComparison> {
+        let Some(cache) = &self.range_cache else {
+            return self.client.get_changes_between(repo, from, to).await;
+        };
+        let base_sha = self.ref_sha(repo, from).await?;
+        let head_sha = match to_sha {
+            Some(sha) => Some(sha.to_string()),
+            None => self.ref_sha(repo, to).await?,
+        };
+        let (Some(base_sha), Some(head_sha)) = (base_sha, head_sha) else {
+            return self.client.get_changes_between(repo, from, to).await;
+        };
+
+        let key = range_key(repo, from, to);
+        {
+            let mut cache = cache.lock().expect("range cache lock poisoned");
+            if let Some(cached) = cache.get_mut(&key).filter(|c| c.base_sha == base_sha && c.head_sha == head_sha) {
+                tracing::debug!("{}: {}...{} answered from the range cache", repo, from, to);
+                cached.last_used = Utc::now();
+                return Ok(cached.comparison.clone());
+            }
+        }
+
+        let comparison = self.client.get_changes_between(repo, from, to).await?;
+        // A comparison cut short by --max-pages would keep answering short after it's raised
+        if comparison.commits.len() as u64 >= comparison.ahead_by {
+            cache.lock().expect("range cache lock poisoned").insert(key, CachedRange {
+                base_sha,
+                head_sha,
+                last_used: Utc::now(),
+                comparison: comparison.clone(),
+            });
+        }
+        Ok(comparison)
+    }
+
+    /// The commit `reference` points at; a full commit SHA is taken as is.
+    async fn ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        if reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(Some(reference.to_string()));
+        }
+        self.client.resolve_ref_sha(repo, reference).await
+    }
+
+    /// Whether `release` is `repo`'s latest release. A release that should be latest but isn't,
+    /// because a release created before it is, was likely published with `make_latest` off by
+    /// mistake, and is warned about.
+    async fn is_latest(&self, repo: &str, release: &Release) -> Result<Option<bool>> {
+        let Some(latest) = self.client.get_latest_release(repo).await? else {
+            return Ok(None);
+        };
+        let is_latest = latest.tag_name == release.tag_name;
+        if !is_latest && !release.prerelease && latest.created_at < release.created_at {
+            tracing::warn!("{}: {} is not marked as the latest release; {} is", repo, release.tag_name, latest.tag_name);
+        }
+        Ok(Some(is_latest))
+    }
+
+    /// Rollout risk of `release`. Contributors are new if they have no commits dated before the
+    /// previous release; only GitHub logins can be checked, and bots are never counted.
+    async fn score_risk(
+        &self,
+        repo: &str,
+        commits: &[EnrichedCommit],
+        files: Option<&[ChangedFile]>,
+        release: &Release,
+        previous_release: Option<&Release>,
+    ) -> Result<RiskScore> {
+        let released_at = self.config.date_source.resolve(release.created_at, release.published_at);
+        let previous_at = previous_release.and_then(|r| self.config.date_source.resolve(r.created_at, r.published_at));
+
+        let mut new_contributors = Vec::new();
+        if let Some(previous_at) = previous_at {
+            let mut authors: Vec<&str> = commits.iter()
+                .map(|c| c.author.as_str())
+                .filter(|author| author.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+                .collect();
+            authors.sort();
+            authors.dedup();
+            for author in authors.into_iter().take(MAX_CONTRIBUTOR_LOOKUPS) {
+                if !self.client.has_commits_before(repo, author, previous_at).await? {
+                    new_contributors.push(author.to_string());
+                }
+            }
+        }
+
+        Ok(RiskScore::compute(&RiskInputs {
+            commits,
+            files,
+            new_contributors: &new_contributors,
+            first_release: previous_release.is_none(),
+            since_previous: released_at.zip(previous_at).map(|(current, previous)| current - previous),
+        }))
+    }
+
+    /// Categorize commits with the repository's parser (when enabled), attach pull requests,
+    /// and apply the configured ordering.
+    async fn enrich_commits(&self, configured_repo: &str, repo: &str, commits: Vec<CommitInfo>) -> Result<Vec<EnrichedCommit>> {
+        let enriched_commits = if self.config.categorize_commits {
+            let parser = self.config.parsers.get(configured_repo).cloned().unwrap_or_default();
+            CommitAnalyzer::analyze_commits_with(commits, &parser)
+        } else {
+            commits.into_iter().map(|c| EnrichedCommit {
+                sha: c.sha.clone(),
+                message: CommitAnalyzer::sanitize_text(&c.message),
+                author: CommitAnalyzer::sanitize_text(&c.author.username.unwrap_or(c.author.name)),
+                date: c.date,
+                commit_type: None,
+                section: None,
+                scope: None,
+                breaking: false,
+                pr_number: None,
+                issues: vec![],
+                issue_keys: vec![],
+                verified: c.verified,
+                resolved_issues: vec![],
+            }).collect()
+        };
+
+        // Get PR information if requested
+        let mut enriched_commits = if self.config.include_prs {
+            self.attach_pull_requests(repo, enriched_commits).await?
+        } else {
+            enriched_commits
+        };
+        if self.config.include_issues {
+            enriched_commits = self.attach_issues(repo, enriched_commits).await?;
+        }
+
+        CommitAnalyzer::sort_commits(&mut enriched_commits, self.config.commit_sort);
+        Ok(enriched_commits)
+    }
+
+    /// Entries for the pull requests merged into `base` within `since..=until`, always
+    /// categorized (labels first, then the title), with the configured ordering.
+    async fn pull_request_entries(
+        &self,
+        configured_repo: &str,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<EnrichedCommit>> {
+        let prs = self.client.list_merged_pull_requests(repo, base, since, until).await?;
+        let parser = self.config.parsers.get(configured_repo).cloned().unwrap_or_default();
+        let mut entries = CommitAnalyzer::analyze_pull_requests(prs, &parser, &self.config.label_sections);
+        if self.config.include_issues {
+            entries = self.attach_issues(repo, entries).await?;
+        }
+        CommitAnalyzer::sort_commits(&mut entries, self.config.commit_sort);
+        Ok(entries)
+    }
+
+    /// Entries for the fragment files at `paths`, read at `tag`, with the configured ordering.
+    async fn fragment_entries(&self, repo: &str, paths: &[String], tag: &str, date: DateTime<Utc>) -> Result<Vec<EnrichedCommit>> {
+        let mut entries = Vec::new();
+        for path in paths {
+            let Some(text) = self.client.get_file_contents(repo, path, tag).await? else {
+                continue;
+            };
+            match Fragment::parse(path, &text) {
+                Some(fragment) => entries.push(fragment.into_entry(date)),
+                None => tracing::debug!("{}: {} is not a changelog fragment", repo, path),
+            }
         }
+        if self.config.include_issues {
+            entries = self.attach_issues(repo, entries).await?;
+        }
+        CommitAnalyzer::sort_commits(&mut entries, self.config.commit_sort);
+        Ok(entries)
+    }
+
+    /// Component for a date range: every release dated within `since..=until`, with the commits
+    /// they shipped combined. The newest is reported as the current version and the release
+    /// before the oldest as the previous one.
+    async fn range_component(
+        &self,
+        configured_repo: &str,
+        repo: &str,
+        renamed_from: Option<String>,
+        (since, until): (DateTime<Utc>, DateTime<Utc>),
+    ) -> Result<ComponentRelease> {
+        let mut dated: Vec<(DateTime<Utc>, Release)> = self.client.list_all_releases(repo).await?
+            .into_iter()
+            .filter_map(|r| self.config.date_source.resolve(r.created_at, r.published_at).map(|date| (date, r)))
+            .filter(|(date, _)| *date <= until)
+            .collect();
+        dated.sort_by_key(|(date, _)| *date);
+        let first_in_window = dated.iter().position(|(date, _)| *date >= since).unwrap_or(dated.len());
+        let in_window = dated.split_off(first_in_window);
+
+        let Some((newest_date, newest)) = in_window.last() else {
+            // Nothing released in the window; report the last release before it, if any
+            return Ok(ComponentRelease {
+                repository: repo.to_string(),
+                renamed_from,
+                status: match dated.pop() {
+                    Some((date, latest)) => ComponentStatus::NoRelease {
+                        latest_version: Some(latest.tag_name),
+                        latest_date: Some(date),
+                    },
+                    None => ComponentStatus::NotYetReleased { empty: false },
+                },
+            });
+        };
+
+        let mut entries = Vec::new();
+        let mut commits: Vec<CommitInfo> = Vec::new();
+        let mut baseline = None;
+        for (index, (date, release)) in in_window.iter().enumerate() {
+            let previous = self.client.get_previous_release(repo, release, self.config.repo_settings.get(configured_repo), self.config.date_source, self.config.previous_strategy).await?;
+            let release_commits = match &previous {
+                Some(previous) => self.changes_between(repo, &previous.tag_name, &release.tag_name, None).await?.commits,
+                None => self.client.get_all_commits_until(repo, &release.tag_name).await?,
+            };
+            if index == 0 {
+                baseline = previous;
+            }
+
+            entries.push(ReleaseEntry {
+                version: release.tag_name.clone(),
+                date: Some(*date),
+                commit_count: release_commits.len(),
+                release_notes: release.body.clone(),
+            });
+            for commit in release_commits {
+                if !commits.iter().any(|c| c.sha == commit.sha) {
+                    commits.push(commit);
+                }
+            }
+        }
+
+        let commits = self.enrich_commits(configured_repo, repo, commits).await?;
+        let previous_downloads = if self.config.download_stats {
+            baseline.as_ref().and_then(DownloadStats::from_release)
+        } else {
+            None
+        };
+
+        Ok(ComponentRelease {
+            repository: repo.to_string(),
+            renamed_from,
+            status: ComponentStatus::Released {
+                current_version: newest.tag_name.clone(),
+                previous_version: baseline.map(|r| r.tag_name),
+                release_date: Some(*newest_date),
+                created_at: newest.created_at,
+                published_at: newest.published_at,
+                target_sha: self.client.resolve_ref_sha(repo, &newest.tag_name).await?,
+                stats: ReleaseStats::from_commits(&commits),
+                commits,
+                release_notes: None,
+                previous_downloads,
+                releases: entries,
+                branch: None,
+                risk: None,
+                is_latest: None,
+                known_issues: vec![],
+            },
+        })
     }
-}
\ No newline at end of file
+
+    /// Commits on the default branch since the latest published release, enriched as a released
+    /// component's are. Returns the tag they were counted from, if any.
+    pub async fn unreleased_commits(&self, configured_repo: &str) -> Result<(Option<String>, Vec<EnrichedCommit>)> {
+        let info = self.client.get_repository(configured_repo).await?;
+        let repo = match &info {
+            Some(info) => self.client.canonical_name(info),
+            None => configured_repo.to_string(),
+        };
+        let head = info.and_then(|info| info.default_branch).unwrap_or_else(|| "HEAD".to_string());
+        let (latest, commits) = self.commits_since_latest(configured_repo, &repo, &head).await?;
+        Ok((latest.map(|r| r.tag_name), commits))
+    }
+
+    /// The commits from `from` to `to` in `repo` as one component, whatever the refs are: tags,
+    /// branches or SHAs. For reviewing a hotfix or backport outside a train.
+    pub async fn compare_refs(&self, configured_repo: &str, from: &str, to: &str) -> Result<ComponentRelease> {
+        let info = self.client.get_repository(configured_repo).await?
+            .with_context(|| format!("Repository {} not found", configured_repo))?;
+        let repo = self.client.canonical_name(&info);
+        let renamed_from = (repo != configured_repo).then(|| configured_repo.to_string());
+        if self.ref_sha(&repo, from).await?.is_none() {
+            anyhow::bail!("{} has no tag, branch or commit {}", repo, from);
+        }
+        let to_sha = self.ref_sha(&repo, to).await?
+            .with_context(|| format!("{} has no tag, branch or commit {}", repo, to))?;
+
+        let changes = self.changes_between(&repo, from, to, Some(&to_sha)).await?;
+        let commits = self.enrich_commits(configured_repo, &repo, changes.commits).await?;
+
+        Ok(ComponentRelease {
+            repository: repo,
+            renamed_from,
+            status: ComponentStatus::Released {
+                current_version: to.to_string(),
+                previous_version: Some(from.to_string()),
+                release_date: commits.iter().map(|c| c.date).max(),
+                created_at: None,
+                published_at: None,
+                target_sha: Some(to_sha),
+                stats: ReleaseStats::from_commits(&commits),
+                commits,
+                release_notes: None,
+                previous_downloads: None,
+                releases: Vec::new(),
+                branch: None,
+                risk: None,
+                is_latest: None,
+                known_issues: vec![],
+            },
+        })
+    }
+
+    async fn commits_since_latest(
+        &self,
+        configured_repo: &str,
+        repo: &str,
+        head: &str,
+    ) -> Result<(Option<Release>, Vec<EnrichedCommit>)> {
+        let latest = self.client.get_latest_release(repo).await?;
+
+        if self.mode == ChangelogMode::Prs {
+            let since = latest.as_ref().and_then(|r| self.config.date_source.resolve(r.created_at, r.published_at));
+            let entries = self.pull_request_entries(configured_repo, repo, Some(head), since, None).await?;
+            return Ok((latest, entries));
+        }
+
+        let commits = match &latest {
+            Some(latest) => self.changes_between(repo, &latest.tag_name, head, None).await?.commits,
+            None => self.client.get_all_commits_until(repo, head).await?,
+        };
+
+        let commits = self.enrich_commits(configured_repo, repo, commits).await?;
+        Ok((latest, commits))
+    }
+
+    /// Component for `--unreleased`: the default branch `head` compared with the latest release.
+    async fn unreleased_component(
+        &self,
+        configured_repo: &str,
+        repo: &str,
+        head: &str,
+        renamed_from: Option<String>,
+    ) -> Result<ComponentRelease> {
+        let (latest, commits) = self.commits_since_latest(configured_repo, repo, head).await?;
+
+        if commits.is_empty() {
+            return Ok(ComponentRelease {
+                repository: repo.to_string(),
+                renamed_from,
+                status: match latest {
+                    Some(latest) => ComponentStatus::NoRelease {
+                        latest_date: self.config.date_source.resolve(latest.created_at, latest.published_at),
+                        latest_version: Some(latest.tag_name),
+                    },
+                    None => ComponentStatus::NotYetReleased { empty: true },
+                },
+            });
+        }
+
+        Ok(ComponentRelease {
+            repository: repo.to_string(),
+            renamed_from,
+            status: ComponentStatus::Released {
+                current_version: head.to_string(),
+                previous_version: latest.map(|r| r.tag_name),
+                release_date: commits.iter().map(|c| c.date).max(),
+                created_at: None,
+                published_at: None,
+                target_sha: self.client.resolve_ref_sha(repo, head).await?,
+                stats: ReleaseStats::from_commits(&commits),
+                commits,
+                release_notes: None,
+                previous_downloads: None,
+                releases: Vec::new(),
+                branch: None,
+                risk: None,
+                is_latest: None,
+                known_issues: vec![],
+            },
+        })
+    }
+
+    /// Component for `ref_mode = "branch"`: the head of `branch` now, or the head recorded when
+    /// train `version` was first generated, compared with the head released by the train before.
+    async fn branch_component(
+        &self,
+        configured_repo: &str,
+        repo: &str,
+        renamed_from: Option<String>,
+        branch: &str,
+        version: &str,
+    ) -> Result<ComponentRelease> {
+        let (previous, recorded) = self.branch_history.get(repo)
+            .map(|history| history.window(version))
+            .unwrap_or_default();
+        let head = match recorded {
+            Some(train) => Some(train.sha.clone()),
+            None => self.client.resolve_ref_sha(repo, branch).await?,
+        };
+        let Some(head) = head else {
+            return Ok(ComponentRelease {
+                repository: repo.to_string(),
+                renamed_from,
+                status: ComponentStatus::NotYetReleased { empty: self.client.is_empty_repository(repo).await? },
+            });
+        };
+        if let Some(previous) = previous.filter(|p| p.sha == head) {
+            return Ok(ComponentRelease {
+                repository: repo.to_string(),
+                renamed_from,
+                status: ComponentStatus::NoRelease {
+                    latest_version: Some(short_sha(&previous.sha).to_string()),
+                    latest_date: Some(previous.recorded_at),
+                },
+            });
+        }
+
+        let commits = match previous {
+            Some(previous) => self.changes_between(repo, &previous.sha, &head, Some(&head)).await?.commits,
+            None => self.client.get_all_commits_until(repo, &head).await?,
+        };
+        let commits = self.enrich_commits(configured_repo, repo, commits).await?;
+
+        Ok(ComponentRelease {
+            repository: repo.to_string(),
+            renamed_from,
+            status: ComponentStatus::Released {
+                current_version: short_sha(&head).to_string(),
+                previous_version: previous.map(|p| short_sha(&p.sha).to_string()),
+                release_date: commits.iter().map(|c| c.date).max(),
+                created_at: None,
+                published_at: None,
+                stats: ReleaseStats::from_commits(&commits),
+                commits,
+                release_notes: None,
+                previous_downloads: None,
+                releases: Vec::new(),
+                target_sha: Some(head),
+                branch: Some(branch.to_string()),
+                risk: None,
+                is_latest: None,
+                known_issues: vec![],
+            },
+        })
+    }
+
+    async fn attach_pull_requests(&self, repo: &str, commits: Vec<EnrichedCommit>) -> Result<Vec<EnrichedCommit>> {
+        let shas = commits.iter().map(|c| c.sha.clone()).collect();
+        let prs = self.client.get_pull_requests_for_commits(repo, shas).await?;
+
+        // Merge PR information into commits. Labels outrank the commit message, but type labels
+        // only count when commits are being categorized anyway.
+        Ok(commits.into_iter().map(|mut commit| {
+            for pr in &prs {
+                if let Some(ref merge_sha) = pr.merge_commit_sha {
+                    if merge_sha == &commit.sha {
+                        commit.pr_number = Some(pr.number);
+                        CommitAnalyzer::apply_labels(
+                            &mut commit,
+                            &pr.labels,
+                            &self.config.label_sections,
+                            self.config.categorize_commits,
+                        );
+                    }
+                }
+            }
+            commit
+        }).collect())
+    }
+
+    /// Resolve the issue numbers referenced by each commit, other than its own pull request.
+    async fn attach_issues(&self, repo: &str, mut commits: Vec<EnrichedCommit>) -> Result<Vec<EnrichedCommit>> {
+        let mut numbers: Vec<u64> = commits.iter()
+            .flat_map(|c| c.issues.iter().copied().filter(move |n| Some(*n) != c.pr_number))
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        if numbers.is_empty() {
+            return Ok(commits);
+        }
+
+        let issues: HashMap<u64, IssueDetails> = self.client.get_issues(repo, numbers).await?
+            .into_iter()
+            .map(|issue| (issue.number, IssueDetails {
+                number: issue.number,
+                title: issue.title,
+                state: issue.state,
+                url: issue.html_url,
+            }))
+            .collect();
+        for commit in &mut commits {
+            commit.resolved_issues = commit.issues.iter()
+                .filter_map(|n| issues.get(n).cloned())
+                .collect();
+        }
+        Ok(commits)
+    }
+}
+
+#[cfg(all(test, feature = "test-utils"))]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockProvider;
+
+    fn provider() -> MockProvider {
+        MockProvider::new("acme")
+            .release("api", "v1.0.0", &["feat: accounts"])
+            .release("api", "v1.1.0", &["feat: invoices (#42)", "fix: rounding"])
+            .release("web", "v1.0.0", &["feat: landing page"])
+    }
+
+    fn released(component: &ComponentRelease) -> (&str, Option<&str>, Vec<&str>) {
+        match &component.status {
+            ComponentStatus::Released { current_version, previous_version, commits, .. } => (
+                current_version.as_str(),
+                previous_version.as_deref(),
+                commits.iter().map(|commit| commit.message.as_str()).collect(),
+            ),
+            status => panic!("{} was not released: {:?}", component.repository, status),
+        }
+    }
+
+    #[tokio::test]
+    async fn aggregates_each_repository_against_its_previous_release() {
+        let aggregator = ReleaseAggregator::new(provider(), AggregatorConfig::default());
+        let release = aggregator.aggregate("v1.1.0", vec!["api".to_string(), "web".to_string()]).await.unwrap();
+
+        assert_eq!(release.components.len(), 2);
+        let (current, previous, messages) = released(&release.components[0]);
+        assert_eq!((current, previous), ("v1.1.0", Some("v1.0.0")));
+        assert_eq!(messages, ["Rounding", "Invoices (#42)"]);
+        assert!(matches!(
+            &release.components[1].status,
+            ComponentStatus::NoRelease { latest_version: Some(latest), .. } if latest == "v1.0.0"
+        ));
+        assert_eq!((release.summary.total_repos, release.summary.updated_repos, release.summary.total_commits), (2, 1, 2));
+    }
+
+    #[tokio::test]
+    async fn skips_archived_repositories_and_flags_empty_ones() {
+        let provider = provider().archived("web").empty_repository("docs");
+        let aggregator = ReleaseAggregator::new(provider, AggregatorConfig::default());
+        let release = aggregator.aggregate("v1.1.0", vec!["api".to_string(), "web".to_string(), "docs".to_string()]).await.unwrap();
+
+        let repositories: Vec<&str> = release.components.iter().map(|c| c.repository.as_str()).collect();
+        assert_eq!(repositories, ["api", "docs"]);
+        assert_eq!(release.summary.skipped_archived, ["web"]);
+        assert!(matches!(release.components[1].status, ComponentStatus::NotYetReleased { empty: true }));
+    }
+
+    #[tokio::test]
+    async fn attaches_pull_requests_only_when_asked() {
+        let provider = provider().pull_request("api", "fix: rounding", 43, "Round invoice totals");
+        let pr_numbers = |release: &AggregatedRelease| -> Vec<Option<u64>> {
+            match &release.components[0].status {
+                ComponentStatus::Released { commits, .. } => commits.iter().map(|commit| commit.pr_number).collect(),
+                status => panic!("api was not released: {:?}", status),
+            }
+        };
+
+        let without = ReleaseAggregator::new(provider.clone(), AggregatorConfig::default())
+            .aggregate("v1.1.0", vec!["api".to_string()]).await.unwrap();
+        assert_eq!(pr_numbers(&without), [None, Some(42)]);
+
+        let config = AggregatorConfig { include_prs: true, ..AggregatorConfig::default() };
+        let with = ReleaseAggregator::new(provider, config)
+            .aggregate("v1.1.0", vec!["api".to_string()]).await.unwrap();
+        assert_eq!(pr_numbers(&with), [Some(43), Some(42)]);
+    }
+
+    #[tokio::test]
+    async fn compares_against_the_base_version() {
+        let provider = provider().release("api", "v1.2.0", &["feat: exports"]);
+        let aggregator = ReleaseAggregator::new(provider, AggregatorConfig::default())
+            .with_base_version(Some("v1.0.0".to_string()));
+        let release = aggregator.aggregate("v1.2.0", vec!["api".to_string()]).await.unwrap();
+
+        let (current, previous, messages) = released(&release.components[0]);
+        assert_eq!((current, previous), ("v1.2.0", Some("v1.0.0")));
+        assert_eq!(messages, ["Exports", "Rounding", "Invoices (#42)"]);
+    }
+
+    #[tokio::test]
+    async fn reports_commits_since_the_latest_release() {
+        let provider = provider().unreleased("api", &["feat: refunds"]);
+        let aggregator = ReleaseAggregator::new(provider, AggregatorConfig::default());
+        let (latest, commits) = aggregator.unreleased_commits("api").await.unwrap();
+
+        assert_eq!(latest.as_deref(), Some("v1.1.0"));
+        let messages: Vec<&str> = commits.iter().map(|commit| commit.message.as_str()).collect();
+        assert_eq!(messages, ["Refunds"]);
+    }
+}
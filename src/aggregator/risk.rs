@@ -0,0 +1,148 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use crate::aggregator::commit_analyzer::EnrichedCommit;
+use crate::github::types::ChangedFile;
+
+/// Path components that mark a file as a schema migration.
+const MIGRATION_DIRS: &[&str] = &["migrations", "migration", "migrate", "alembic", "flyway", "liquibase"];
+
+/// Schema files whose changes amount to a migration wherever they live.
+const SCHEMA_FILES: &[&str] = &["schema.rb", "structure.sql", "schema.prisma"];
+
+/// How risky a component's release is to roll out, from 0 (routine) to 100.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskScore {
+    pub score: u8,
+    pub level: RiskLevel,
+    /// What raised the score, e.g. "2 breaking changes", heaviest first
+    pub factors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    fn from_score(score: u8) -> Self {
+        match score {
+            60.. => RiskLevel::High,
+            30.. => RiskLevel::Medium,
+            _ => RiskLevel::Low,
+        }
+    }
+
+    /// Badge shown in the summary table.
+    pub fn badge(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "🟢 Low",
+            RiskLevel::Medium => "🟡 Medium",
+            RiskLevel::High => "🔴 High",
+        }
+    }
+}
+
+/// What a component's score is computed from.
+#[derive(Debug, Clone, Copy)]
+pub struct RiskInputs<'a> {
+    pub commits: &'a [EnrichedCommit],
+    /// Files changed since the previous release; `None` when there was no comparison (a first
+    /// release, or entries built from pull requests), and commit counts stand in for diff size
+    pub files: Option<&'a [ChangedFile]>,
+    /// Authors with no commits before the previous release
+    pub new_contributors: &'a [String],
+    /// No earlier release to compare against
+    pub first_release: bool,
+    /// Time since the previous release, if both are dated
+    pub since_previous: Option<Duration>,
+}
+
+impl RiskScore {
+    /// Sum the weight of each factor present, capped at 100.
+    pub fn compute(inputs: &RiskInputs) -> Self {
+        let mut factors: Vec<(u8, String)> = Vec::new();
+
+        match inputs.files {
+            Some(files) => {
+                let lines: u64 = files.iter().map(|f| f.additions + f.deletions).sum();
+                let weight = match lines {
+                    2000.. => 30,
+                    500.. => 20,
+                    100.. => 10,
+                    _ => 0,
+                };
+                factors.push((weight, format!("{} lines changed in {} files", lines, files.len())));
+            }
+            None => {
+                let commits = inputs.commits.len();
+                let weight = match commits {
+                    100.. => 25,
+                    25.. => 15,
+                    10.. => 5,
+                    _ => 0,
+                };
+                factors.push((weight, format!("{} commits", commits)));
+            }
+        }
+
+        let breaking = inputs.commits.iter().filter(|c| c.breaking).count();
+        if breaking > 0 {
+            let weight = (20 + 5 * breaking).min(35) as u8;
+            factors.push((weight, plural(breaking, "breaking change")));
+        }
+
+        let migrations = match inputs.files {
+            Some(files) => files.iter().filter(|f| is_migration(&f.filename)).count(),
+            None => inputs.commits.iter().filter(|c| c.message.to_lowercase().contains("migration")).count(),
+        };
+        if migrations > 0 {
+            let what = if inputs.files.is_some() { "migration file" } else { "migration commit" };
+            factors.push((20, plural(migrations, what)));
+        }
+
+        let newcomers = inputs.new_contributors.len();
+        if newcomers > 0 {
+            let weight = (5 + 5 * newcomers).min(20) as u8;
+            factors.push((weight, plural(newcomers, "new contributor")));
+        }
+
+        if inputs.first_release {
+            factors.push((20, "first release".to_string()));
+        } else if let Some(gap) = inputs.since_previous {
+            let weight = match gap.num_days() {
+                180.. => 15,
+                90.. => 10,
+                _ => 0,
+            };
+            factors.push((weight, format!("{} days since the previous release", gap.num_days())));
+        }
+
+        factors.retain(|(weight, _)| *weight > 0);
+        factors.sort_by(|a, b| b.0.cmp(&a.0));
+        let score = factors.iter().map(|(weight, _)| u32::from(*weight)).sum::<u32>().min(100) as u8;
+
+        RiskScore {
+            score,
+            level: RiskLevel::from_score(score),
+            factors: factors.into_iter().map(|(_, factor)| factor).collect(),
+        }
+    }
+}
+
+/// Whether `path` looks like a database migration or schema change.
+pub fn is_migration(path: &str) -> bool {
+    let mut components = path.split('/');
+    let file = components.next_back().unwrap_or_default();
+    SCHEMA_FILES.contains(&file) || components.any(|dir| MIGRATION_DIRS.contains(&dir.to_lowercase().as_str()))
+}
+
+fn plural(count: usize, what: &str) -> String {
+    if count == 1 {
+        format!("1 {}", what)
+    } else {
+        format!("{} {}s", count, what)
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentStatus};
+use crate::aggregator::risk::RiskLevel;
+use crate::config::types::RolloutConfig;
+
+/// Suggested order to roll a train out in: waves of components, each watched for a soak time
+/// before the next starts. Written as JSON by `generate --rollout-plan` for deploy tooling.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutPlan {
+    pub version: String,
+    pub waves: Vec<RolloutWave>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutWave {
+    /// 1-based position in the plan
+    pub wave: usize,
+    /// Riskiest level among the wave's components
+    pub risk: RiskLevel,
+    /// How long to watch the wave before starting the next
+    pub soak_minutes: u32,
+    pub components: Vec<PlannedComponent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedComponent {
+    pub repository: String,
+    pub version: String,
+    /// `None` when the component wasn't scored, and is then planned as medium risk
+    pub risk_score: Option<u8>,
+    pub risk: RiskLevel,
+    /// Components of this train that must be rolled out first
+    pub depends_on: Vec<String>,
+}
+
+impl RolloutPlan {
+    /// Plan the released components of `release`. Dependencies (`depends_on` per configured
+    /// repository name) come first, wave by wave; within a dependency level, low risk
+    /// components go out together, then medium ones, then each high risk one on its own as a
+    /// canary. Dependencies on components not released in the train are ignored.
+    pub fn build(release: &AggregatedRelease, depends_on: &HashMap<String, Vec<String>>, config: &RolloutConfig) -> Result<Self> {
+        let mut pending: Vec<(PlannedComponent, Vec<&str>)> = Vec::new();
+        for component in &release.components {
+            let ComponentStatus::Released { current_version, risk, .. } = &component.status else {
+                continue;
+            };
+            let configured = component.renamed_from.as_deref().unwrap_or(&component.repository);
+            let dependencies: Vec<&str> = depends_on.get(configured)
+                .map(|deps| deps.iter().map(String::as_str).collect())
+                .unwrap_or_default();
+            pending.push((
+                PlannedComponent {
+                    repository: component.repository.clone(),
+                    version: current_version.clone(),
+                    risk_score: risk.as_ref().map(|r| r.score),
+                    risk: risk.as_ref().map(|r| r.level).unwrap_or(RiskLevel::Medium),
+                    depends_on: Vec::new(),
+                },
+                dependencies,
+            ));
+        }
+
+        // Resolve dependencies to the components released in this train, by either name
+        let released: Vec<(String, Option<String>)> = release.components.iter()
+            .filter(|c| matches!(c.status, ComponentStatus::Released { .. }))
+            .map(|c| (c.repository.clone(), c.renamed_from.clone()))
+            .collect();
+        for (component, dependencies) in &mut pending {
+            component.depends_on = dependencies.iter()
+                .filter_map(|dep| released.iter().find(|(name, old)| name == dep || old.as_deref() == Some(*dep)))
+                .map(|(name, _)| name.clone())
+                .filter(|name| *name != component.repository)
+                .collect();
+            component.depends_on.sort();
+            component.depends_on.dedup();
+        }
+        let mut pending: Vec<PlannedComponent> = pending.into_iter().map(|(component, _)| component).collect();
+
+        let mut waves: Vec<RolloutWave> = Vec::new();
+        let mut rolled_out: Vec<String> = Vec::new();
+        while !pending.is_empty() {
+            let (mut ready, blocked): (Vec<_>, Vec<_>) = pending.into_iter()
+                .partition(|c| c.depends_on.iter().all(|dep| rolled_out.contains(dep)));
+            if ready.is_empty() {
+                let names: Vec<&str> = blocked.iter().map(|c| c.repository.as_str()).collect();
+                anyhow::bail!("Rollout dependencies form a cycle between {}", names.join(", "));
+            }
+            pending = blocked;
+            ready.sort_by(|a, b| {
+                a.risk.cmp(&b.risk)
+                    .then_with(|| a.risk_score.cmp(&b.risk_score))
+                    .then_with(|| a.repository.cmp(&b.repository))
+            });
+            rolled_out.extend(ready.iter().map(|c| c.repository.clone()));
+
+            for level in [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High] {
+                let group: Vec<PlannedComponent> = ready.iter().filter(|c| c.risk == level).cloned().collect();
+                let size = match level {
+                    RiskLevel::High => 1,
+                    _ => config.max_wave_size.unwrap_or(usize::MAX).max(1),
+                };
+                for chunk in group.chunks(size) {
+                    waves.push(RolloutWave {
+                        wave: waves.len() + 1,
+                        risk: level,
+                        soak_minutes: config.soak_minutes.for_level(level),
+                        components: chunk.to_vec(),
+                    });
+                }
+            }
+        }
+
+        Ok(Self { version: release.version.clone(), waves })
+    }
+}
+
+/// Soak time as `45m`, `2h`, `1d 6h`.
+pub fn format_minutes(minutes: u32) -> String {
+    let (days, hours, minutes) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+    let parts: Vec<String> = [(days, "d"), (hours, "h"), (minutes, "m")].iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{}{}", amount, unit))
+        .collect();
+    if parts.is_empty() {
+        "none".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use crate::config::types::{ScaffoldConfig, ScaffoldSection, SectionPosition};
+
+/// The fixed sections of one train's announcement (`[[scaffold.sections]]`), with the text
+/// each was filled in with.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Scaffold {
+    pub sections: Vec<FilledSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilledSection {
+    pub title: String,
+    pub position: SectionPosition,
+    pub required: bool,
+    pub placeholder: Option<String>,
+    /// Markdown the section was filled in with; `None` until it is
+    pub content: Option<String>,
+}
+
+impl Scaffold {
+    pub fn new(sections: &[ScaffoldSection]) -> Self {
+        Self {
+            sections: sections.iter()
+                .map(|section| FilledSection {
+                    title: section.title.clone(),
+                    position: section.position,
+                    required: section.required,
+                    placeholder: section.placeholder.clone(),
+                    content: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// The `configured` sections of train `version`, filled in from `sections_file` (else the
+    /// configured file, when it exists yet) and then from `fills`, each `TITLE=TEXT`.
+    pub fn for_train(configured: &ScaffoldConfig, sections_file: Option<&Path>, fills: &[String], version: &str) -> Result<Self> {
+        let mut scaffold = Scaffold::new(&configured.sections);
+        if scaffold.is_empty() {
+            if sections_file.is_some() || !fills.is_empty() {
+                bail!("--sections-file and --section fill in [[scaffold.sections]], and the config file has none");
+            }
+            return Ok(scaffold);
+        }
+
+        match (sections_file, &configured.file) {
+            (Some(path), _) => scaffold.fill_from_file(path)?,
+            // The configured file is written per train, so it may not exist yet
+            (None, Some(file)) => {
+                let path = PathBuf::from(file.replace("{version}", version));
+                if path.exists() {
+                    scaffold.fill_from_file(&path)?;
+                }
+            }
+            (None, None) => {}
+        }
+        for fill in fills {
+            let (title, text) = fill.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("--section takes TITLE=TEXT, got \"{}\"", fill))?;
+            scaffold.fill(title, text)?;
+        }
+        Ok(scaffold)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Fill the section titled `title` (case-insensitively) with `content`, replacing any
+    /// earlier text. Blank content leaves the section unfilled.
+    pub fn fill(&mut self, title: &str, content: &str) -> Result<()> {
+        let Some(section) = self.sections.iter_mut().find(|s| s.title.eq_ignore_ascii_case(title.trim())) else {
+            let titles: Vec<&str> = self.sections.iter().map(|s| s.title.as_str()).collect();
+            bail!("No scaffold section is titled \"{}\" (expected one of: {})", title.trim(), titles.join(", "));
+        };
+        let content = content.trim();
+        section.content = (!content.is_empty()).then(|| content.to_string());
+        Ok(())
+    }
+
+    /// Fill sections from Markdown with a `## Title` heading per section. Text above the
+    /// first heading is ignored, and deeper headings stay part of their section's text.
+    pub fn fill_from_markdown(&mut self, markdown: &str) -> Result<()> {
+        let mut current: Option<(&str, Vec<&str>)> = None;
+        for line in markdown.lines() {
+            if let Some(title) = line.strip_prefix("## ") {
+                if let Some((title, lines)) = current.take() {
+                    self.fill(title, &lines.join("\n"))?;
+                }
+                current = Some((title, Vec::new()));
+            } else if let Some((_, lines)) = &mut current {
+                lines.push(line);
+            }
+        }
+        if let Some((title, lines)) = current {
+            self.fill(title, &lines.join("\n"))?;
+        }
+        Ok(())
+    }
+
+    /// Fill sections from the Markdown file at `path`.
+    pub fn fill_from_file(&mut self, path: &Path) -> Result<()> {
+        let markdown = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read sections file {}", path.display()))?;
+        self.fill_from_markdown(&markdown)
+            .with_context(|| format!("Failed to fill sections from {}", path.display()))
+    }
+
+    /// Titles of the required sections that haven't been filled in.
+    pub fn unfilled(&self) -> Vec<&str> {
+        self.sections.iter()
+            .filter(|s| s.required && s.content.is_none())
+            .map(|s| s.title.as_str())
+            .collect()
+    }
+
+    /// Sections rendered at `position` as `(title, text)`. Required sections that haven't been
+    /// filled in show their placeholder in italics; optional ones are left out.
+    pub fn rendered(&self, position: SectionPosition) -> Vec<(&str, String)> {
+        self.sections.iter()
+            .filter(|s| s.position == position)
+            .filter_map(|s| {
+                let text = match (&s.content, &s.placeholder) {
+                    (Some(content), _) => content.clone(),
+                    (None, _) if !s.required => return None,
+                    (None, Some(placeholder)) => format!("_{}_", placeholder.trim()),
+                    (None, None) => format!("_{} to be filled in._", s.title),
+                };
+                Some((s.title.as_str(), text))
+            })
+            .collect()
+    }
+}
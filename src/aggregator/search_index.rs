@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use super::release_fetcher::{AggregatedRelease, ComponentStatus};
+
+/// Fields worth indexing for full-text search, in the order lunr should register them.
+const SEARCH_FIELDS: &[&str] = &["message", "repository", "category", "scope", "author", "release"];
+
+/// A flat, static search index over every changelog entry of one or more trains.
+///
+/// The layout loads directly into lunr (`ref` plus `fields`, then `add` each document), and
+/// `documents` can be streamed as JSON lines into tantivy.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchIndex {
+    #[serde(rename = "ref")]
+    pub ref_field: String,
+    pub fields: Vec<String>,
+    pub documents: Vec<SearchDocument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchDocument {
+    /// `<release>/<repository>/<sha>`, unique across trains
+    pub id: String,
+    /// Train version the entry shipped in
+    pub release: String,
+    pub repository: String,
+    /// The component's own release tag
+    pub component_version: String,
+    pub sha: String,
+    /// First line of the commit message
+    pub message: String,
+    pub category: Option<String>,
+    pub scope: Option<String>,
+    pub author: String,
+    pub date: chrono::DateTime<chrono::Utc>,
+    pub breaking: bool,
+    pub pr_number: Option<u64>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            ref_field: "id".to_string(),
+            fields: SEARCH_FIELDS.iter().map(|f| f.to_string()).collect(),
+            documents: vec![],
+        }
+    }
+}
+
+impl SearchIndex {
+    /// Read an index written by an earlier run, or start an empty one if `path` doesn't exist.
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read search index {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse search index {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write search index {}", path.display()))
+    }
+
+    /// Replace any entries previously indexed for this release with its current ones, so
+    /// regenerating a train doesn't leave duplicates. Sub-trains are indexed under the parent.
+    pub fn upsert_release(&mut self, release: &AggregatedRelease) {
+        self.documents.retain(|doc| doc.release != release.version);
+        Self::collect(&release.version, release, &mut self.documents);
+        self.documents.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    fn collect(train: &str, release: &AggregatedRelease, documents: &mut Vec<SearchDocument>) {
+        for component in &release.components {
+            let ComponentStatus::Released { current_version, commits, .. } = &component.status else {
+                continue;
+            };
+            for commit in commits {
+                documents.push(SearchDocument {
+                    id: format!("{}/{}/{}", train, component.repository, commit.sha),
+                    release: train.to_string(),
+                    repository: component.repository.clone(),
+                    component_version: current_version.clone(),
+                    sha: commit.sha.clone(),
+                    message: commit.message.lines().next().unwrap_or_default().to_string(),
+                    category: commit.commit_type.as_ref().map(|t| format!("{:?}", t)),
+                    scope: commit.scope.clone(),
+                    author: commit.author.clone(),
+                    date: commit.date,
+                    breaking: commit.breaking,
+                    pr_number: commit.pr_number,
+                });
+            }
+        }
+
+        for sub_train in &release.sub_trains {
+            Self::collect(train, sub_train, documents);
+        }
+    }
+}
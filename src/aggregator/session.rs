@@ -0,0 +1,59 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use crate::aggregator::backfill;
+use crate::aggregator::release_fetcher::{AggregatedRelease, AggregatorConfig, ReleaseAggregator};
+use crate::config::types::{select_repos, Config};
+use crate::github::client::GitHubClient;
+use crate::provider::connection::Connection;
+use crate::provider::forges::Forges;
+use crate::provider::ProviderKind;
+use crate::state::store::{self, StateStore};
+
+/// The repositories a run aggregates and an aggregator reading them from the forges they're
+/// routed to, answering commit ranges earlier runs resolved from the state file.
+pub struct Session {
+    pub aggregator: ReleaseAggregator<Forges>,
+    pub repos: Vec<String>,
+    state: PathBuf,
+}
+
+impl Session {
+    /// Select `repos` (the configured ones when empty) across `github` and the other forges
+    /// `connection` routes to, and check the credentials can read the GitHub ones.
+    pub async fn open(
+        connection: &Connection,
+        github: GitHubClient,
+        file_config: Option<&Config>,
+        repos: Vec<String>,
+        config: AggregatorConfig,
+    ) -> Result<Self> {
+        let forges = connection.forges(github, file_config)?;
+        let repos = select_repos(&forges, file_config, repos).await?;
+        let github_repos: Vec<String> = repos.iter()
+            .filter(|repo| connection.routing.provider(repo) == ProviderKind::Github)
+            .cloned()
+            .collect();
+        forges.github().preflight(&connection.auth, &github_repos).await?;
+
+        let aggregator = ReleaseAggregator::new(forges, config)
+            .with_range_cache(StateStore::open(&connection.state)?.ranges);
+        Ok(Session { aggregator, repos, state: connection.state.clone() })
+    }
+
+    pub fn state(&self) -> &Path {
+        &self.state
+    }
+
+    /// Versions released in any of the repositories whose tag matches the glob `pattern`,
+    /// lowest first.
+    pub async fn matching_versions(&self, pattern: &str) -> Result<Vec<String>> {
+        backfill::matching_versions(self.aggregator.client(), &self.repos, &self.aggregator.config().repo_settings, pattern).await
+    }
+
+    /// Aggregate `version` across the repositories, keeping the commit ranges it resolved.
+    pub async fn aggregate(&self, version: &str) -> Result<AggregatedRelease> {
+        let release = self.aggregator.aggregate(version, self.repos.clone()).await?;
+        store::save_range_cache(&self.state, &self.aggregator)?;
+        Ok(release)
+    }
+}
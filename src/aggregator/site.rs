@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use crate::aggregator::backfill::{self, BackfillIndex};
+use crate::aggregator::branding::Branding;
+use crate::aggregator::changelog_generator::{ChangelogGenerator, OutputFormat};
+use crate::aggregator::entry_format::EntryFormats;
+use crate::aggregator::locale::Locale;
+use crate::aggregator::release_fetcher::AggregatorConfig;
+use crate::aggregator::search_index::SearchIndex;
+use crate::aggregator::session::Session;
+use crate::config::types::Config;
+use crate::github::client::GitHubClient;
+use crate::provider::connection::Connection;
+
+/// What `site` builds, with the command line's defaults already applied.
+pub struct SiteOptions {
+    pub output: PathBuf,
+    /// Repositories to cover; the configured ones when empty
+    pub repos: Vec<String>,
+    /// Glob the versions to publish must match
+    pub matching: String,
+    pub locale: Option<Locale>,
+    pub branding: Branding,
+    pub entry_formats: EntryFormats,
+    pub include_prs: bool,
+    pub categorize: bool,
+    pub deterministic: bool,
+    pub generated_at: Option<DateTime<Utc>>,
+}
+
+/// Build a static HTML site in `options.output`: a directory of pages per released version
+/// matching `options.matching`, a front page listing them and a search index.
+pub async fn run(
+    connection: &Connection,
+    github: GitHubClient,
+    file_config: Option<&Config>,
+    base: AggregatorConfig,
+    options: SiteOptions,
+) -> Result<()> {
+    let SiteOptions {
+        output, repos, matching, locale, branding, entry_formats, include_prs, categorize, deterministic, generated_at,
+    } = options;
+    let config = AggregatorConfig {
+        include_prs,
+        include_issues: false,
+        categorize_commits: categorize,
+        ..base
+    };
+    let session = Session::open(connection, github, file_config, repos, config).await?;
+
+    let versions = session.matching_versions(&matching).await?;
+    if versions.is_empty() {
+        anyhow::bail!("No release tag matches {} in the selected repositories", matching);
+    }
+    println!("Building a site of {} versions in {}", versions.len(), output.display());
+
+    let mut generator = ChangelogGenerator::new(OutputFormat::Html, None)?
+        .with_entry_formats(entry_formats)
+        .with_links(session.aggregator.client().web_links())
+        .with_branding(branding)?
+        .with_index_link("← All releases", "../index.html");
+    if let Some(locale) = locale {
+        generator = generator.with_locale(locale);
+    }
+
+    let mut index = BackfillIndex::default();
+    let mut search = SearchIndex::default();
+    for version in versions {
+        let release = backfill::shipped_release(&session, &version, deterministic, generated_at).await?;
+        let dir_name = version.replace('/', "-");
+        let dir = output.join(&dir_name);
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let written = generator.write_split(&release, &dir)?;
+        search.upsert_release(&release);
+        index.push(&release, format!("{}/index.html", dir_name));
+        println!("✓ {}: {} pages", version, written.len());
+    }
+
+    index.sort();
+    search.save(&output.join("search.json"))?;
+    let front_page = output.join("index.html");
+    std::fs::write(&front_page, generator.generate_site_index(&index)?)
+        .with_context(|| format!("Failed to write {}", front_page.display()))?;
+    println!("Site written to {}", front_page.display());
+    Ok(())
+}
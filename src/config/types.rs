@@ -1,73 +1,837 @@
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use crate::aggregator::check::CheckRule;
+use crate::aggregator::risk::RiskLevel;
+use crate::github::types::{loose_version, MakeLatest, ReleaseSource};
+use crate::provider::{ProviderKind, ReleaseProvider};
+use super::validate::UnknownKey;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub github: GithubConfig,
+    /// GitLab instance and group that repositories with `provider = "gitlab"` (or every
+    /// repository, with `--provider gitlab`) are read from; gitlab.com when not given
+    pub gitlab: Option<GitLabConfig>,
     pub repos: ReposConfig,
     pub output: OutputConfig,
     pub features: FeaturesConfig,
-    pub commit_types: HashMap<String, String>,
+    /// Bullet format per category (`default`, `other`, or a type keyword like `feat`)
+    pub entry_formats: HashMap<String, String>,
+    /// Section title per pull request label, e.g. `breaking = "💥 Breaking"`; used wherever
+    /// pull requests are attached and ahead of the commit message's type
+    pub labels: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<SectionConfig>,
+    pub publish: PublishConfig,
+    pub jira: Option<JiraConfig>,
+    /// Status page that `publish --status-page` posts a maintenance notice to
+    pub status_page: Option<StatusPageConfig>,
+    /// Where `publish --notify` announces trains
+    pub notify: Option<NotifyConfig>,
+    /// Credentials for container registries, keyed by host (e.g. `ghcr.io`)
+    pub image_registries: HashMap<String, ImageRegistryConfig>,
+    pub branding: BrandingConfig,
+    pub serve: ServeConfig,
+    /// Release calendar written by `calendar` and served at `/calendar.ics`
+    pub calendar: CalendarConfig,
+    /// What external audiences don't see
+    pub redaction: RedactionConfig,
+    /// Soak times and wave sizes of the plan written by `generate --rollout-plan`
+    pub rollout: RolloutConfig,
+    pub check: CheckConfig,
+    /// Fixed sections every announcement carries, such as an overview or support contacts
+    pub scaffold: ScaffoldConfig,
+    /// Named environments (`dev`, `staging`, `prod`, ...) selected with `--profile`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Config files picked up from the working directory when `--config` isn't given.
+pub const DEFAULT_CONFIG_FILES: &[&str] = &["release-inator.toml", "release-inator.yaml", "release-inator.yml"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GithubConfig {
     pub org: String,
+    /// Most API requests per second, for servers that object to bursts; `--throttle` overrides
+    pub requests_per_second: Option<f64>,
+    /// REST API root of a GitHub Enterprise Server, e.g. `https://ghe.example.com/api/v3`;
+    /// `--github-url` overrides
+    pub api_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitLabConfig {
+    /// Web address of the instance, e.g. `https://gitlab.example.com` for a self-hosted one;
+    /// `--gitlab-url` overrides
+    #[serde(default = "default_gitlab_url")]
+    pub url: String,
+    /// Group bare repository names belong to; defaults to `github.org`
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Environment variable holding the access token (`read_api` scope)
+    #[serde(default = "default_gitlab_token_env")]
+    pub token_env: String,
+}
+
+impl Default for GitLabConfig {
+    fn default() -> Self {
+        Self { url: default_gitlab_url(), group: None, token_env: default_gitlab_token_env() }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReposConfig {
+    /// Repositories to aggregate when `--repos` isn't given
     pub include: Vec<String>,
+    /// Repositories always left out, even when passed with `--repos`
     pub exclude: Vec<String>,
+    /// Per-repository overrides, keyed by repository name
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub settings: HashMap<String, RepoSettings>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepoSettings {
+    pub parser: ParserKind,
+    /// Regex with named groups `type` and `message` (optional `breaking`) for `custom-regex`
+    pub parser_pattern: Option<String>,
+    /// Registry the component is published to; `check` verifies the version exists there
+    pub registry: Option<RegistryKind>,
+    /// Package name on the registry, when it differs from the repository name
+    pub package: Option<String>,
+    /// Skip the GitHub Release check and rely on the registry alone
+    pub registry_only: bool,
+    /// Container image built from the repository, e.g. `ghcr.io/acme/api`; `check --artifacts`
+    /// verifies a tag matching the release version exists
+    pub image: Option<String>,
+    /// Release assets `check --assets` expects, as glob patterns (`*.tar.gz`,
+    /// `api-{version}-linux-amd64`); `{version}` is the version without its leading `v`
+    pub assets: Vec<String>,
+    /// Tag name template, where `{version}` is the version without its leading `v`
+    /// (e.g. `{version}` for `1.2.3` tags, `service-a/v{version}`)
+    pub tag_pattern: Option<String>,
+    /// Prepended to the version as given (e.g. `service-a/` for `service-a/v1.2.3`); ignored
+    /// when `tag_pattern` is set
+    pub tag_prefix: Option<String>,
+    /// Where versions come from, overriding `--source`: `releases` or `tags` for repositories
+    /// that only push git tags
+    pub source: Option<ReleaseSource>,
+    /// Where the repository is read from, overriding `--provider`: `github`, `gitlab`, or
+    /// `local` for a clone in `--repos-dir`
+    pub provider: Option<ProviderKind>,
+    /// `branch` for components deployed straight from a branch with no tags: each train
+    /// releases the branch head, and commits are counted from the head the previous train
+    /// recorded in the state file
+    pub ref_mode: RefMode,
+    /// Branch a `ref_mode = "branch"` component deploys from; defaults to the default branch
+    pub branch: Option<String>,
+    /// Components that must be rolled out before this one, when both are in a train
+    pub depends_on: Vec<String>,
+    /// Directory of changelog fragments read by `--mode fragments` (default `changelog.d`)
+    pub fragments_dir: Option<String>,
+    /// Last train version the component was part of, e.g. `v1.3`. Later trains leave it out
+    /// and note that it was retired; earlier ones still render it.
+    pub retired_after: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl RepoSettings {
+    /// Tag name of the logical `version` in this repository.
+    pub fn tag_for(&self, version: &str) -> String {
+        if let Some(pattern) = &self.tag_pattern {
+            let bare = version.strip_prefix(['v', 'V']).unwrap_or(version);
+            pattern.replace("{version}", bare)
+        } else if let Some(prefix) = &self.tag_prefix {
+            format!("{}{}", prefix, version)
+        } else {
+            version.to_string()
+        }
+    }
+
+    /// The logical version `tag` stands for under this repository's tag scheme; `None` for
+    /// tags that don't follow it.
+    pub fn version_of(&self, tag: &str) -> Option<String> {
+        if let Some(pattern) = &self.tag_pattern {
+            let (before, after) = pattern.split_once("{version}")?;
+            tag.strip_prefix(before)?.strip_suffix(after).map(str::to_string)
+        } else if let Some(prefix) = &self.tag_prefix {
+            tag.strip_prefix(prefix.as_str()).map(str::to_string)
+        } else {
+            Some(tag.to_string())
+        }
+    }
+
+    /// Asset patterns expected on the release of `version`, with `{version}` filled in.
+    pub fn asset_patterns(&self, version: &str) -> Vec<String> {
+        let bare = version.strip_prefix(['v', 'V']).unwrap_or(version);
+        self.assets.iter().map(|pattern| pattern.replace("{version}", bare)).collect()
+    }
+
+    /// Whether the component was retired before train `version`. Versions that can't be
+    /// compared (e.g. `Unreleased`) never retire a component.
+    pub fn retired_in(&self, version: &str) -> bool {
+        let retired_after = self.retired_after.as_deref().and_then(loose_version);
+        retired_after.zip(loose_version(version)).is_some_and(|(retired_after, version)| version > retired_after)
+    }
+}
+
+/// What marks a release of a component.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefMode {
+    /// A release (or tag) named after the train version
+    #[default]
+    Tag,
+    /// Whatever the branch head is when the train is generated
+    Branch,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParserKind {
+    #[default]
+    Conventional,
+    BracketTags,
+    CustomRegex,
+    Gitmoji,
+}
+
+/// Package registries that can stand in for (or back up) a GitHub Release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RegistryKind {
+    CratesIo,
+    Npm,
+    Pypi,
+}
+
+impl std::fmt::Display for RegistryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryKind::CratesIo => write!(f, "crates.io"),
+            RegistryKind::Npm => write!(f, "npm"),
+            RegistryKind::Pypi => write!(f, "PyPI"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OutputConfig {
     pub format: String,
-    pub path: String,
     pub template: Option<String>,
+    /// Handlebars template for the one-paragraph summary written by `generate --short-output`
+    pub short_template: Option<String>,
+    /// Locale numbers and dates are written for: en, de, fr
+    pub locale: Option<String>,
+    /// Ordering of commits within a category: api, date, scope, pr, alphabetical
+    pub sort: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FeaturesConfig {
     pub categorize_commits: bool,
     pub include_prs: bool,
     pub include_issues: bool,
-    pub include_stats: bool,
+    /// List each released component's open issues labeled `known_issue_label`
+    pub include_known_issues: bool,
+    pub known_issue_label: String,
+}
+
+impl FeaturesConfig {
+    /// Label of the known issues to list, when `include_known_issues` is set.
+    pub fn known_issues(&self) -> Option<String> {
+        self.include_known_issues.then(|| self.known_issue_label.clone())
+    }
+}
+
+/// A named group of components rendered under its own heading, in config order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionConfig {
+    pub name: String,
+    pub repos: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PublishConfig {
+    /// Repository that receives the umbrella release for each train
+    pub repo: Option<String>,
+    /// Whether the umbrella release becomes the repository's latest release: `true`, `false`
+    /// or `legacy`; GitHub makes every new release latest when not set
+    pub make_latest: Option<MakeLatest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraConfig {
+    pub base_url: String,
+    /// Project keys that receive a version for each published train
+    pub projects: Vec<String>,
+    /// Account email for Jira Cloud basic auth; omit to send the token as a bearer token
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Environment variable holding the API token
+    #[serde(default = "default_jira_token_env")]
+    pub token_env: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusPageConfig {
+    pub provider: StatusPageProvider,
+    /// Statuspage page ID, or the Instatus page ID
+    pub page_id: String,
+    /// Environment variable holding the API key
+    #[serde(default = "default_status_page_token_env")]
+    pub token_env: String,
+    /// Length of the maintenance window, in minutes
+    #[serde(default = "default_window_minutes")]
+    pub window_minutes: u32,
+    /// Status page component ID per repository; released repositories listed here are marked
+    /// as affected
+    #[serde(default)]
+    pub components: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusPageProvider {
+    /// Atlassian Statuspage
+    Statuspage,
+    Instatus,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Proxy for webhook requests, e.g. `socks5h://proxy.corp:1080`; the environment's proxy
+    /// settings apply when not given
+    pub proxy: Option<String>,
+    pub targets: Vec<NotifyTarget>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NotifyTarget {
+    /// Slack incoming webhook; its URL is a secret, so it is read from the environment
+    Slack {
+        #[serde(default = "default_slack_webhook_env")]
+        url_env: String,
+    },
+    /// Announcement POSTed as JSON
+    Webhook { url: String },
+    /// Plain-text mail handed to the local `sendmail`
+    Email {
+        to: Vec<String>,
+        #[serde(default)]
+        from: Option<String>,
+    },
+}
+
+impl std::fmt::Display for NotifyTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotifyTarget::Slack { url_env } => write!(f, "Slack (${})", url_env),
+            NotifyTarget::Webhook { url } => write!(f, "webhook {}", url),
+            NotifyTarget::Email { to, .. } => write!(f, "email to {}", to.join(", ")),
+        }
+    }
+}
+
+/// Styling for HTML output; paths are relative to the working directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrandingConfig {
+    pub css: Option<String>,
+    /// Image URL or path used as the logo's `src`
+    pub logo: Option<String>,
+    /// Handlebars template rendered above the notes
+    pub header: Option<String>,
+    /// Handlebars template rendered below the notes
+    pub footer: Option<String>,
+    /// Absolute URL of the image shown when links to the HTML unfurl in Slack and similar
+    pub social_image: Option<String>,
+    /// Site name shown in link previews
+    pub site_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServeConfig {
+    /// Bearer tokens accepted in addition to `--api-token`, each limited to an audience
+    pub tokens: Vec<ServeToken>,
+    /// `/release-notes <version>` comments on a repository's issues, delivered by webhook
+    pub commands: Option<SlashCommandConfig>,
+    /// Slack slash command (`/release <version> [section]`) answered at `/slack/commands`
+    pub slack: Option<SlackCommandConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackCommandConfig {
+    /// Environment variable holding the Slack app's signing secret
+    #[serde(default = "default_slack_secret_env")]
+    pub signing_secret_env: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashCommandConfig {
+    /// Repository whose issue comments are read, e.g. `release-requests` or `acme/release-requests`
+    pub repo: String,
+    /// Environment variable holding the webhook secret
+    #[serde(default = "default_webhook_secret_env")]
+    pub secret_env: String,
+    /// Least permission on `repo` a commenter needs
+    #[serde(default)]
+    pub permission: CommandPermission,
+    /// Address this server is reachable at, to link replies to the generated train
+    #[serde(default)]
+    pub public_url: Option<String>,
+}
+
+/// Repository permission levels, as GitHub reports them for collaborators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandPermission {
+    Read,
+    #[default]
+    Write,
+    Admin,
+}
+
+impl CommandPermission {
+    /// The level of a collaborator permission from the API (`admin`, `write`, `read` or `none`).
+    pub fn from_github(permission: &str) -> Option<Self> {
+        match permission {
+            "admin" => Some(CommandPermission::Admin),
+            "maintain" | "write" => Some(CommandPermission::Write),
+            "triage" | "read" => Some(CommandPermission::Read),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandPermission::Read => "read",
+            CommandPermission::Write => "write",
+            CommandPermission::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeToken {
+    /// Environment variable holding the token
+    pub token_env: String,
+    pub audience: Audience,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Calendar title shown by subscribing clients
+    pub name: Option<String>,
+    /// Link to each train's notes, with `{version}` replaced by the train version
+    pub notes_url: Option<String>,
+    /// Trains planned but not generated yet
+    pub scheduled: Vec<ScheduledTrain>,
+    /// Google Calendar that `calendar --google` writes the events to
+    pub google: Option<GoogleCalendarConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTrain {
+    pub version: String,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleCalendarConfig {
+    /// Calendar ID from the calendar's settings, e.g. `abc123@group.calendar.google.com`
+    pub calendar_id: String,
+    /// Environment variable holding an OAuth access token with the calendar.events scope
+    #[serde(default = "default_google_token_env")]
+    pub token_env: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RolloutConfig {
+    /// Minutes to watch a wave before starting the next, by its riskiest component
+    pub soak_minutes: SoakMinutes,
+    /// Most low or medium risk components rolled out in one wave; unlimited when unset
+    pub max_wave_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckConfig {
+    /// Consistency rules `check` doesn't apply; `--disable-rule` adds to these
+    pub disabled_rules: Vec<CheckRule>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScaffoldConfig {
+    /// Markdown file filling in the sections under `## Title` headings, with `{version}`
+    /// replaced by the train version; `--sections-file` overrides
+    pub file: Option<String>,
+    pub sections: Vec<ScaffoldSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldSection {
+    pub title: String,
+    /// Shown in place of the section's text until it is filled in
+    #[serde(default)]
+    pub placeholder: Option<String>,
+    /// Whether `lint` and `publish` insist on the section being filled in
+    #[serde(default = "default_required")]
+    pub required: bool,
+    #[serde(default)]
+    pub position: SectionPosition,
+}
+
+/// Where a scaffold section goes in the notes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionPosition {
+    /// Below the release heading, before the summary
+    #[default]
+    Top,
+    /// After the components and any appendices
+    Bottom,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoakMinutes {
+    pub low: u32,
+    pub medium: u32,
+    pub high: u32,
+}
+
+impl Default for SoakMinutes {
+    fn default() -> Self {
+        Self { low: 30, medium: 120, high: 1440 }
+    }
+}
+
+impl SoakMinutes {
+    pub fn for_level(&self, level: RiskLevel) -> u32 {
+        match level {
+            RiskLevel::Low => self.low,
+            RiskLevel::Medium => self.medium,
+            RiskLevel::High => self.high,
+        }
+    }
+}
+
+/// Who a token is issued to; external audiences get redacted trains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Audience {
+    Internal,
+    External,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Repositories (names or glob patterns) left out entirely
+    pub hide_repos: Vec<String>,
+    /// Commit types dropped, by keyword (`chore`, `ci`, ...)
+    pub hide_commit_types: Vec<String>,
+    /// Drop commits that match no commit type
+    pub hide_uncategorized: bool,
+    /// Remove commit authors and contributor lists
+    pub hide_authors: bool,
+    /// Remove issue tracker keys such as `API-123`
+    pub hide_issue_keys: bool,
+    /// Regexes whose matches in commit messages and release notes become `[redacted]`
+    pub patterns: Vec<String>,
+}
+
+/// Tables that replace the top-level ones of the same name when the profile is selected. A
+/// table given here replaces the base table as a whole; unset fields take their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub output: Option<OutputConfig>,
+    pub publish: Option<PublishConfig>,
+    pub jira: Option<JiraConfig>,
+    pub status_page: Option<StatusPageConfig>,
+    pub branding: Option<BrandingConfig>,
+    pub serve: Option<ServeConfig>,
+    pub redaction: Option<RedactionConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageRegistryConfig {
+    pub username: String,
+    /// Environment variable holding the password or token (for ECR, the output of
+    /// `aws ecr get-login-password`)
+    pub password_env: String,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+fn default_gitlab_url() -> String {
+    crate::gitlab::client::GITLAB_URL.to_string()
+}
+
+fn default_gitlab_token_env() -> String {
+    "GITLAB_TOKEN".to_string()
+}
+
+fn default_jira_token_env() -> String {
+    "JIRA_API_TOKEN".to_string()
+}
+
+fn default_slack_webhook_env() -> String {
+    "SLACK_WEBHOOK_URL".to_string()
+}
+
+fn default_status_page_token_env() -> String {
+    "STATUS_PAGE_API_KEY".to_string()
+}
+
+fn default_webhook_secret_env() -> String {
+    "GITHUB_WEBHOOK_SECRET".to_string()
+}
+
+fn default_slack_secret_env() -> String {
+    "SLACK_SIGNING_SECRET".to_string()
+}
+
+fn default_window_minutes() -> u32 {
+    30
+}
+
+fn default_google_token_env() -> String {
+    "GOOGLE_CALENDAR_TOKEN".to_string()
+}
+
+/// A date written either as a string or, in TOML, as a bare local date (`2024-07-01`).
+fn deserialize_date<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<NaiveDate, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DateValue {
+        Text(String),
+        Toml(toml::value::Datetime),
+    }
+
+    let text = match DateValue::deserialize(deserializer)? {
+        DateValue::Text(text) => text,
+        DateValue::Toml(datetime) => datetime.to_string(),
+    };
+    NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+        .map_err(|_| serde::de::Error::custom(format!("invalid date `{}`, expected YYYY-MM-DD", text)))
+}
+
+impl Config {
+    /// Load a TOML or YAML config file, chosen by extension (TOML unless `.yaml`/`.yml`).
+    pub fn load(path: &Path) -> Result<Self> {
+        let (config, unknown_keys) = Self::load_with_unknown_keys(path)?;
+        for key in unknown_keys {
+            tracing::warn!("{}: {}", path.display(), key);
+        }
+        Ok(config)
+    }
+
+    /// Load a config file, also returning the keys that aren't part of the schema, located in
+    /// the file where possible.
+    pub fn load_with_unknown_keys(path: &Path) -> Result<(Self, Vec<UnknownKey>)> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let is_yaml = matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml"));
+
+        let mut unknown_keys = Vec::new();
+        let record = |key: serde_ignored::Path| unknown_keys.push(key.to_string());
+        let config = if is_yaml {
+            serde_ignored::deserialize(serde_yaml::Deserializer::from_str(&content), record)
+                .with_context(|| format!("Failed to parse config file {}", path.display()))?
+        } else {
+            serde_ignored::deserialize(toml::Deserializer::new(&content), record)
+                .with_context(|| format!("Failed to parse config file {}", path.display()))?
+        };
+
+        let toml_source = (!is_yaml).then_some(content.as_str());
+        let unknown_keys = unknown_keys.iter()
+            .map(|key| UnknownKey::new(key, toml_source))
+            .collect();
+        Ok((config, unknown_keys))
+    }
+
+    /// This config with the tables of profile `name` applied over the base settings.
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let known: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                anyhow::bail!("Unknown profile `{}`; the config file defines no [profiles]", name);
+            }
+            anyhow::bail!("Unknown profile `{}`; the config file defines: {}", name, known.join(", "));
+        };
+
+        if let Some(output) = profile.output {
+            self.output = output;
+        }
+        if let Some(publish) = profile.publish {
+            self.publish = publish;
+        }
+        if profile.jira.is_some() {
+            self.jira = profile.jira;
+        }
+        if profile.status_page.is_some() {
+            self.status_page = profile.status_page;
+        }
+        if let Some(branding) = profile.branding {
+            self.branding = branding;
+        }
+        if let Some(serve) = profile.serve {
+            self.serve = serve;
+        }
+        if let Some(redaction) = profile.redaction {
+            self.redaction = redaction;
+        }
+        Ok(self)
+    }
+
+    /// First of `DEFAULT_CONFIG_FILES` present in the working directory.
+    pub fn discover() -> Option<PathBuf> {
+        DEFAULT_CONFIG_FILES.iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+    }
+
+    /// Whether `repos.include` uses glob patterns that need the org's repository list to expand.
+    pub fn has_repo_patterns(&self) -> bool {
+        self.repos.include.iter().any(|r| is_pattern(r))
+    }
+
+    /// Repositories to process: the ones given on the command line, or `repos.include` when none
+    /// were, minus `repos.exclude`. Glob patterns (`service-*`) in `include` are expanded against
+    /// `org_repos`; patterns in `exclude` apply to either source.
+    pub fn select_repos(&self, cli_repos: Vec<String>, org_repos: &[String]) -> Vec<String> {
+        let repos = if cli_repos.is_empty() {
+            let mut repos = Vec::new();
+            for entry in &self.repos.include {
+                if is_pattern(entry) {
+                    let mut matched: Vec<String> = org_repos.iter()
+                        .filter(|r| matches_pattern(entry, r))
+                        .cloned()
+                        .collect();
+                    matched.sort();
+                    repos.extend(matched);
+                } else {
+                    repos.push(entry.clone());
+                }
+            }
+            repos.dedup();
+            repos
+        } else {
+            cli_repos
+        };
+
+        repos.into_iter()
+            .filter(|r| !self.repos.exclude.iter().any(|pattern| matches_pattern(pattern, r)))
+            .collect()
+    }
+
+    /// The org's repositories that `repos.include` takes in (all of them when it's empty),
+    /// minus `repos.exclude`.
+    pub fn filter_org_repos(&self, org_repos: Vec<String>) -> Vec<String> {
+        org_repos.into_iter()
+            .filter(|r| self.repos.include.is_empty() || self.repos.include.iter().any(|pattern| matches_pattern(pattern, r)))
+            .filter(|r| !self.repos.exclude.iter().any(|pattern| matches_pattern(pattern, r)))
+            .collect()
+    }
 }
 
-impl Default for Config {
+/// Repositories for a command: `repos` if given, otherwise `repos.include` (with globs expanded
+/// against the org's repositories, listed from `client`), minus `repos.exclude`.
+pub async fn select_repos(client: &impl ReleaseProvider, config: Option<&Config>, repos: Vec<String>) -> Result<Vec<String>> {
+    let repos = match config {
+        Some(config) => {
+            let org_repos = if repos.is_empty() && config.has_repo_patterns() {
+                client.list_org_repos().await?
+            } else {
+                vec![]
+            };
+            config.select_repos(repos, &org_repos)
+        }
+        None => repos,
+    };
+    if repos.is_empty() {
+        anyhow::bail!("No repositories given; pass --repos or set repos.include in the config");
+    }
+    Ok(repos)
+}
+
+/// A `--flag` / `--no-flag` pair: whichever was given last, otherwise the config file's setting.
+pub fn feature_flag(on: bool, off: bool, configured: bool) -> bool {
+    if on || off {
+        on
+    } else {
+        configured
+    }
+}
+
+fn is_pattern(entry: &str) -> bool {
+    entry.contains(['*', '?', '['])
+}
+
+/// Match a repository name against a literal name or glob pattern.
+pub fn matches_pattern(pattern: &str, repo: &str) -> bool {
+    if !is_pattern(pattern) {
+        return pattern == repo;
+    }
+    glob::Pattern::new(pattern).map(|p| p.matches(repo)).unwrap_or(false)
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        RedactionConfig {
+            hide_repos: Vec::new(),
+            hide_commit_types: ["chore", "ci", "test", "build"].map(String::from).to_vec(),
+            hide_uncategorized: false,
+            hide_authors: true,
+            hide_issue_keys: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl Default for OutputConfig {
     fn default() -> Self {
-        let mut commit_types = HashMap::new();
-        commit_types.insert("feat".to_string(), "✨ Features".to_string());
-        commit_types.insert("fix".to_string(), "🐛 Bug Fixes".to_string());
-        commit_types.insert("docs".to_string(), "📚 Documentation".to_string());
-        commit_types.insert("perf".to_string(), "⚡ Performance".to_string());
-        commit_types.insert("refactor".to_string(), "♻️ Refactoring".to_string());
-        commit_types.insert("test".to_string(), "✅ Tests".to_string());
-        commit_types.insert("build".to_string(), "📦 Build System".to_string());
-        commit_types.insert("ci".to_string(), "👷 CI/CD".to_string());
-        
-        Config {
-            github: GithubConfig {
-                org: String::new(),
-            },
-            repos: ReposConfig {
-                include: vec![],
-                exclude: vec![],
-            },
-            output: OutputConfig {
-                format: "markdown".to_string(),
-                path: "releases".to_string(),
-                template: None,
-            },
-            features: FeaturesConfig {
-                categorize_commits: true,
-                include_prs: true,
-                include_issues: true,
-                include_stats: true,
-            },
-            commit_types,
-        }
-    }
-}
\ No newline at end of file
+        OutputConfig {
+            format: "markdown".to_string(),
+            template: None,
+            short_template: None,
+            locale: None,
+            sort: None,
+        }
+    }
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        FeaturesConfig {
+            categorize_commits: false,
+            include_prs: false,
+            include_issues: false,
+            include_known_issues: false,
+            known_issue_label: crate::aggregator::known_issues::DEFAULT_KNOWN_ISSUE_LABEL.to_string(),
+        }
+    }
+}
@@ -0,0 +1,620 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use crate::aggregator::branding::Branding;
+use crate::aggregator::changelog_generator::{ChangelogGenerator, OutputFormat};
+use crate::aggregator::commit_analyzer::{CommitParser, CommitSort};
+use crate::aggregator::entry_format::EntryFormats;
+use crate::aggregator::locale::Locale;
+use crate::aggregator::redaction::Redactor;
+use crate::github::types::loose_version;
+use super::types::{
+    matches_pattern, Audience, CommandPermission, Config, GitLabConfig, GoogleCalendarConfig, ImageRegistryConfig, JiraConfig, NotifyTarget, ParserKind, RefMode,
+    RepoSettings, ScaffoldSection, ScheduledTrain, SectionConfig, SectionPosition, ServeToken, SlackCommandConfig, SlashCommandConfig, StatusPageConfig, StatusPageProvider,
+};
+
+/// Comments placed above each table of the starter config, keyed by table name.
+const TABLE_COMMENTS: &[(&str, &str)] = &[
+    ("github", "# Organization (or user) that owns the repositories. Overridden by --org / GITHUB_ORG.\n# Add requests_per_second = 5 to spread API calls out (overridden by --throttle), and\n# api_url = \"https://ghe.example.com/api/v3\" for GitHub Enterprise Server (overridden by --github-url)."),
+    ("repos", "# Repositories aggregated when --repos isn't given. Entries may be globs like \"service-*\";\n# exclude always applies, even to --repos."),
+    ("output", "# format: markdown | json | html. template: custom Handlebars template.\n# short_template: template for the one-paragraph summary of `generate --short-output`.\n# locale: en | de | fr, for number and date formats.\n# sort: api | date | scope | pr | alphabetical (commit order within a category)."),
+    ("features", "# Defaults for --categorize, --include-prs, --include-issues and --include-known-issues; the flags and\n# their --no-* forms override these. known_issue_label picks the open issues listed as known issues."),
+    ("entry_formats", "# Bullet format per category: \"default\", \"other\" (uncategorized), or a type keyword.\n# Placeholders: {title} {message} {sha} {sha_url} {author} {pr_number} {pr_url} {pr_link} {issues}"),
+    ("labels", "# Section title per pull request label, e.g. breaking = \"💥 Breaking\". Applied when pull requests\n# are attached (--include-prs, --mode prs); labelled entries are listed under these sections first."),
+    ("publish", "# Repository that receives the umbrella release for `publish`."),
+    ("image_registries", "# Container registry credentials for `check --artifacts`, keyed by host."),
+    ("branding", "# HTML styling: css and header/footer (Handlebars) are file paths; logo is used as the image src.\n# social_image (absolute URL) and site_name fill in link previews."),
+    ("serve", "# Extra `serve` bearer tokens, each read from an environment variable and limited to an audience.\n# Replace `tokens = []` with entries like:
+# [[serve.tokens]]
+# token_env = \"STATUS_PAGE_TOKEN\"
+# audience = \"external\"   # internal | external"),
+    ("calendar", "# Release calendar written by `calendar` and served at /calendar.ics. notes_url links each\n# train's event to its notes, e.g. \"https://releases.acme.com/{version}.html\". Replace\n# `scheduled = []` with planned trains:
+# [[calendar.scheduled]]
+# version = \"v2.5.0\"
+# date = 2024-07-01
+# description = \"Summer train\"   # optional"),
+    ("rollout.soak_minutes", "# Plan written by `generate --rollout-plan`: minutes to watch each wave before the next, by\n# its riskiest component. Add max_wave_size = 3 under [rollout] to split large low and medium\n# risk waves."),
+    ("check", "# Consistency rules `check` skips: prerelease | draft | stale-tag | downgrade (see also --disable-rule)."),
+    ("scaffold", "# Fixed sections of every announcement, filled in per train from `file` ({version} is replaced)\n# or --section. `lint` lists required ones left empty and `publish` refuses until they're filled.
+# Replace `sections = []` with entries like:
+# [[scaffold.sections]]
+# title = \"Known Issues\"
+# placeholder = \"List known issues, or write None.\"
+# required = true                  # default
+# position = \"bottom\"          # top (above the summary, default) | bottom"),
+    ("redaction", "# What `serve` hides from external audiences. hide_repos takes names or globs;
+# matches of patterns in commit messages and release notes become [redacted]."),
+];
+
+/// Optional sections that have no defaults, shown commented out at the end of the starter file.
+const OPTIONAL_EXAMPLES: &str = r#"
+# Per-repository settings, keyed by repository name.
+# [repos.settings.legacy-api]
+# parser = "bracket-tags"          # conventional | bracket-tags | gitmoji | custom-regex
+# parser_pattern = '^(?P<type>[A-Z]+)-\d+: (?P<message>.*)$'   # for custom-regex
+# registry = "npm"                 # crates-io | npm | pypi, verified by `check`
+# package = "@acme/sdk"
+# registry_only = false
+# image = "ghcr.io/acme/legacy-api"
+# assets = ["*.tar.gz", "*-linux-amd64"]   # release assets verified by `check --assets`
+# tag_pattern = "legacy-api/v{version}"   # or tag_prefix = "legacy-api/"; {version} has no leading v
+# source = "tags"                 # releases | tags, for repositories that only push git tags
+# provider = "gitlab"             # github | gitlab | local (a clone in --repos-dir)
+# ref_mode = "branch"             # tag | branch, for components deployed from a branch head
+# branch = "production"            # with ref_mode = "branch"; defaults to the default branch
+# depends_on = ["auth-service"]   # rolled out first in `generate --rollout-plan` plans
+# fragments_dir = "changes"       # changelog fragments read by `--mode fragments`; default changelog.d
+# retired_after = "v1.3"          # last train the component is part of; later trains note it as retired
+
+# GitLab instance for repositories with provider = "gitlab" (or all of them, with --provider gitlab);
+# the access token is read from $GITLAB_TOKEN.
+# [gitlab]
+# url = "https://gitlab.example.com"   # defaults to https://gitlab.com
+# group = "acme"                        # defaults to github.org
+
+# Named groups of components, rendered in this order.
+# [[sections]]
+# name = "Platform services"
+# repos = ["api-server", "auth-service"]
+
+# Jira versions for `publish --jira`; the token is read from $JIRA_API_TOKEN.
+# [jira]
+# base_url = "https://acme.atlassian.net"
+# projects = ["API"]
+# email = "release-bot@acme.com"
+
+# Google Calendar that `calendar --google` writes train events to; the OAuth access token
+# (calendar.events scope) is read from $GOOGLE_CALENDAR_TOKEN.
+# [calendar.google]
+# calendar_id = "abc123@group.calendar.google.com"
+
+# Maintenance notice for `publish --status-page`, built from the redacted (external) view of the
+# train; the API key is read from $STATUS_PAGE_API_KEY.
+# [status_page]
+# provider = "statuspage"          # statuspage | instatus
+# page_id = "kctbh9vrtdwd"
+# window_minutes = 30
+# components = { api-server = "8kbf7d35c070", web = "vtnh60py4yd7" }
+
+# Announcements sent by `publish --notify`. Failed deliveries are queued in the state file and
+# retried by the next publish or `notify --flush-queue`. Slack webhook URLs are read from the
+# environment; email goes through the local sendmail.
+# [notify]
+# proxy = "socks5h://proxy.acme.com:1080"
+# [[notify.targets]]
+# kind = "slack"                   # slack | webhook | email
+# url_env = "SLACK_WEBHOOK_URL"
+# [[notify.targets]]
+# kind = "email"
+# to = ["releases@acme.com"]
+
+# `/release-notes <version>` comments on issues of a repository, run by `serve` from GitHub's
+# issue_comment webhook (POST /webhooks/github); the webhook secret is read from $GITHUB_WEBHOOK_SECRET.
+# [serve.commands]
+# repo = "release-requests"
+# permission = "write"             # read | write | admin
+# public_url = "https://releases.acme.com"
+
+# Slack slash command `/release <version> [section]`, with its request URL set to
+# https://<server>/slack/commands; the signing secret is read from $SLACK_SIGNING_SECRET.
+# [serve.slack]
+# signing_secret_env = "SLACK_SIGNING_SECRET"
+
+# Profiles selected with --profile. Each table given (output, publish, jira, status_page,
+# branding, serve, redaction) replaces the top-level one as a whole.
+# [profiles.staging.publish]
+# repo = "release-notes-sandbox"
+"#;
+
+/// Render `Config::default()` as a commented TOML starter file.
+pub fn starter_toml() -> Result<String> {
+    let rendered = toml::to_string_pretty(&Config::default())?;
+    let comments: HashMap<&str, &str> = TABLE_COMMENTS.iter().copied().collect();
+
+    let mut output = String::from(
+        "# release-inator configuration. Every setting is optional; command-line flags take precedence.\n\n",
+    );
+    for line in rendered.lines() {
+        let table = line.strip_prefix('[')
+            .and_then(|l| l.strip_suffix(']'))
+            .filter(|_| !line.starts_with("[["));
+        if let Some(comment) = table.and_then(|t| comments.get(t)) {
+            output.push_str(comment);
+            output.push('\n');
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output.push_str(OPTIONAL_EXAMPLES);
+
+    Ok(output)
+}
+
+/// Settings that parse but contradict each other or can't be used at runtime, including
+/// those that only appear once a profile is applied.
+pub fn validate(config: &Config) -> Vec<String> {
+    let mut problems = validate_settings(config);
+    let base = problems.clone();
+    for name in config.profiles.keys() {
+        let Ok(applied) = config.clone().with_profile(name) else {
+            continue;
+        };
+        for problem in validate_settings(&applied) {
+            if !base.contains(&problem) {
+                problems.push(format!("profiles.{}: {}", name, problem));
+            }
+        }
+    }
+    problems
+}
+
+fn validate_settings(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if let Some(rate) = config.github.requests_per_second {
+        if !(rate.is_finite() && rate > 0.0) {
+            problems.push(format!("github.requests_per_second: must be a positive number, not {}", rate));
+        }
+    }
+    if let Some(url) = &config.github.api_url {
+        if let Err(e) = check_url(url) {
+            problems.push(format!("github.api_url: {}", e));
+        }
+    }
+    if let Some(gitlab) = &config.gitlab {
+        if let Err(e) = check_url(&gitlab.url) {
+            problems.push(format!("gitlab.url: {}", e));
+        }
+    }
+    if let Err(e) = config.output.format.parse::<OutputFormat>() {
+        problems.push(format!("output.format: {}", e));
+    }
+    if let Some(sort) = &config.output.sort {
+        if let Err(e) = sort.parse::<CommitSort>() {
+            problems.push(format!("output.sort: {}", e));
+        }
+    }
+    if let Some(locale) = &config.output.locale {
+        if let Err(e) = locale.parse::<Locale>() {
+            problems.push(format!("output.locale: {}", e));
+        }
+    }
+    if let Some(template) = &config.output.template {
+        if !Path::new(template).is_file() {
+            problems.push(format!("output.template: {} does not exist", template));
+        } else {
+            let format = config.output.format.parse().unwrap_or(OutputFormat::Markdown);
+            if let Err(e) = ChangelogGenerator::new(format, Some(PathBuf::from(template))) {
+                problems.push(format!("output.template: {:#}", e));
+            }
+        }
+    }
+    if let Some(template) = &config.output.short_template {
+        let loaded = ChangelogGenerator::new(OutputFormat::Markdown, None)
+            .and_then(|generator| generator.with_short_template(Path::new(template)));
+        if let Err(e) = loaded {
+            problems.push(format!("output.short_template: {:#}", e));
+        }
+    }
+    problems.extend(validate_branding(config));
+    if let Err(e) = EntryFormats::from_config(&config.entry_formats) {
+        problems.push(format!("entry_formats: {}", e));
+    }
+
+    for repo in &config.repos.include {
+        if let Some(pattern) = config.repos.exclude.iter().find(|p| matches_pattern(p, repo)) {
+            problems.push(format!("repos: {} is included but also excluded by \"{}\"", repo, pattern));
+        }
+    }
+
+    let mut settings: Vec<_> = config.repos.settings.iter().collect();
+    settings.sort_by_key(|(repo, _)| *repo);
+    for (repo, repo_settings) in settings {
+        if let Err(e) = CommitParser::from_settings(repo_settings) {
+            problems.push(format!("repos.settings.{}: {:#}", repo, e));
+        }
+        if repo_settings.parser_pattern.is_some() && repo_settings.parser != ParserKind::CustomRegex {
+            problems.push(format!("repos.settings.{}: parser_pattern is only used with parser = \"custom-regex\"", repo));
+        }
+        if repo_settings.registry_only && repo_settings.registry.is_none() {
+            problems.push(format!("repos.settings.{}: registry_only requires a registry", repo));
+        }
+        if repo_settings.package.is_some() && repo_settings.registry.is_none() {
+            problems.push(format!("repos.settings.{}: package is set but registry is not", repo));
+        }
+        for pattern in &repo_settings.asset_patterns("v0.0.0") {
+            if let Err(e) = glob::Pattern::new(pattern) {
+                problems.push(format!("repos.settings.{}: asset pattern \"{}\": {}", repo, pattern, e));
+            }
+        }
+        if let Some(pattern) = &repo_settings.tag_pattern {
+            if !pattern.contains("{version}") {
+                problems.push(format!("repos.settings.{}: tag_pattern \"{}\" has no {{version}} placeholder", repo, pattern));
+            }
+            if repo_settings.tag_prefix.is_some() {
+                problems.push(format!("repos.settings.{}: tag_prefix is ignored when tag_pattern is set", repo));
+            }
+        }
+        if let Some(retired_after) = &repo_settings.retired_after {
+            if loose_version(retired_after).is_none() {
+                problems.push(format!("repos.settings.{}: retired_after \"{}\" is not a version", repo, retired_after));
+            }
+        }
+        if repo_settings.branch.is_some() && repo_settings.ref_mode != RefMode::Branch {
+            problems.push(format!("repos.settings.{}: branch is only used with ref_mode = \"branch\"", repo));
+        }
+        if repo_settings.ref_mode == RefMode::Branch
+            && (repo_settings.tag_pattern.is_some() || repo_settings.tag_prefix.is_some() || repo_settings.source.is_some())
+        {
+            problems.push(format!("repos.settings.{}: tag_pattern, tag_prefix and source are ignored with ref_mode = \"branch\"", repo));
+        }
+        problems.extend(check_repo_reference(config, &format!("repos.settings.{}", repo), repo));
+        if repo_settings.depends_on.contains(repo) {
+            problems.push(format!("repos.settings.{}: depends_on lists the repository itself", repo));
+        } else if depends_on_itself(config, repo) {
+            problems.push(format!("repos.settings.{}: depends_on forms a cycle; rollout plans will fail", repo));
+        }
+    }
+    if config.rollout.max_wave_size == Some(0) {
+        problems.push("rollout.max_wave_size: must be at least 1".to_string());
+    }
+
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for section in &config.sections {
+        for repo in &section.repos {
+            problems.extend(check_repo_reference(config, &format!("sections.{}", section.name), repo));
+            if let Some(previous) = seen.insert(repo.as_str(), section.name.as_str()) {
+                problems.push(format!(
+                    "sections: {} is listed in both \"{}\" and \"{}\"; it will render under \"{}\"",
+                    repo, previous, section.name, previous
+                ));
+            }
+        }
+    }
+
+    if let Some(jira) = &config.jira {
+        if jira.projects.is_empty() {
+            problems.push("jira: no projects configured".to_string());
+        }
+        if let Err(e) = check_url(&jira.base_url) {
+            problems.push(format!("jira.base_url: {}", e));
+        }
+    }
+
+    if let Some(status_page) = &config.status_page {
+        if status_page.page_id.is_empty() {
+            problems.push("status_page: page_id is empty".to_string());
+        }
+        if status_page.window_minutes == 0 {
+            problems.push("status_page.window_minutes: must be at least 1".to_string());
+        }
+        if status_page.components.is_empty() {
+            problems.push("status_page: no components mapped; notices won't mark anything as affected".to_string());
+        }
+    }
+
+    if let Some(notify) = &config.notify {
+        if notify.targets.is_empty() {
+            problems.push("notify: no targets configured".to_string());
+        }
+        if let Some(proxy) = &notify.proxy {
+            if let Err(e) = reqwest::Proxy::all(proxy.as_str()) {
+                problems.push(format!("notify.proxy: {}", e));
+            }
+        }
+        for (i, target) in notify.targets.iter().enumerate() {
+            match target {
+                NotifyTarget::Webhook { url } => {
+                    if let Err(e) = check_url(url) {
+                        problems.push(format!("notify.targets.{}.url: {}", i, e));
+                    }
+                }
+                NotifyTarget::Email { to, .. } if to.is_empty() => {
+                    problems.push(format!("notify.targets.{}: no recipients", i));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut hosts: Vec<_> = config.image_registries.keys().collect();
+    hosts.sort();
+    for host in hosts {
+        if host.contains("://") || host.contains('/') {
+            problems.push(format!("image_registries.{}: key should be a bare host such as ghcr.io", host));
+        }
+    }
+
+    for (i, token) in config.serve.tokens.iter().enumerate() {
+        if token.token_env.is_empty() {
+            problems.push(format!("serve.tokens.{}: token_env is empty", i));
+        }
+    }
+    if let Some(commands) = &config.serve.commands {
+        if commands.repo.is_empty() {
+            problems.push("serve.commands: repo is empty".to_string());
+        }
+        if let Some(url) = &commands.public_url {
+            if let Err(e) = check_url(url) {
+                problems.push(format!("serve.commands.public_url: {}", e));
+            }
+        }
+    }
+    if let Err(e) = Redactor::from_config(&config.redaction) {
+        problems.push(format!("{:#}", e));
+    }
+
+    for (label, title) in &config.labels {
+        if title.trim().is_empty() {
+            problems.push(format!("labels.{}: section title is empty", label));
+        }
+    }
+
+    let calendar = &config.calendar;
+    if let Some(url) = &calendar.notes_url {
+        if !url.contains("{version}") {
+            problems.push("calendar.notes_url: has no {version} placeholder; every train links to the same page".to_string());
+        }
+        if let Err(e) = check_url(&url.replace("{version}", "v1.0.0")) {
+            problems.push(format!("calendar.notes_url: {}", e));
+        }
+    }
+    let mut planned = std::collections::HashSet::new();
+    for train in &calendar.scheduled {
+        if !planned.insert(&train.version) {
+            problems.push(format!("calendar.scheduled: {} is scheduled more than once", train.version));
+        }
+    }
+    if calendar.google.as_ref().is_some_and(|google| google.calendar_id.is_empty()) {
+        problems.push("calendar.google: calendar_id is empty".to_string());
+    }
+
+    let mut titles = std::collections::HashSet::new();
+    for section in &config.scaffold.sections {
+        if section.title.trim().is_empty() {
+            problems.push("scaffold.sections: section title is empty".to_string());
+        } else if !titles.insert(section.title.trim().to_lowercase()) {
+            problems.push(format!("scaffold.sections: {} is listed more than once", section.title));
+        }
+    }
+    if config.scaffold.file.is_some() && config.scaffold.sections.is_empty() {
+        problems.push("scaffold.file: there are no scaffold.sections for it to fill in".to_string());
+    }
+
+    problems
+}
+
+/// Whether following `depends_on` from `repo` leads back to it.
+fn depends_on_itself(config: &Config, repo: &str) -> bool {
+    let mut visited = std::collections::HashSet::new();
+    let mut pending: Vec<&str> = vec![repo];
+    while let Some(current) = pending.pop() {
+        let Some(settings) = config.repos.settings.get(current) else {
+            continue;
+        };
+        for dependency in &settings.depends_on {
+            if dependency == repo {
+                return true;
+            }
+            if visited.insert(dependency.as_str()) {
+                pending.push(dependency);
+            }
+        }
+    }
+    false
+}
+
+/// Branding files exist and the header and footer templates compile.
+fn validate_branding(config: &Config) -> Vec<String> {
+    let branding = &config.branding;
+    let mut problems = Vec::new();
+    for (key, path) in [("css", &branding.css), ("header", &branding.header), ("footer", &branding.footer)] {
+        if let Some(path) = path {
+            if !Path::new(path).is_file() {
+                problems.push(format!("branding.{}: {} does not exist", key, path));
+            }
+        }
+    }
+    if let Some(url) = &branding.social_image {
+        if let Err(e) = check_url(url) {
+            problems.push(format!("branding.social_image: {}", e));
+        }
+    }
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    let compiled = Branding::load(
+        branding.css.as_deref().map(Path::new),
+        branding.logo.clone(),
+        branding.header.as_deref().map(Path::new),
+        branding.footer.as_deref().map(Path::new),
+    )
+    .and_then(|loaded| ChangelogGenerator::new(OutputFormat::Html, None)?.with_branding(loaded));
+    if let Err(e) = compiled {
+        problems.push(format!("branding: {:#}", e));
+    }
+    problems
+}
+
+/// `repo`, referenced at `key`, will actually be aggregated: it's not excluded, and it's in
+/// `repos.include` when that list is used.
+fn check_repo_reference(config: &Config, key: &str, repo: &str) -> Option<String> {
+    if let Some(pattern) = config.repos.exclude.iter().find(|p| matches_pattern(p, repo)) {
+        return Some(format!("{}: {} is excluded by \"{}\" and will never be aggregated", key, repo, pattern));
+    }
+    let included = config.repos.include.is_empty()
+        || config.repos.include.iter().any(|p| matches_pattern(p, repo));
+    (!included).then(|| format!("{}: {} is not in repos.include, so it only applies when passed with --repos", key, repo))
+}
+
+fn check_url(url: &str) -> std::result::Result<(), String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        Ok(_) => Err(format!("{} is not an http(s) URL", url)),
+        Err(e) => Err(format!("{} is not a valid URL ({})", url, e)),
+    }
+}
+
+/// A key in the config file that isn't part of the schema, usually a typo.
+#[derive(Debug, Clone)]
+pub struct UnknownKey {
+    /// Dotted path of the key, e.g. `repos.settings.api.tag_patern`
+    pub path: String,
+    /// 1-based line and column of the key; only known for TOML files
+    pub position: Option<(usize, usize)>,
+    /// Closest valid key at the same level, when one is close enough to be what was meant
+    pub suggestion: Option<String>,
+}
+
+impl UnknownKey {
+    /// Describe `path` as reported by `serde_ignored`, locating it in `toml_source` if given.
+    pub fn new(path: &str, toml_source: Option<&str>) -> Self {
+        // serde_ignored marks `Option` and newtype wrappers with `?` segments
+        let segments: Vec<&str> = path.split('.').filter(|s| *s != "?").collect();
+        let (name, parents) = segments.split_last().map(|(n, p)| (*n, p)).unwrap_or((path, &[]));
+
+        let schema = schema();
+        let suggestion = known_keys(&schema, parents)
+            .into_iter()
+            .flatten()
+            .map(|known| (strsim::levenshtein(name, known), known))
+            .filter(|(distance, _)| *distance <= 2.max(name.len() / 3))
+            .min()
+            .map(|(_, known)| parents.iter().copied().chain([known.as_str()]).collect::<Vec<_>>().join("."));
+
+        Self {
+            path: segments.join("."),
+            position: toml_source.and_then(|source| key_position(source, &segments)),
+            suggestion,
+        }
+    }
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown key `{}`", self.path)?;
+        if let Some((line, column)) = self.position {
+            write!(f, " at line {}, column {}", line, column)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean `{}`?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every valid key, as JSON of the default config. Collections that are empty by default get a
+/// sample entry (keyed `*` in maps) so the keys of their entries are known too.
+fn schema() -> Value {
+    let mut schema = serde_json::to_value(Config::default()).unwrap_or_default();
+    schema["repos"]["settings"] = json!({ "*": RepoSettings::default() });
+    schema["sections"] = json!([SectionConfig { name: String::new(), repos: Vec::new() }]);
+    schema["gitlab"] = json!(GitLabConfig { url: String::new(), group: None, token_env: String::new() });
+    schema["jira"] = json!(JiraConfig {
+        base_url: String::new(),
+        projects: Vec::new(),
+        email: None,
+        token_env: String::new(),
+    });
+    schema["status_page"] = json!(StatusPageConfig {
+        provider: StatusPageProvider::Statuspage,
+        page_id: String::new(),
+        token_env: String::new(),
+        window_minutes: 0,
+        components: Default::default(),
+    });
+    // Targets are tagged by kind; the sample holds the keys of every kind
+    schema["notify"] = json!({
+        "proxy": "",
+        "targets": [{ "kind": "", "url_env": "", "url": "", "to": [], "from": "" }],
+    });
+    schema["image_registries"] = json!({
+        "*": ImageRegistryConfig { username: String::new(), password_env: String::new() },
+    });
+    schema["serve"]["tokens"] = json!([ServeToken { token_env: String::new(), audience: Audience::Internal }]);
+    schema["serve"]["commands"] = json!(SlashCommandConfig {
+        repo: String::new(),
+        secret_env: String::new(),
+        permission: CommandPermission::default(),
+        public_url: None,
+    });
+    schema["serve"]["slack"] = json!(SlackCommandConfig { signing_secret_env: String::new() });
+    schema["calendar"]["scheduled"] = json!([ScheduledTrain {
+        version: String::new(),
+        date: NaiveDate::default(),
+        description: None,
+    }]);
+    schema["scaffold"]["sections"] = json!([ScaffoldSection {
+        title: String::new(),
+        placeholder: None,
+        required: true,
+        position: SectionPosition::default(),
+    }]);
+    schema["calendar"]["google"] = json!(GoogleCalendarConfig { calendar_id: String::new(), token_env: String::new() });
+    let profile: serde_json::Map<String, Value> = ["output", "publish", "jira", "status_page", "branding", "serve", "redaction"]
+        .into_iter()
+        .map(|table| (table.to_string(), schema[table].clone()))
+        .collect();
+    schema["profiles"] = json!({ "*": profile });
+    schema
+}
+
+/// Keys valid under the table at `parents`, or `None` if `parents` isn't a table in the schema.
+fn known_keys<'a>(schema: &'a Value, parents: &[&str]) -> Option<Vec<&'a String>> {
+    let mut node = schema;
+    for segment in parents {
+        node = match node {
+            Value::Object(map) => map.get(*segment).or_else(|| map.get("*"))?,
+            Value::Array(items) => items.first()?,
+            _ => return None,
+        };
+    }
+    match node {
+        Value::Object(map) => Some(map.keys().filter(|k| *k != "*").collect()),
+        _ => None,
+    }
+}
+
+/// Line and column of the key at `segments` in a TOML document.
+fn key_position(source: &str, segments: &[&str]) -> Option<(usize, usize)> {
+    let document = toml_edit::ImDocument::parse(source).ok()?;
+    let (name, parents) = segments.split_last()?;
+    let mut item = document.as_item();
+    for segment in parents {
+        item = match segment.parse::<usize>() {
+            Ok(index) => item.get(index)?,
+            Err(_) => item.get(*segment)?,
+        };
+    }
+    let (key, _) = item.as_table_like()?.get_key_value(name)?;
+    let offset = key.span()?.start;
+
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    Some((line, column))
+}
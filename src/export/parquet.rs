@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::path::Path;
+use std::sync::Arc;
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentStatus};
+
+/// Write a train as `trains/`, `components/` and `commits/` Parquet files under `dir`, each
+/// named after the train version. Sub-trains are flattened into the same files, told apart by
+/// their `train` column. Re-exporting a version overwrites its files.
+pub fn export(dir: &Path, release: &AggregatedRelease) -> Result<()> {
+    let mut trains = Vec::new();
+    flatten(release, None, &mut trains);
+
+    write(dir, "trains", &release.version, trains_batch(&trains)?)?;
+    write(dir, "components", &release.version, components_batch(&trains)?)?;
+    write(dir, "commits", &release.version, commits_batch(&trains)?)?;
+    Ok(())
+}
+
+fn flatten<'a>(release: &'a AggregatedRelease, parent: Option<&'a str>, out: &mut Vec<(&'a AggregatedRelease, Option<&'a str>)>) {
+    out.push((release, parent));
+    for sub_train in &release.sub_trains {
+        flatten(sub_train, Some(&release.version), out);
+    }
+}
+
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+}
+
+fn string_list(name: &str) -> Field {
+    Field::new_list(name, Field::new("item", DataType::Utf8, true), false)
+}
+
+fn micros(date: &DateTime<Utc>) -> i64 {
+    date.timestamp_micros()
+}
+
+fn trains_batch(trains: &[(&AggregatedRelease, Option<&str>)]) -> Result<RecordBatch> {
+    let schema = Schema::new(vec![
+        Field::new("version", DataType::Utf8, false),
+        Field::new("parent", DataType::Utf8, true),
+        Field::new("generated_at", timestamp_type(), false),
+        Field::new("total_repos", DataType::Int64, false),
+        Field::new("updated_repos", DataType::Int64, false),
+        Field::new("total_commits", DataType::Int64, false),
+        string_list("contributors"),
+    ]);
+
+    let mut contributors = ListBuilder::new(StringBuilder::new());
+    for (train, _) in trains {
+        for login in &train.summary.contributors {
+            contributors.values().append_value(login);
+        }
+        contributors.append(true);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(trains.iter().map(|(t, _)| t.version.as_str()))),
+        Arc::new(StringArray::from_iter(trains.iter().map(|(_, parent)| *parent))),
+        Arc::new(TimestampMicrosecondArray::from_iter_values(trains.iter().map(|(t, _)| micros(&t.date))).with_timezone("UTC")),
+        Arc::new(Int64Array::from_iter_values(trains.iter().map(|(t, _)| t.summary.total_repos as i64))),
+        Arc::new(Int64Array::from_iter_values(trains.iter().map(|(t, _)| t.summary.updated_repos as i64))),
+        Arc::new(Int64Array::from_iter_values(trains.iter().map(|(t, _)| t.summary.total_commits as i64))),
+        Arc::new(contributors.finish()),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn components_batch(trains: &[(&AggregatedRelease, Option<&str>)]) -> Result<RecordBatch> {
+    let schema = Schema::new(vec![
+        Field::new("train", DataType::Utf8, false),
+        Field::new("repository", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("current_version", DataType::Utf8, true),
+        Field::new("previous_version", DataType::Utf8, true),
+        Field::new("release_date", timestamp_type(), true),
+        Field::new("commit_count", DataType::Int64, false),
+        Field::new("features", DataType::Int64, false),
+        Field::new("fixes", DataType::Int64, false),
+        Field::new("breaking_changes", DataType::Int64, false),
+        string_list("contributors"),
+    ]);
+
+    let mut train_col = Vec::new();
+    let mut repository = Vec::new();
+    let mut status = Vec::new();
+    let mut current_version = Vec::new();
+    let mut previous_version = Vec::new();
+    let mut release_date = Vec::new();
+    let mut counts: [Vec<i64>; 4] = Default::default();
+    let mut contributors = ListBuilder::new(StringBuilder::new());
+
+    for (train, _) in trains {
+        for component in &train.components {
+            train_col.push(train.version.as_str());
+            repository.push(component.repository.as_str());
+            match &component.status {
+                ComponentStatus::Released { current_version: current, previous_version: previous, release_date: date, stats, .. } => {
+                    status.push("released");
+                    current_version.push(Some(current.as_str()));
+                    previous_version.push(previous.as_deref());
+                    release_date.push(date.as_ref().map(micros));
+                    for (column, value) in counts.iter_mut().zip([stats.commit_count, stats.features, stats.fixes, stats.breaking_changes]) {
+                        column.push(value as i64);
+                    }
+                    for login in &stats.contributors {
+                        contributors.values().append_value(login);
+                    }
+                }
+                ComponentStatus::NoRelease { latest_version, latest_date } => {
+                    status.push("unchanged");
+                    current_version.push(latest_version.as_deref());
+                    previous_version.push(None);
+                    release_date.push(latest_date.as_ref().map(micros));
+                    counts.iter_mut().for_each(|column| column.push(0));
+                }
+                ComponentStatus::NotYetReleased { .. } => {
+                    status.push("not_yet_released");
+                    current_version.push(None);
+                    previous_version.push(None);
+                    release_date.push(None);
+                    counts.iter_mut().for_each(|column| column.push(0));
+                }
+            }
+            contributors.append(true);
+        }
+    }
+
+    let [commit_count, features, fixes, breaking_changes] = counts;
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(train_col)),
+        Arc::new(StringArray::from(repository)),
+        Arc::new(StringArray::from(status)),
+        Arc::new(StringArray::from(current_version)),
+        Arc::new(StringArray::from(previous_version)),
+        Arc::new(TimestampMicrosecondArray::from(release_date).with_timezone("UTC")),
+        Arc::new(Int64Array::from(commit_count)),
+        Arc::new(Int64Array::from(features)),
+        Arc::new(Int64Array::from(fixes)),
+        Arc::new(Int64Array::from(breaking_changes)),
+        Arc::new(contributors.finish()),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn commits_batch(trains: &[(&AggregatedRelease, Option<&str>)]) -> Result<RecordBatch> {
+    let schema = Schema::new(vec![
+        Field::new("train", DataType::Utf8, false),
+        Field::new("repository", DataType::Utf8, false),
+        Field::new("component_version", DataType::Utf8, false),
+        Field::new("sha", DataType::Utf8, false),
+        Field::new("message", DataType::Utf8, false),
+        Field::new("author", DataType::Utf8, false),
+        Field::new("date", timestamp_type(), false),
+        Field::new("type", DataType::Utf8, true),
+        Field::new("scope", DataType::Utf8, true),
+        Field::new("breaking", DataType::Boolean, false),
+        Field::new("pr_number", DataType::Int64, true),
+        Field::new("verified", DataType::Boolean, false),
+        string_list("issues"),
+    ]);
+
+    let mut train_col = Vec::new();
+    let mut repository = Vec::new();
+    let mut component_version = Vec::new();
+    let mut sha = Vec::new();
+    let mut message = Vec::new();
+    let mut author = Vec::new();
+    let mut date = Vec::new();
+    let mut commit_type = Vec::new();
+    let mut scope = Vec::new();
+    let mut breaking = Vec::new();
+    let mut pr_number = Vec::new();
+    let mut verified = Vec::new();
+    let mut issues = ListBuilder::new(StringBuilder::new());
+
+    for (train, _) in trains {
+        for component in &train.components {
+            let ComponentStatus::Released { current_version, commits, .. } = &component.status else {
+                continue;
+            };
+            for commit in commits {
+                train_col.push(train.version.as_str());
+                repository.push(component.repository.as_str());
+                component_version.push(current_version.as_str());
+                sha.push(commit.sha.as_str());
+                message.push(commit.message.as_str());
+                author.push(commit.author.as_str());
+                date.push(micros(&commit.date));
+                commit_type.push(commit.commit_type.as_ref().map(|t| format!("{:?}", t)));
+                scope.push(commit.scope.as_deref());
+                breaking.push(commit.breaking);
+                pr_number.push(commit.pr_number.map(|n| n as i64));
+                verified.push(commit.verified);
+                for number in &commit.issues {
+                    issues.values().append_value(format!("#{}", number));
+                }
+                for key in &commit.issue_keys {
+                    issues.values().append_value(key);
+                }
+                issues.append(true);
+            }
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(train_col)),
+        Arc::new(StringArray::from(repository)),
+        Arc::new(StringArray::from(component_version)),
+        Arc::new(StringArray::from(sha)),
+        Arc::new(StringArray::from(message)),
+        Arc::new(StringArray::from(author)),
+        Arc::new(TimestampMicrosecondArray::from(date).with_timezone("UTC")),
+        Arc::new(StringArray::from(commit_type)),
+        Arc::new(StringArray::from(scope)),
+        Arc::new(BooleanArray::from(breaking)),
+        Arc::new(Int64Array::from(pr_number)),
+        Arc::new(BooleanArray::from(verified)),
+        Arc::new(issues.finish()),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn write(dir: &Path, table: &str, version: &str, batch: RecordBatch) -> Result<()> {
+    let table_dir = dir.join(table);
+    std::fs::create_dir_all(&table_dir)?;
+    let path = table_dir.join(format!("{}.parquet", version.replace('/', "_")));
+
+    let file = std::fs::File::create(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Transaction};
+use std::path::Path;
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentStatus};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trains (
+    version         TEXT PRIMARY KEY,
+    parent          TEXT,
+    generated_at    TEXT NOT NULL,
+    total_repos     INTEGER NOT NULL,
+    updated_repos   INTEGER NOT NULL,
+    total_commits   INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS components (
+    train            TEXT NOT NULL,
+    repository       TEXT NOT NULL,
+    status           TEXT NOT NULL,
+    current_version  TEXT,
+    previous_version TEXT,
+    release_date     TEXT,
+    commit_count     INTEGER NOT NULL,
+    breaking_changes INTEGER NOT NULL,
+    PRIMARY KEY (train, repository)
+);
+CREATE TABLE IF NOT EXISTS commits (
+    train       TEXT NOT NULL,
+    repository  TEXT NOT NULL,
+    sha         TEXT NOT NULL,
+    message     TEXT NOT NULL,
+    author      TEXT NOT NULL,
+    date        TEXT NOT NULL,
+    type        TEXT,
+    scope       TEXT,
+    breaking    INTEGER NOT NULL,
+    pr_number   INTEGER,
+    verified    INTEGER NOT NULL,
+    PRIMARY KEY (train, repository, sha)
+);
+CREATE TABLE IF NOT EXISTS contributors (
+    train       TEXT NOT NULL,
+    repository  TEXT NOT NULL,
+    login       TEXT NOT NULL,
+    PRIMARY KEY (train, repository, login)
+);
+CREATE TABLE IF NOT EXISTS issues (
+    train       TEXT NOT NULL,
+    repository  TEXT NOT NULL,
+    sha         TEXT NOT NULL,
+    reference   TEXT NOT NULL,
+    PRIMARY KEY (train, repository, sha, reference)
+);
+CREATE INDEX IF NOT EXISTS commits_by_author ON commits (author);
+CREATE INDEX IF NOT EXISTS issues_by_reference ON issues (reference);
+";
+
+/// Tables that hold rows keyed by train, cleared before a train is re-exported.
+const TRAIN_TABLES: &[&str] = &["components", "commits", "contributors", "issues"];
+
+/// Write a train (and its sub-trains) into a SQLite database, creating the schema if needed.
+/// Rows from an earlier export of the same version are replaced, so the database accumulates
+/// one copy of every train it has seen.
+pub fn export(path: &Path, release: &AggregatedRelease) -> Result<()> {
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to open SQLite database {}", path.display()))?;
+    conn.execute_batch(SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    write_train(&tx, release, None)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn write_train(tx: &Transaction, release: &AggregatedRelease, parent: Option<&str>) -> Result<()> {
+    let train = release.version.as_str();
+    for table in TRAIN_TABLES {
+        tx.execute(&format!("DELETE FROM {} WHERE train = ?1", table), params![train])?;
+    }
+
+    tx.execute(
+        "INSERT OR REPLACE INTO trains (version, parent, generated_at, total_repos, updated_repos, total_commits)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            train,
+            parent,
+            release.date.to_rfc3339(),
+            release.summary.total_repos as i64,
+            release.summary.updated_repos as i64,
+            release.summary.total_commits as i64,
+        ],
+    )?;
+
+    for component in &release.components {
+        let repository = component.repository.as_str();
+        match &component.status {
+            ComponentStatus::Released { current_version, previous_version, release_date, commits, stats, .. } => {
+                tx.execute(
+                    "INSERT INTO components (train, repository, status, current_version, previous_version, release_date, commit_count, breaking_changes)
+                     VALUES (?1, ?2, 'released', ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        train,
+                        repository,
+                        current_version,
+                        previous_version,
+                        release_date.map(|d| d.to_rfc3339()),
+                        stats.commit_count as i64,
+                        stats.breaking_changes as i64,
+                    ],
+                )?;
+
+                for commit in commits {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO commits (train, repository, sha, message, author, date, type, scope, breaking, pr_number, verified)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                        params![
+                            train,
+                            repository,
+                            commit.sha,
+                            commit.message,
+                            commit.author,
+                            commit.date.to_rfc3339(),
+                            commit.commit_type.as_ref().map(|t| format!("{:?}", t)),
+                            commit.scope,
+                            commit.breaking,
+                            commit.pr_number.map(|n| n as i64),
+                            commit.verified,
+                        ],
+                    )?;
+
+                    let references = commit.issues.iter()
+                        .map(|n| format!("#{}", n))
+                        .chain(commit.issue_keys.iter().cloned());
+                    for reference in references {
+                        tx.execute(
+                            "INSERT OR IGNORE INTO issues (train, repository, sha, reference) VALUES (?1, ?2, ?3, ?4)",
+                            params![train, repository, commit.sha, reference],
+                        )?;
+                    }
+                }
+
+                for login in &stats.contributors {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO contributors (train, repository, login) VALUES (?1, ?2, ?3)",
+                        params![train, repository, login],
+                    )?;
+                }
+            }
+            ComponentStatus::NoRelease { latest_version, latest_date } => {
+                tx.execute(
+                    "INSERT INTO components (train, repository, status, current_version, previous_version, release_date, commit_count, breaking_changes)
+                     VALUES (?1, ?2, 'unchanged', ?3, NULL, ?4, 0, 0)",
+                    params![train, repository, latest_version, latest_date.map(|d| d.to_rfc3339())],
+                )?;
+            }
+            ComponentStatus::NotYetReleased { .. } => {
+                tx.execute(
+                    "INSERT INTO components (train, repository, status, current_version, previous_version, release_date, commit_count, breaking_changes)
+                     VALUES (?1, ?2, 'not_yet_released', NULL, NULL, NULL, 0, 0)",
+                    params![train, repository],
+                )?;
+            }
+        }
+    }
+
+    for sub_train in &release.sub_trains {
+        write_train(tx, sub_train, Some(train))?;
+    }
+
+    Ok(())
+}
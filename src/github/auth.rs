@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use octocrab::models::{AppId, InstallationId};
+use octocrab::Octocrab;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use super::host::GitHubHost;
+use super::types::TokenKind;
+
+/// How requests to GitHub are authenticated.
+#[derive(Debug, Clone)]
+pub enum GitHubAuth {
+    /// A personal access, OAuth or pre-issued installation token
+    Token(String),
+    /// A GitHub App installation. Installation tokens are requested with a JWT signed by the
+    /// app's private key, and requested again shortly before they expire, so long runs never
+    /// hold a stale token.
+    App {
+        app_id: u64,
+        private_key_path: PathBuf,
+        /// `None` looks up the app's installation on the org
+        installation_id: Option<u64>,
+    },
+    /// No credentials: public repositories only, at GitHub's anonymous rate limit. Used when
+    /// every repository is read from another provider
+    Anonymous,
+}
+
+impl GitHubAuth {
+    /// The token the `gh` CLI is logged in to `host` with: `gh auth token`, or failing that
+    /// (`gh` missing, or older than the `token` subcommand), the `oauth_token` in its `hosts.yml`.
+    pub fn from_gh_cli(host: &GitHubHost) -> Option<Self> {
+        let hostname = host.hostname();
+        let token = gh_auth_token(hostname).or_else(|| gh_hosts_token(&gh_config_dir()?, hostname))?;
+        tracing::debug!("Using the gh CLI's credentials for {}", hostname);
+        Some(GitHubAuth::Token(token))
+    }
+
+    /// The static token, if requests use one (installation tokens are short-lived and held by octocrab).
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            GitHubAuth::Token(token) => Some(token),
+            GitHubAuth::App { .. } | GitHubAuth::Anonymous => None,
+        }
+    }
+
+    /// Whether the credentials only see the repositories they were granted, so a missing
+    /// repository is more likely an access problem than a typo.
+    pub fn is_repository_scoped(&self) -> bool {
+        match self {
+            GitHubAuth::Token(token) => TokenKind::detect(token) == TokenKind::FineGrained,
+            GitHubAuth::App { .. } => true,
+            GitHubAuth::Anonymous => false,
+        }
+    }
+
+    /// What to call the credentials in error messages.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            GitHubAuth::Token(_) => "fine-grained token",
+            GitHubAuth::App { .. } => "GitHub App installation",
+            GitHubAuth::Anonymous => "anonymous access",
+        }
+    }
+
+    /// An octocrab client for `host`'s REST API, plus one rooted for its GraphQL endpoint when
+    /// that lives outside the REST root (Enterprise Server), and for a GitHub App, a way to
+    /// reach its installations on owners other than `org`.
+    pub(super) async fn clients(&self, host: &GitHubHost, org: &str) -> Result<(Octocrab, Option<Octocrab>, Option<AppInstallations>)> {
+        let graphql_root = (!host.is_github_com()).then(|| host.graphql_url().trim_end_matches("/graphql"));
+        match self {
+            GitHubAuth::Token(token) => {
+                let build = |base: &str| -> Result<Octocrab> {
+                    Ok(Octocrab::builder().base_uri(base)?.personal_token(token.clone()).build()?)
+                };
+                Ok((build(host.api_url())?, graphql_root.map(build).transpose()?, None))
+            }
+            GitHubAuth::App { app_id, private_key_path, installation_id } => {
+                let pem = std::fs::read(private_key_path)
+                    .with_context(|| format!("Failed to read the GitHub App private key {}", private_key_path.display()))?;
+                let key = jsonwebtoken::EncodingKey::from_rsa_pem(&pem)
+                    .with_context(|| format!("{} is not an RSA private key in PEM format", private_key_path.display()))?;
+                let build = |base: &str| -> Result<Octocrab> {
+                    Ok(Octocrab::builder().base_uri(base)?.app(AppId(*app_id), key.clone()).build()?)
+                };
+
+                let app = build(host.api_url())?;
+                let installation = match installation_id {
+                    Some(id) => InstallationId(*id),
+                    None => app.apps().get_org_installation(org).await
+                        .with_context(|| format!("GitHub App {} is not installed on {}; install it or pass --installation-id", app_id, org))?
+                        .id,
+                };
+                tracing::debug!("Authenticating as installation {} of GitHub App {}", installation, app_id);
+                let graphql_app = graphql_root.map(build).transpose()?;
+                let graphql = graphql_app.as_ref().map(|app| app.installation(installation));
+                let installations = AppInstallations {
+                    app_id: *app_id,
+                    app: app.clone(),
+                    graphql_app,
+                    clients: tokio::sync::Mutex::new(HashMap::new()),
+                };
+                Ok((app.installation(installation), graphql, Some(installations)))
+            }
+            GitHubAuth::Anonymous => {
+                let build = |base: &str| -> Result<Octocrab> { Ok(Octocrab::builder().base_uri(base)?.build()?) };
+                Ok((build(host.api_url())?, graphql_root.map(build).transpose()?, None))
+            }
+        }
+    }
+}
+
+/// A GitHub App's installations on the owners of repositories outside the configured org, each
+/// looked up the first time one of the owner's repositories is read.
+pub(super) struct AppInstallations {
+    app_id: u64,
+    app: Octocrab,
+    graphql_app: Option<Octocrab>,
+    /// REST and GraphQL clients keyed by lowercased owner; `None` where the app isn't installed
+    clients: tokio::sync::Mutex<HashMap<String, Option<(Octocrab, Octocrab)>>>,
+}
+
+impl AppInstallations {
+    /// Clients of the installation on `owner`, found through its repository `name`.
+    pub(super) async fn clients(&self, owner: &str, name: &str) -> Option<(Octocrab, Octocrab)> {
+        let key = owner.to_lowercase();
+        let mut clients = self.clients.lock().await;
+        if let Some(found) = clients.get(&key) {
+            return found.clone();
+        }
+        let found = match self.app.apps().get_repository_installation(owner, name).await {
+            Ok(installation) => {
+                tracing::debug!("Reading {}'s repositories as installation {} of GitHub App {}", owner, installation.id, self.app_id);
+                let rest = self.app.installation(installation.id);
+                let graphql = self.graphql_app.as_ref().map(|app| app.installation(installation.id)).unwrap_or_else(|| rest.clone());
+                Some((rest, graphql))
+            }
+            Err(e) => {
+                tracing::warn!("GitHub App {} is not installed on {} ({}); its repositories may not be readable", self.app_id, owner, e);
+                None
+            }
+        };
+        clients.insert(key, found.clone());
+        found
+    }
+}
+
+fn gh_auth_token(hostname: &str) -> Option<String> {
+    let output = Command::new("gh").args(["auth", "token", "--hostname", hostname]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!token.is_empty()).then_some(token)
+}
+
+/// Where `gh` keeps its config: `$GH_CONFIG_DIR`, else `$XDG_CONFIG_HOME/gh`, else
+/// `%AppData%/GitHub CLI` on Windows and `~/.config/gh` elsewhere.
+fn gh_config_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    if let Some(dir) = var("GH_CONFIG_DIR") {
+        return Some(dir);
+    }
+    if let Some(dir) = var("XDG_CONFIG_HOME") {
+        return Some(dir.join("gh"));
+    }
+    if cfg!(windows) {
+        return var("AppData").map(|dir| dir.join("GitHub CLI"));
+    }
+    var("HOME").map(|dir| dir.join(".config").join("gh"))
+}
+
+/// `<hostname>.oauth_token` from `hosts.yml`. Recent `gh` versions keep the token in the system
+/// keyring instead, which only `gh auth token` can read.
+fn gh_hosts_token(config_dir: &Path, hostname: &str) -> Option<String> {
+    let hosts = std::fs::read_to_string(config_dir.join("hosts.yml")).ok()?;
+    let hosts: serde_yaml::Value = serde_yaml::from_str(&hosts).ok()?;
+    let token = hosts.get(hostname)?.get("oauth_token")?.as_str()?.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
@@ -0,0 +1,136 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use super::retry::{self, RateLimitHeaders, RetryReason};
+
+/// Longest pause after repeated secondary rate limits.
+const MAX_PAUSE: Duration = Duration::from_secs(15 * 60);
+
+/// Pauses every request sharing the client once GitHub's abuse detection answers with a
+/// secondary rate limit. Retrying each request on its own would have every concurrent task come
+/// back at about the same moment and extend the ban; instead the circuit opens for the advised
+/// window, a single probe request goes first when it ends, and the rest resume once the probe
+/// gets through. Each failed probe doubles the pause.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    state: Mutex<State>,
+    changed: Notify,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// When the current pause ends; `None` while the circuit is closed
+    open_until: Option<Instant>,
+    /// Whether a probe request is in flight
+    probing: bool,
+    /// Pauses since the circuit last closed
+    trips: u32,
+}
+
+/// The one request let through after a pause. Dropping it without `close` or
+/// `CircuitBreaker::trip` (say, because the task was cancelled) hands the probe to another waiter.
+pub struct Probe<'a> {
+    breaker: &'a CircuitBreaker,
+}
+
+impl CircuitBreaker {
+    /// Wait until requests may be sent. Returns a `Probe` when this request is the first one
+    /// after a pause; its outcome decides whether everyone else resumes.
+    pub async fn wait(&self) -> Option<Probe<'_>> {
+        loop {
+            // Registered before reading the state so a probe finishing in between isn't missed
+            let changed = self.changed.notified();
+            let sleep_until = {
+                let mut state = self.state.lock().unwrap();
+                match state.open_until {
+                    None => return None,
+                    Some(until) if until > Instant::now() => Some(until),
+                    Some(_) if !state.probing => {
+                        state.probing = true;
+                        return Some(Probe { breaker: self });
+                    }
+                    Some(_) => None,
+                }
+            };
+            match sleep_until {
+                Some(until) => tokio::time::sleep_until(until).await,
+                None => changed.await,
+            }
+        }
+    }
+
+    /// Open the circuit after a secondary rate limit, returning the pause: the window the
+    /// response's `Retry-After` or `X-RateLimit-Reset` advises, or exponential backoff when it
+    /// has neither. Returns `None` when the circuit was already open and `probe` is `None`: the
+    /// request was sent before the circuit tripped, so it says nothing new about the ban.
+    pub fn trip(&self, probe: Option<Probe<'_>>, headers: &RateLimitHeaders) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        if state.open_until.is_some() && probe.is_none() {
+            return None;
+        }
+        state.trips += 1;
+        let advised = match (headers.retry_after, headers.reset) {
+            (Some(seconds), _) => Some(Duration::from_secs(seconds)),
+            (None, Some(reset)) => retry::until_reset(reset),
+            (None, None) => None,
+        };
+        let pause = advised
+            .unwrap_or_else(|| retry::backoff(RetryReason::SecondaryRateLimit, state.trips))
+            .min(MAX_PAUSE);
+        state.open_until = Some(Instant::now() + pause);
+        state.probing = false;
+        Some(pause)
+    }
+
+    fn release_probe(&self, close: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.probing = false;
+        if close {
+            state.open_until = None;
+            state.trips = 0;
+        }
+        drop(state);
+        self.changed.notify_waiters();
+    }
+}
+
+impl Probe<'_> {
+    /// The probe got an answer other than a secondary rate limit, so the ban is over.
+    pub fn close(self) {
+        self.breaker.release_probe(true);
+    }
+}
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        self.breaker.release_probe(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitBreaker, MAX_PAUSE};
+    use crate::github::retry::RateLimitHeaders;
+    use std::time::Duration;
+
+    #[test]
+    fn opens_for_the_advised_window() {
+        let breaker = CircuitBreaker::default();
+        let headers = RateLimitHeaders { retry_after: Some(30), ..RateLimitHeaders::default() };
+        assert_eq!(breaker.trip(None, &headers), Some(Duration::from_secs(30)));
+        // Requests sent before the circuit opened don't extend the pause
+        assert_eq!(breaker.trip(None, &headers), None);
+
+        let breaker = CircuitBreaker::default();
+        let headers = RateLimitHeaders { retry_after: Some(3600), ..RateLimitHeaders::default() };
+        assert_eq!(breaker.trip(None, &headers), Some(MAX_PAUSE));
+    }
+
+    #[test]
+    fn backs_off_without_an_advised_window() {
+        let breaker = CircuitBreaker::default();
+        let pause = breaker.trip(None, &RateLimitHeaders::default()).unwrap();
+        assert!(pause >= Duration::from_secs(60) && pause <= Duration::from_secs(75), "{:?}", pause);
+    }
+}
@@ -1,27 +1,424 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use octocrab::Octocrab;
 use octocrab::models;
-use super::types::{CommitInfo, CommitAuthor, PullRequest};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
+use super::auth::{AppInstallations, GitHubAuth};
+use super::circuit::CircuitBreaker;
+use super::etag::EtagStore;
+use super::graphql::{self, PrefetchedRepository};
+use super::host::GitHubHost;
+use super::links::WebLinks;
+use super::retry::{self, Failure, RateLimitHeaders, RetryReason};
+use super::throttle::Throttle;
+use crate::config::types::RepoSettings;
+use crate::provider;
+use super::types::{ApiBackend, toggle_v_prefix, AnnotatedTag, AssociatedPullRequest, Nodes, ASSOCIATED_PULL_REQUEST_FIELDS, CollaboratorPermission, CommitComparison, CommitInfo, CommitPayload, CommitRef, ComparePage, DateSource, GitObject, GitRef, Issue, MakeLatest, PreviousStrategy, PullRequest, Release, ReleaseReactions, ReleaseSource, RepoAccess, RepoPermissions, RepositoryInfo, Team, UserRef};
+
+/// Pages of 100 read per listing before giving up, unless overridden with `with_max_pages`.
+pub const DEFAULT_MAX_PAGES: usize = 50;
+
+/// Commits whose pull requests are looked up in one GraphQL query.
+const PR_LOOKUP_BATCH: usize = 50;
+
+/// Tags resolved at once when building releases from git tags.
+const TAG_LOOKUP_CONCURRENCY: usize = 8;
 
 pub struct GitHubClient {
     client: Octocrab,
+    /// Same credentials, rooted where `client.graphql` finds the GraphQL endpoint
+    graphql_client: Octocrab,
+    host: GitHubHost,
     org: String,
+    max_pages: usize,
+    etags: EtagStore,
+    release_source: ReleaseSource,
+    /// Per-repository overrides of `release_source`
+    release_sources: HashMap<String, ReleaseSource>,
+    throttle: Option<Throttle>,
+    breaker: CircuitBreaker,
+    api: ApiBackend,
+    /// Repositories read by `prefetch`, keyed by lowercase `owner/name`
+    prefetched: RwLock<HashMap<String, PrefetchedRepository>>,
+    /// With GitHub App auth, the installations on owners other than `org`
+    installations: Option<AppInstallations>,
 }
 
 impl GitHubClient {
-    pub async fn new(token: String, org: String) -> Result<Self> {
-        let client = Octocrab::builder()
-            .personal_token(token)
-            .build()?;
-        Ok(Self { client, org })
-    }
-
-    pub async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<models::repos::Release>> {
-        let result = self.client
-            .repos(&self.org, repo)
-            .releases()
-            .get_by_tag(tag)
-            .await;
+    pub async fn new(auth: &GitHubAuth, org: String, host: GitHubHost) -> Result<Self> {
+        let (client, graphql_client, installations) = auth.clients(&host, &org).await?;
+        let graphql_client = graphql_client.unwrap_or_else(|| client.clone());
+        Ok(Self {
+            client,
+            graphql_client,
+            host,
+            org,
+            max_pages: DEFAULT_MAX_PAGES,
+            etags: EtagStore::default(),
+            release_source: ReleaseSource::default(),
+            release_sources: HashMap::new(),
+            throttle: None,
+            breaker: CircuitBreaker::default(),
+            api: ApiBackend::default(),
+            prefetched: RwLock::new(HashMap::new()),
+            installations,
+        })
+    }
+
+    /// Remember ETags in `path` across runs, so unchanged releases and commits cost no rate limit.
+    pub fn with_etag_store(mut self, path: &Path) -> Result<Self> {
+        self.etags = EtagStore::open(path)?;
+        Ok(self)
+    }
+
+    /// Cap how many pages any single listing follows, to bound API usage on huge histories.
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    /// Space every request at least `throttle.interval()` apart, for servers whose admins would
+    /// rather see a slow steady trickle than bursts.
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    async fn throttle(&self) {
+        if let Some(throttle) = &self.throttle {
+            throttle.wait().await;
+        }
+    }
+
+    /// Read versions from `default` for every repository except those listed in `per_repo`.
+    /// With `ReleaseSource::Tags`, the release lookups below return releases synthesized from
+    /// git tags, so callers don't need to know which source a repository uses.
+    pub fn with_release_source(mut self, default: ReleaseSource, per_repo: HashMap<String, ReleaseSource>) -> Self {
+        self.release_source = default;
+        self.release_sources = per_repo;
+        self
+    }
+
+    /// With `ApiBackend::Graphql`, `prefetch` reads repositories, releases and tags in bulk
+    /// and the lookups below answer from what it fetched before making REST requests.
+    pub fn with_api(mut self, api: ApiBackend) -> Self {
+        self.api = api;
+        self
+    }
+
+    pub fn release_source(&self, repo: &str) -> ReleaseSource {
+        self.release_sources.get(repo).copied().unwrap_or(self.release_source)
+    }
+
+    /// Run a request, retrying rate limits and server errors with backoff, or for `Retry-After`
+    /// when the failed response says. The closure is called once per attempt, so it must build
+    /// the request from scratch. A secondary rate limit trips the shared circuit breaker, pausing
+    /// every request rather than just this one.
+    async fn retry<T, E, F, Fut>(&self, what: &str, mut request: F) -> octocrab::Result<T>
+    where
+        E: Into<Failure>,
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let probe = self.breaker.wait().await;
+            self.throttle().await;
+            let failure = match request().await {
+                Ok(value) => {
+                    if let Some(probe) = probe {
+                        probe.close();
+                    }
+                    return Ok(value);
+                }
+                Err(error) => error.into(),
+            };
+            let reason = retry::retry_reason(&failure);
+            if reason == Some(RetryReason::SecondaryRateLimit) {
+                if let Some(pause) = self.breaker.trip(probe, &failure.headers.unwrap_or_default()) {
+                    tracing::warn!("{}: secondary rate limit, pausing all requests for {}s", what, pause.as_secs());
+                }
+            } else if let Some(probe) = probe {
+                probe.close();
+            }
+            let Some(reason) = reason else {
+                return Err(failure.error);
+            };
+            if attempt >= retry::MAX_ATTEMPTS {
+                tracing::warn!("{}: {} after {} attempts, giving up", what, reason, attempt);
+                return Err(failure.error);
+            }
+
+            let headers = match (reason, failure.headers) {
+                (RetryReason::RateLimit, None) => self.rate_limit_status().await.unwrap_or_default(),
+                (_, headers) => headers.unwrap_or_default(),
+            };
+            let delay = match reason {
+                // The breaker holds the next attempt back until the pause is over
+                RetryReason::SecondaryRateLimit => std::time::Duration::ZERO,
+                _ => match retry::delay(reason, attempt, &headers) {
+                    Some(delay) => delay,
+                    None => {
+                        tracing::warn!("{}: {} lifts too far in the future to wait for", what, reason);
+                        return Err(failure.error);
+                    }
+                },
+            };
+
+            if !delay.is_zero() {
+                tracing::warn!(
+                    "{}: {}, retrying in {}s (attempt {}/{})",
+                    what, reason, delay.as_secs(), attempt + 1, retry::MAX_ATTEMPTS
+                );
+                tokio::time::sleep(delay).await;
+            }
+            attempt += 1;
+        }
+    }
+
+    /// REST and GraphQL clients for `owner/name`: the GitHub App's installation on `owner` when
+    /// a repository lives outside the configured org, otherwise the default ones.
+    async fn clients_for(&self, owner: &str, name: &str) -> (Octocrab, Octocrab) {
+        if let Some(installations) = self.installations.as_ref().filter(|_| !owner.eq_ignore_ascii_case(&self.org)) {
+            if let Some(clients) = installations.clients(owner, name).await {
+                return clients;
+            }
+        }
+        (self.client.clone(), self.graphql_client.clone())
+    }
+
+    /// REST client for requests about `repo`.
+    async fn client_for(&self, repo: &str) -> Octocrab {
+        let (owner, name) = self.owner_and_name(repo);
+        self.clients_for(owner, name).await.0
+    }
+
+    /// Run a GraphQL query about repositories of `repo`'s owner, with retries, returning its
+    /// `data`. Partial data (say, one aliased repository not found) is returned as is; a response
+    /// without any is an error.
+    async fn graphql(&self, what: &str, repo: &str, payload: &serde_json::Value) -> Result<serde_json::Value> {
+        let (owner, name) = self.owner_and_name(repo);
+        let (_, client) = self.clients_for(owner, name).await;
+        let mut response: serde_json::Value = self.retry(what, || client.graphql(payload)).await?;
+        match response.get_mut("data").map(serde_json::Value::take) {
+            Some(data) if !data.is_null() => Ok(data),
+            _ => {
+                let errors = response.get("errors").map(|e| e.to_string()).unwrap_or_default();
+                anyhow::bail!("no data in the GraphQL response {}", errors)
+            }
+        }
+    }
+
+    /// GET a REST route into `T`, with retries.
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, route: &str) -> octocrab::Result<T> {
+        self.retry(route, || self.get_conditional(route)).await
+    }
+
+    /// `X-RateLimit-Remaining` and `X-RateLimit-Reset` for the token, read from `/rate_limit`
+    /// (which doesn't count against the limit).
+    async fn rate_limit_status(&self) -> Option<RateLimitHeaders> {
+        self.throttle().await;
+        let response = self.client._get("/rate_limit").await.ok()?;
+        let headers = RateLimitHeaders::read(|name| response.headers().get(name)?.to_str().ok()?.parse().ok());
+        (headers.remaining.is_some() && headers.reset.is_some()).then_some(headers)
+    }
+
+    /// GET `route` with `If-None-Match` when an earlier response carried an ETag, replaying the
+    /// stored body on `304 Not Modified`. Failures keep the response's rate-limit headers.
+    async fn get_conditional<T: serde::de::DeserializeOwned>(&self, route: &str) -> Result<T, Failure> {
+        let cached = self.etags.get(route);
+        let headers = cached.as_ref().and_then(|cached| {
+            Some(std::iter::once(("if-none-match".parse().ok()?, cached.etag.parse().ok()?)).collect())
+        });
+
+        let client = match route_repository(route) {
+            Some((owner, name)) => self.clients_for(owner, name).await.0,
+            None => self.client.clone(),
+        };
+        let response = client._get_with_headers(route, headers).await?;
+        if response.status().as_u16() == 304 {
+            if let Some(value) = cached.and_then(|cached| serde_json::from_str(&cached.body).ok()) {
+                tracing::debug!("{}: not modified", route);
+                return Ok(value);
+            }
+        }
+
+        let headers = (!response.status().is_success())
+            .then(|| RateLimitHeaders::read(|name| response.headers().get(name)?.to_str().ok()?.parse().ok()));
+        let response = octocrab::map_github_error(response).await
+            .map_err(|error| Failure { error, headers })?;
+        let etag = response.headers().get("etag")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = client.body_to_string(response).await?;
+
+        match serde_json::from_str(&body) {
+            Ok(value) => {
+                if let Some(etag) = etag {
+                    self.etags.insert(route, etag, body);
+                }
+                Ok(value)
+            }
+            // Let octocrab fetch it again so the error carries its usual context
+            Err(_) => {
+                self.throttle().await;
+                Ok(client.get(route, None::<&()>).await?)
+            }
+        }
+    }
+
+    /// Read every page of a list route, up to `limit` items or the page cap.
+    async fn get_paginated<T: serde::de::DeserializeOwned>(&self, route: &str, limit: usize, what: &str) -> octocrab::Result<Vec<T>> {
+        let separator = if route.contains('?') { '&' } else { '?' };
+        let per_page = limit.clamp(1, 100);
+        let mut items = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let page_items: Vec<T> = self.get_json(&format!("{}{}per_page={}&page={}", route, separator, per_page, page)).await?;
+            let received = page_items.len();
+            items.extend(page_items);
+
+            if received < per_page || items.len() >= limit {
+                break;
+            }
+            if page >= self.max_pages {
+                tracing::warn!("Stopped listing {} after {} pages; results are incomplete (raise --max-pages)", what, page);
+                break;
+            }
+            page += 1;
+        }
+
+        items.truncate(limit);
+        Ok(items)
+    }
+
+    /// Write cached responses to disk now rather than when the client is dropped.
+    pub fn save_cache(&self) -> Result<()> {
+        self.etags.save()
+    }
+
+    pub fn org(&self) -> &str {
+        &self.org
+    }
+
+    pub fn host(&self) -> &GitHubHost {
+        &self.host
+    }
+
+    /// Split a repository reference into owner and name. Bare names belong to the configured
+    /// org; `owner/name` addresses a repository that was transferred elsewhere.
+    fn owner_and_name<'a>(&'a self, repo: &'a str) -> (&'a str, &'a str) {
+        repo.split_once('/').unwrap_or((&self.org, repo))
+    }
+
+    /// Web URL for a repository reference.
+    pub fn repo_url(&self, repo: &str) -> String {
+        self.web_links().repo(repo).url().to_string()
+    }
+
+    /// Builder for commit, pull request and compare page URLs in this org.
+    pub fn web_links(&self) -> WebLinks {
+        WebLinks::new(self.host.web_url(), &self.org)
+    }
+
+    /// Read `repos` (with their releases or tags) `graphql::REPOS_PER_QUERY` at a time, so the
+    /// lookups of an aggregation mostly skip REST. Does nothing with `ApiBackend::Rest`.
+    /// Repositories the queries can't resolve are simply left to REST.
+    pub async fn prefetch(&self, repos: &[String]) -> Result<()> {
+        if self.api != ApiBackend::Graphql {
+            return Ok(());
+        }
+        // Queries go to the client of their repositories' owner, so owners are batched apart
+        let mut by_owner: Vec<(String, Vec<String>)> = Vec::new();
+        for repo in repos {
+            let owner = self.owner_and_name(repo).0.to_lowercase();
+            match by_owner.iter_mut().find(|(o, _)| *o == owner) {
+                Some((_, owned)) => owned.push(repo.clone()),
+                None => by_owner.push((owner, vec![repo.clone()])),
+            }
+        }
+        for batch in by_owner.iter().flat_map(|(_, owned)| owned.chunks(graphql::REPOS_PER_QUERY)) {
+            let targets: Vec<(&str, &str, ReleaseSource)> = batch.iter()
+                .map(|repo| {
+                    let (owner, name) = self.owner_and_name(repo);
+                    (owner, name, self.release_source(repo))
+                })
+                .collect();
+            let data = self.graphql("graphql repositories", &batch[0], &graphql::repositories_query(&targets)).await?;
+
+            let mut prefetched = self.prefetched.write().unwrap_or_else(|e| e.into_inner());
+            for (i, (repo, (_, _, source))) in batch.iter().zip(&targets).enumerate() {
+                match graphql::parse_repository(&data, &format!("r{}", i), *source, &self.host) {
+                    Ok(Some(found)) => {
+                        // Renamed repositories are looked up by their new name afterwards
+                        prefetched.insert(found.info.full_name.to_lowercase(), found.clone());
+                        prefetched.insert(self.prefetch_key(repo), found);
+                    }
+                    Ok(None) => tracing::debug!("{} not found by GraphQL, using REST", repo),
+                    Err(e) => tracing::debug!("Unreadable GraphQL data for {}, using REST: {:#}", repo, e),
+                }
+            }
+        }
+        tracing::debug!("Prefetched {} repositories over GraphQL", repos.len());
+        Ok(())
+    }
+
+    fn prefetch_key(&self, repo: &str) -> String {
+        let (owner, name) = self.owner_and_name(repo);
+        format!("{}/{}", owner, name).to_lowercase()
+    }
+
+    /// Answer `lookup` from the prefetched copy of `repo`, if there is one and it can.
+    fn prefetched<T>(&self, repo: &str, lookup: impl FnOnce(&PrefetchedRepository) -> Option<T>) -> Option<T> {
+        let prefetched = self.prefetched.read().unwrap_or_else(|e| e.into_inner());
+        prefetched.get(&self.prefetch_key(repo)).and_then(lookup)
+    }
+
+    /// Drop the prefetched copy of `repo` once its releases change.
+    fn forget_prefetched(&self, repo: &str) {
+        let mut prefetched = self.prefetched.write().unwrap_or_else(|e| e.into_inner());
+        prefetched.remove(&self.prefetch_key(repo));
+    }
+
+    /// Release tagged `tag`, or failing that the same tag with the `v` prefix added or removed,
+    /// so `1.2.3` finds `v1.2.3` and vice versa. The returned release's `tag_name` is the tag
+    /// that matched.
+    pub async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        if let Some(release) = self.get_release_exact(repo, tag).await? {
+            return Ok(Some(release));
+        }
+        let Some(alternate) = toggle_v_prefix(tag) else {
+            return Ok(None);
+        };
+        let release = self.get_release_exact(repo, &alternate).await?;
+        if release.is_some() {
+            tracing::debug!("{}: no release {}, using {}", repo, tag, alternate);
+        }
+        Ok(release)
+    }
+
+    async fn get_release_exact(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        match self.release_source(repo) {
+            ReleaseSource::Releases => {
+                if let Some(release) = self.prefetched(repo, |p| p.release(tag)) {
+                    return Ok(release);
+                }
+                Ok(self.get_github_release(repo, tag).await?.map(Release::from))
+            }
+            ReleaseSource::Tags => self.get_tag_release(repo, tag).await,
+        }
+    }
+
+    /// The GitHub Release object for exactly `tag`, whatever the repository's release source,
+    /// with the ids needed to write to it. Always fetched, never prefetched.
+    pub async fn get_github_release(&self, repo: &str, tag: &str) -> Result<Option<models::repos::Release>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/releases/tags/{}", owner, name, tag);
+        let result: octocrab::Result<models::repos::Release> = self.get_json(&route).await;
 
         match result {
             Ok(release) => Ok(Some(release)),
@@ -32,12 +429,11 @@ impl GitHubClient {
         }
     }
 
-    pub async fn get_latest_release(&self, repo: &str) -> Result<Option<models::repos::Release>> {
-        let result = self.client
-            .repos(&self.org, repo)
-            .releases()
-            .get_latest()
-            .await;
+    /// Reactions on the GitHub Release for exactly `tag`.
+    pub async fn get_release_reactions(&self, repo: &str, tag: &str) -> Result<Option<ReleaseReactions>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/releases/tags/{}", owner, name, tag);
+        let result: octocrab::Result<ReleaseReactions> = self.get_json(&route).await;
 
         match result {
             Ok(release) => Ok(Some(release)),
@@ -48,152 +444,740 @@ impl GitHubClient {
         }
     }
 
-    pub async fn list_releases(&self, repo: &str, limit: usize) -> Result<Vec<models::repos::Release>> {
-        let releases = self.client
-            .repos(&self.org, repo)
-            .releases()
-            .list()
-            .per_page(limit as u8)
-            .send()
-            .await?;
+    /// Reactions on the `limit` most recent GitHub Releases, newest first.
+    pub async fn list_release_reactions(&self, repo: &str, limit: usize) -> Result<Vec<ReleaseReactions>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/releases", owner, name);
+        Ok(self.get_paginated(&route, limit, &format!("releases of {}", repo)).await?)
+    }
+
+    /// Create the release for `tag`, or update the one that exists. `make_latest` leaves
+    /// GitHub's default (the new release becomes latest) when not given.
+    pub async fn upsert_release(
+        &self,
+        repo: &str,
+        tag: &str,
+        name: &str,
+        body: &str,
+        draft: bool,
+        make_latest: Option<MakeLatest>,
+    ) -> Result<models::repos::Release> {
+        self.forget_prefetched(repo);
+        // Exact match only: publishing `1.2.3` must not update an existing `v1.2.3`
+        let existing = self.get_github_release(repo, tag).await?;
+        let what = format!("publish release {} of {}", tag, repo);
+        let client = self.client_for(repo).await;
+        let (owner, repo) = self.owner_and_name(repo);
+        let payload = ReleasePayload { tag_name: tag, name, body, draft, make_latest };
 
-        Ok(releases.items)
+        let release = match existing {
+            Some(existing) => {
+                let route = format!("/repos/{}/{}/releases/{}", owner, repo, existing.id);
+                self.retry(&what, || client.patch(&route, Some(&payload))).await?
+            }
+            None => {
+                let route = format!("/repos/{}/{}/releases", owner, repo);
+                self.retry(&what, || client.post(&route, Some(&payload))).await?
+            }
+        };
+
+        Ok(release)
     }
 
-    pub async fn get_previous_release(&self, repo: &str, current_release: &models::repos::Release) -> Result<Option<models::repos::Release>> {
-        let releases = self.client
-            .repos(&self.org, repo)
-            .releases()
-            .list()
-            .per_page(100)
-            .send()
-            .await?;
+    pub async fn update_release_body(&self, repo: &str, release_id: u64, body: &str) -> Result<()> {
+        self.forget_prefetched(repo);
+        let client = self.client_for(repo).await;
+        let (owner, name) = self.owner_and_name(repo);
+        self.retry(&format!("update release {} of {}", release_id, repo), || async {
+            client
+                .repos(owner, name)
+                .releases()
+                .update(release_id)
+                .body(body)
+                .send()
+                .await
+        })
+        .await?;
+        Ok(())
+    }
 
-        let current_date = current_release.created_at;
-        
-        // Find the release immediately before the current one by date
-        let mut previous: Option<models::repos::Release> = None;
-        for release in releases.items {
-            if release.created_at < current_date {
-                if previous.is_none() || release.created_at > previous.as_ref().unwrap().created_at {
-                    previous = Some(release);
-                }
+    /// A file's text as of `reference`, from the contents API (which serves files up to 1 MB);
+    /// `None` if there is no such file.
+    pub async fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> Result<Option<String>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/contents/{}?ref={}", owner, name, path, reference);
+        match self.get_json::<models::repos::Content>(&route).await {
+            Ok(content) => Ok(content.decoded_content()),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Paths of the files directly in directory `path` as of `reference`; empty if there is no
+    /// such directory.
+    pub async fn list_directory(&self, repo: &str, path: &str, reference: &str) -> Result<Vec<String>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/contents/{}?ref={}", owner, name, path, reference);
+        match self.get_json::<Vec<models::repos::Content>>(&route).await {
+            Ok(entries) => Ok(entries.into_iter().filter(|entry| entry.r#type == "file").map(|entry| entry.path).collect()),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create or replace a file on the default branch; returns the file's web URL.
+    pub async fn put_file(&self, repo: &str, path: &str, message: &str, content: &str) -> Result<Option<String>> {
+        let what = format!("{} in {}", path, repo);
+        let client = self.client_for(repo).await;
+        let (owner, repo) = self.owner_and_name(repo);
+        let repo_handler = client.repos(owner, repo);
+
+        let existing_sha = match self.retry(&what, || repo_handler.get_content().path(path).send()).await {
+            Ok(items) => items.items.into_iter().next().map(|item| item.sha),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let update = match existing_sha {
+            Some(sha) => self.retry(&what, || repo_handler.update_file(path, message, content, &sha).send()).await?,
+            None => self.retry(&what, || repo_handler.create_file(path, message, content).send()).await?,
+        };
+
+        Ok(update.content.html_url)
+    }
+
+    pub async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        if let Some(latest) = self.prefetched(repo, PrefetchedRepository::latest) {
+            return Ok(latest);
+        }
+        if self.release_source(repo) == ReleaseSource::Tags {
+            return Ok(self.list_releases(repo, 1).await?.into_iter().next());
+        }
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/releases/latest", owner, name);
+        let result: octocrab::Result<models::repos::Release> = self.get_json(&route).await;
+
+        match result {
+            Ok(release) => Ok(Some(release.into())),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => {
+                Ok(None)
             }
+            Err(e) => Err(e.into()),
         }
+    }
+
+    /// Names of the repositories owned by the org (or user), used to expand repo globs.
+    pub async fn list_org_repos(&self) -> Result<Vec<String>> {
+        let what = format!("repositories of {}", self.org);
+        let route = format!("/orgs/{}/repos", self.org);
+        let result: octocrab::Result<Vec<RepositoryInfo>> = self.get_paginated(&route, usize::MAX, &what).await;
 
-        Ok(previous)
+        let repos = match result {
+            Ok(repos) => repos,
+            // Not an org; fall back to the user's repositories
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => {
+                let route = format!("/users/{}/repos", self.org);
+                self.get_paginated(&route, usize::MAX, &what).await?
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(repos.into_iter().map(|r| r.name).collect())
     }
 
-    pub async fn get_commits_between(&self, repo: &str, from: &str, to: &str) -> Result<Vec<CommitInfo>> {
-        // Get all commits for the 'to' ref
-        let to_commits = self.client
-            .repos(&self.org, repo)
-            .list_commits()
-            .sha(to)
-            .per_page(100)
-            .send()
-            .await?;
+    /// Fetch repository metadata; renamed or transferred repositories resolve to their new location.
+    pub async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        if let Some(info) = self.prefetched(repo, |p| Some(p.info.clone())) {
+            return Ok(Some(info));
+        }
+        let (owner, repo) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}", owner, repo);
+        let result: octocrab::Result<RepositoryInfo> = self.get_json(&route).await;
+
+        match result {
+            Ok(info) => Ok(Some(info)),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Probe which parts of a repository the token can read, without failing on missing access.
+    pub async fn probe_access(&self, repo: &str) -> Result<RepoAccess> {
+        let metadata = self.get_repository(repo).await?.is_some();
+        if !metadata {
+            return Ok(RepoAccess { repo: repo.to_string(), metadata, contents: false });
+        }
+
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/commits?per_page=1", owner, name);
+        let result: octocrab::Result<Vec<CommitRef>> = self.get_json(&route).await;
+
+        let contents = match result {
+            Ok(_) => true,
+            // An empty repository is readable, it just has nothing in it
+            Err(e) if is_empty_repository_error(&e) => true,
+            Err(octocrab::Error::GitHub { source, .. })
+                if source.message.contains("Not Found") || source.message.contains("not accessible") =>
+            {
+                false
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(RepoAccess { repo: repo.to_string(), metadata, contents })
+    }
 
-        // Get all commits for the 'from' ref
-        let from_commits = self.client
-            .repos(&self.org, repo)
-            .list_commits()
-            .sha(from)
-            .per_page(100)
-            .send()
+    /// Fine-grained tokens answer 404 for repositories they weren't granted, which is
+    /// indistinguishable from "no release". Probe access up front and name the repos `auth`
+    /// can't read.
+    pub async fn preflight(&self, auth: &GitHubAuth, repos: &[String]) -> Result<()> {
+        if !auth.is_repository_scoped() {
+            return Ok(());
+        }
+
+        let mut missing = Vec::new();
+        for repo in repos {
+            let access = self.probe_access(repo).await?;
+            if !access.is_complete() {
+                missing.push(access);
+            }
+        }
+
+        if !missing.is_empty() {
+            let details: Vec<String> = missing.iter().map(|a| format!("  - {}", a)).collect();
+            anyhow::bail!(
+                "The {} can't read {} of {} repositories:\n{}\nGrant \"Metadata: read\" and \"Contents: read\" on these repositories and try again.",
+                auth.describe(),
+                missing.len(),
+                repos.len(),
+                details.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Whether the credentials may push to `repo` (create releases, commit files), as GitHub
+    /// reports it; `None` if the repository isn't visible to them.
+    pub async fn can_push(&self, repo: &str) -> Result<Option<bool>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}", owner, name);
+        let result: octocrab::Result<RepoPermissions> = self.get_json(&route).await;
+
+        match result {
+            Ok(repository) => Ok(Some(repository.permissions.is_some_and(|p| p.push))),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// A client writing with `auth` to the repositories this one reads, checked up front for
+    /// push access to each of `targets` so credentials that can't publish fail before a train
+    /// is aggregated.
+    pub async fn writer(&self, auth: &GitHubAuth, targets: &[String]) -> Result<Self> {
+        let client = GitHubClient::new(auth, self.org.clone(), self.host.clone()).await?
+            .with_release_source(self.release_source, self.release_sources.clone());
+
+        let mut denied = Vec::new();
+        for repo in targets {
+            match client.can_push(repo).await? {
+                Some(true) => {}
+                Some(false) => denied.push(format!("  - {}: no push access", repo)),
+                None => denied.push(format!("  - {}: not visible to the write token", repo)),
+            }
+        }
+        if !denied.is_empty() {
+            anyhow::bail!(
+                "The write token can't publish to {} of {} repositories:\n{}\nGrant it \"Contents: write\" on these repositories and try again.",
+                denied.len(),
+                targets.len(),
+                denied.join("\n")
+            );
+        }
+        Ok(client)
+    }
+
+    /// Whether the repository has no commits at all (freshly created, nothing pushed yet).
+    pub async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        let (owner, repo) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/commits?per_page=1", owner, repo);
+        let result: octocrab::Result<Vec<CommitRef>> = self.get_json(&route).await;
+
+        match result {
+            Ok(commits) => Ok(commits.is_empty()),
+            Err(e) if is_empty_repository_error(&e) => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Resolve a tag, branch, or SHA to the commit SHA it currently points at.
+    pub async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        if let Some(sha) = self.prefetched(repo, |p| p.ref_sha(reference)) {
+            return Ok(Some(sha));
+        }
+        let (owner, repo) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/commits/{}", owner, repo, reference);
+        let result: octocrab::Result<CommitRef> = self.get_json(&route).await;
+
+        match result {
+            Ok(commit) => Ok(Some(commit.sha)),
+            Err(octocrab::Error::GitHub { source, .. })
+                if source.message.contains("Not Found") || source.message.contains("No commit found") =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Up to `limit` releases, newest first.
+    pub async fn list_releases(&self, repo: &str, limit: usize) -> Result<Vec<Release>> {
+        if let Some(releases) = self.prefetched(repo, |p| p.releases(limit)) {
+            return Ok(releases);
+        }
+        if self.release_source(repo) == ReleaseSource::Tags {
+            return self.list_tag_releases(repo, limit).await;
+        }
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/releases", owner, name);
+        let releases: Vec<models::repos::Release> = self.get_paginated(&route, limit, &format!("releases of {}", repo)).await?;
+        Ok(releases.into_iter().map(Release::from).collect())
+    }
+
+    pub async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        self.list_releases(repo, usize::MAX).await
+    }
+
+    /// Release for the git tag `tag`, for repositories that only push tags.
+    async fn get_tag_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        if let Some(release) = self.prefetched(repo, |p| p.release(tag)) {
+            return Ok(release);
+        }
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/git/ref/tags/{}", owner, name, tag);
+        let result: octocrab::Result<GitRef> = self.get_json(&route).await;
+
+        match result {
+            Ok(git_ref) => Ok(Some(self.tag_release(repo, tag, &git_ref.object).await?)),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message.contains("Not Found") => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Up to `limit` releases built from the repository's tags, newest first. Every tag is
+    /// resolved to find its date, so this costs a request per tag (cached by ETag afterwards).
+    async fn list_tag_releases(&self, repo: &str, limit: usize) -> Result<Vec<Release>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/git/refs/tags", owner, name);
+        let refs: Vec<GitRef> = match self.get_paginated(&route, usize::MAX, &format!("tags of {}", repo)).await {
+            Ok(refs) => refs,
+            // No tags yet, or an empty repository
+            Err(octocrab::Error::GitHub { source, .. })
+                if source.message.contains("Not Found") || source.message.contains("empty") =>
+            {
+                Vec::new()
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut releases: Vec<Release> = stream::iter(refs)
+            .map(|git_ref| async move {
+                let tag = git_ref.reference.trim_start_matches("refs/tags/").to_string();
+                self.tag_release(repo, &tag, &git_ref.object).await
+            })
+            .buffer_unordered(TAG_LOOKUP_CONCURRENCY)
+            .try_collect()
             .await?;
+        releases.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.tag_name.cmp(&a.tag_name)));
+        releases.truncate(limit);
+        Ok(releases)
+    }
 
-        // Create a set of SHAs from the 'from' commits
-        let from_shas: std::collections::HashSet<String> = from_commits.items
-            .iter()
-            .map(|c| c.sha.clone())
-            .collect();
+    /// The release standing in for `tag`: dated by the tagger (annotated tags) or the tagged
+    /// commit (lightweight tags), with the annotated tag's message as its notes.
+    async fn tag_release(&self, repo: &str, tag: &str, object: &GitObject) -> Result<Release> {
+        let (owner, name) = self.owner_and_name(repo);
+        let (date, body, target): (Option<DateTime<Utc>>, Option<String>, String) = if object.kind == "tag" {
+            let annotated: AnnotatedTag = self.get_json(&format!("/repos/{}/{}/git/tags/{}", owner, name, object.sha)).await?;
+            let message = annotated.message.trim().to_string();
+            (
+                annotated.tagger.and_then(|t| t.date),
+                Some(message).filter(|m| !m.is_empty()),
+                annotated.object.sha,
+            )
+        } else {
+            let commit: CommitPayload = self.get_json(&format!("/repos/{}/{}/commits/{}", owner, name, object.sha)).await?;
+            (commit.commit.author.and_then(|a| a.date), None, object.sha.clone())
+        };
 
-        // Filter to get commits that are in 'to' but not in 'from'
-        let commits = to_commits.items
-            .into_iter()
-            .filter(|c| !from_shas.contains(&c.sha))
-            .map(|commit| {
-                let commit_data = commit.commit;
-                CommitInfo {
-                    sha: commit.sha.clone(),
-                    message: commit_data.message.clone(),
-                    author: CommitAuthor {
-                        name: commit.author.as_ref().map(|a| a.login.clone()).unwrap_or_else(|| "Unknown".to_string()),
-                        email: "".to_string(), // Email not directly available from API
-                        username: commit.author.as_ref().map(|a| a.login.clone()),
-                    },
-                    date: commit_data.author.as_ref().and_then(|a| a.date).unwrap_or_else(|| chrono::Utc::now()),
+        Ok(Release::from_tag(tag, date, body, target).with_html_url(self.host.tag_url(owner, name, tag)))
+    }
+
+    pub async fn get_previous_release(
+        &self,
+        repo: &str,
+        current_release: &Release,
+        settings: Option<&RepoSettings>,
+        date_source: DateSource,
+        strategy: PreviousStrategy,
+    ) -> Result<Option<Release>> {
+        let releases = self.list_all_releases(repo).await?;
+        Ok(provider::previous_release(repo, releases, current_release, settings, date_source, strategy))
+    }
+
+    pub async fn get_commits_between(&self, repo: &str, from: &str, to: &str) -> Result<Vec<CommitInfo>> {
+        Ok(self.get_changes_between(repo, from, to).await?.commits)
+    }
+
+    /// Commits and changed files between two refs, warning when `from` isn't an ancestor of `to`.
+    pub async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        let comparison = self.compare(repo, from, to).await?;
+        tracing::debug!("{}: {}...{} is {} by {}", repo, from, to, comparison.status, comparison.ahead_by);
+        if comparison.status == "diverged" {
+            tracing::warn!(
+                "{}: {} is not an ancestor of {} ({} commits behind); listing only the commits {} adds",
+                repo, from, to, comparison.behind_by, to
+            );
+        }
+        Ok(comparison)
+    }
+
+    /// Compare two refs with the compare API, following pagination so ranges of any size are exact.
+    pub async fn compare(&self, repo: &str, base: &str, head: &str) -> Result<CommitComparison> {
+        let (owner, name) = self.owner_and_name(repo);
+        let mut comparison: Option<CommitComparison> = None;
+        let mut page = 1;
+
+        loop {
+            let route = format!("/repos/{}/{}/compare/{}...{}?per_page=100&page={}", owner, name, base, head, page);
+            let result: octocrab::Result<ComparePage> = self.get_json(&route).await;
+            let compare_page = match result {
+                Ok(compare_page) => compare_page,
+                Err(e) if comparison.is_none() && is_empty_repository_error(&e) => {
+                    return Ok(CommitComparison {
+                        status: "identical".to_string(),
+                        ahead_by: 0,
+                        behind_by: 0,
+                        commits: vec![],
+                        files: vec![],
+                    });
                 }
-            })
-            .collect();
+                Err(e) => return Err(e.into()),
+            };
 
-        Ok(commits)
+            let total = compare_page.total_commits as usize;
+            let received = compare_page.commits.len();
+            let comparison = comparison.get_or_insert_with(|| CommitComparison {
+                status: compare_page.status.clone(),
+                ahead_by: compare_page.ahead_by,
+                behind_by: compare_page.behind_by,
+                commits: Vec::with_capacity(total),
+                files: compare_page.files,
+            });
+            comparison.commits.extend(compare_page.commits.into_iter().map(CommitInfo::from));
+
+            if received == 0 || comparison.commits.len() >= total {
+                break;
+            }
+            if page >= self.max_pages {
+                tracing::warn!(
+                    "Stopped comparing {}...{} in {} after {} pages; {} of {} commits listed (raise --max-pages)",
+                    base, head, repo, page, comparison.commits.len(), total
+                );
+                break;
+            }
+            page += 1;
+        }
+
+        let mut comparison = comparison.expect("at least one page was fetched");
+        comparison.commits.reverse();
+        Ok(comparison)
     }
 
     pub async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        let (owner, name) = self.owner_and_name(repo);
         // Get commits from the beginning up to the specified tag
-        let commits_page = self.client
-            .repos(&self.org, repo)
-            .list_commits()
-            .sha(until)
-            .per_page(100)
-            .send()
-            .await?;
-
-        let commits = commits_page.items
-            .into_iter()
-            .map(|commit| {
-                let commit_data = commit.commit;
-                CommitInfo {
-                    sha: commit.sha.clone(),
-                    message: commit_data.message.clone(),
-                    author: CommitAuthor {
-                        name: commit.author.as_ref().map(|a| a.login.clone()).unwrap_or_else(|| "Unknown".to_string()),
-                        email: "".to_string(), // Email not directly available from API
-                        username: commit.author.as_ref().map(|a| a.login.clone()),
-                    },
-                    date: commit_data.author.as_ref().and_then(|a| a.date).unwrap_or_else(|| chrono::Utc::now()),
-                }
-            })
-            .collect();
+        let route = format!("/repos/{}/{}/commits?sha={}", owner, name, until);
+        let result: octocrab::Result<Vec<CommitPayload>> = self
+            .get_paginated(&route, usize::MAX, &format!("commits of {} up to {}", repo, until))
+            .await;
+        let commits = match result {
+            Ok(commits) => commits.into_iter().map(CommitInfo::from).collect(),
+            Err(e) if is_empty_repository_error(&e) => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
 
         Ok(commits)
     }
 
+    /// Whether `author` (a login or email) committed to the repository before `until`; a single
+    /// commit is enough to tell, so this is one request.
+    pub async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!(
+            "/repos/{}/{}/commits?author={}&until={}&per_page=1",
+            owner, name, author, until.format("%Y-%m-%dT%H:%M:%SZ")
+        );
+        let result: octocrab::Result<Vec<CommitRef>> = self.get_json(&route).await;
+
+        match result {
+            Ok(commits) => Ok(!commits.is_empty()),
+            Err(e) if is_empty_repository_error(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Pull requests associated with each commit, looked up `PR_LOOKUP_BATCH` commits per GraphQL
+    /// query. If GraphQL is unavailable (say, a GitHub Enterprise Server with the API disabled),
+    /// falls back to the commit pulls route, one request per commit. Lookups that fail are skipped.
     pub async fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> Result<Vec<PullRequest>> {
-        // This is a simplified implementation
-        // In practice, you might need to search for PRs that contain these commits
-        let mut prs = Vec::new();
-        
-        for sha in shas {
-            // Try to find PRs associated with this commit
-            let pr_search = self.client
-                .search()
-                .issues_and_pull_requests(&format!("repo:{}/{} sha:{}", self.org, repo, &sha[..7]))
-                .send()
-                .await;
-
-            if let Ok(results) = pr_search {
-                for item in results {
-                    // Fetch full PR details
-                    if let Ok(pr) = self.client
-                        .pulls(&self.org, repo)
-                        .get(item.number)
-                        .await
-                    {
-                        prs.push(PullRequest {
-                            number: pr.number,
-                            title: pr.title.unwrap_or_default(),
-                            body: pr.body,
-                            merged_at: pr.merged_at,
-                            merge_commit_sha: pr.merge_commit_sha,
-                        });
+        let mut prs: Vec<PullRequest> = Vec::new();
+        let mut use_graphql = true;
+
+        for batch in shas.chunks(PR_LOOKUP_BATCH) {
+            let found = if use_graphql {
+                match self.pull_requests_for_commits_graphql(repo, batch).await {
+                    Ok(found) => Some(found),
+                    Err(e) => {
+                        tracing::debug!("GraphQL pull request lookup failed for {}, using REST: {:#}", repo, e);
+                        use_graphql = false;
+                        None
                     }
                 }
+            } else {
+                None
+            };
+            let found = match found {
+                Some(found) => found,
+                None => self.pull_requests_for_commits_rest(repo, batch).await,
+            };
+            for pr in found {
+                if !prs.iter().any(|p| p.number == pr.number) {
+                    prs.push(pr);
+                }
             }
         }
 
         Ok(prs)
     }
-}
\ No newline at end of file
+
+    /// One GraphQL query aliasing each commit (`c0`, `c1`, ...) of `shas`. Commits the
+    /// repository doesn't have come back as `null` and are skipped.
+    async fn pull_requests_for_commits_graphql(&self, repo: &str, shas: &[String]) -> Result<Vec<PullRequest>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let mut query = String::from("query($owner: String!, $name: String!) { repository(owner: $owner, name: $name) {");
+        for (i, sha) in shas.iter().enumerate() {
+            // SHAs go into the query text, so anything but hex would be an injection
+            anyhow::ensure!(sha.chars().all(|c| c.is_ascii_hexdigit()), "{} is not a commit SHA", sha);
+            query.push_str(&format!(
+                " c{}: object(oid: \"{}\") {{ ... on Commit {{ associatedPullRequests(first: 5) {{ nodes {{ {} }} }} }} }}",
+                i, sha, ASSOCIATED_PULL_REQUEST_FIELDS
+            ));
+        }
+        query.push_str(" } }");
+        let payload = serde_json::json!({ "query": query, "variables": { "owner": owner, "name": name } });
+
+        let data = self.graphql("graphql associatedPullRequests", repo, &payload).await?;
+        let Some(repository) = data.get("repository").filter(|value| !value.is_null()) else {
+            anyhow::bail!("{} not found by GraphQL", repo);
+        };
+
+        let mut prs = Vec::new();
+        for i in 0..shas.len() {
+            let Some(connection) = repository.pointer(&format!("/c{}/associatedPullRequests", i)) else {
+                continue;
+            };
+            let found: Nodes<AssociatedPullRequest> = serde_json::from_value(connection.clone())?;
+            prs.extend(found.nodes.into_iter().map(PullRequest::from));
+        }
+        Ok(prs)
+    }
+
+    /// The (cacheable) commit pulls route, one request per commit.
+    async fn pull_requests_for_commits_rest(&self, repo: &str, shas: &[String]) -> Vec<PullRequest> {
+        let (owner, name) = self.owner_and_name(repo);
+        let mut prs = Vec::new();
+        for sha in shas {
+            let route = format!("/repos/{}/{}/commits/{}/pulls", owner, name, sha);
+            match self.get_json::<Vec<PullRequest>>(&route).await {
+                Ok(found) => prs.extend(found),
+                Err(e) => tracing::debug!("No pull requests for {}@{}: {}", repo, sha, e),
+            }
+        }
+        prs
+    }
+
+    /// Pull requests merged into `base` (any branch when `None`) after `since` and up to
+    /// `until` (unbounded when `None`), oldest merge first. Closed pull requests are walked newest update first, which
+    /// can stop at the first one last updated before `since`: a merge always precedes the last
+    /// update.
+    pub async fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let base = base.map(|b| format!("&base={}", b)).unwrap_or_default();
+        let mut merged = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let route = format!(
+                "/repos/{}/{}/pulls?state=closed&sort=updated&direction=desc{}&per_page=100&page={}",
+                owner, name, base, page
+            );
+            let prs: Vec<PullRequest> = self.get_json(&route).await?;
+            let received = prs.len();
+            let mut reached_since = false;
+            for pr in prs {
+                if since.is_some_and(|since| pr.updated_at.is_some_and(|updated| updated <= since)) {
+                    reached_since = true;
+                    break;
+                }
+                let in_window = pr.merged_at.is_some_and(|merged_at| {
+                    !since.is_some_and(|since| merged_at <= since) && until.is_none_or(|until| merged_at <= until)
+                });
+                if in_window && pr.merge_commit_sha.is_some() {
+                    merged.push(pr);
+                }
+            }
+
+            if reached_since || received < 100 {
+                break;
+            }
+            if page >= self.max_pages {
+                tracing::warn!("Stopped listing pull requests of {} after {} pages; results are incomplete (raise --max-pages)", repo, page);
+                break;
+            }
+            page += 1;
+        }
+
+        merged.sort_by_key(|pr| pr.merged_at);
+        Ok(merged)
+    }
+
+    /// Open issues carrying `label`, oldest first; pull requests (which GitHub lists as issues)
+    /// are left out.
+    pub async fn list_open_issues(&self, repo: &str, label: &str) -> Result<Vec<Issue>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!(
+            "/repos/{}/{}/issues?state=open&labels={}&sort=created&direction=asc",
+            owner, name, provider::encode(label)
+        );
+        let issues: Vec<Issue> = self.get_paginated(&route, usize::MAX, &format!("{} issues of {}", label, repo)).await?;
+        Ok(issues.into_iter().filter(|issue| issue.pull_request.is_none()).collect())
+    }
+
+    /// Issues with the given numbers; numbers that turn out to be pull requests, or that can't
+    /// be read (deleted, transferred, or in a repository with issues disabled), are left out.
+    pub async fn get_issues(&self, repo: &str, numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        let (owner, name) = self.owner_and_name(repo);
+        let mut issues = Vec::new();
+
+        for number in numbers {
+            let route = format!("/repos/{}/{}/issues/{}", owner, name, number);
+            match self.get_json::<Issue>(&route).await {
+                Ok(issue) if issue.pull_request.is_none() => issues.push(issue),
+                Ok(_) => {}
+                Err(e) => tracing::debug!("No issue {}#{}: {}", repo, number, e),
+            }
+        }
+
+        Ok(issues)
+    }
+
+    pub async fn get_pull_request_approvals(&self, repo: &str, number: u64) -> Result<Vec<String>> {
+        let client = self.client_for(repo).await;
+        let (owner, name) = self.owner_and_name(repo);
+        let reviews = self
+            .retry(&format!("reviews of #{} in {}", number, repo), || async {
+                client.pulls(owner, name).list_reviews(number).per_page(100).send().await
+            })
+            .await?;
+
+        let mut approvers: Vec<String> = reviews.items
+            .into_iter()
+            .filter(|r| matches!(r.state, Some(models::pulls::ReviewState::Approved)))
+            .filter_map(|r| r.user.map(|u| u.login))
+            .collect();
+        approvers.sort();
+        approvers.dedup();
+
+        Ok(approvers)
+    }
+
+    pub async fn get_pull_request_author(&self, repo: &str, number: u64) -> Result<Option<String>> {
+        let client = self.client_for(repo).await;
+        let (owner, name) = self.owner_and_name(repo);
+        let pr = self
+            .retry(&format!("pull request #{} of {}", number, repo), || async {
+                client.pulls(owner, name).get(number).await
+            })
+            .await?;
+        Ok(pr.user.map(|u| u.login))
+    }
+
+    /// `login`'s permission on `repo`: `admin`, `write`, `read` or `none`.
+    pub async fn get_collaborator_permission(&self, repo: &str, login: &str) -> Result<String> {
+        let (owner, name) = self.owner_and_name(repo);
+        let route = format!("/repos/{}/{}/collaborators/{}/permission", owner, name, login);
+        let permission: CollaboratorPermission = self.get_json(&route).await?;
+        Ok(permission.permission)
+    }
+
+    /// Comment on issue `number` of `repo`; returns the comment's web URL.
+    pub async fn create_issue_comment(&self, repo: &str, number: u64, body: &str) -> Result<String> {
+        let client = self.client_for(repo).await;
+        let (owner, name) = self.owner_and_name(repo);
+        let comment = self
+            .retry(&format!("comment on #{} in {}", number, repo), || async {
+                client.issues(owner, name).create_comment(number, body).await
+            })
+            .await?;
+        Ok(comment.html_url.to_string())
+    }
+
+    /// Map each member login in the org to the teams they belong to, as mentionable `org/slug`.
+    pub async fn get_team_memberships(&self) -> Result<std::collections::HashMap<String, Vec<String>>> {
+        let route = format!("/orgs/{}/teams", self.org);
+        let teams: Vec<Team> = self.get_paginated(&route, usize::MAX, &format!("teams of {}", self.org)).await?;
+
+        let mut memberships: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+        for team in teams {
+            let route = format!("/orgs/{}/teams/{}/members", self.org, team.slug);
+            let members: Vec<UserRef> = self.get_paginated(&route, usize::MAX, &format!("members of {}/{}", self.org, team.slug)).await?;
+            for member in members {
+                memberships.entry(member.login).or_default().push(format!("{}/{}", self.org, team.slug));
+            }
+        }
+
+        Ok(memberships)
+    }
+}
+
+/// Body of the create and update release requests. `make_latest` is sent by hand since the
+/// release builders don't take it.
+#[derive(serde::Serialize)]
+struct ReleasePayload<'a> {
+    tag_name: &'a str,
+    name: &'a str,
+    body: &'a str,
+    draft: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    make_latest: Option<MakeLatest>,
+}
+
+/// GitHub answers 409 "Git Repository is empty." for commit listings on repos with no commits.
+fn is_empty_repository_error(error: &octocrab::Error) -> bool {
+    matches!(error, octocrab::Error::GitHub { source, .. } if source.message.contains("is empty"))
+}
+
+/// Owner and name of the repository a `/repos/{owner}/{name}/...` route is about.
+fn route_repository(route: &str) -> Option<(&str, &str)> {
+    let mut segments = route.strip_prefix("/repos/")?.split(['/', '?']);
+    Some((segments.next()?, segments.next()?))
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Last response seen for a route, replayed when GitHub answers `304 Not Modified`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub etag: String,
+    pub body: String,
+}
+
+/// ETags and bodies of earlier GET responses, keyed by route, so repeated runs can send
+/// `If-None-Match` and spend no rate limit on unchanged data. Persisted on drop when a path
+/// is set.
+#[derive(Debug, Default)]
+pub struct EtagStore {
+    path: Option<PathBuf>,
+    entries: Mutex<HashMap<String, CachedResponse>>,
+    dirty: AtomicBool,
+}
+
+impl EtagStore {
+    /// Load the store at `path`, starting empty if it doesn't exist or can't be parsed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let entries = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read ETag store {}", path.display()))?;
+            serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable ETag store {}: {}", path.display(), e);
+                HashMap::new()
+            })
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            entries: Mutex::new(entries),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    pub fn get(&self, route: &str) -> Option<CachedResponse> {
+        self.entries.lock().unwrap().get(route).cloned()
+    }
+
+    pub fn insert(&self, route: &str, etag: String, body: String) {
+        self.entries.lock().unwrap().insert(route.to_string(), CachedResponse { etag, body });
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(&*self.entries.lock().unwrap())?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write ETag store {}", path.display()))?;
+        self.dirty.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Drop for EtagStore {
+    fn drop(&mut self) {
+        if self.dirty.load(Ordering::Relaxed) {
+            if let Err(e) = self.save() {
+                tracing::warn!("{:#}", e);
+            }
+        }
+    }
+}
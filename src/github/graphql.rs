@@ -0,0 +1,289 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use super::host::GitHubHost;
+use super::types::{GitObjectId, Nodes, Release, ReleaseAsset, ReleaseSource, RepositoryInfo};
+
+/// Repositories fetched per query by `GitHubClient::prefetch`. Each brings up to 100 releases
+/// with their assets, which keeps a query well under GraphQL's node limit.
+pub const REPOS_PER_QUERY: usize = 10;
+
+/// Releases (or tags) fetched per repository; repositories with more fall back to REST for
+/// anything past the newest page.
+const PAGE_SIZE: usize = 100;
+
+const RELEASE_FIELDS: &str = "tagName name description isDraft isPrerelease createdAt publishedAt url \
+    tagCommit { oid } author { login } releaseAssets(first: 50) { nodes { name downloadCount } }";
+
+const TAG_FIELDS: &str = "name target { __typename oid ... on Commit { author { date } } \
+    ... on Tag { message tagger { date } target { oid } } }";
+
+/// What one prefetch query returned for a repository, answering release and ref lookups
+/// without a REST request each.
+#[derive(Debug, Clone)]
+pub struct PrefetchedRepository {
+    pub info: RepositoryInfo,
+    source: ReleaseSource,
+    /// Newest first: every release (or tag) when `complete`, otherwise the newest page
+    releases: Vec<Release>,
+    complete: bool,
+    /// GitHub's latest release; only known for `ReleaseSource::Releases`
+    latest: Option<Release>,
+    /// Commit SHAs of the default branch and of every fetched tag
+    ref_shas: HashMap<String, String>,
+}
+
+impl PrefetchedRepository {
+    /// Up to `limit` releases, newest first, or `None` if more than were fetched are needed.
+    /// Tags come back ordered by commit date rather than by tag date, so a partial page of
+    /// them is never used.
+    pub fn releases(&self, limit: usize) -> Option<Vec<Release>> {
+        let usable = self.complete || (self.source == ReleaseSource::Releases && limit <= self.releases.len());
+        usable.then(|| self.releases.iter().take(limit).cloned().collect())
+    }
+
+    /// The published release (or tag) named exactly `tag`. `Some(None)` means the repository
+    /// is known not to have it; `None` means it may be past the fetched page.
+    pub fn release(&self, tag: &str) -> Option<Option<Release>> {
+        match self.releases.iter().find(|r| r.tag_name == tag && !r.draft) {
+            Some(release) => Some(Some(release.clone())),
+            None if self.complete => Some(None),
+            None => None,
+        }
+    }
+
+    /// Latest release, as `releases/latest` (or the newest tag) would return it.
+    pub fn latest(&self) -> Option<Option<Release>> {
+        match self.source {
+            ReleaseSource::Releases => Some(self.latest.clone()),
+            ReleaseSource::Tags => self.complete.then(|| self.releases.first().cloned()),
+        }
+    }
+
+    /// Commit SHA of the default branch or a fetched tag.
+    pub fn ref_sha(&self, reference: &str) -> Option<String> {
+        self.ref_shas.get(reference).cloned()
+    }
+}
+
+/// Query aliasing each of `repos` (`r0`, `r1`, ...), as `(owner, name, source)`, with the
+/// owners and names passed as variables. Releases are fetched for repositories that use them
+/// and tags for those that don't.
+pub fn repositories_query(repos: &[(&str, &str, ReleaseSource)]) -> serde_json::Value {
+    let mut declarations = Vec::new();
+    let mut fields = String::new();
+    let mut variables = serde_json::Map::new();
+    for (i, (owner, name, source)) in repos.iter().enumerate() {
+        declarations.push(format!("$o{}: String!, $n{}: String!", i, i));
+        variables.insert(format!("o{}", i), serde_json::json!(owner));
+        variables.insert(format!("n{}", i), serde_json::json!(name));
+        let versions = match source {
+            ReleaseSource::Releases => format!(
+                "latestRelease {{ {fields} }} releases(first: {page}, orderBy: {{ field: CREATED_AT, direction: DESC }}) \
+                 {{ pageInfo {{ hasNextPage }} nodes {{ {fields} }} }}",
+                fields = RELEASE_FIELDS, page = PAGE_SIZE,
+            ),
+            ReleaseSource::Tags => format!(
+                "tags: refs(refPrefix: \"refs/tags/\", first: {page}, orderBy: {{ field: TAG_COMMIT_DATE, direction: DESC }}) \
+                 {{ pageInfo {{ hasNextPage }} nodes {{ {fields} }} }}",
+                fields = TAG_FIELDS, page = PAGE_SIZE,
+            ),
+        };
+        fields.push_str(&format!(
+            " r{}: repository(owner: $o{}, name: $n{}) {{ name nameWithOwner url isArchived \
+             defaultBranchRef {{ name target {{ oid }} }} {} }}",
+            i, i, i, versions
+        ));
+    }
+    let query = format!("query({}) {{{} }}", declarations.join(", "), fields);
+    serde_json::json!({ "query": query, "variables": variables })
+}
+
+/// Parse the repository under `alias` in a `repositories_query` response. `None` if the
+/// repository wasn't found (or the token can't see it), leaving it to REST.
+pub fn parse_repository(data: &serde_json::Value, alias: &str, source: ReleaseSource, host: &GitHubHost) -> Result<Option<PrefetchedRepository>> {
+    let Some(value) = data.get(alias).filter(|value| !value.is_null()) else {
+        return Ok(None);
+    };
+    let node: RepositoryNode = serde_json::from_value(value.clone())?;
+    let (owner, name) = node.name_with_owner.split_once('/').unwrap_or_default();
+
+    let mut ref_shas = HashMap::new();
+    let default_branch = node.default_branch_ref.map(|branch| {
+        if let Some(target) = branch.target {
+            ref_shas.insert(branch.name.clone(), target.oid);
+        }
+        branch.name
+    });
+
+    let (mut releases, complete, latest) = match source {
+        ReleaseSource::Releases => {
+            let page = node.releases.unwrap_or_default();
+            let releases: Vec<_> = page.nodes.into_iter().map(ReleaseNode::into_release).collect();
+            let latest = node.latest_release.map(ReleaseNode::into_release);
+            (releases, !page.page_info.has_next_page, latest)
+        }
+        ReleaseSource::Tags => {
+            let page = node.tags.unwrap_or_default();
+            let releases = page.nodes.into_iter().map(|tag| tag.into_release(host, owner, name)).collect();
+            (releases, !page.page_info.has_next_page, None)
+        }
+    };
+    if source == ReleaseSource::Tags {
+        releases.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.tag_name.cmp(&a.tag_name)));
+    }
+    for release in &releases {
+        if let Some(target) = &release.target_commitish {
+            ref_shas.entry(release.tag_name.clone()).or_insert_with(|| target.clone());
+        }
+    }
+
+    Ok(Some(PrefetchedRepository {
+        info: RepositoryInfo {
+            name: node.name,
+            full_name: node.name_with_owner.clone(),
+            html_url: node.url,
+            archived: node.is_archived,
+            default_branch,
+        },
+        source,
+        releases,
+        complete,
+        latest,
+        ref_shas,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryNode {
+    name: String,
+    name_with_owner: String,
+    url: String,
+    is_archived: bool,
+    default_branch_ref: Option<BranchNode>,
+    latest_release: Option<ReleaseNode>,
+    releases: Option<Page<ReleaseNode>>,
+    tags: Option<Page<TagNode>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchNode {
+    name: String,
+    target: Option<GitObjectId>,
+}
+
+/// One page of a GraphQL connection.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Page<T> {
+    page_info: PageInfo,
+    nodes: Vec<T>,
+}
+
+impl<T> Default for Page<T> {
+    fn default() -> Self {
+        Self { page_info: PageInfo::default(), nodes: Vec::new() }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+}
+
+/// `RELEASE_FIELDS` of a release.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseNode {
+    tag_name: String,
+    name: Option<String>,
+    description: Option<String>,
+    is_draft: bool,
+    is_prerelease: bool,
+    created_at: DateTime<Utc>,
+    published_at: Option<DateTime<Utc>>,
+    url: String,
+    tag_commit: Option<GitObjectId>,
+    author: Option<UserNode>,
+    release_assets: Nodes<AssetNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserNode {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetNode {
+    name: String,
+    download_count: u64,
+}
+
+/// `TAG_FIELDS` of a tag ref: the target is the tagged commit for lightweight tags and a
+/// `Tag` object for annotated ones.
+#[derive(Debug, Deserialize)]
+struct TagNode {
+    name: String,
+    target: TagTarget,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagTarget {
+    #[serde(rename = "__typename")]
+    kind: String,
+    oid: String,
+    author: Option<Signature>,
+    message: Option<String>,
+    tagger: Option<Signature>,
+    target: Option<GitObjectId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Signature {
+    date: Option<DateTime<Utc>>,
+}
+
+impl ReleaseNode {
+    /// GraphQL has no `target_commitish`; the tagged commit's SHA stands in for it. Every asset
+    /// GraphQL lists has been uploaded.
+    fn into_release(self) -> Release {
+        Release {
+            tag_name: self.tag_name,
+            name: self.name,
+            body: self.description,
+            draft: self.is_draft,
+            prerelease: self.is_prerelease,
+            created_at: Some(self.created_at),
+            published_at: self.published_at,
+            target_commitish: self.tag_commit.map(|commit| commit.oid),
+            html_url: Some(self.url),
+            author: self.author.map(|user| user.login),
+            assets: self.release_assets.nodes.into_iter()
+                .map(|asset| ReleaseAsset { name: asset.name, state: "uploaded".to_string(), download_count: asset.download_count })
+                .collect(),
+        }
+    }
+}
+
+impl TagNode {
+    /// The release `GitHubClient` builds for a tag, from the fields fetched here rather than a
+    /// request per tag.
+    fn into_release(self, host: &GitHubHost, owner: &str, name: &str) -> Release {
+        let target = self.target;
+        let (date, body, sha) = if target.kind == "Tag" {
+            let message = target.message.unwrap_or_default().trim().to_string();
+            (
+                target.tagger.and_then(|t| t.date),
+                Some(message).filter(|m| !m.is_empty()),
+                target.target.map(|t| t.oid).unwrap_or(target.oid),
+            )
+        } else {
+            (target.author.and_then(|a| a.date), None, target.oid)
+        };
+        Release::from_tag(&self.name, date, body, sha).with_html_url(host.tag_url(owner, name, &self.name))
+    }
+}
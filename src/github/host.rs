@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+
+/// REST API root of github.com.
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Where a GitHub instance serves its REST API, GraphQL API and web pages: github.com, or a
+/// GitHub Enterprise Server at `https://<host>/api/v3`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitHubHost {
+    api_url: String,
+    web_url: String,
+    graphql_url: String,
+}
+
+impl Default for GitHubHost {
+    fn default() -> Self {
+        Self {
+            api_url: GITHUB_API_URL.to_string(),
+            web_url: "https://github.com".to_string(),
+            graphql_url: format!("{}/graphql", GITHUB_API_URL),
+        }
+    }
+}
+
+impl GitHubHost {
+    /// Host for the REST API root `url`. github.com may be given as `https://api.github.com`
+    /// or `https://github.com`; an Enterprise Server as its API root (`https://ghe.example.com/api/v3`)
+    /// or just its web address, to which `/api/v3` is added.
+    pub fn parse(url: &str) -> Result<Self> {
+        let parsed = reqwest::Url::parse(url).with_context(|| format!("{} is not a valid GitHub API URL", url))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            anyhow::bail!("{} is not an http(s) URL", url);
+        }
+        if matches!(parsed.host_str(), Some("github.com" | "api.github.com")) {
+            return Ok(Self::default());
+        }
+
+        let url = url.trim_end_matches('/');
+        let web_url = url.strip_suffix("/api/v3").unwrap_or(url).to_string();
+        Ok(Self {
+            api_url: format!("{}/api/v3", web_url),
+            graphql_url: format!("{}/api/graphql", web_url),
+            web_url,
+        })
+    }
+
+    /// Root of the REST API, e.g. `https://api.github.com`.
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// Root of the web pages that links point at, e.g. `https://github.com`.
+    pub fn web_url(&self) -> &str {
+        &self.web_url
+    }
+
+    /// The GraphQL endpoint. On Enterprise Server it lives beside the REST root (`/api/graphql`)
+    /// rather than under it.
+    pub fn graphql_url(&self) -> &str {
+        &self.graphql_url
+    }
+
+    /// Host name of the web address, e.g. `github.com`, as tools like `gh` key credentials by.
+    pub fn hostname(&self) -> &str {
+        let host = self.web_url.split_once("://").map_or(self.web_url.as_str(), |(_, rest)| rest);
+        host.split('/').next().unwrap_or(host)
+    }
+
+    /// The web page of `tag` in `owner/name`, which tag-backed releases link to.
+    pub fn tag_url(&self, owner: &str, name: &str, tag: &str) -> String {
+        format!("{}/{}/{}/tree/{}", self.web_url, owner, name, tag)
+    }
+
+    pub fn is_github_com(&self) -> bool {
+        self.api_url == GITHUB_API_URL
+    }
+}
@@ -0,0 +1,100 @@
+use crate::provider::{ProviderKind, ProviderRouting};
+
+/// Builds web URLs for repositories on a GitHub host. Bare repository names belong to `org`;
+/// `owner/name` references are used as-is.
+#[derive(Debug, Clone)]
+pub struct WebLinks {
+    web_url: String,
+    org: String,
+    gitlab: Option<GitLabLinks>,
+}
+
+/// Where the repositories routed to GitLab live.
+#[derive(Debug, Clone)]
+struct GitLabLinks {
+    web_url: String,
+    group: String,
+    routing: ProviderRouting,
+}
+
+impl WebLinks {
+    pub fn new(web_url: &str, org: &str) -> Self {
+        Self { web_url: web_url.trim_end_matches('/').to_string(), org: org.to_string(), gitlab: None }
+    }
+
+    /// Link the repositories `routing` puts on GitLab to the instance at `web_url`, with bare
+    /// names belonging to `group`.
+    pub fn with_gitlab(mut self, web_url: &str, group: &str, routing: ProviderRouting) -> Self {
+        self.gitlab = Some(GitLabLinks {
+            web_url: web_url.trim_end_matches('/').to_string(),
+            group: group.to_string(),
+            routing,
+        });
+        self
+    }
+
+    pub fn repo(&self, repo: &str) -> RepoLinks {
+        if let Some(gitlab) = self.gitlab.as_ref().filter(|g| g.routing.provider(repo) == ProviderKind::Gitlab) {
+            // GitLab paths may nest subgroups, so only bare names get the group prepended
+            let path = match repo.split_once('/') {
+                Some(_) => repo.to_string(),
+                None => format!("{}/{}", gitlab.group, repo),
+            };
+            return RepoLinks { url: format!("{}/{}", gitlab.web_url, path), gitlab: true };
+        }
+        let (owner, name) = repo.split_once('/').unwrap_or((&self.org, repo));
+        RepoLinks { url: format!("{}/{}/{}", self.web_url, owner, name), gitlab: false }
+    }
+}
+
+/// Web URLs for one repository's pages.
+#[derive(Debug, Clone)]
+pub struct RepoLinks {
+    url: String,
+    /// GitLab nests project pages under `/-/`
+    gitlab: bool,
+}
+
+impl RepoLinks {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn page(&self) -> String {
+        if self.gitlab {
+            format!("{}/-", self.url)
+        } else {
+            self.url.clone()
+        }
+    }
+
+    pub fn commit(&self, sha: &str) -> String {
+        format!("{}/commit/{}", self.page(), sha)
+    }
+
+    /// A pull request, or on GitLab the merge request.
+    pub fn pull(&self, number: u64) -> String {
+        if self.gitlab {
+            format!("{}/-/merge_requests/{}", self.url, number)
+        } else {
+            format!("{}/pull/{}", self.url, number)
+        }
+    }
+
+    /// GitHub redirects to the pull request when `number` turns out to be one.
+    pub fn issue(&self, number: u64) -> String {
+        format!("{}/issues/{}", self.page(), number)
+    }
+
+    pub fn release(&self, tag: &str) -> String {
+        if self.gitlab {
+            format!("{}/-/releases/{}", self.url, tag)
+        } else {
+            format!("{}/releases/tag/{}", self.url, tag)
+        }
+    }
+
+    pub fn compare(&self, base: &str, head: &str) -> String {
+        format!("{}/compare/{}...{}", self.page(), base, head)
+    }
+}
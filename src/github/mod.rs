@@ -1,2 +1,11 @@
+pub mod auth;
+pub mod circuit;
 pub mod client;
+pub mod etag;
+pub mod graphql;
+pub mod host;
+pub mod links;
+pub mod provider;
+pub mod retry;
+pub mod throttle;
 pub mod types;
\ No newline at end of file
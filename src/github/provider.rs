@@ -0,0 +1,97 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crate::config::types::RepoSettings;
+use crate::provider::ReleaseProvider;
+use super::client::GitHubClient;
+use super::types::{CommitComparison, CommitInfo, DateSource, Issue, PreviousStrategy, PullRequest, Release, RepositoryInfo};
+
+impl ReleaseProvider for GitHubClient {
+    fn org(&self) -> &str {
+        GitHubClient::org(self)
+    }
+
+    async fn prefetch(&self, repos: &[String]) -> Result<()> {
+        GitHubClient::prefetch(self, repos).await
+    }
+
+    async fn list_org_repos(&self) -> Result<Vec<String>> {
+        GitHubClient::list_org_repos(self).await
+    }
+
+    async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        GitHubClient::get_repository(self, repo).await
+    }
+
+    async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        GitHubClient::get_release(self, repo, tag).await
+    }
+
+    async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        GitHubClient::get_latest_release(self, repo).await
+    }
+
+    async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        GitHubClient::list_all_releases(self, repo).await
+    }
+
+    async fn get_previous_release(
+        &self,
+        repo: &str,
+        current_release: &Release,
+        settings: Option<&RepoSettings>,
+        date_source: DateSource,
+        strategy: PreviousStrategy,
+    ) -> Result<Option<Release>> {
+        GitHubClient::get_previous_release(self, repo, current_release, settings, date_source, strategy).await
+    }
+
+    async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        GitHubClient::resolve_ref_sha(self, repo, reference).await
+    }
+
+    async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        GitHubClient::get_changes_between(self, repo, from, to).await
+    }
+
+    async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        GitHubClient::get_all_commits_until(self, repo, until).await
+    }
+
+    async fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> Result<Option<String>> {
+        GitHubClient::get_file_contents(self, repo, path, reference).await
+    }
+
+    async fn list_directory(&self, repo: &str, path: &str, reference: &str) -> Result<Vec<String>> {
+        GitHubClient::list_directory(self, repo, path, reference).await
+    }
+
+    async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        GitHubClient::has_commits_before(self, repo, author, until).await
+    }
+
+    async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        GitHubClient::is_empty_repository(self, repo).await
+    }
+
+    async fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> Result<Vec<PullRequest>> {
+        GitHubClient::get_pull_requests_for_commits(self, repo, shas).await
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        GitHubClient::list_merged_pull_requests(self, repo, base, since, until).await
+    }
+
+    async fn list_open_issues(&self, repo: &str, label: &str) -> Result<Vec<Issue>> {
+        GitHubClient::list_open_issues(self, repo, label).await
+    }
+
+    async fn get_issues(&self, repo: &str, numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        GitHubClient::get_issues(self, repo, numbers).await
+    }
+}
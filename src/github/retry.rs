@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+/// Attempts per request, including the first one.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// GitHub's documented wait for a secondary rate limit when no `Retry-After` is available.
+const SECONDARY_LIMIT_WAIT: Duration = Duration::from_secs(60);
+const SERVER_ERROR_WAIT: Duration = Duration::from_secs(1);
+/// Longest we'll sleep for a primary rate limit reset before giving up.
+const MAX_RESET_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Why a failed request is worth retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryReason {
+    /// Primary rate limit exhausted; wait for `X-RateLimit-Reset`
+    RateLimit,
+    /// Secondary (abuse-detection) rate limit
+    SecondaryRateLimit,
+    /// 5xx from GitHub
+    ServerError,
+}
+
+impl std::fmt::Display for RetryReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetryReason::RateLimit => write!(f, "rate limit exceeded"),
+            RetryReason::SecondaryRateLimit => write!(f, "secondary rate limit"),
+            RetryReason::ServerError => write!(f, "server error"),
+        }
+    }
+}
+
+/// Rate-limit headers of a response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitHeaders {
+    /// `Retry-After`, in seconds
+    pub retry_after: Option<u64>,
+    /// `X-RateLimit-Remaining`
+    pub remaining: Option<u64>,
+    /// `X-RateLimit-Reset`, in epoch seconds
+    pub reset: Option<u64>,
+}
+
+impl RateLimitHeaders {
+    /// Read the headers through `header`, which returns the named header as a number.
+    pub fn read(header: impl Fn(&str) -> Option<u64>) -> Self {
+        RateLimitHeaders {
+            retry_after: header("retry-after"),
+            remaining: header("x-ratelimit-remaining"),
+            reset: header("x-ratelimit-reset"),
+        }
+    }
+}
+
+/// A failed request, with its response's rate-limit headers when the request was sent raw.
+/// Errors from octocrab's typed calls convert into one without headers.
+#[derive(Debug)]
+pub struct Failure {
+    pub error: octocrab::Error,
+    pub headers: Option<RateLimitHeaders>,
+}
+
+impl From<octocrab::Error> for Failure {
+    fn from(error: octocrab::Error) -> Self {
+        Failure { error, headers: None }
+    }
+}
+
+/// Classify a failed request, or `None` if retrying won't help.
+pub fn retry_reason(failure: &Failure) -> Option<RetryReason> {
+    let octocrab::Error::GitHub { source, .. } = &failure.error else {
+        return None;
+    };
+    classify(source.status_code.as_u16(), &source.message, &failure.headers.unwrap_or_default())
+}
+
+/// A response with no requests left is a primary rate limit; one with `Retry-After` is a
+/// secondary one. Without those headers the message tells them apart.
+fn classify(status: u16, message: &str, headers: &RateLimitHeaders) -> Option<RetryReason> {
+    let message = message.to_lowercase();
+    let limited = matches!(status, 403 | 429);
+    let secondary = message.contains("secondary rate limit") || message.contains("abuse");
+
+    if limited && headers.remaining == Some(0) {
+        Some(RetryReason::RateLimit)
+    } else if (limited && headers.retry_after.is_some()) || secondary {
+        Some(RetryReason::SecondaryRateLimit)
+    } else if message.contains("rate limit") {
+        Some(RetryReason::RateLimit)
+    } else if (500..600).contains(&status) {
+        Some(RetryReason::ServerError)
+    } else {
+        None
+    }
+}
+
+/// Exponential backoff from the reason's base delay, with up to 25% jitter so concurrent
+/// requests don't all retry at the same moment.
+pub fn backoff(reason: RetryReason, attempt: u32) -> Duration {
+    let base = match reason {
+        RetryReason::SecondaryRateLimit | RetryReason::RateLimit => SECONDARY_LIMIT_WAIT,
+        RetryReason::ServerError => SERVER_ERROR_WAIT,
+    };
+    with_jitter(base * 2u32.pow(attempt.saturating_sub(1)))
+}
+
+/// How long to wait before retrying: `Retry-After` when the response has one, the primary
+/// rate limit's reset when it's exhausted, otherwise `backoff`. `None` when either is too far
+/// away to be worth sleeping for.
+pub fn delay(reason: RetryReason, attempt: u32, headers: &RateLimitHeaders) -> Option<Duration> {
+    if let Some(seconds) = headers.retry_after {
+        let wait = Duration::from_secs(seconds);
+        return (wait <= MAX_RESET_WAIT).then_some(wait);
+    }
+    match (reason, headers.remaining, headers.reset) {
+        (RetryReason::RateLimit, Some(0), Some(reset)) => until_reset(reset),
+        // Already reset
+        (RetryReason::RateLimit, Some(_), Some(_)) => Some(Duration::from_secs(1)),
+        _ => Some(backoff(reason, attempt)),
+    }
+}
+
+/// Wait until a primary rate limit resets (`X-RateLimit-Reset`, in epoch seconds), or `None`
+/// if that's too far away to be worth sleeping for.
+pub fn until_reset(reset_epoch: u64) -> Option<Duration> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let wait = Duration::from_secs(reset_epoch.saturating_sub(now) + 1);
+    (wait <= MAX_RESET_WAIT).then(|| with_jitter(wait))
+}
+
+fn with_jitter(delay: Duration) -> Duration {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    let max_jitter_ms = (delay.as_millis() / 4).max(1) as u64;
+    delay + Duration::from_millis(nanos as u64 % max_jitter_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, delay, RateLimitHeaders, RetryReason};
+    use std::time::Duration;
+
+    fn now() -> u64 {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn classifies_by_headers_then_message_then_status() {
+        let none = RateLimitHeaders::default();
+        let exhausted = RateLimitHeaders { remaining: Some(0), reset: Some(now() + 30), ..none };
+        let retry_after = RateLimitHeaders { retry_after: Some(30), remaining: Some(4000), ..none };
+
+        assert_eq!(classify(403, "API rate limit exceeded for installation", &exhausted), Some(RetryReason::RateLimit));
+        assert_eq!(classify(403, "Forbidden", &retry_after), Some(RetryReason::SecondaryRateLimit));
+        assert_eq!(classify(429, "Too many requests", &retry_after), Some(RetryReason::SecondaryRateLimit));
+        assert_eq!(classify(403, "You have exceeded a secondary rate limit", &none), Some(RetryReason::SecondaryRateLimit));
+        assert_eq!(classify(403, "API rate limit exceeded", &none), Some(RetryReason::RateLimit));
+        assert_eq!(classify(502, "Bad Gateway", &none), Some(RetryReason::ServerError));
+        // Headers only mark rate limits on 403 and 429
+        assert_eq!(classify(404, "Not Found", &exhausted), None);
+        assert_eq!(classify(403, "Resource not accessible by integration", &none), None);
+    }
+
+    #[test]
+    fn waits_for_retry_after_before_anything_else() {
+        let headers = RateLimitHeaders { retry_after: Some(42), remaining: Some(0), reset: Some(now() + 600) };
+        assert_eq!(delay(RetryReason::SecondaryRateLimit, 3, &headers), Some(Duration::from_secs(42)));
+        assert_eq!(delay(RetryReason::RateLimit, 1, &headers), Some(Duration::from_secs(42)));
+
+        let too_long = RateLimitHeaders { retry_after: Some(3600), ..RateLimitHeaders::default() };
+        assert_eq!(delay(RetryReason::SecondaryRateLimit, 1, &too_long), None);
+    }
+
+    #[test]
+    fn waits_for_the_reset_of_an_exhausted_limit() {
+        let headers = RateLimitHeaders { remaining: Some(0), reset: Some(now() + 30), ..RateLimitHeaders::default() };
+        let wait = delay(RetryReason::RateLimit, 1, &headers).unwrap();
+        assert!(wait >= Duration::from_secs(30) && wait <= Duration::from_secs(40), "{:?}", wait);
+
+        let far = RateLimitHeaders { reset: Some(now() + 3600), ..headers };
+        assert_eq!(delay(RetryReason::RateLimit, 1, &far), None);
+
+        let reset = RateLimitHeaders { remaining: Some(5000), ..headers };
+        assert_eq!(delay(RetryReason::RateLimit, 1, &reset), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn backs_off_exponentially_without_headers() {
+        let none = RateLimitHeaders::default();
+        let first = delay(RetryReason::ServerError, 1, &none).unwrap();
+        let third = delay(RetryReason::ServerError, 3, &none).unwrap();
+        assert!(first >= Duration::from_secs(1) && first <= Duration::from_millis(1250), "{:?}", first);
+        assert!(third >= Duration::from_secs(4) && third <= Duration::from_secs(5), "{:?}", third);
+    }
+}
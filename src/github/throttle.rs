@@ -0,0 +1,36 @@
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Spaces requests at least `interval` apart, across every task sharing the client, so long or
+/// continuous runs don't arrive at the API in bursts.
+#[derive(Debug)]
+pub struct Throttle {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl Throttle {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, next: Mutex::new(Instant::now()) }
+    }
+
+    /// Throttle allowing `per_second` requests each second.
+    pub fn per_second(per_second: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / per_second))
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Wait for this request's slot. Waiters queue on the lock, so slots are handed out in order.
+    pub async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep_until(*next).await;
+        }
+        *next = (*next).max(now) + self.interval;
+    }
+}
@@ -1,17 +1,88 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A release as every backend reports it: a GitHub or GitLab release, or a git tag standing in
+/// for one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Release {
-    pub id: u64,
     pub tag_name: String,
+    #[serde(default)]
     pub name: Option<String>,
+    /// Release notes; an annotated tag's message
+    #[serde(default)]
     pub body: Option<String>,
+    #[serde(default)]
     pub draft: bool,
+    #[serde(default)]
     pub prerelease: bool,
-    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
     pub published_at: Option<DateTime<Utc>>,
-    pub target_commitish: String,
+    /// Commit the tag points at, or the branch the release was cut from
+    #[serde(default)]
+    pub target_commitish: Option<String>,
+    #[serde(default)]
+    pub html_url: Option<String>,
+    /// Login of whoever published the release
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// A file attached to a release.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    /// `uploaded` once the upload finished
+    pub state: String,
+    pub download_count: u64,
+}
+
+impl Release {
+    /// The release standing in for git tag `tag`: dated `date`, with `body` (an annotated tag's
+    /// message) as its notes and `target` as the tagged commit. It has no author or assets.
+    pub fn from_tag(tag: &str, date: Option<DateTime<Utc>>, body: Option<String>, target: String) -> Self {
+        Release {
+            tag_name: tag.to_string(),
+            name: Some(tag.to_string()),
+            body,
+            created_at: date,
+            published_at: date,
+            target_commitish: Some(target),
+            ..Release::default()
+        }
+    }
+
+    pub fn with_html_url(mut self, html_url: String) -> Self {
+        self.html_url = Some(html_url);
+        self
+    }
+}
+
+impl From<octocrab::models::repos::Release> for Release {
+    fn from(release: octocrab::models::repos::Release) -> Self {
+        Release {
+            tag_name: release.tag_name,
+            name: release.name,
+            body: release.body,
+            draft: release.draft,
+            prerelease: release.prerelease,
+            created_at: release.created_at,
+            published_at: release.published_at,
+            target_commitish: Some(release.target_commitish).filter(|target| !target.is_empty()),
+            html_url: Some(release.html_url.to_string()),
+            author: release.author.map(|author| author.login),
+            assets: release.assets.into_iter()
+                .map(|asset| ReleaseAsset {
+                    name: asset.name,
+                    state: asset.state,
+                    download_count: asset.download_count.max(0) as u64,
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +91,259 @@ pub struct CommitInfo {
     pub message: String,
     pub author: CommitAuthor,
     pub date: DateTime<Utc>,
+    pub verified: bool,
+}
+
+/// Repository metadata as returned by `GET /repos/{owner}/{repo}` (after following renames).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryInfo {
+    pub name: String,
+    pub full_name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+}
+
+impl RepositoryInfo {
+    pub fn owner(&self) -> &str {
+        self.full_name.split('/').next().unwrap_or_default()
+    }
+}
+
+/// Minimal commit payload used when only the resolved SHA of a ref is needed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitRef {
+    pub sha: String,
+}
+
+/// One page of `GET /repos/{owner}/{repo}/compare/{base}...{head}`. Commits are oldest first.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComparePage {
+    pub status: String,
+    pub ahead_by: u64,
+    pub behind_by: u64,
+    pub total_commits: u64,
+    pub commits: Vec<CommitPayload>,
+    /// Up to 300 changed files, repeated on every page
+    #[serde(default)]
+    pub files: Vec<ChangedFile>,
+}
+
+/// A file changed between the two sides of a comparison.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedFile {
+    pub filename: String,
+    #[serde(default)]
+    pub additions: u64,
+    #[serde(default)]
+    pub deletions: u64,
+    #[serde(default)]
+    pub status: FileStatus,
+}
+
+/// How a file changed between the two sides of a comparison.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileStatus {
+    Added,
+    Removed,
+    Renamed,
+    /// Changed in place, or a status this tool doesn't tell apart (copied, type changed)
+    #[default]
+    #[serde(other)]
+    Modified,
+}
+
+/// A commit as returned by the REST commit list and compare endpoints.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitPayload {
+    pub sha: String,
+    pub commit: CommitDetail,
+    pub author: Option<UserRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitDetail {
+    pub message: String,
+    pub author: Option<GitSignature>,
+    #[serde(default)]
+    pub verification: Option<Verification>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitSignature {
+    pub name: String,
+    pub email: String,
+    pub date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Verification {
+    pub verified: bool,
+}
+
+impl From<CommitPayload> for CommitInfo {
+    fn from(payload: CommitPayload) -> Self {
+        let signature = payload.commit.author;
+        CommitInfo {
+            sha: payload.sha,
+            message: payload.commit.message,
+            author: CommitAuthor {
+                name: payload.author.as_ref().map(|a| a.login.clone())
+                    .or_else(|| signature.as_ref().map(|s| s.name.clone()))
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                email: signature.as_ref().map(|s| s.email.clone()).unwrap_or_default(),
+                username: payload.author.map(|a| a.login),
+            },
+            date: signature.and_then(|s| s.date).unwrap_or_else(Utc::now),
+            verified: payload.commit.verification.map(|v| v.verified).unwrap_or(false),
+        }
+    }
+}
+
+/// An issue from `GET /repos/{owner}/{repo}/issues/{number}`. Pull requests share the
+/// numbering and are returned by the same route, marked by `pull_request`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    /// `open` or `closed`
+    pub state: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+}
+
+/// Where a repository's versions come from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseSource {
+    /// GitHub Release objects
+    #[default]
+    Releases,
+    /// Git tags alone, for repositories that never publish a GitHub Release. Annotated tag
+    /// messages stand in for release notes.
+    Tags,
+}
+
+impl std::str::FromStr for ReleaseSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "releases" | "release" => Ok(ReleaseSource::Releases),
+            "tags" | "tag" => Ok(ReleaseSource::Tags),
+            _ => Err(format!("Unknown release source: {} (expected releases or tags)", s)),
+        }
+    }
+}
+
+/// Whether a published release becomes the repository's "Latest" release on GitHub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MakeLatest {
+    /// Always, even if an older version is being published
+    True,
+    /// Never, e.g. for a hotfix of an older release line
+    False,
+    /// Only if it is the newest release by creation date and semantic version
+    Legacy,
+}
+
+impl std::str::FromStr for MakeLatest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "true" | "yes" => Ok(MakeLatest::True),
+            "false" | "no" => Ok(MakeLatest::False),
+            "legacy" => Ok(MakeLatest::Legacy),
+            _ => Err(format!("Unknown make_latest setting: {} (expected true, false or legacy)", s)),
+        }
+    }
+}
+
+/// How repositories and releases are read from GitHub.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiBackend {
+    /// REST requests per repository, cached by ETag across runs
+    #[default]
+    Rest,
+    /// Repositories, releases and tags prefetched in bulk GraphQL queries, with REST for the rest
+    Graphql,
+}
+
+impl std::str::FromStr for ApiBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rest" => Ok(ApiBackend::Rest),
+            "graphql" => Ok(ApiBackend::Graphql),
+            _ => Err(format!("Unknown API: {} (expected rest or graphql)", s)),
+        }
+    }
+}
+
+/// A ref from `GET /repos/{owner}/{repo}/git/refs/tags`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitRef {
+    #[serde(rename = "ref")]
+    pub reference: String,
+    pub object: GitObject,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitObject {
+    /// `commit` for lightweight tags, `tag` for annotated ones
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub sha: String,
+}
+
+/// An annotated tag object from `GET /repos/{owner}/{repo}/git/tags/{sha}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnnotatedTag {
+    pub message: String,
+    pub tagger: Option<GitSignature>,
+    pub object: GitObject,
+}
+
+/// Commits reachable from `head` but not from `base`, with GitHub's ahead/behind counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitComparison {
+    /// `ahead`, `behind`, `diverged`, or `identical`
+    pub status: String,
+    pub ahead_by: u64,
+    pub behind_by: u64,
+    /// Newest first, matching the commit list endpoint
+    pub commits: Vec<CommitInfo>,
+    /// Files changed between `base` and `head`; GitHub lists at most 300
+    pub files: Vec<ChangedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Team {
+    pub slug: String,
+    pub name: String,
+}
+
+/// A user's permission on a repository, from `collaborators/{login}/permission`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollaboratorPermission {
+    /// `admin`, `write`, `read` or `none`
+    pub permission: String,
+}
+
+/// Just the login of a user payload (team members, PR authors).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRef {
+    pub login: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,4 +360,259 @@ pub struct PullRequest {
     pub body: Option<String>,
     pub merged_at: Option<DateTime<Utc>>,
     pub merge_commit_sha: Option<String>,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub user: Option<UserRef>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub name: String,
+}
+
+/// A pull request from GraphQL's `associatedPullRequests`, selected with
+/// `ASSOCIATED_PULL_REQUEST_FIELDS`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssociatedPullRequest {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub merge_commit: Option<GitObjectId>,
+    pub author: Option<UserRef>,
+    pub labels: Option<Nodes<Label>>,
+}
+
+/// GraphQL fields of `AssociatedPullRequest`.
+pub const ASSOCIATED_PULL_REQUEST_FIELDS: &str =
+    "number title body mergedAt updatedAt mergeCommit { oid } author { login } labels(first: 20) { nodes { name } }";
+
+#[derive(Debug, Deserialize)]
+pub struct GitObjectId {
+    pub oid: String,
+}
+
+/// The `nodes` of a GraphQL connection.
+#[derive(Debug, Deserialize)]
+pub struct Nodes<T> {
+    pub nodes: Vec<T>,
+}
+
+impl From<AssociatedPullRequest> for PullRequest {
+    fn from(pr: AssociatedPullRequest) -> Self {
+        PullRequest {
+            number: pr.number,
+            title: pr.title,
+            body: pr.body.filter(|body| !body.is_empty()),
+            merged_at: pr.merged_at,
+            merge_commit_sha: pr.merge_commit.map(|commit| commit.oid),
+            updated_at: pr.updated_at,
+            user: pr.author,
+            labels: pr.labels.map(|labels| labels.nodes).unwrap_or_default(),
+        }
+    }
+}
+
+/// Which GitHub release timestamp is authoritative for ordering and display.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateSource {
+    /// `published_at`, falling back to `created_at` (drafts have no publish date)
+    #[default]
+    Published,
+    /// `created_at`, falling back to `published_at`
+    Created,
+}
+
+impl DateSource {
+    pub fn resolve(&self, created_at: Option<DateTime<Utc>>, published_at: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        match self {
+            DateSource::Published => published_at.or(created_at),
+            DateSource::Created => created_at.or(published_at),
+        }
+    }
+}
+
+impl std::str::FromStr for DateSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "published" | "published_at" => Ok(DateSource::Published),
+            "created" | "created_at" => Ok(DateSource::Created),
+            _ => Err(format!("Unknown date source: {}", s)),
+        }
+    }
+}
+
+/// How the release preceding a component's current release is chosen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviousStrategy {
+    /// Most recent release dated before the current one
+    #[default]
+    Date,
+    /// Highest version below the current one, so hotfixes to older lines are skipped
+    Semver,
+}
+
+impl std::str::FromStr for PreviousStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "date" => Ok(PreviousStrategy::Date),
+            "semver" => Ok(PreviousStrategy::Semver),
+            _ => Err(format!("Unknown previous-release strategy: {}", s)),
+        }
+    }
+}
+
+/// Parse the semantic version in a tag such as `v1.4.7`, `1.4.7`, `api-v1.4.7`, or
+/// `api@1.4.7`. Returns `None` for tags without one.
+pub fn tag_version(tag: &str) -> Option<semver::Version> {
+    semver::Version::parse(&tag[version_start(tag)?..]).ok()
+}
+
+/// Like `tag_version`, but also reads versions with fewer than three parts or leading zeros,
+/// as train versions are often written (`v1.3` as 1.3.0, `v2024.06` as 2024.6.0). Pre-release
+/// and build suffixes of such versions are ignored.
+pub fn loose_version(tag: &str) -> Option<semver::Version> {
+    tag_version(tag).or_else(|| {
+        let core = tag[version_start(tag)?..].split(['-', '+']).next()?;
+        let parts: Vec<u64> = core.split('.').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+        if parts.len() > 3 {
+            return None;
+        }
+        let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+        Some(semver::Version::new(part(0), part(1), part(2)))
+    })
+}
+
+/// Where the version starts in a tag: the first digit at the start or after a separator.
+fn version_start(tag: &str) -> Option<usize> {
+    tag.char_indices().find_map(|(i, c)| {
+        let boundary = i == 0 || matches!(tag.as_bytes()[i - 1], b'v' | b'V' | b'-' | b'_' | b'@' | b'/');
+        (c.is_ascii_digit() && boundary).then_some(i)
+    })
+}
+
+/// `tag` with its `v` prefix added or removed (`1.2.3` ↔ `v1.2.3`), for repositories that
+/// differ only in that convention. `None` for tags that don't start with a version.
+pub fn toggle_v_prefix(tag: &str) -> Option<String> {
+    match tag.strip_prefix(['v', 'V']) {
+        Some(bare) if bare.starts_with(|c: char| c.is_ascii_digit()) => Some(bare.to_string()),
+        Some(_) => None,
+        None if tag.starts_with(|c: char| c.is_ascii_digit()) => Some(format!("v{}", tag)),
+        None => None,
+    }
+}
+
+/// Kind of GitHub token, recognised from its prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// `github_pat_`: scoped to selected repositories and permissions
+    FineGrained,
+    /// `ghp_`: classic personal access token with OAuth scopes
+    Classic,
+    /// `gho_`, `ghu_`, `ghs_`: OAuth app, user-to-server, or app installation token
+    App,
+    Unknown,
+}
+
+impl TokenKind {
+    pub fn detect(token: &str) -> Self {
+        if token.starts_with("github_pat_") {
+            TokenKind::FineGrained
+        } else if token.starts_with("ghp_") {
+            TokenKind::Classic
+        } else if ["gho_", "ghu_", "ghs_"].iter().any(|p| token.starts_with(p)) {
+            TokenKind::App
+        } else {
+            TokenKind::Unknown
+        }
+    }
+}
+
+/// The caller's permissions on a repository, from `GET /repos/{owner}/{repo}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepoPermissions {
+    #[serde(default)]
+    pub permissions: Option<Permissions>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Permissions {
+    #[serde(default)]
+    pub push: bool,
+}
+
+/// What a token can actually read in one repository.
+#[derive(Debug, Clone)]
+pub struct RepoAccess {
+    pub repo: String,
+    /// The repository itself is visible (fine-grained: selected, with Metadata read)
+    pub metadata: bool,
+    /// Commits and releases are readable (fine-grained: Contents read)
+    pub contents: bool,
+}
+
+impl RepoAccess {
+    pub fn is_complete(&self) -> bool {
+        self.metadata && self.contents
+    }
+}
+
+impl std::fmt::Display for RepoAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.metadata {
+            write!(f, "{}: not visible to the token (add it to the token's repository access)", self.repo)
+        } else if !self.contents {
+            write!(f, "{}: missing \"Contents: read\" permission", self.repo)
+        } else {
+            write!(f, "{}: ok", self.repo)
+        }
+    }
+}
+
+/// Reaction counts GitHub rolls up on releases, issues and comments.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReactionRollup {
+    #[serde(default)]
+    pub total_count: u64,
+    #[serde(default, rename = "+1")]
+    pub plus_one: u64,
+    #[serde(default, rename = "-1")]
+    pub minus_one: u64,
+    #[serde(default)]
+    pub laugh: u64,
+    #[serde(default)]
+    pub hooray: u64,
+    #[serde(default)]
+    pub confused: u64,
+    #[serde(default)]
+    pub heart: u64,
+    #[serde(default)]
+    pub rocket: u64,
+    #[serde(default)]
+    pub eyes: u64,
+}
+
+/// The parts of a release that show how it was received. GitHub leaves `reactions` out of
+/// releases nobody has reacted to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseReactions {
+    pub tag_name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reactions: Option<ReactionRollup>,
+}
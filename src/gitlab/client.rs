@@ -0,0 +1,404 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use crate::github::client::DEFAULT_MAX_PAGES;
+use crate::github::types::{CommitComparison, CommitInfo, Issue, PullRequest, Release, ReleaseSource, RepositoryInfo};
+use crate::github::types::toggle_v_prefix;
+use crate::provider::{encode, ReleaseProvider};
+use super::types::{CommitId, Compare, Commit, GitLabIssue, GitLabRelease, MergeRequest, Project, Tag, TreeEntry};
+
+/// Web address of gitlab.com.
+pub const GITLAB_URL: &str = "https://gitlab.com";
+
+/// Reads releases, tags, commits and merge requests from a GitLab instance's REST API (v4).
+/// Bare repository names are projects in `group`; `group/subgroup/name` paths are used as-is.
+pub struct GitLabClient {
+    http: reqwest::Client,
+    /// Web address of the instance, e.g. `https://gitlab.com`
+    web_url: String,
+    group: String,
+    /// Personal, group or project access token; public projects can be read without one
+    token: Option<String>,
+    max_pages: usize,
+    release_source: ReleaseSource,
+    /// Per-repository overrides of `release_source`
+    release_sources: HashMap<String, ReleaseSource>,
+}
+
+impl GitLabClient {
+    /// Client for the instance at `url` (its web address; `/api/v4` is added).
+    pub fn new(url: &str, group: String, token: Option<String>) -> Result<Self> {
+        let parsed = reqwest::Url::parse(url).with_context(|| format!("{} is not a valid GitLab URL", url))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            anyhow::bail!("{} is not an http(s) URL", url);
+        }
+        let url = url.trim_end_matches('/');
+        Ok(Self {
+            http: reqwest::Client::new(),
+            web_url: url.strip_suffix("/api/v4").unwrap_or(url).to_string(),
+            group,
+            token,
+            max_pages: DEFAULT_MAX_PAGES,
+            release_source: ReleaseSource::default(),
+            release_sources: HashMap::new(),
+        })
+    }
+
+    /// Cap how many pages any single listing follows, like `GitHubClient::with_max_pages`.
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    /// Read versions from `default` for every repository except those listed in `per_repo`;
+    /// with `ReleaseSource::Tags`, releases are synthesized from git tags.
+    pub fn with_release_source(mut self, default: ReleaseSource, per_repo: HashMap<String, ReleaseSource>) -> Self {
+        self.release_source = default;
+        self.release_sources = per_repo;
+        self
+    }
+
+    pub fn web_url(&self) -> &str {
+        &self.web_url
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    fn release_source(&self, repo: &str) -> ReleaseSource {
+        self.release_sources.get(repo).copied().unwrap_or(self.release_source)
+    }
+
+    /// Full path of a repository reference, e.g. `acme/api`.
+    fn project_path(&self, repo: &str) -> String {
+        match repo.split_once('/') {
+            Some(_) => repo.to_string(),
+            None => format!("{}/{}", self.group, repo),
+        }
+    }
+
+    /// API route of a project; the API addresses projects by their URL-encoded path.
+    fn project(&self, repo: &str) -> String {
+        format!("/projects/{}", encode(&self.project_path(repo)))
+    }
+
+    /// GET `route`; `None` when GitLab answers 404 (missing, or not visible to the token).
+    async fn send(&self, route: &str, query: &[(&str, String)]) -> Result<Option<reqwest::Response>> {
+        let mut request = self.http.get(format!("{}/api/v4{}", self.web_url, route)).query(query);
+        if let Some(token) = &self.token {
+            request = request.header("PRIVATE-TOKEN", token);
+        }
+        let response = request.send().await.with_context(|| format!("GitLab request {} failed", route))?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("GitLab answered {} for {}: {}", status, route, body.trim());
+        }
+        Ok(Some(response))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, route: &str, query: &[(&str, String)]) -> Result<Option<T>> {
+        match self.send(route, query).await? {
+            Some(response) => Ok(Some(response.json().await.with_context(|| format!("Unreadable GitLab response for {}", route))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Up to `limit` items of a listing, following `X-Next-Page` for at most `max_pages` pages.
+    /// A listing that doesn't exist is empty.
+    async fn get_paginated<T: DeserializeOwned>(&self, route: &str, query: &[(&str, String)], limit: usize, what: &str) -> Result<Vec<T>> {
+        let per_page = limit.clamp(1, 100);
+        let mut items = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let mut paged = query.to_vec();
+            paged.push(("per_page", per_page.to_string()));
+            paged.push(("page", page.to_string()));
+            let Some(response) = self.send(route, &paged).await? else {
+                break;
+            };
+            let next = response.headers().get("x-next-page")
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| !value.is_empty());
+            let batch: Vec<T> = response.json().await.with_context(|| format!("Unreadable GitLab response for {}", route))?;
+            let received = batch.len();
+            items.extend(batch);
+
+            if items.len() >= limit || received < per_page || !next {
+                break;
+            }
+            if page >= self.max_pages {
+                tracing::warn!("Stopped listing {} after {} pages; results are incomplete (raise --max-pages)", what, page);
+                break;
+            }
+            page += 1;
+        }
+
+        items.truncate(limit);
+        Ok(items)
+    }
+
+    /// Release tagged exactly `tag`, from the repository's release source.
+    async fn get_release_exact(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        match self.release_source(repo) {
+            ReleaseSource::Releases => {
+                let route = format!("{}/releases/{}", self.project(repo), encode(tag));
+                Ok(self.get::<GitLabRelease>(&route, &[]).await?.map(|release| self.release(repo, release)))
+            }
+            ReleaseSource::Tags => {
+                let route = format!("{}/repository/tags/{}", self.project(repo), encode(tag));
+                Ok(self.get::<Tag>(&route, &[]).await?.map(|tag| self.tag_release(repo, tag)))
+            }
+        }
+    }
+
+    /// Up to `limit` releases, newest first.
+    async fn list_releases(&self, repo: &str, limit: usize) -> Result<Vec<Release>> {
+        match self.release_source(repo) {
+            ReleaseSource::Releases => {
+                let query = [("order_by", "released_at".to_string()), ("sort", "desc".to_string())];
+                let route = format!("{}/releases", self.project(repo));
+                let releases: Vec<GitLabRelease> = self.get_paginated(&route, &query, limit, &format!("releases of {}", repo)).await?;
+                Ok(releases.into_iter().map(|release| self.release(repo, release)).collect())
+            }
+            ReleaseSource::Tags => {
+                let route = format!("{}/repository/tags", self.project(repo));
+                let tags: Vec<Tag> = self.get_paginated(&route, &[], usize::MAX, &format!("tags of {}", repo)).await?;
+                let mut releases = tags.into_iter().map(|tag| self.tag_release(repo, tag)).collect::<Vec<_>>();
+                releases.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.tag_name.cmp(&a.tag_name)));
+                releases.truncate(limit);
+                Ok(releases)
+            }
+        }
+    }
+
+    /// A GitLab release as the aggregator reads it. Upcoming releases (dated in the future)
+    /// count as drafts.
+    fn release(&self, repo: &str, release: GitLabRelease) -> Release {
+        let html_url = release.links.and_then(|links| links.self_url)
+            .unwrap_or_else(|| format!("{}/{}/-/releases/{}", self.web_url, self.project_path(repo), release.tag_name));
+        Release {
+            name: Some(release.name.unwrap_or_else(|| release.tag_name.clone())),
+            tag_name: release.tag_name,
+            body: release.description.filter(|d| !d.trim().is_empty()),
+            draft: release.upcoming_release,
+            created_at: release.created_at,
+            published_at: release.released_at,
+            target_commitish: release.commit.map(|commit| commit.id),
+            html_url: Some(html_url),
+            ..Release::default()
+        }
+    }
+
+    /// Release standing in for a git tag: dated by the annotated tag, or else the tagged commit,
+    /// with the tag message as its notes.
+    fn tag_release(&self, repo: &str, tag: Tag) -> Release {
+        let date = tag.created_at.or(tag.commit.committed_date);
+        let body = tag.message.map(|m| m.trim().to_string()).filter(|m| !m.is_empty());
+        let html_url = format!("{}/{}/-/tags/{}", self.web_url, self.project_path(repo), tag.name);
+        Release::from_tag(&tag.name, date, body, tag.commit.id).with_html_url(html_url)
+    }
+}
+
+impl ReleaseProvider for GitLabClient {
+    fn org(&self) -> &str {
+        &self.group
+    }
+
+    /// Projects of the group and its subgroups, named as `canonical_name` names them: projects
+    /// in subgroups by their full namespace path.
+    async fn list_org_repos(&self) -> Result<Vec<String>> {
+        let what = format!("projects of {}", self.group);
+        let route = format!("/groups/{}/projects", encode(&self.group));
+        let query = [("include_subgroups", "true".to_string())];
+        let mut projects: Vec<Project> = self.get_paginated(&route, &query, usize::MAX, &what).await?;
+        if projects.is_empty() {
+            // Not a group (or an empty one); try the user's projects
+            let route = format!("/users/{}/projects", encode(&self.group));
+            projects = self.get_paginated(&route, &[], usize::MAX, &what).await?;
+        }
+        Ok(projects.into_iter()
+            .map(|project| self.canonical_name(&RepositoryInfo::from(project)))
+            .collect())
+    }
+
+    async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        Ok(self.get::<Project>(&self.project(repo), &[]).await?.map(RepositoryInfo::from))
+    }
+
+    /// Projects directly in the group keep their bare name; subgroups and other namespaces
+    /// are addressed by full path.
+    fn canonical_name(&self, info: &RepositoryInfo) -> String {
+        match info.full_name.rsplit_once('/') {
+            Some((namespace, name)) if namespace.eq_ignore_ascii_case(&self.group) => name.to_string(),
+            _ => info.full_name.clone(),
+        }
+    }
+
+    async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        if let Some(release) = self.get_release_exact(repo, tag).await? {
+            return Ok(Some(release));
+        }
+        match toggle_v_prefix(tag) {
+            Some(toggled) => self.get_release_exact(repo, &toggled).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        Ok(self.list_releases(repo, 1).await?.into_iter().next())
+    }
+
+    async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        self.list_releases(repo, usize::MAX).await
+    }
+
+    async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        let route = format!("{}/repository/commits/{}", self.project(repo), encode(reference));
+        Ok(self.get::<CommitId>(&route, &[]).await?.map(|commit| commit.id))
+    }
+
+    /// GitLab's compare isn't paginated and doesn't report how far `to` is behind `from`, so
+    /// diverged refs aren't flagged.
+    async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        let route = format!("{}/repository/compare", self.project(repo));
+        let query = [("from", from.to_string()), ("to", to.to_string()), ("straight", "false".to_string())];
+        let compare: Compare = self.get(&route, &query).await?
+            .with_context(|| format!("Can't compare {}...{} in {}", from, to, repo))?;
+
+        let commits: Vec<CommitInfo> = compare.commits.into_iter().rev().map(CommitInfo::from).collect();
+        Ok(CommitComparison {
+            status: if commits.is_empty() { "identical" } else { "ahead" }.to_string(),
+            ahead_by: commits.len() as u64,
+            behind_by: 0,
+            commits,
+            files: compare.diffs.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        let route = format!("{}/repository/commits", self.project(repo));
+        let query = [("ref_name", until.to_string())];
+        let commits: Vec<Commit> = self.get_paginated(&route, &query, usize::MAX, &format!("commits of {} up to {}", repo, until)).await?;
+        Ok(commits.into_iter().map(CommitInfo::from).collect())
+    }
+
+    async fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> Result<Option<String>> {
+        let route = format!("{}/repository/files/{}/raw", self.project(repo), encode(path));
+        match self.send(&route, &[("ref", reference.to_string())]).await? {
+            Some(response) => Ok(Some(response.text().await.with_context(|| format!("Unreadable GitLab response for {}", route))?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_directory(&self, repo: &str, path: &str, reference: &str) -> Result<Vec<String>> {
+        let route = format!("{}/repository/tree", self.project(repo));
+        let query = [("path", path.to_string()), ("ref", reference.to_string())];
+        let entries: Vec<TreeEntry> = self.get_paginated(&route, &query, usize::MAX, &format!("{} in {}", path, repo)).await?;
+        Ok(entries.into_iter().filter(|entry| entry.kind == "blob").map(|entry| entry.path).collect())
+    }
+
+    async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        let route = format!("{}/repository/commits", self.project(repo));
+        let query = [
+            ("author", author.to_string()),
+            ("until", until.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+            ("per_page", "1".to_string()),
+        ];
+        let commits: Option<Vec<CommitId>> = self.get(&route, &query).await?;
+        Ok(commits.is_some_and(|commits| !commits.is_empty()))
+    }
+
+    async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        let project = self.get::<Project>(&self.project(repo), &[]).await?
+            .with_context(|| format!("No GitLab project {}", self.project_path(repo)))?;
+        Ok(project.empty_repo)
+    }
+
+    /// Merged merge requests containing each commit, one request per commit. A merge request
+    /// is attached to the commit it landed as: its merge commit, or its squash commit.
+    async fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> Result<Vec<PullRequest>> {
+        let mut prs = Vec::new();
+        for sha in &shas {
+            let route = format!("{}/repository/commits/{}/merge_requests", self.project(repo), sha);
+            match self.get::<Vec<MergeRequest>>(&route, &[]).await {
+                Ok(found) => prs.extend(found.into_iter().flatten().filter(|mr| mr.merged_at.is_some()).map(|mr| {
+                    let landed_as = [&mr.merge_commit_sha, &mr.squash_commit_sha]
+                        .into_iter()
+                        .flatten()
+                        .find(|landed| *landed == sha)
+                        .cloned()
+                        .or_else(|| mr.landed_as());
+                    mr.into_pull_request(landed_as)
+                })),
+                Err(e) => tracing::debug!("No merge requests for {}@{}: {:#}", repo, sha, e),
+            }
+        }
+        Ok(prs)
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        let route = format!("{}/merge_requests", self.project(repo));
+        let mut query = vec![
+            ("state", "merged".to_string()),
+            ("order_by", "updated_at".to_string()),
+            ("sort", "desc".to_string()),
+        ];
+        if let Some(base) = base {
+            query.push(("target_branch", base.to_string()));
+        }
+        if let Some(since) = since {
+            query.push(("updated_after", since.format("%Y-%m-%dT%H:%M:%SZ").to_string()));
+        }
+        let mrs: Vec<MergeRequest> = self.get_paginated(&route, &query, usize::MAX, &format!("merge requests of {}", repo)).await?;
+
+        let mut merged: Vec<PullRequest> = mrs.into_iter()
+            .filter(|mr| mr.merged_at.is_some_and(|merged_at| since.is_none_or(|since| merged_at > since) && until.is_none_or(|until| merged_at <= until)))
+            .filter_map(|mr| {
+                let landed_as = mr.landed_as()?;
+                Some(mr.into_pull_request(Some(landed_as)))
+            })
+            .collect();
+        merged.sort_by_key(|pr| pr.merged_at);
+        Ok(merged)
+    }
+
+    async fn list_open_issues(&self, repo: &str, label: &str) -> Result<Vec<Issue>> {
+        let route = format!("{}/issues", self.project(repo));
+        let query = [
+            ("state", "opened".to_string()),
+            ("labels", label.to_string()),
+            ("order_by", "created_at".to_string()),
+            ("sort", "asc".to_string()),
+        ];
+        let issues: Vec<GitLabIssue> = self.get_paginated(&route, &query, usize::MAX, &format!("{} issues of {}", label, repo)).await?;
+        Ok(issues.into_iter().map(Issue::from).collect())
+    }
+
+    async fn get_issues(&self, repo: &str, numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        let mut issues = Vec::new();
+        for number in numbers {
+            let route = format!("{}/issues/{}", self.project(repo), number);
+            match self.get::<GitLabIssue>(&route, &[]).await {
+                Ok(Some(issue)) => issues.push(issue.into()),
+                Ok(None) => {}
+                Err(e) => tracing::debug!("No issue {}#{}: {:#}", repo, number, e),
+            }
+        }
+        Ok(issues)
+    }
+}
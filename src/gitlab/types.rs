@@ -0,0 +1,233 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use crate::github::types::{ChangedFile, CommitAuthor, CommitInfo, FileStatus, Issue, Label, PullRequest, RepositoryInfo, UserRef};
+
+/// A project from `GET /projects/:id`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Project {
+    pub path: String,
+    pub path_with_namespace: String,
+    pub web_url: String,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub empty_repo: bool,
+}
+
+impl From<Project> for RepositoryInfo {
+    fn from(project: Project) -> Self {
+        RepositoryInfo {
+            name: project.path,
+            full_name: project.path_with_namespace,
+            html_url: project.web_url,
+            archived: project.archived,
+            default_branch: project.default_branch,
+        }
+    }
+}
+
+/// A release from `GET /projects/:id/releases`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitLabRelease {
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the release was (or will be) published; in the future for upcoming releases
+    pub released_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub upcoming_release: bool,
+    #[serde(default)]
+    pub commit: Option<CommitId>,
+    #[serde(rename = "_links", default)]
+    pub links: Option<ReleaseLinks>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseLinks {
+    #[serde(rename = "self")]
+    pub self_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitId {
+    pub id: String,
+    #[serde(default)]
+    pub committed_date: Option<DateTime<Utc>>,
+}
+
+/// A tag from `GET /projects/:id/repository/tags`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    /// Message of an annotated tag; empty or missing for lightweight tags
+    #[serde(default)]
+    pub message: Option<String>,
+    pub commit: CommitId,
+    /// When an annotated tag was created (GitLab 15.7+); lightweight tags have none
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// An entry of `GET /projects/:id/repository/tree`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TreeEntry {
+    pub path: String,
+    /// `blob` for files, `tree` for directories
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// A commit from the commit list and compare endpoints.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Commit {
+    pub id: String,
+    pub message: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub authored_date: DateTime<Utc>,
+}
+
+impl From<Commit> for CommitInfo {
+    /// GitLab's commit listings don't report signatures, so commits count as unverified.
+    fn from(commit: Commit) -> Self {
+        CommitInfo {
+            sha: commit.id,
+            message: commit.message,
+            author: CommitAuthor {
+                name: commit.author_name,
+                email: commit.author_email,
+                username: None,
+            },
+            date: commit.authored_date,
+            verified: false,
+        }
+    }
+}
+
+/// `GET /projects/:id/repository/compare`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Compare {
+    /// Oldest first
+    #[serde(default)]
+    pub commits: Vec<Commit>,
+    #[serde(default)]
+    pub diffs: Vec<Diff>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Diff {
+    pub new_path: String,
+    #[serde(default)]
+    pub diff: String,
+    #[serde(default)]
+    pub new_file: bool,
+    #[serde(default)]
+    pub renamed_file: bool,
+    #[serde(default)]
+    pub deleted_file: bool,
+}
+
+impl From<Diff> for ChangedFile {
+    /// Line counts come from the unified diff, which GitLab leaves empty for binary and
+    /// oversized files.
+    fn from(diff: Diff) -> Self {
+        let mut additions = 0;
+        let mut deletions = 0;
+        for line in diff.diff.lines() {
+            if line.starts_with('+') && !line.starts_with("+++") {
+                additions += 1;
+            } else if line.starts_with('-') && !line.starts_with("---") {
+                deletions += 1;
+            }
+        }
+        let status = if diff.new_file {
+            FileStatus::Added
+        } else if diff.deleted_file {
+            FileStatus::Removed
+        } else if diff.renamed_file {
+            FileStatus::Renamed
+        } else {
+            FileStatus::Modified
+        };
+        ChangedFile { filename: diff.new_path, additions, deletions, status }
+    }
+}
+
+/// A merge request, from the commit and project merge request listings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeRequest {
+    /// Number within the project, as shown in `!42`
+    pub iid: u64,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub merged_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub merge_commit_sha: Option<String>,
+    #[serde(default)]
+    pub squash_commit_sha: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub author: Option<GitLabUser>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitLabUser {
+    pub username: String,
+}
+
+impl MergeRequest {
+    /// The merge request as a pull request landing as `landed_as`: its squash commit when
+    /// squashed, otherwise its merge commit.
+    pub fn into_pull_request(self, landed_as: Option<String>) -> PullRequest {
+        PullRequest {
+            number: self.iid,
+            title: self.title,
+            body: self.description,
+            merged_at: self.merged_at,
+            merge_commit_sha: landed_as,
+            updated_at: self.updated_at,
+            user: self.author.map(|author| UserRef { login: author.username }),
+            labels: self.labels.into_iter().map(|name| Label { name }).collect(),
+        }
+    }
+
+    /// The commit that put the merge request on its target branch.
+    pub fn landed_as(&self) -> Option<String> {
+        self.merge_commit_sha.clone().or_else(|| self.squash_commit_sha.clone())
+    }
+}
+
+/// An issue from `GET /projects/:id/issues/:iid`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitLabIssue {
+    pub iid: u64,
+    pub title: String,
+    /// `opened` or `closed`
+    pub state: String,
+    pub web_url: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl From<GitLabIssue> for Issue {
+    fn from(issue: GitLabIssue) -> Self {
+        Issue {
+            number: issue.iid,
+            title: issue.title,
+            state: if issue.state == "opened" { "open".to_string() } else { issue.state },
+            html_url: issue.web_url,
+            body: issue.description,
+            pull_request: None,
+        }
+    }
+}
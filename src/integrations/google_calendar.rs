@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+use crate::aggregator::calendar::{next_day, TrainEvent};
+use crate::config::types::GoogleCalendarConfig;
+
+const API_URL: &str = "https://www.googleapis.com/calendar/v3";
+
+pub struct GoogleCalendarClient {
+    http: reqwest::Client,
+    calendar_id: String,
+    token: String,
+}
+
+impl GoogleCalendarClient {
+    pub fn new(config: &GoogleCalendarConfig) -> Result<Self> {
+        let token = std::env::var(&config.token_env)
+            .with_context(|| format!("Google Calendar token not found in ${}", config.token_env))?;
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            calendar_id: config.calendar_id.clone(),
+            token,
+        })
+    }
+
+    /// URL of the calendar's events collection, or of one event in it.
+    fn events_url(&self, event_id: Option<&str>) -> Result<reqwest::Url> {
+        let mut url = reqwest::Url::parse(API_URL)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("{} cannot be a base URL", API_URL))?
+            .extend(["calendars", &self.calendar_id, "events"])
+            .extend(event_id);
+        Ok(url)
+    }
+
+    /// Create the train's event, or update it in place if an earlier run created it. Event IDs
+    /// are derived from the train version, so a scheduled train's event becomes the shipped one.
+    pub async fn upsert_event(&self, event: &TrainEvent) -> Result<()> {
+        let id = event_id(event);
+        let mut body = json!({
+            "id": id,
+            "summary": event.summary(),
+            "description": event.description,
+            "start": { "date": event.date.format("%Y-%m-%d").to_string() },
+            "end": { "date": next_day(event.date).format("%Y-%m-%d").to_string() },
+            "status": if event.scheduled { "tentative" } else { "confirmed" },
+            "transparency": "transparent",
+            "iCalUID": event.uid(),
+        });
+        if let Some(url) = &event.url {
+            body["source"] = json!({ "title": "Release notes", "url": url });
+            body["description"] = json!(format!("{}\nRelease notes: {}", event.description, url));
+        }
+
+        let response = self.http.put(self.events_url(Some(&id))?)
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await?;
+        if response.status() != reqwest::StatusCode::NOT_FOUND {
+            response.error_for_status()
+                .with_context(|| format!("Failed to update the calendar event for {}", event.version))?;
+            return Ok(());
+        }
+
+        self.http.post(self.events_url(None)?)
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to create the calendar event for {}", event.version))?;
+        Ok(())
+    }
+}
+
+/// Google Calendar event IDs only allow the base32hex alphabet (`0-9a-v`), so the UID is
+/// encoded with it.
+fn event_id(event: &TrainEvent) -> String {
+    const ALPHABET: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+    let mut id = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in event.uid().bytes() {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            id.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        id.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    id
+}
@@ -0,0 +1,147 @@
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::config::types::JiraConfig;
+
+pub struct JiraClient {
+    http: reqwest::Client,
+    base_url: String,
+    email: Option<String>,
+    token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JiraVersion {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub released: bool,
+    #[serde(default)]
+    pub release_date: Option<String>,
+}
+
+/// What happened in one project when a train version was published.
+#[derive(Debug)]
+pub struct JiraProjectResult {
+    pub project: String,
+    pub version: JiraVersion,
+    pub assigned: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl JiraClient {
+    pub fn new(config: &JiraConfig) -> Result<Self> {
+        let token = std::env::var(&config.token_env)
+            .with_context(|| format!("Jira token not found in ${}", config.token_env))?;
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            email: config.email.clone(),
+            token,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self.http.request(method, format!("{}/rest/api/2/{}", self.base_url, path));
+        match &self.email {
+            Some(email) => builder.basic_auth(email, Some(&self.token)),
+            None => builder.bearer_auth(&self.token),
+        }
+    }
+
+    pub async fn find_version(&self, project: &str, name: &str) -> Result<Option<JiraVersion>> {
+        let response = self.request(reqwest::Method::GET, &format!("project/{}/versions", project))
+            .send()
+            .await?
+            .error_for_status()?;
+        let versions: Vec<JiraVersion> = response.json().await?;
+
+        Ok(versions.into_iter().find(|v| v.name == name))
+    }
+
+    pub async fn ensure_version(&self, project: &str, name: &str, description: &str) -> Result<JiraVersion> {
+        if let Some(version) = self.find_version(project, name).await? {
+            return Ok(version);
+        }
+
+        let response = self.request(reqwest::Method::POST, "version")
+            .json(&json!({
+                "name": name,
+                "project": project,
+                "description": description,
+            }))
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to create Jira version {} in {}", name, project))?;
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn add_fix_version(&self, issue_key: &str, version_name: &str) -> Result<()> {
+        self.request(reqwest::Method::PUT, &format!("issue/{}", issue_key))
+            .json(&json!({
+                "update": { "fixVersions": [ { "add": { "name": version_name } } ] }
+            }))
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to set fix version on {}", issue_key))?;
+        Ok(())
+    }
+
+    pub async fn release_version(&self, version: &JiraVersion, release_date: NaiveDate) -> Result<JiraVersion> {
+        let response = self.request(reqwest::Method::PUT, &format!("version/{}", version.id))
+            .json(&json!({
+                "released": true,
+                "releaseDate": release_date.format("%Y-%m-%d").to_string(),
+            }))
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to mark Jira version {} released", version.name))?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Create (or reuse) the train's version in `project`, attach the referenced issues, and release it.
+    pub async fn publish_version(
+        &self,
+        project: &str,
+        version_name: &str,
+        description: &str,
+        release_date: NaiveDate,
+        issue_keys: &[String],
+    ) -> Result<JiraProjectResult> {
+        if project.is_empty() {
+            bail!("Jira project key must not be empty");
+        }
+
+        let version = self.ensure_version(project, version_name, description).await?;
+        let prefix = format!("{}-", project);
+
+        let mut assigned = Vec::new();
+        let mut failed = Vec::new();
+        for key in issue_keys.iter().filter(|k| k.starts_with(&prefix)) {
+            match self.add_fix_version(key, version_name).await {
+                Ok(()) => assigned.push(key.clone()),
+                Err(e) => {
+                    tracing::warn!("{:#}", e);
+                    failed.push(key.clone());
+                }
+            }
+        }
+
+        let version = self.release_version(&version, release_date).await?;
+
+        Ok(JiraProjectResult {
+            project: project.to_string(),
+            version,
+            assigned,
+            failed,
+        })
+    }
+}
@@ -0,0 +1,6 @@
+pub mod google_calendar;
+pub mod jira;
+pub mod notify;
+pub mod oci;
+pub mod registry;
+pub mod status_page;
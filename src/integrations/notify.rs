@@ -0,0 +1,194 @@
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentStatus};
+use crate::config::types::NotifyTarget;
+use crate::state::store::{QueuedNotification, StateStore};
+
+/// Announcement of a published train, as sent to every notification target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub event: AnnouncementEvent,
+    pub version: String,
+    /// The umbrella release
+    pub url: String,
+    pub summary: String,
+    /// Entries added and removed, when a train was republished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementEvent {
+    Published,
+    Updated,
+}
+
+impl Announcement {
+    pub fn new(event: AnnouncementEvent, version: &str, url: &str, summary: String) -> Self {
+        Self { event, version: version.to_string(), url: url.to_string(), summary, diff: None }
+    }
+
+    /// Announcement of `release`, listing the components it updates.
+    pub fn from_release(event: AnnouncementEvent, release: &AggregatedRelease, url: &str) -> Self {
+        let updated: Vec<&str> = release.components.iter()
+            .filter(|c| matches!(c.status, ComponentStatus::Released { .. }))
+            .map(|c| c.repository.as_str())
+            .collect();
+        let summary = format!("{} component(s) updated: {}", updated.len(), updated.join(", "));
+        Self::new(event, &release.version, url, summary)
+    }
+
+    pub fn with_diff(mut self, diff: String) -> Self {
+        self.diff = Some(diff);
+        self
+    }
+
+    pub fn title(&self) -> String {
+        match self.event {
+            AnnouncementEvent::Published => format!("Release train {} published", self.version),
+            AnnouncementEvent::Updated => format!("Release train {} updated", self.version),
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!("{}\n{}\n{}", self.title(), self.summary, self.url);
+        if let Some(diff) = &self.diff {
+            text.push_str("\n\n");
+            text.push_str(diff.trim_end());
+        }
+        text
+    }
+
+    fn slack_text(&self) -> String {
+        let mut text = format!("*<{}|{}>*\n{}", self.url, self.title(), self.summary);
+        if let Some(diff) = &self.diff {
+            text.push_str(&format!("\n```{}```", diff.trim_end()));
+        }
+        text
+    }
+}
+
+/// Outcome of sending an announcement to one target.
+#[derive(Debug)]
+pub struct Delivery {
+    pub target: NotifyTarget,
+    /// Why the delivery failed; it was queued for a later retry
+    pub error: Option<String>,
+}
+
+/// Retried deliveries from the queue.
+#[derive(Debug, Default)]
+pub struct FlushReport {
+    pub delivered: Vec<QueuedNotification>,
+    /// Deliveries that failed again and stay queued
+    pub remaining: usize,
+}
+
+/// Sends announcements to Slack incoming webhooks, generic webhooks and email (through the
+/// local `sendmail`). Deliveries that fail are queued in the state store rather than lost.
+pub struct Notifier {
+    http: reqwest::Client,
+}
+
+impl Notifier {
+    /// A notifier sending its requests through `proxy` (`http://`, `https://`, `socks5://` or
+    /// `socks5h://` to resolve names on the proxy), or the environment's proxy settings.
+    pub fn new(proxy: Option<&str>) -> Result<Self> {
+        let mut builder = reqwest::Client::builder().timeout(std::time::Duration::from_secs(30));
+        if let Some(proxy) = proxy {
+            let proxy = reqwest::Proxy::all(proxy).with_context(|| format!("Invalid notification proxy {}", proxy))?;
+            builder = builder.proxy(proxy);
+        }
+        Ok(Self { http: builder.build()? })
+    }
+
+    pub async fn send(&self, target: &NotifyTarget, announcement: &Announcement) -> Result<()> {
+        match target {
+            NotifyTarget::Slack { url_env } => {
+                let url = std::env::var(url_env).with_context(|| format!("Slack webhook URL not found in ${}", url_env))?;
+                self.post(&url, &json!({ "text": announcement.slack_text() })).await
+            }
+            NotifyTarget::Webhook { url } => self.post(url, announcement).await,
+            NotifyTarget::Email { to, from } => send_mail(to, from.as_deref(), &announcement.title(), &announcement.text()).await,
+        }
+    }
+
+    async fn post(&self, url: &str, body: &impl Serialize) -> Result<()> {
+        self.http.post(url)
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Send `announcement` to each of `targets`, queueing the ones that fail in `store`.
+    pub async fn announce(&self, targets: &[NotifyTarget], announcement: &Announcement, store: &mut StateStore) -> Vec<Delivery> {
+        let mut deliveries = Vec::new();
+        for target in targets {
+            let error = match self.send(target, announcement).await {
+                Ok(()) => None,
+                Err(e) => {
+                    let error = format!("{:#}", e);
+                    store.queue_notification(target.clone(), announcement.clone(), &error);
+                    Some(error)
+                }
+            };
+            deliveries.push(Delivery { target: target.clone(), error });
+        }
+        deliveries
+    }
+
+    /// Retry every queued delivery, oldest first, dropping the ones that go through.
+    pub async fn flush(&self, store: &mut StateStore) -> FlushReport {
+        let mut report = FlushReport::default();
+        let mut remaining = Vec::new();
+        for mut queued in std::mem::take(&mut store.notifications) {
+            match self.send(&queued.target, &queued.announcement).await {
+                Ok(()) => report.delivered.push(queued),
+                Err(e) => {
+                    queued.attempts += 1;
+                    queued.last_attempt = Utc::now();
+                    queued.last_error = format!("{:#}", e);
+                    remaining.push(queued);
+                }
+            }
+        }
+        report.remaining = remaining.len();
+        store.notifications = remaining;
+        report
+    }
+}
+
+/// Hand a plain-text message to `sendmail -t`, which reads the recipients from the headers.
+async fn send_mail(to: &[String], from: Option<&str>, subject: &str, body: &str) -> Result<()> {
+    if to.is_empty() {
+        bail!("Email target has no recipients");
+    }
+    let mut message = format!("To: {}\n", to.join(", "));
+    if let Some(from) = from {
+        message.push_str(&format!("From: {}\n", from));
+    }
+    message.push_str(&format!("Subject: {}\nContent-Type: text/plain; charset=utf-8\n\n{}\n", subject, body));
+
+    let mut child = tokio::process::Command::new("sendmail")
+        .arg("-t")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run sendmail; is it installed and on the PATH?")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        bail!("sendmail failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
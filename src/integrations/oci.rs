@@ -0,0 +1,146 @@
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.index.v1+json, \
+application/vnd.oci.image.manifest.v1+json, \
+application/vnd.docker.distribution.manifest.list.v2+json, \
+application/vnd.docker.distribution.manifest.v2+json";
+
+/// Username and password for one registry host.
+#[derive(Debug, Clone)]
+pub struct OciCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Image reference split into the registry host and repository path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    pub host: String,
+    pub path: String,
+}
+
+impl ImageRef {
+    /// Parse `ghcr.io/acme/api`, `123.dkr.ecr.us-east-1.amazonaws.com/api`, or Docker Hub
+    /// shorthand like `acme/api` and `nginx`.
+    pub fn parse(image: &str) -> Self {
+        let (first, rest) = image.split_once('/').unwrap_or(("", image));
+        let is_host = first.contains('.') || first.contains(':') || first == "localhost";
+
+        if is_host && first != "docker.io" {
+            return Self { host: first.to_string(), path: rest.to_string() };
+        }
+
+        let path = if is_host { rest } else { image };
+        let path = if path.contains('/') { path.to_string() } else { format!("library/{}", path) };
+        Self { host: "registry-1.docker.io".to_string(), path }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+/// Checks image tags against any registry implementing the OCI distribution API
+/// (GHCR, ECR, Docker Hub, ...).
+pub struct OciClient {
+    http: reqwest::Client,
+    credentials: HashMap<String, OciCredentials>,
+}
+
+impl OciClient {
+    /// `credentials` is keyed by registry host; hosts without an entry are queried anonymously.
+    pub fn new(credentials: HashMap<String, OciCredentials>) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("release-aggregator/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self { http, credentials })
+    }
+
+    /// Digest of `image:tag`, or `None` if the tag does not exist.
+    pub async fn manifest_digest(&self, image: &str, tag: &str) -> Result<Option<String>> {
+        let image_ref = ImageRef::parse(image);
+        let url = format!("https://{}/v2/{}/manifests/{}", image_ref.host, image_ref.path, tag);
+        let credentials = self.credentials.get(&image_ref.host).or_else(|| {
+            // Docker Hub credentials are naturally configured under `docker.io`
+            (image_ref.host == "registry-1.docker.io").then(|| self.credentials.get("docker.io")).flatten()
+        });
+
+        let mut response = self.http.head(&url)
+            .header(reqwest::header::ACCEPT, MANIFEST_ACCEPT)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", image_ref.host))?;
+
+        // Registries answer anonymous requests with a challenge describing how to authenticate
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            let challenge = response.headers()
+                .get(reqwest::header::WWW_AUTHENTICATE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+
+            let request = self.http.head(&url).header(reqwest::header::ACCEPT, MANIFEST_ACCEPT);
+            let request = if challenge.starts_with("Bearer") {
+                let token = self.bearer_token(&challenge, credentials).await?;
+                request.bearer_auth(token)
+            } else if let Some(creds) = credentials {
+                request.basic_auth(&creds.username, Some(&creds.password))
+            } else {
+                bail!("{} requires credentials; configure them under [image_registries]", image_ref.host);
+            };
+
+            response = request.send().await?;
+        }
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response.error_for_status()
+            .with_context(|| format!("{} returned an error for {}:{}", image_ref.host, image, tag))?;
+
+        let digest = response.headers()
+            .get("docker-content-digest")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown")
+            .to_string();
+
+        Ok(Some(digest))
+    }
+
+    async fn bearer_token(&self, challenge: &str, credentials: Option<&OciCredentials>) -> Result<String> {
+        let params: HashMap<String, String> = Regex::new(r#"(\w+)="([^"]*)""#)
+            .unwrap()
+            .captures_iter(challenge)
+            .map(|c| (c[1].to_string(), c[2].to_string()))
+            .collect();
+
+        let realm = params.get("realm")
+            .with_context(|| format!("Malformed registry auth challenge: {}", challenge))?;
+        let query: Vec<(&str, &str)> = ["service", "scope"].iter()
+            .filter_map(|key| params.get(*key).map(|value| (*key, value.as_str())))
+            .collect();
+
+        let mut request = self.http.get(realm).query(&query);
+        if let Some(creds) = credentials {
+            request = request.basic_auth(&creds.username, Some(&creds.password));
+        }
+
+        let response: TokenResponse = request.send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to obtain a registry token from {}", realm))?
+            .json()
+            .await?;
+
+        response.token
+            .or(response.access_token)
+            .with_context(|| format!("No token returned by {}", realm))
+    }
+}
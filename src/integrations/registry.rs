@@ -0,0 +1,40 @@
+use anyhow::{Context, Result};
+use crate::config::types::RegistryKind;
+
+pub struct RegistryClient {
+    http: reqwest::Client,
+}
+
+impl RegistryClient {
+    pub fn new() -> Result<Self> {
+        // crates.io rejects requests without an identifying User-Agent
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("release-aggregator/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self { http })
+    }
+
+    /// Whether `package` has `version` published. A leading `v` on the version (as in git tags) is ignored.
+    pub async fn version_exists(&self, registry: RegistryKind, package: &str, version: &str) -> Result<bool> {
+        let version = version.strip_prefix('v').unwrap_or(version);
+        let url = match registry {
+            RegistryKind::CratesIo => format!("https://crates.io/api/v1/crates/{}/{}", package, version),
+            // Scoped packages keep the `@` but need the slash encoded
+            RegistryKind::Npm => format!("https://registry.npmjs.org/{}/{}", package.replace('/', "%2F"), version),
+            RegistryKind::Pypi => format!("https://pypi.org/pypi/{}/{}/json", package, version),
+        };
+
+        let response = self.http.get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to query {} for {}", registry, package))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        response.error_for_status()
+            .with_context(|| format!("{} returned an error for {}@{}", registry, package, version))?;
+
+        Ok(true)
+    }
+}
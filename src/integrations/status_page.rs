@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use crate::aggregator::commit_analyzer::CommitType;
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentStatus};
+use crate::config::types::{StatusPageConfig, StatusPageProvider};
+
+const STATUSPAGE_API: &str = "https://api.statuspage.io/v1";
+const INSTATUS_API: &str = "https://api.instatus.com/v1";
+
+/// Features and fixes listed in a notice before the rest are summed up as "and N more".
+const MAX_HIGHLIGHTS: usize = 10;
+
+/// A scheduled maintenance announcing a train, built from its external (redacted) view.
+#[derive(Debug, Clone)]
+pub struct MaintenanceNotice {
+    pub name: String,
+    pub body: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Status page components of the released repositories
+    pub component_ids: Vec<String>,
+}
+
+impl MaintenanceNotice {
+    /// Notice for `release`, which should already be redacted for external audiences: its
+    /// messages end up on a public page.
+    pub fn from_release(release: &AggregatedRelease, config: &StatusPageConfig, start: DateTime<Utc>) -> Self {
+        let mut updated = Vec::new();
+        let mut component_ids = Vec::new();
+        let mut highlights = Vec::new();
+        for component in &release.components {
+            let ComponentStatus::Released { commits, .. } = &component.status else {
+                continue;
+            };
+            updated.push(component.repository.as_str());
+            if let Some(id) = config.components.get(&component.repository) {
+                if !component_ids.contains(id) {
+                    component_ids.push(id.clone());
+                }
+            }
+            highlights.extend(commits.iter()
+                .filter(|c| matches!(c.commit_type, Some(CommitType::Feature | CommitType::Fix)))
+                .map(|c| c.message.lines().next().unwrap_or_default().to_string()));
+        }
+
+        let mut body = format!("Release {} updates {} component(s): {}.", release.version, updated.len(), updated.join(", "));
+        if !highlights.is_empty() {
+            body.push_str("\n\nHighlights:");
+            for highlight in highlights.iter().take(MAX_HIGHLIGHTS) {
+                body.push_str(&format!("\n- {}", highlight));
+            }
+            if highlights.len() > MAX_HIGHLIGHTS {
+                body.push_str(&format!("\n- and {} more", highlights.len() - MAX_HIGHLIGHTS));
+            }
+        }
+
+        Self {
+            name: format!("Release {}", release.version),
+            body,
+            start,
+            end: start + Duration::minutes(i64::from(config.window_minutes)),
+            component_ids,
+        }
+    }
+}
+
+/// What posting a notice did.
+#[derive(Debug)]
+pub enum PostedNotice {
+    Created { id: String },
+    /// A maintenance with the same name was already scheduled, so nothing was posted
+    AlreadyScheduled { id: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct Maintenance {
+    id: String,
+    #[serde(default)]
+    name: String,
+}
+
+pub struct StatusPageClient {
+    http: reqwest::Client,
+    provider: StatusPageProvider,
+    page_id: String,
+    token: String,
+}
+
+impl StatusPageClient {
+    pub fn new(config: &StatusPageConfig) -> Result<Self> {
+        let token = std::env::var(&config.token_env)
+            .with_context(|| format!("Status page API key not found in ${}", config.token_env))?;
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            provider: config.provider,
+            page_id: config.page_id.clone(),
+            token,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        match self.provider {
+            StatusPageProvider::Statuspage => self.http
+                .request(method, format!("{}/pages/{}/{}", STATUSPAGE_API, self.page_id, path))
+                .header(reqwest::header::AUTHORIZATION, format!("OAuth {}", self.token)),
+            StatusPageProvider::Instatus => self.http
+                .request(method, format!("{}/{}/{}", INSTATUS_API, self.page_id, path))
+                .bearer_auth(&self.token),
+        }
+    }
+
+    /// Upcoming maintenances, to avoid announcing the same train twice.
+    async fn scheduled(&self) -> Result<Vec<Maintenance>> {
+        let path = match self.provider {
+            StatusPageProvider::Statuspage => "incidents/scheduled",
+            StatusPageProvider::Instatus => "maintenances",
+        };
+        let response = self.request(reqwest::Method::GET, path)
+            .send()
+            .await?
+            .error_for_status()
+            .context("Failed to list scheduled maintenances")?;
+        Ok(response.json().await?)
+    }
+
+    /// Schedule `notice` as a maintenance, unless one with the same name is already scheduled.
+    pub async fn post_notice(&self, notice: &MaintenanceNotice) -> Result<PostedNotice> {
+        if let Some(existing) = self.scheduled().await?.into_iter().find(|m| m.name == notice.name) {
+            return Ok(PostedNotice::AlreadyScheduled { id: existing.id });
+        }
+
+        let (path, body) = match self.provider {
+            StatusPageProvider::Statuspage => ("incidents", json!({
+                "incident": {
+                    "name": notice.name,
+                    "status": "scheduled",
+                    "body": notice.body,
+                    "scheduled_for": notice.start.to_rfc3339(),
+                    "scheduled_until": notice.end.to_rfc3339(),
+                    "scheduled_auto_in_progress": true,
+                    "scheduled_auto_completed": true,
+                    "component_ids": notice.component_ids,
+                },
+            })),
+            StatusPageProvider::Instatus => ("maintenances", json!({
+                "name": notice.name,
+                "message": notice.body,
+                "start": notice.start.to_rfc3339(),
+                "duration": (notice.end - notice.start).num_minutes(),
+                "status": "NOTSTARTEDYET",
+                "notify": true,
+                "components": notice.component_ids,
+                "statuses": notice.component_ids.iter()
+                    .map(|id| json!({ "id": id, "status": "UNDERMAINTENANCE" }))
+                    .collect::<Vec<_>>(),
+            })),
+        };
+        let response = self.request(reqwest::Method::POST, path)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to schedule \"{}\"", notice.name))?;
+        let created: Maintenance = response.json().await?;
+
+        Ok(PostedNotice::Created { id: created.id })
+    }
+}
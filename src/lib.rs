@@ -1,3 +1,56 @@
+//! Aggregate the releases of many repositories into one release train and render its notes.
+//!
+//! The `release-aggregator` binary is a thin CLI over this library; bots and other tools can
+//! embed the same pipeline instead of shelling out to it:
+//!
+//! 1. A [`ReleaseProvider`] reads releases, commits and pull requests from a forge:
+//!    [`GitHubClient`], [`GitLabClient`], [`LocalGitClient`] for clones on disk, or
+//!    [`provider::forges::Forges`] to route repositories between them.
+//! 2. [`ReleaseAggregator`] finds each repository's release for a version and the entries it
+//!    shipped, categorized by [`CommitAnalyzer`], producing an [`AggregatedRelease`].
+//! 3. [`ChangelogGenerator`] renders that release as Markdown, HTML or JSON.
+//!
+//! ```no_run
+//! use release_aggregator::{AggregatorConfig, ChangelogGenerator, GitHubClient, OutputFormat, ReleaseAggregator};
+//! use release_aggregator::github::auth::GitHubAuth;
+//! use release_aggregator::github::host::GitHubHost;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let auth = GitHubAuth::Token(std::env::var("GITHUB_TOKEN")?);
+//! let client = GitHubClient::new(&auth, "acme".to_string(), GitHubHost::default()).await?;
+//! let aggregator = ReleaseAggregator::new(client, AggregatorConfig::default());
+//! let release = aggregator.aggregate("v2.4.0", vec!["api".to_string(), "web".to_string()]).await?;
+//!
+//! let notes = ChangelogGenerator::new(OutputFormat::Markdown, None)?.generate(&release)?;
+//! println!("{}", notes);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! To run with a config file's settings as the CLI does, load it with [`config::types::Config::load`],
+//! pick repositories with [`config::types::select_repos`] and aggregate with
+//! [`AggregatorConfig::from_config`].
+//!
+//! The modules below hold the rest of what the CLI is built from (config files, the state
+//! store, exports, integrations and serve mode). With the `test-utils` feature, `test_utils`
+//! adds fixture builders and a `MockProvider` for testing code built on this crate.
 pub mod aggregator;
 pub mod config;
-pub mod github;
\ No newline at end of file
+pub mod export;
+pub mod github;
+pub mod gitlab;
+pub mod integrations;
+pub mod local;
+pub mod provider;
+pub mod serve;
+pub mod state;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
+pub use aggregator::{AggregatedRelease, AggregatorConfig, ChangelogMode, CommitType, ReleaseAggregator};
+pub use aggregator::changelog_generator::{ChangelogGenerator, OutputFormat};
+pub use aggregator::commit_analyzer::{CommitAnalyzer, EnrichedCommit};
+pub use github::client::GitHubClient;
+pub use gitlab::client::GitLabClient;
+pub use local::client::LocalGitClient;
+pub use provider::ReleaseProvider;
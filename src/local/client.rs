@@ -0,0 +1,356 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use crate::github::host::GitHubHost;
+use crate::github::types::{toggle_v_prefix, ChangedFile, CommitAuthor, CommitComparison, CommitInfo, FileStatus, Issue, PullRequest, Release, RepositoryInfo};
+use crate::provider::ReleaseProvider;
+use super::encoding::{decode, InvalidTextPolicy};
+
+/// Separates the fields of one `git log` / `git for-each-ref` record.
+const FIELD: char = '\u{0}';
+/// Separates records; commit and tag messages may contain newlines.
+const RECORD: char = '\u{1e}';
+const LOG_FORMAT: &str = "--format=%H%x00%an%x00%ae%x00%aI%x00%B%x1e";
+
+/// Reads releases and commits from local clones by running `git`, with no network or token.
+/// Each repository is a clone (or bare clone, `name.git`) in `root`, named after it; an
+/// `owner/name` reference is looked up at `root/owner/name`.
+///
+/// Releases are always synthesized from tags, as with `ReleaseSource::Tags`. Local clones have
+/// no pull requests or issues, so lookups of those come back empty. Releases and repositories
+/// link to `host`, where the clones' upstreams are expected to live.
+///
+/// git converts messages that declare their encoding to UTF-8; bytes that still aren't valid
+/// UTF-8 are handled by the client's `InvalidTextPolicy`.
+pub struct LocalGitClient {
+    root: PathBuf,
+    org: String,
+    host: GitHubHost,
+    text_policy: InvalidTextPolicy,
+}
+
+/// A tag from `git for-each-ref refs/tags`.
+struct LocalTag {
+    name: String,
+    /// The commit the tag points at
+    target: String,
+    /// Tagger date of an annotated tag, commit date of a lightweight one
+    date: Option<DateTime<Utc>>,
+    /// Message of an annotated tag
+    message: Option<String>,
+}
+
+impl LocalGitClient {
+    pub fn new(root: PathBuf, org: String, host: GitHubHost) -> Self {
+        Self { root, org, host, text_policy: InvalidTextPolicy::default() }
+    }
+
+    /// Handle git output that isn't valid UTF-8 as `policy` says.
+    pub fn with_text_policy(mut self, policy: InvalidTextPolicy) -> Self {
+        self.text_policy = policy;
+        self
+    }
+
+    /// Directory of a repository's clone, if there is one.
+    fn path(&self, repo: &str) -> Option<PathBuf> {
+        let path = self.root.join(repo);
+        if path.is_dir() {
+            return Some(path);
+        }
+        let bare = self.root.join(format!("{}.git", repo));
+        bare.is_dir().then_some(bare)
+    }
+
+    fn clone_of(&self, repo: &str) -> Result<PathBuf> {
+        self.path(repo).with_context(|| format!("No clone of {} in {}", repo, self.root.display()))
+    }
+
+    async fn run(&self, path: &Path, args: &[&str]) -> Result<std::process::Output> {
+        Command::new("git")
+            .arg("-C")
+            .arg(path)
+            .args(["-c", "i18n.logOutputEncoding=UTF-8"])
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .output()
+            .await
+            .context("Failed to run git; is it installed and on the PATH?")
+    }
+
+    /// Run `git` in `repo`'s clone; `None` when git exits with an error.
+    async fn try_git(&self, repo: &str, args: &[&str]) -> Result<Option<String>> {
+        let path = self.clone_of(repo)?;
+        let output = self.run(&path, args).await?;
+        if !output.status.success() {
+            tracing::debug!("git {} in {} failed: {}", args.join(" "), path.display(), String::from_utf8_lossy(&output.stderr).trim());
+            return Ok(None);
+        }
+        Ok(Some(decode(&output.stdout, self.text_policy)))
+    }
+
+    /// Run `git` in `repo`'s clone, failing with its error output.
+    async fn git(&self, repo: &str, args: &[&str]) -> Result<String> {
+        let path = self.clone_of(repo)?;
+        let output = self.run(&path, args).await?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed in {}: {}",
+                args.join(" "),
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(decode(&output.stdout, self.text_policy))
+    }
+
+    /// Every tag pointing at a commit, newest first (highest version first among tags made in the
+    /// same second).
+    async fn tags(&self, repo: &str) -> Result<Vec<LocalTag>> {
+        let format = "--format=%(refname:strip=2)%00%(objecttype)%00%(objectname)%00%(*objectname)%00%(creatordate:iso-strict)%00%(contents)%1e";
+        let output = self.git(repo, &["for-each-ref", "--sort=-v:refname", "--sort=-creatordate", format, "refs/tags"]).await?;
+
+        let mut tags = Vec::new();
+        for record in records(&output) {
+            let fields: Vec<&str> = record.splitn(6, FIELD).collect();
+            let [name, kind, object, peeled, date, contents] = fields[..] else {
+                continue;
+            };
+            let annotated = kind == "tag";
+            // Tags of trees and blobs have no commit to release
+            if annotated && peeled.is_empty() || !annotated && kind != "commit" {
+                continue;
+            }
+            tags.push(LocalTag {
+                name: name.to_string(),
+                target: if annotated { peeled } else { object }.to_string(),
+                date: parse_date(date),
+                message: annotated.then(|| tag_message(contents)).flatten(),
+            });
+        }
+        Ok(tags)
+    }
+
+    fn release(&self, repo: &str, tag: LocalTag) -> Release {
+        let (owner, name) = repo.split_once('/').unwrap_or((&self.org, repo));
+        let html_url = self.host.tag_url(owner, name, &tag.name);
+        Release::from_tag(&tag.name, tag.date, tag.message, tag.target).with_html_url(html_url)
+    }
+
+    async fn log(&self, repo: &str, args: &[&str]) -> Result<Vec<CommitInfo>> {
+        let mut log_args = vec!["log", LOG_FORMAT];
+        log_args.extend_from_slice(args);
+        let output = self.git(repo, &log_args).await?;
+        Ok(records(&output).filter_map(parse_commit).collect())
+    }
+}
+
+impl ReleaseProvider for LocalGitClient {
+    fn org(&self) -> &str {
+        &self.org
+    }
+
+    /// Clones in the root directory.
+    async fn list_org_repos(&self) -> Result<Vec<String>> {
+        let entries = std::fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?;
+        let mut repos = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if path.join(".git").exists() {
+                repos.push(name.to_string());
+            } else if path.join("HEAD").is_file() {
+                repos.push(name.strip_suffix(".git").unwrap_or(name).to_string());
+            }
+        }
+        repos.sort();
+        Ok(repos)
+    }
+
+    async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        if self.path(repo).is_none() {
+            return Ok(None);
+        }
+        let (owner, name) = repo.split_once('/').unwrap_or((&self.org, repo));
+        let default_branch = self.try_git(repo, &["symbolic-ref", "--short", "HEAD"]).await?
+            .map(|branch| branch.trim().to_string())
+            .filter(|branch| !branch.is_empty());
+        Ok(Some(RepositoryInfo {
+            name: name.to_string(),
+            full_name: format!("{}/{}", owner, name),
+            html_url: format!("{}/{}/{}", self.host.web_url(), owner, name),
+            archived: false,
+            default_branch,
+        }))
+    }
+
+    async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        let tags = self.tags(repo).await?;
+        let toggled = toggle_v_prefix(tag);
+        let found = tags.iter().position(|t| t.name == tag)
+            .or_else(|| tags.iter().position(|t| Some(&t.name) == toggled.as_ref()));
+        match found {
+            Some(i) => {
+                let tag = tags.into_iter().nth(i).expect("position is in range");
+                Ok(Some(self.release(repo, tag)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        Ok(self.tags(repo).await?.into_iter().next().map(|tag| self.release(repo, tag)))
+    }
+
+    async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        Ok(self.tags(repo).await?.into_iter().map(|tag| self.release(repo, tag)).collect())
+    }
+
+    async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        let commit = format!("{}^{{commit}}", reference);
+        Ok(self.try_git(repo, &["rev-parse", "--verify", "--quiet", &commit]).await?
+            .map(|sha| sha.trim().to_string())
+            .filter(|sha| !sha.is_empty()))
+    }
+
+    async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        let commits = self.log(repo, &[&format!("{}..{}", from, to)]).await?;
+        let behind = self.git(repo, &["rev-list", "--count", &format!("{}..{}", to, from)]).await?;
+        let behind_by: u64 = behind.trim().parse().unwrap_or(0);
+        let numstat = self.git(repo, &["diff", "--numstat", &format!("{}...{}", from, to)]).await?;
+        let name_status = self.git(repo, &["diff", "--name-status", &format!("{}...{}", from, to)]).await?;
+        let statuses: Vec<(&str, FileStatus)> = name_status.lines().filter_map(parse_name_status).collect();
+
+        let ahead_by = commits.len() as u64;
+        let status = match (ahead_by, behind_by) {
+            (0, 0) => "identical",
+            (_, 0) => "ahead",
+            (0, _) => "behind",
+            _ => "diverged",
+        };
+        Ok(CommitComparison {
+            status: status.to_string(),
+            ahead_by,
+            behind_by,
+            commits,
+            files: numstat.lines()
+                .filter_map(parse_numstat)
+                .map(|mut file| {
+                    if let Some((_, status)) = statuses.iter().find(|(path, _)| *path == file.filename) {
+                        file.status = *status;
+                    }
+                    file
+                })
+                .collect(),
+        })
+    }
+
+    async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        if self.resolve_ref_sha(repo, until).await?.is_none() {
+            return Ok(vec![]);
+        }
+        self.log(repo, &[until]).await
+    }
+
+    async fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> Result<Option<String>> {
+        self.try_git(repo, &["show", &format!("{}:{}", reference, path)]).await
+    }
+
+    async fn list_directory(&self, repo: &str, path: &str, reference: &str) -> Result<Vec<String>> {
+        let directory = format!("{}/", path.trim_end_matches('/'));
+        let output = self.try_git(repo, &["ls-tree", "--name-only", reference, &directory]).await?;
+        Ok(output.unwrap_or_default().lines().map(str::to_string).collect())
+    }
+
+    async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        let author = format!("--author={}", author);
+        let until = format!("--until={}", until.to_rfc3339());
+        let output = self.try_git(repo, &["log", "-1", "--fixed-strings", &author, &until, "--format=%H", "HEAD"]).await?;
+        Ok(output.is_some_and(|sha| !sha.trim().is_empty()))
+    }
+
+    async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        Ok(self.resolve_ref_sha(repo, "HEAD").await?.is_none())
+    }
+
+    /// Local clones have no pull requests.
+    async fn get_pull_requests_for_commits(&self, _repo: &str, _shas: Vec<String>) -> Result<Vec<PullRequest>> {
+        Ok(vec![])
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        _repo: &str,
+        _base: Option<&str>,
+        _since: Option<DateTime<Utc>>,
+        _until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        Ok(vec![])
+    }
+
+    /// Local clones have no issues.
+    async fn list_open_issues(&self, _repo: &str, _label: &str) -> Result<Vec<Issue>> {
+        Ok(vec![])
+    }
+
+    async fn get_issues(&self, _repo: &str, _numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        Ok(vec![])
+    }
+}
+
+fn records(output: &str) -> impl Iterator<Item = &str> {
+    output.split(RECORD).map(|record| record.trim_start_matches('\n')).filter(|record| !record.is_empty())
+}
+
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date.trim()).ok().map(|date| date.with_timezone(&Utc))
+}
+
+/// An annotated tag's message, without any signature appended to it.
+fn tag_message(contents: &str) -> Option<String> {
+    let message = contents.split("-----BEGIN PGP SIGNATURE-----").next().unwrap_or(contents).trim();
+    (!message.is_empty()).then(|| message.to_string())
+}
+
+/// A commit from a `LOG_FORMAT` record. Signatures aren't checked, so commits count as unverified.
+fn parse_commit(record: &str) -> Option<CommitInfo> {
+    let fields: Vec<&str> = record.splitn(5, FIELD).collect();
+    let [sha, name, email, date, message] = fields[..] else {
+        return None;
+    };
+    Some(CommitInfo {
+        sha: sha.to_string(),
+        message: message.trim_end().to_string(),
+        author: CommitAuthor {
+            name: name.to_string(),
+            email: email.to_string(),
+            username: None,
+        },
+        date: parse_date(date)?,
+        verified: false,
+    })
+}
+
+/// A `git diff --numstat` line: additions, deletions and path (`-` counts for binary files).
+fn parse_numstat(line: &str) -> Option<ChangedFile> {
+    let mut fields = line.splitn(3, '\t');
+    let additions = fields.next()?.parse().unwrap_or(0);
+    let deletions = fields.next()?.parse().unwrap_or(0);
+    Some(ChangedFile { filename: fields.next()?.to_string(), additions, deletions, status: FileStatus::Modified })
+}
+
+/// A `git diff --name-status` line: the status letter (with a similarity score for renames)
+/// and the path, or the old and new paths of a rename.
+fn parse_name_status(line: &str) -> Option<(&str, FileStatus)> {
+    let mut fields = line.split('\t');
+    let status = match fields.next()?.chars().next()? {
+        'A' => FileStatus::Added,
+        'D' => FileStatus::Removed,
+        'R' => FileStatus::Renamed,
+        _ => FileStatus::Modified,
+    };
+    Some((fields.next_back()?, status))
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// What to do with bytes of git output that aren't valid UTF-8, such as Latin-1 commit
+/// messages from old repositories that don't declare their encoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvalidTextPolicy {
+    /// Read each invalid byte as Windows-1252 (a superset of Latin-1), so `caf\xe9` becomes
+    /// `café`
+    #[default]
+    Transcode,
+    /// Put U+FFFD (`�`) in place of each invalid sequence
+    Replace,
+    /// Drop invalid sequences
+    Strip,
+}
+
+impl std::str::FromStr for InvalidTextPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "transcode" => Ok(InvalidTextPolicy::Transcode),
+            "replace" => Ok(InvalidTextPolicy::Replace),
+            "strip" => Ok(InvalidTextPolicy::Strip),
+            _ => Err(format!("Unknown invalid text policy: {} (expected transcode, replace or strip)", s)),
+        }
+    }
+}
+
+/// Characters of Windows-1252 bytes 0x80-0x9F, where it differs from Latin-1; the five bytes it
+/// leaves undefined map to U+FFFD.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{fffd}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{fffd}', 'Ž', '\u{fffd}',
+    '\u{fffd}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{fffd}', 'ž', 'Ÿ',
+];
+
+/// Decode `bytes` as UTF-8, handling invalid sequences as `policy` says. Valid UTF-8 comes out
+/// unchanged whatever the policy.
+pub fn decode(bytes: &[u8], policy: InvalidTextPolicy) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                return text;
+            }
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                // Checked by from_utf8 above
+                text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                let invalid_len = e.error_len().unwrap_or(after.len());
+                let (invalid, after) = after.split_at(invalid_len);
+                match policy {
+                    InvalidTextPolicy::Transcode => text.extend(invalid.iter().map(|&byte| windows_1252(byte))),
+                    InvalidTextPolicy::Replace => text.push('\u{fffd}'),
+                    InvalidTextPolicy::Strip => {}
+                }
+                rest = after;
+            }
+        }
+    }
+}
+
+fn windows_1252(byte: u8) -> char {
+    match byte {
+        0x80..=0x9f => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
@@ -1,13 +1,21 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use futures::StreamExt;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tracing_subscriber;
 
-mod aggregator;
-mod config;
-mod github;
+use release_aggregator::{aggregator, config, github, integrations, local, provider, serve, state};
 
 use aggregator::changelog_generator::OutputFormat;
+use aggregator::commit_analyzer::CommitSort;
+use aggregator::branding::Branding;
+use aggregator::scaffold::Scaffold;
+use aggregator::entry_format::EntryFormats;
+use aggregator::locale::Locale;
+use github::types::{ApiBackend, DateSource, PreviousStrategy, ReleaseSource};
+use config::types::feature_flag;
+use provider::{ProviderKind, ProviderRouting, ReleaseProvider};
 
 #[derive(Parser)]
 #[command(name = "release-aggregator")]
@@ -15,11 +23,115 @@ use aggregator::changelog_generator::OutputFormat;
 struct Cli {
     /// GitHub token (can also be set via GITHUB_TOKEN env var)
     #[arg(long, env = "GITHUB_TOKEN")]
-    token: String,
+    token: Option<String>,
 
-    /// Organization or user name
+    /// Token for the commands that write to GitHub (publish, prefill, and serve's replies), so
+    /// the token above can be read-only. Also read from GITHUB_WRITE_TOKEN, but only when such
+    /// a command runs; without either, writes use the read credentials
+    #[arg(long)]
+    write_token: Option<String>,
+
+    /// Authenticate as this GitHub App instead of with a token (needs --private-key-path)
+    #[arg(long, env = "GITHUB_APP_ID", requires = "private_key_path")]
+    app_id: Option<u64>,
+
+    /// PEM private key of the GitHub App given with --app-id
+    #[arg(long, env = "GITHUB_APP_PRIVATE_KEY_PATH", requires = "app_id")]
+    private_key_path: Option<PathBuf>,
+
+    /// Installation of the GitHub App to act as (looked up from the org if not given)
+    #[arg(long, env = "GITHUB_APP_INSTALLATION_ID", requires = "app_id")]
+    installation_id: Option<u64>,
+
+    /// Organization or user name (falls back to github.org in the config file); repositories
+    /// given as owner/name are read from their own owner
     #[arg(short, long, env = "GITHUB_ORG")]
-    org: String,
+    org: Option<String>,
+
+    /// Path to a TOML or YAML configuration file (defaults to ./release-inator.toml if present)
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Profile from the config file's [profiles] to apply, e.g. staging or prod
+    #[arg(long, env = "RELEASE_INATOR_PROFILE")]
+    profile: Option<String>,
+
+    /// Path to the state file tracking previously seen releases
+    #[arg(long, default_value = state::store::DEFAULT_STATE_PATH)]
+    state: PathBuf,
+
+    /// Ordering of commits within a category: api, date, scope, pr, alphabetical
+    #[arg(long)]
+    sort: Option<CommitSort>,
+
+    /// Release timestamp used for ordering and display: published (falls back to created) or created
+    #[arg(long, default_value = "published")]
+    date_source: DateSource,
+
+    /// How each component's previous release is found: date (most recent before it) or semver (highest lower version)
+    #[arg(long, default_value = "date")]
+    previous_strategy: PreviousStrategy,
+
+    /// Where component versions come from: releases (GitHub Releases) or tags (git tags, with
+    /// annotated tag messages as notes). `source` in a repository's settings overrides it
+    #[arg(long, default_value = "releases")]
+    source: ReleaseSource,
+
+    /// How GitHub is read: rest (requests per repository, cached by ETag) or graphql (repositories
+    /// and releases prefetched in bulk queries, for large orgs)
+    #[arg(long, env = "RELEASE_INATOR_API", default_value = "rest")]
+    api: ApiBackend,
+
+    /// REST API root of a GitHub Enterprise Server, e.g. https://ghe.example.com/api/v3
+    /// (overrides github.api_url; defaults to github.com)
+    #[arg(long, env = "GITHUB_API_URL")]
+    github_url: Option<String>,
+
+    /// Where the repositories are read from: github, gitlab, or local (clones in --repos-dir,
+    /// offline). `provider` in a repository's settings overrides it, so one train can span them
+    #[arg(long, env = "RELEASE_INATOR_PROVIDER", default_value = "github")]
+    provider: ProviderKind,
+
+    /// Web address of a self-hosted GitLab, e.g. https://gitlab.example.com (overrides
+    /// gitlab.url; defaults to gitlab.com)
+    #[arg(long, env = "GITLAB_URL")]
+    gitlab_url: Option<String>,
+
+    /// Directory of local clones read with --provider local (or provider = "local" in a
+    /// repository's settings), each named after its repository
+    #[arg(long, env = "RELEASE_INATOR_REPOS_DIR", default_value = ".")]
+    repos_dir: PathBuf,
+
+    /// What to do with bytes of local clones' history that aren't valid UTF-8: transcode
+    /// (read them as Windows-1252/Latin-1), replace (with U+FFFD) or strip
+    #[arg(long, default_value = "transcode")]
+    invalid_text: local::encoding::InvalidTextPolicy,
+
+    /// Minimum gap between API requests, e.g. 100ms or 1s (overrides github.requests_per_second)
+    #[arg(long, value_parser = parse_interval)]
+    throttle: Option<std::time::Duration>,
+
+    /// Aggregate archived repositories instead of skipping them (for historical backfills)
+    #[arg(long)]
+    include_archived: bool,
+
+    /// Maximum pages of 100 followed when listing commits or releases
+    #[arg(long, default_value_t = github::client::DEFAULT_MAX_PAGES)]
+    max_pages: usize,
+
+    /// Pin the generation time and sort every collection, so identical inputs render byte-for-byte
+    /// identical output (for snapshot tests)
+    #[arg(long)]
+    deterministic: bool,
+
+    /// Generation time recorded in output, as Unix seconds or RFC 3339 (with --deterministic,
+    /// defaults to the Unix epoch)
+    #[arg(long, env = "SOURCE_DATE_EPOCH", value_parser = parse_timestamp)]
+    generated_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Maximum number of repositories fetched at once
+    #[arg(long, default_value_t = aggregator::release_fetcher::DEFAULT_CONCURRENCY)]
+    concurrency: usize,
 
     #[command(subcommand)]
     command: Commands,
@@ -27,35 +139,276 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Serve stored trains over HTTP for dashboards
+    Serve {
+        /// Directory of JSON exports written by `generate -f json`
+        #[arg(long, default_value = "releases")]
+        data: PathBuf,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
+
+        /// Bearer token with internal (unredacted) access; more tokens can be set under `serve.tokens` in the config
+        #[arg(long, env = "RELEASE_INATOR_API_TOKEN", hide_env_values = true)]
+        api_token: Option<String>,
+    },
+
+    /// Write past and scheduled release trains as an iCalendar (.ics) feed
+    Calendar {
+        /// Directory of JSON exports written by `generate -f json`
+        #[arg(long, default_value = "releases")]
+        data: PathBuf,
+
+        /// Output file path (stdout if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Link to each train's notes, with {version} replaced; overrides calendar.notes_url
+        #[arg(long)]
+        notes_url: Option<String>,
+
+        /// Also create or update the events in the Google Calendar set under calendar.google
+        #[arg(long)]
+        google: bool,
+    },
+
+    /// Create or check a configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
     /// Generate release notes for a specific version
     Generate {
         /// Version/tag name to aggregate
-        #[arg(short, long)]
-        version: String,
+        #[arg(short, long, visible_alias = "to", required_unless_present_any = ["unreleased", "since", "versions", "all_matching"])]
+        version: Option<String>,
+
+        /// Generate each of these versions (comma-separated) in one run, one document per
+        /// version in the --output directory
+        #[arg(long, value_delimiter = ',', conflicts_with_all = ["version", "from", "unreleased", "since", "include_train", "rollout_plan"])]
+        versions: Vec<String>,
+
+        /// Generate every version with a release whose tag matches this glob, e.g. 'v2024.*',
+        /// like --versions
+        #[arg(long, conflicts_with_all = ["version", "versions", "from", "unreleased", "since", "include_train", "rollout_plan"])]
+        all_matching: Option<String>,
+
+        /// Compare against this tag instead of each component's previous release, covering
+        /// everything between the two versions
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Report what each default branch would ship next instead of an existing release
+        #[arg(long, conflicts_with = "from")]
+        unreleased: bool,
+
+        /// Roll up every release published on or after this date (YYYY-MM-DD), e.g. for a quarterly report
+        #[arg(long, conflicts_with_all = ["from", "unreleased"])]
+        since: Option<chrono::NaiveDate>,
+
+        /// Last day of the --since window, inclusive (default: today)
+        #[arg(long, requires = "since")]
+        until: Option<chrono::NaiveDate>,
 
-        /// Comma-separated list of repository names
+        /// Comma-separated list of repository names (owner/name for another owner's)
         #[arg(short, long, value_delimiter = ',')]
         repos: Vec<String>,
 
-        /// Output file path (stdout if not specified)
+        /// Output file path (stdout if not specified); the directory to write into with
+        /// --versions or --all-matching
         #[arg(short, long)]
         output: Option<PathBuf>,
 
-        /// Output format
-        #[arg(short = 'f', long, default_value = "markdown")]
-        format: OutputFormat,
+        /// Output format (defaults to output.format in the config, then markdown)
+        #[arg(short = 'f', long)]
+        format: Option<OutputFormat>,
 
-        /// Include PR links
+        /// Write numbers and dates for this locale: en, de, fr (defaults to output.locale in
+        /// the config; plain numbers and YYYY-MM-DD dates without one)
+        #[arg(long)]
+        locale: Option<Locale>,
+
+        /// Handlebars template to render with (defaults to output.template in the config)
+        #[arg(long)]
+        template: Option<PathBuf>,
+
+        /// Also write a one-paragraph Markdown summary (counts and highlights) to this file;
+        /// `{version}` in the path is replaced with each version
         #[arg(long)]
+        short_output: Option<PathBuf>,
+
+        /// Handlebars template for the short summary (defaults to output.short_template in the
+        /// config, then the built-in one)
+        #[arg(long, requires = "short_output")]
+        short_template: Option<PathBuf>,
+
+        #[command(flatten)]
+        branding: Box<BrandingArgs>,
+
+        #[command(flatten)]
+        scaffold: Box<ScaffoldArgs>,
+
+        /// Build entries from `commits` (default), from `prs` merged between the release
+        /// dates, categorized by label (suits squash-merge repositories), or from changelog
+        /// `fragments` added between the release tags
+        #[arg(long, default_value = "commits")]
+        mode: aggregator::ChangelogMode,
+
+        /// Include PR links
+        #[arg(long, overrides_with = "no_include_prs")]
         include_prs: bool,
 
+        /// Leave PR links out, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+
         /// Include issue links
-        #[arg(long)]
+        #[arg(long, overrides_with = "no_include_issues")]
         include_issues: bool,
 
+        /// Leave issue links out, even if the config file's features.include_issues turns them on
+        #[arg(long, overrides_with = "include_issues")]
+        no_include_issues: bool,
+
+        /// List each released component's open issues labeled as known issues
+        /// (features.known_issue_label, default known-issue), with their workarounds
+        #[arg(long, overrides_with = "no_include_known_issues")]
+        include_known_issues: bool,
+
+        /// Leave known issues out, even if the config file's features.include_known_issues turns them on
+        #[arg(long, overrides_with = "include_known_issues")]
+        no_include_known_issues: bool,
+
+        /// Categorize commits by type (feat, fix, etc.)
+        #[arg(long, overrides_with = "no_categorize")]
+        categorize: bool,
+
+        /// Don't categorize commits, even if the config file's features.categorize_commits does
+        #[arg(long, overrides_with = "categorize")]
+        no_categorize: bool,
+
+        /// Previously generated JSON exports to nest as sub-trains
+        #[arg(long, value_delimiter = ',')]
+        include_train: Vec<PathBuf>,
+
+        /// Append an adoption appendix with asset downloads of each component's previous release
+        #[arg(long)]
+        download_stats: bool,
+
+        /// Score each released component's rollout risk, shown in the summary and JSON output
+        #[arg(long)]
+        risk: bool,
+
+        /// Write a staged rollout plan as JSON to this path and add it to the notes (implies --risk)
+        #[arg(long)]
+        rollout_plan: Option<PathBuf>,
+
+        /// Add this release's entries to a JSON search index (created if missing)
+        #[arg(long)]
+        search_index: Option<PathBuf>,
+
+        /// Write the aggregated data into a SQLite database (created if missing)
+        #[arg(long)]
+        export_sqlite: Option<PathBuf>,
+
+        /// Write trains, components and commits as Parquet files under this directory
+        #[arg(long)]
+        export_parquet: Option<PathBuf>,
+    },
+
+    /// Generate the notes of every past version into a directory, with an index, to bootstrap
+    /// a release-notes site
+    Backfill {
+        /// Directory to write one document per version and the index into
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Comma-separated list of repository names (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// Only versions whose tag matches this glob, e.g. 'v2*' (default: all)
+        #[arg(long, default_value = "*")]
+        matching: String,
+
+        /// Output format (defaults to output.format in the config, then markdown)
+        #[arg(short = 'f', long)]
+        format: Option<OutputFormat>,
+
+        /// Write numbers and dates for this locale: en, de, fr (defaults to output.locale in
+        /// the config; plain numbers and YYYY-MM-DD dates without one)
+        #[arg(long)]
+        locale: Option<Locale>,
+
+        /// Handlebars template to render with (defaults to output.template in the config)
+        #[arg(long)]
+        template: Option<PathBuf>,
+
+        #[command(flatten)]
+        branding: Box<BrandingArgs>,
+
+        /// Leave versions whose document already exists alone, so an interrupted backfill
+        /// can be resumed
+        #[arg(long)]
+        skip_existing: bool,
+
+        /// Include PR links
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Leave PR links out, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+
         /// Categorize commits by type (feat, fix, etc.)
+        #[arg(long, overrides_with = "no_categorize")]
+        categorize: bool,
+
+        /// Don't categorize commits, even if the config file's features.categorize_commits does
+        #[arg(long, overrides_with = "categorize")]
+        no_categorize: bool,
+    },
+
+    /// Build a static HTML site of every past version: a release list with a search box, and
+    /// per version an index page and one page per component
+    Site {
+        /// Directory to write the site into
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Comma-separated list of repository names (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// Only versions whose tag matches this glob, e.g. 'v2*' (default: all)
+        #[arg(long, default_value = "*")]
+        matching: String,
+
+        /// Write numbers and dates for this locale: en, de, fr (defaults to output.locale in
+        /// the config; plain numbers and YYYY-MM-DD dates without one)
         #[arg(long)]
+        locale: Option<Locale>,
+
+        #[command(flatten)]
+        branding: Box<BrandingArgs>,
+
+        /// Include PR links
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Leave PR links out, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+
+        /// Categorize commits by type (feat, fix, etc.)
+        #[arg(long, overrides_with = "no_categorize")]
         categorize: bool,
+
+        /// Don't categorize commits, even if the config file's features.categorize_commits does
+        #[arg(long, overrides_with = "categorize")]
+        no_categorize: bool,
     },
 
     /// Check if all repos have a specific release
@@ -65,6 +418,28 @@ enum Commands {
         
         #[arg(short, long, value_delimiter = ',')]
         repos: Vec<String>,
+
+        /// Also verify each component's container image tag exists in its registry
+        #[arg(long)]
+        artifacts: bool,
+
+        /// Also verify each release has the assets listed in its repository's `assets` setting
+        #[arg(long)]
+        assets: bool,
+
+        /// Output format: markdown (a table with a row per repository) or json (a report per
+        /// repository)
+        #[arg(short = 'f', long, default_value = "markdown")]
+        format: OutputFormat,
+
+        /// Stop at the first repository missing the release, in the order given
+        #[arg(long)]
+        fail_fast: bool,
+
+        /// Consistency rules to skip: prerelease, draft, stale-tag, downgrade (comma-separated;
+        /// added to `check.disabled_rules`)
+        #[arg(long, value_delimiter = ',')]
+        disable_rule: Vec<aggregator::check::CheckRule>,
     },
 
     /// List recent releases across repositories
@@ -72,90 +447,1517 @@ enum Commands {
         #[arg(short, long, value_delimiter = ',')]
         repos: Vec<String>,
         
+        /// Releases to show per repository (in total with --org-wide), counted after filtering
         #[arg(long, default_value = "10")]
         limit: usize,
+
+        /// List the newest releases across every repository in the org, filtered by
+        /// repos.include and repos.exclude
+        #[arg(long, conflicts_with_all = ["repos", "sort"])]
+        org_wide: bool,
+
+        /// Output format: table, json or csv
+        #[arg(short = 'f', long, default_value = "table")]
+        format: aggregator::listing::ListFormat,
+
+        /// Order of each repository's releases: date (newest first) or semver (highest first)
+        #[arg(long, default_value = "date")]
+        sort: aggregator::listing::ListSort,
+
+        /// Also list prereleases, which are left out by default
+        #[arg(long)]
+        include_prereleases: bool,
+
+        /// Leave out draft releases
+        #[arg(long)]
+        exclude_drafts: bool,
     },
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+    /// Generate a conventional changelog for a single repository
+    Single {
+        /// Version/tag name to generate notes for
+        #[arg(short, long)]
+        version: String,
 
-    let cli = Cli::parse();
+        /// Repository name
+        #[arg(short, long)]
+        repo: String,
 
-    // Create GitHub client
-    let github_client = github::client::GitHubClient::new(cli.token.clone(), cli.org.clone()).await?;
+        /// Output file path (stdout if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
 
-    match cli.command {
-        Commands::Generate {
-            version,
-            repos,
-            output,
-            format,
-            include_prs,
-            include_issues,
-            categorize,
-        } => {
-            let config = aggregator::AggregatorConfig {
-                include_prs,
-                include_issues,
-                categorize_commits: categorize,
-                template_path: None,
-            };
+        /// Output format (defaults to output.format in the config, then markdown)
+        #[arg(short = 'f', long)]
+        format: Option<OutputFormat>,
 
-            let aggregator = aggregator::ReleaseAggregator::new(github_client, config);
-            let release = aggregator.aggregate(&version, repos).await?;
+        /// Write numbers and dates for this locale: en, de, fr (defaults to output.locale in
+        /// the config; plain numbers and YYYY-MM-DD dates without one)
+        #[arg(long)]
+        locale: Option<Locale>,
 
-            let generator = aggregator::changelog_generator::ChangelogGenerator::new(format, None)?;
-            let content = generator.generate(&release)?;
+        #[command(flatten)]
+        branding: BrandingArgs,
 
-            if let Some(output_path) = output {
-                std::fs::write(output_path, content)?;
-                println!("Release notes written successfully!");
-            } else {
-                println!("{}", content);
-            }
-        }
-        Commands::Check { version, repos } => {
-            println!("Checking release {} for repositories: {:?}", version, repos);
-            
-            let mut all_present = true;
-            for repo in repos {
-                let release = github_client.get_release(&repo, &version).await?;
-                if release.is_some() {
-                    println!("✓ {}: Release {} found", repo, version);
-                } else {
-                    println!("✗ {}: Release {} not found", repo, version);
-                    all_present = false;
-                }
-            }
-            
-            if !all_present {
-                std::process::exit(1);
-            }
-        }
-        Commands::List { repos, limit } => {
-            println!("Recent releases (limit: {}):", limit);
-            println!();
-            
-            for repo in repos {
-                println!("Repository: {}", repo);
-                let releases = github_client.list_releases(&repo, limit).await?;
-                
-                if releases.is_empty() {
-                    println!("  No releases found");
-                } else {
-                    for release in releases {
-                        println!("  - {}: {}", release.tag_name, release.published_at.unwrap_or_default());
-                    }
-                }
-                println!();
-            }
-        }
-    }
+        /// Include PR links
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Leave PR links out, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+    },
 
+    /// Changelog of one repository between two refs: tags, branches or commit SHAs
+    Compare {
+        /// Repository name
+        #[arg(short, long)]
+        repo: String,
+
+        /// Ref the changes are counted from, e.g. the release a hotfix branched from
+        #[arg(long)]
+        from: String,
+
+        /// Ref the changes are counted up to
+        #[arg(long)]
+        to: String,
+
+        /// Output file path (stdout if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format (defaults to output.format in the config, then markdown)
+        #[arg(short = 'f', long)]
+        format: Option<OutputFormat>,
+
+        /// Write numbers and dates for this locale: en, de, fr (defaults to output.locale in
+        /// the config; plain numbers and YYYY-MM-DD dates without one)
+        #[arg(long)]
+        locale: Option<Locale>,
+
+        #[command(flatten)]
+        branding: BrandingArgs,
+
+        /// Include PR links
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Leave PR links out, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+    },
+
+    /// Publish aggregated notes as a release in the umbrella repository
+    Publish {
+        /// Version/tag name of the train
+        #[arg(short, long)]
+        version: String,
+
+        /// Comma-separated list of repository names (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// Repository that receives the umbrella release (defaults to publish.repo in config)
+        #[arg(long)]
+        release_repo: Option<String>,
+
+        /// Create the umbrella release as a draft
+        #[arg(long)]
+        draft: bool,
+
+        /// Whether the umbrella release becomes the release repository's latest: true, false
+        /// (e.g. for a hotfix train of an older version) or legacy (only if it is the newest).
+        /// Defaults to publish.make_latest in the config, then GitHub's default of true
+        #[arg(long)]
+        make_latest: Option<github::types::MakeLatest>,
+
+        /// Include PR links
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Leave PR links out, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+
+        /// List each released component's open issues labeled as known issues
+        #[arg(long, overrides_with = "no_include_known_issues")]
+        include_known_issues: bool,
+
+        /// Leave known issues out, even if the config file's features.include_known_issues turns them on
+        #[arg(long, overrides_with = "include_known_issues")]
+        no_include_known_issues: bool,
+
+        /// Create a matching Jira version in the configured projects and release it
+        #[arg(long)]
+        jira: bool,
+
+        /// Schedule a maintenance notice for the train on the status page in the config
+        #[arg(long)]
+        status_page: bool,
+
+        /// Start of the status page maintenance window, as Unix seconds or RFC 3339 (default: now)
+        #[arg(long, requires = "status_page", value_parser = parse_timestamp)]
+        window_start: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Announce the train to the targets in the config's [notify] section
+        #[arg(long)]
+        notify: bool,
+
+        /// Append a "Part of release train" link to each component's GitHub release
+        #[arg(long)]
+        backfill_links: bool,
+
+        /// Append an adoption appendix with asset downloads of each component's previous release
+        #[arg(long)]
+        download_stats: bool,
+
+        /// What to do when the notes exceed GitHub's release body limit: details or truncate
+        #[arg(long, default_value = "details")]
+        oversize: aggregator::publisher::OversizeStrategy,
+
+        /// Where the full notes are hosted, linked from a truncated body (by default they are
+        /// committed to release-notes/<version>.md in the release repository)
+        #[arg(long)]
+        full_notes_url: Option<String>,
+
+        /// When the train was already published, write the entries added and removed per
+        /// component since then to this file, for the update announcement
+        #[arg(long)]
+        diff_output: Option<PathBuf>,
+
+        #[command(flatten)]
+        scaffold: ScaffoldArgs,
+    },
+
+    /// List announcements that failed to send and are queued for a retry
+    Notify {
+        /// Retry the queued announcements now, keeping the ones that fail again
+        #[arg(long)]
+        flush_queue: bool,
+    },
+
+    /// Report the reactions on published umbrella releases, to judge how far announcements reached
+    Feedback {
+        /// Comma-separated train versions (defaults to the most recently published trains)
+        #[arg(short, long, value_delimiter = ',')]
+        versions: Vec<String>,
+
+        /// Repository holding the umbrella releases (defaults to publish.repo in config)
+        #[arg(long)]
+        release_repo: Option<String>,
+
+        /// How many recent trains to report when no versions are given
+        #[arg(long, default_value = "10")]
+        limit: usize,
+
+        /// Output format (markdown or json)
+        #[arg(short = 'f', long, default_value = "markdown")]
+        format: OutputFormat,
+    },
+
+    /// Create draft releases prefilled with unreleased commits for repos that haven't drafted one
+    Prefill {
+        /// Version/tag name the drafts are created for
+        #[arg(short, long)]
+        version: String,
+
+        /// Comma-separated list of repository names (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// Include PR links
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Leave PR links out, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+    },
+
+    /// Report problems in a release's source data, such as uncategorized commits
+    Lint {
+        /// Version/tag name to lint
+        #[arg(short, long)]
+        version: String,
+
+        /// Comma-separated list of repository names (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// Output format (markdown or json)
+        #[arg(short = 'f', long, default_value = "markdown")]
+        format: OutputFormat,
+
+        /// Look up the org teams of each uncategorized commit's PR author
+        #[arg(long)]
+        owners: bool,
+
+        #[command(flatten)]
+        scaffold: ScaffoldArgs,
+    },
+
+    /// Produce a compliance evidence bundle for a release
+    Evidence {
+        /// Version/tag name to collect evidence for
+        #[arg(short, long)]
+        version: String,
+
+        /// Comma-separated list of repository names (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// Output directory for the bundle (JSON to stdout if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Pre-fetch releases, commits and pull requests so later runs are answered from the cache
+    WarmCache {
+        /// Comma-separated list of repository names (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// How far back to fetch releases, e.g. 90d, 12w, or 48h
+        #[arg(long, default_value = "90d", value_parser = parse_age)]
+        since: chrono::Duration,
+
+        /// Also fetch the pull requests of each commit
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Don't fetch pull requests, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+    },
+
+    /// Time aggregation and rendering against recorded API responses, compared with the last baseline
+    Bench {
+        /// Fixtures directory to replay, as written by --record
+        #[arg(long, required_unless_present = "record", conflicts_with = "record")]
+        replay: Option<PathBuf>,
+
+        /// Record a train's API responses into this fixtures directory
+        #[arg(long, requires = "version")]
+        record: Option<PathBuf>,
+
+        /// Version/tag name to record
+        #[arg(short, long)]
+        version: Option<String>,
+
+        /// Comma-separated list of repository names to record (owner/name for another owner's)
+        #[arg(short, long, value_delimiter = ',')]
+        repos: Vec<String>,
+
+        /// Record pull request lookups as well
+        #[arg(long, overrides_with = "no_include_prs")]
+        include_prs: bool,
+
+        /// Don't record pull request lookups, even if the config file's features.include_prs turns them on
+        #[arg(long, overrides_with = "include_prs")]
+        no_include_prs: bool,
+
+        /// Number of replays to run; the median time is reported
+        #[arg(long, default_value = "5")]
+        iterations: usize,
+
+        /// Save this replay as the baseline later replays are compared with
+        #[arg(long)]
+        save_baseline: bool,
+
+        /// Percent slowdown over the baseline that counts as a regression
+        #[arg(long, default_value = "20")]
+        tolerance: f64,
+    },
+}
+
+/// HTML branding flags; each overrides the matching `[branding]` config value.
+#[derive(Args)]
+struct BrandingArgs {
+    /// Stylesheet added after the built-in HTML styles
+    #[arg(long)]
+    css: Option<PathBuf>,
+
+    /// Logo image URL or path shown at the top of HTML output
+    #[arg(long)]
+    logo: Option<String>,
+
+    /// Handlebars template rendered above the notes in HTML output
+    #[arg(long)]
+    header: Option<PathBuf>,
+
+    /// Handlebars template rendered below the notes in HTML output
+    #[arg(long)]
+    footer: Option<PathBuf>,
+}
+
+impl BrandingArgs {
+    fn resolve(self, file_config: Option<&config::types::Config>) -> Result<Branding> {
+        let configured = file_config.map(|c| c.branding.clone()).unwrap_or_default();
+        let css = self.css.or_else(|| configured.css.map(PathBuf::from));
+        let header = self.header.or_else(|| configured.header.map(PathBuf::from));
+        let footer = self.footer.or_else(|| configured.footer.map(PathBuf::from));
+
+        let mut branding = Branding::load(css.as_deref(), self.logo.or(configured.logo), header.as_deref(), footer.as_deref())?;
+        branding.social_image = configured.social_image;
+        branding.site_name = configured.site_name;
+        Ok(branding)
+    }
+}
+
+/// Text for the config's `[[scaffold.sections]]`.
+#[derive(Args, Clone)]
+struct ScaffoldArgs {
+    /// Markdown file filling in the scaffold sections under `## Title` headings (overrides
+    /// scaffold.file)
+    #[arg(long)]
+    sections_file: Option<PathBuf>,
+
+    /// Fill in a scaffold section, as TITLE=TEXT; repeat for more. Takes precedence over the
+    /// sections file
+    #[arg(long = "section", value_name = "TITLE=TEXT")]
+    section: Vec<String>,
+}
+
+impl ScaffoldArgs {
+    fn resolve(self, file_config: Option<&config::types::Config>, version: &str) -> Result<Scaffold> {
+        let configured = file_config.map(|c| c.scaffold.clone()).unwrap_or_default();
+        Scaffold::for_train(&configured, self.sections_file.as_deref(), &self.section, version)
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Write a commented starter config with the default settings
+    Init {
+        /// Where to write the file
+        #[arg(default_value = config::types::DEFAULT_CONFIG_FILES[0])]
+        path: PathBuf,
+
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Parse a config file and report unknown keys and conflicting settings
+    Validate {
+        /// Config file to check (defaults to --config, then ./release-inator.toml)
+        path: Option<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+
+    // Config commands work on the file alone and need neither a token nor an org
+    if let Commands::Config { action } = &cli.command {
+        return run_config_command(action, &cli).await;
+    }
+
+    let config_path = cli.config.clone().or_else(config::types::Config::discover);
+    let file_config = match (&config_path, &cli.profile) {
+        (Some(path), Some(profile)) => Some(config::types::Config::load(path)?.with_profile(profile)?),
+        (Some(path), None) => Some(config::types::Config::load(path)?),
+        (None, Some(profile)) => anyhow::bail!("--profile {} needs a config file; pass --config", profile),
+        (None, None) => None,
+    };
+    if let (Some(path), Some(file_config)) = (&config_path, &file_config) {
+        for problem in config::validate::validate(file_config) {
+            eprintln!("⚠ {}: {} (run `config validate` for details)", path.display(), problem);
+        }
+    }
+
+    // Serving reads earlier exports, and only calls GitHub to answer slash commands
+    if let Commands::Serve { data, bind, api_token } = &cli.command {
+        let (commands, slack) = match &file_config {
+            Some(config) if config.serve.commands.is_some() || config.serve.slack.is_some() => {
+                let host = github_host(&cli, Some(config))?;
+                let auth = github_auth(&cli, &host)
+                    .ok_or_else(|| anyhow::anyhow!("serve commands need GitHub credentials; pass --token (or set GITHUB_TOKEN), --app-id and --private-key-path, or log in with `gh auth login`"))?;
+                let org = cli.org.clone()
+                    .or_else(|| Some(config.github.org.clone()).filter(|org| !org.is_empty()))
+                    .ok_or_else(|| anyhow::anyhow!("serve commands need an organization; pass --org, set GITHUB_ORG, or set github.org in the config"))?;
+                let connection = connection(&cli, auth, org, host, ProviderRouting::default());
+                let reply_auth = write_auth(cli.write_token.as_deref());
+                serve::chat_commands(connection, reply_auth, config, aggregator_config(&cli, Some(config))?).await?
+            }
+            _ => (None, None),
+        };
+        return serve::run(data, bind, api_token.clone(), file_config.as_ref(), commands, slack).await;
+    }
+    if let Commands::Calendar { data, output, notes_url, google } = &cli.command {
+        return run_calendar_command(data, output.as_deref(), notes_url.clone(), *google, file_config.as_ref()).await;
+    }
+    if let Commands::Notify { flush_queue } = &cli.command {
+        return run_notify_command(&cli.state, *flush_queue, file_config.as_ref()).await;
+    }
+    // Replays answer every call from the fixtures, so need no credentials
+    if let Commands::Bench { replay: Some(dir), iterations, save_baseline, tolerance, .. } = &cli.command {
+        return run_bench_replay(&cli, dir, *iterations, *save_baseline, *tolerance, file_config.as_ref()).await;
+    }
+
+    if cli.write_token.is_some() && !matches!(cli.command, Commands::Publish { .. } | Commands::Prefill { .. }) {
+        eprintln!("⚠ --write-token is only used by publish, prefill and serve; ignoring it");
+    }
+    let routing = ProviderRouting::from_config(cli.provider, file_config.as_ref());
+    if cli.provider != ProviderKind::Github
+        && !matches!(cli.command, Commands::Generate { .. } | Commands::Backfill { .. } | Commands::Site { .. })
+    {
+        anyhow::bail!("Only `generate`, `backfill` and `site` read GitLab and local clones; run this command with --provider github");
+    }
+    let host = github_host(&cli, file_config.as_ref())?;
+    let auth = match github_auth(&cli, &host) {
+        Some(auth) => auth,
+        None if !routing.uses(ProviderKind::Github) => github::auth::GitHubAuth::Anonymous,
+        None => anyhow::bail!("No GitHub credentials given; pass --token (or set GITHUB_TOKEN), --app-id and --private-key-path, or log in with `gh auth login`"),
+    };
+    let org = cli.org.clone()
+        .or_else(|| file_config.as_ref().map(|c| c.github.org.clone()).filter(|org| !org.is_empty()))
+        .or_else(|| file_config.as_ref().and_then(|c| c.gitlab.as_ref()).and_then(|g| g.group.clone()).filter(|_| cli.provider == ProviderKind::Gitlab))
+        .or_else(|| (cli.provider == ProviderKind::Local).then(|| local_org(&cli.repos_dir)).flatten())
+        .ok_or_else(|| anyhow::anyhow!("No organization given; pass --org, set GITHUB_ORG, or set github.org in the config"))?;
+    let features = file_config.as_ref().map(|c| &c.features);
+    let default_format = match file_config.as_ref() {
+        Some(config) => config.output.format.parse().map_err(anyhow::Error::msg)?,
+        None => OutputFormat::Markdown,
+    };
+    let default_locale = file_config.as_ref()
+        .and_then(|c| c.output.locale.as_deref())
+        .map(str::parse::<Locale>)
+        .transpose()
+        .map_err(anyhow::Error::msg)?;
+    let default_template = file_config.as_ref()
+        .and_then(|c| c.output.template.as_ref())
+        .map(PathBuf::from);
+    let base_config = aggregator_config(&cli, file_config.as_ref())?;
+    let entry_formats = match &file_config {
+        Some(config) => EntryFormats::from_config(&config.entry_formats)?,
+        None => EntryFormats::default(),
+    };
+    let connection = connection(&cli, auth.clone(), org, host, routing);
+    let github_client = connection.github_client(file_config.as_ref()).await?;
+
+    match cli.command {
+        Commands::Config { .. } => unreachable!("config commands are handled before the client is created"),
+        Commands::Serve { .. } => unreachable!("serve is handled before the client is created"),
+        Commands::Calendar { .. } => unreachable!("calendar is handled before the client is created"),
+        Commands::Notify { .. } => unreachable!("notify is handled before the client is created"),
+        Commands::Bench { replay: Some(_), .. } => unreachable!("bench replays are handled before the client is created"),
+        Commands::Generate {
+            version,
+            versions,
+            all_matching,
+            from,
+            unreleased,
+            since,
+            until,
+            repos,
+            output,
+            format,
+            locale,
+            template,
+            short_output,
+            short_template,
+            branding,
+            scaffold,
+            mode,
+            include_prs,
+            no_include_prs,
+            include_issues,
+            no_include_issues,
+            include_known_issues,
+            no_include_known_issues,
+            categorize,
+            no_categorize,
+            include_train,
+            download_stats,
+            risk,
+            rollout_plan,
+            search_index,
+            export_sqlite,
+            export_parquet,
+        } => {
+            let short_template = short_template.or_else(|| file_config.as_ref()
+                .and_then(|c| c.output.short_template.as_ref())
+                .map(PathBuf::from));
+            let options = aggregator::generate::GenerateOptions {
+                version,
+                versions,
+                all_matching,
+                from,
+                unreleased,
+                since,
+                until,
+                repos,
+                output,
+                format: format.unwrap_or(default_format),
+                locale: locale.or(default_locale),
+                template: template.or(default_template),
+                short_output,
+                short_template,
+                branding: branding.resolve(file_config.as_ref())?,
+                entry_formats,
+                sections_file: scaffold.sections_file,
+                sections: scaffold.section,
+                mode,
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                include_issues: feature_flag(include_issues, no_include_issues, base_config.include_issues),
+                known_issue_label: known_issue_label(include_known_issues, no_include_known_issues, features),
+                categorize: feature_flag(categorize, no_categorize, base_config.categorize_commits),
+                include_train,
+                download_stats,
+                risk,
+                rollout_plan,
+                search_index,
+                export_sqlite,
+                export_parquet,
+                deterministic: cli.deterministic,
+                generated_at: cli.generated_at,
+            };
+            aggregator::generate::run(&connection, github_client, file_config.as_ref(), base_config, options).await?;
+        }
+        Commands::Backfill { output, repos, matching, format, locale, template, branding, skip_existing, include_prs, no_include_prs, categorize, no_categorize } => {
+            let options = aggregator::backfill::BackfillOptions {
+                output,
+                repos,
+                matching,
+                format: format.unwrap_or(default_format),
+                template: template.or(default_template),
+                locale: locale.or(default_locale),
+                branding: branding.resolve(file_config.as_ref())?,
+                entry_formats,
+                skip_existing,
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                categorize: feature_flag(categorize, no_categorize, base_config.categorize_commits),
+                deterministic: cli.deterministic,
+                generated_at: cli.generated_at,
+            };
+            aggregator::backfill::run(&connection, github_client, file_config.as_ref(), base_config, options).await?;
+        }
+        Commands::Site { output, repos, matching, locale, branding, include_prs, no_include_prs, categorize, no_categorize } => {
+            let options = aggregator::site::SiteOptions {
+                output,
+                repos,
+                matching,
+                locale: locale.or(default_locale),
+                branding: branding.resolve(file_config.as_ref())?,
+                entry_formats,
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                categorize: feature_flag(categorize, no_categorize, base_config.categorize_commits),
+                deterministic: cli.deterministic,
+                generated_at: cli.generated_at,
+            };
+            aggregator::site::run(&connection, github_client, file_config.as_ref(), base_config, options).await?;
+        }
+        Commands::Check { version, repos, artifacts, assets, format, fail_fast, disable_rule } => {
+            let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+            github_client.preflight(&auth, &repos).await?;
+            let json = matches!(format, OutputFormat::Json);
+            if !json {
+                println!("Checking release {} for repositories: {:?}", version, repos);
+            }
+
+            let registry_client = integrations::registry::RegistryClient::new()?;
+            let oci_client = if artifacts {
+                Some(integrations::oci::OciClient::new(image_credentials(file_config.as_ref(), auth.token())?)?)
+            } else {
+                None
+            };
+            let mut disabled_rules = file_config.as_ref().map(|c| c.check.disabled_rules.clone()).unwrap_or_default();
+            disabled_rules.extend(disable_rule);
+            let checker = aggregator::check::ReleaseChecker::new(&github_client, &registry_client, oci_client.as_ref(), cli.include_archived)
+                .with_assets(assets)
+                .with_disabled_rules(disabled_rules)
+                .with_previous_release(cli.date_source, cli.previous_strategy);
+
+            // Checked concurrently, in the order given, so --fail-fast stops at the first miss
+            let mut checks = futures::stream::iter(repos)
+                .map(|repo| {
+                    let settings = file_config.as_ref()
+                        .and_then(|c| c.repos.settings.get(&repo).cloned())
+                        .unwrap_or_default();
+                    let checker = &checker;
+                    let version = &version;
+                    async move { checker.check(&repo, &settings, version).await }
+                })
+                .buffered(cli.concurrency.max(1));
+            let mut report = aggregator::check::CheckReport { version: version.clone(), repositories: Vec::new() };
+            while let Some(check) = checks.next().await {
+                let check = check?;
+                let missing = check.status == aggregator::check::CheckStatus::NotFound;
+                report.repositories.push(check);
+                if missing && fail_fast {
+                    break;
+                }
+            }
+            drop(checks);
+
+            let mut state = state::store::StateStore::open(&cli.state)?;
+            for check in &mut report.repositories {
+                let Some(release) = &check.release else {
+                    continue;
+                };
+                if let Some(sha) = &release.sha {
+                    if let Some(drift) = state.record_release(&check.repository, &release.tag, sha) {
+                        check.warnings.push(drift.to_string());
+                    }
+                }
+            }
+            state.save()?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!();
+                print!("{}", report.to_table());
+                if fail_fast && !report.is_complete() {
+                    println!("Stopped at the first missing release (--fail-fast)");
+                }
+            }
+            if !report.is_complete() {
+                std::process::exit(1);
+            }
+        }
+        Commands::List { repos, limit, org_wide, format, sort, include_prereleases, exclude_drafts } => {
+            let filter = aggregator::listing::ListFilter { include_prereleases, exclude_drafts };
+            let mut list = aggregator::listing::ReleaseList::default();
+
+            if org_wide {
+                let org_repos = github_client.list_org_repos().await?;
+                let repos = match &file_config {
+                    Some(config) => config.filter_org_repos(org_repos),
+                    None => org_repos,
+                };
+                // Any one repository may hold all of the newest releases, so fetch `limit` from each
+                let mut listings = futures::stream::iter(repos)
+                    .map(|repo| {
+                        let github_client = &github_client;
+                        async move { recent_releases(github_client, &repo, limit, filter, cli.date_source).await }
+                    })
+                    .buffered(cli.concurrency.max(1));
+                while let Some(releases) = listings.next().await {
+                    list.releases.extend(releases?);
+                }
+                list.keep_latest(limit);
+            } else {
+                let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+                github_client.preflight(&auth, &repos).await?;
+                for repo in repos {
+                    list.extend(recent_releases(&github_client, &repo, limit, filter, cli.date_source).await?, sort);
+                }
+            }
+
+            match format {
+                aggregator::listing::ListFormat::Table => print!("{}", list.to_table()),
+                aggregator::listing::ListFormat::Json => println!("{}", serde_json::to_string_pretty(&list)?),
+                aggregator::listing::ListFormat::Csv => print!("{}", list.to_csv()),
+            }
+        }
+        Commands::Single { version, repo, output, format, locale, branding, include_prs, no_include_prs } => {
+            github_client.preflight(&auth, std::slice::from_ref(&repo)).await?;
+            let format = format.unwrap_or(default_format);
+            let config = aggregator::AggregatorConfig {
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                include_issues: false,
+                categorize_commits: true,
+                sections: vec![],
+                // The repository was asked for by name, so archived status doesn't exclude it
+                include_archived: true,
+                ..base_config
+            };
+
+            let state = state::store::StateStore::open(&cli.state)?;
+            let aggregator = aggregator::ReleaseAggregator::new(github_client, config)
+                .with_branch_history(state.branches)
+                .with_range_cache(state.ranges);
+            let mut release = aggregator.aggregate(&version, vec![repo.clone()]).await?;
+            state::store::save_range_cache(&cli.state, &aggregator)?;
+            release.pin(cli.deterministic, cli.generated_at);
+            let component = release.components.into_iter().next()
+                .ok_or_else(|| anyhow::anyhow!("No component produced for {}", repo))?;
+            match &component.status {
+                aggregator::release_fetcher::ComponentStatus::Released { .. } => {}
+                aggregator::release_fetcher::ComponentStatus::NoRelease { .. } => {
+                    anyhow::bail!("Release {} not found in {}", version, repo);
+                }
+                aggregator::release_fetcher::ComponentStatus::NotYetReleased { .. } => {
+                    anyhow::bail!("{} has no releases yet", repo);
+                }
+            }
+
+            // Links use the component's canonical name, so compare links survive renames
+            let mut generator = aggregator::changelog_generator::ChangelogGenerator::new(format, None)?
+                .with_links(aggregator.client().web_links())
+                .with_branding(branding.resolve(file_config.as_ref())?)?;
+            if let Some(locale) = locale.or(default_locale) {
+                generator = generator.with_locale(locale);
+            }
+            let content = generator.generate_single(&component)?;
+
+            if let Some(output_path) = output {
+                std::fs::write(output_path, content)?;
+                println!("Release notes written successfully!");
+            } else {
+                println!("{}", content);
+            }
+        }
+        Commands::Compare { repo, from, to, output, format, locale, branding, include_prs, no_include_prs } => {
+            github_client.preflight(&auth, std::slice::from_ref(&repo)).await?;
+            let format = format.unwrap_or(default_format);
+            let config = aggregator::AggregatorConfig {
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                include_issues: false,
+                categorize_commits: true,
+                sections: vec![],
+                include_archived: true,
+                ..base_config
+            };
+
+            let aggregator = aggregator::ReleaseAggregator::new(github_client, config)
+                .with_range_cache(state::store::StateStore::open(&cli.state)?.ranges);
+            let component = aggregator.compare_refs(&repo, &from, &to).await?;
+            state::store::save_range_cache(&cli.state, &aggregator)?;
+
+            let mut generator = aggregator::changelog_generator::ChangelogGenerator::new(format, None)?
+                .with_links(aggregator.client().web_links())
+                .with_branding(branding.resolve(file_config.as_ref())?)?;
+            if let Some(locale) = locale.or(default_locale) {
+                generator = generator.with_locale(locale);
+            }
+            let content = generator.generate_single(&component)?;
+
+            if let Some(output_path) = output {
+                std::fs::write(output_path, content)?;
+                println!("Release notes written successfully!");
+            } else {
+                println!("{}", content);
+            }
+        }
+        Commands::Publish {
+            version,
+            repos,
+            release_repo,
+            draft,
+            make_latest,
+            include_prs,
+            no_include_prs,
+            include_known_issues,
+            no_include_known_issues,
+            jira,
+            status_page,
+            window_start,
+            notify,
+            backfill_links,
+            download_stats,
+            oversize,
+            full_notes_url,
+            diff_output,
+            scaffold,
+        } => {
+            let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+            github_client.preflight(&auth, &repos).await?;
+            let release_repo = release_repo
+                .or_else(|| file_config.as_ref().and_then(|c| c.publish.repo.clone()))
+                .ok_or_else(|| anyhow::anyhow!("No release repository given; pass --release-repo or set publish.repo in the config"))?;
+            let make_latest = make_latest.or_else(|| file_config.as_ref().and_then(|c| c.publish.make_latest));
+            let scaffold = scaffold.resolve(file_config.as_ref(), &version)?;
+            let unfilled = scaffold.unfilled();
+            if !unfilled.is_empty() {
+                anyhow::bail!(
+                    "Required sections aren't filled in: {}; fill them in with --sections-file or --section before publishing",
+                    unfilled.join(", ")
+                );
+            }
+            let jira_config = if jira {
+                let jira_config = file_config.as_ref().and_then(|c| c.jira.clone())
+                    .ok_or_else(|| anyhow::anyhow!("--jira requires a [jira] section in the config file"))?;
+                Some(jira_config)
+            } else {
+                None
+            };
+            let status_page_config = if status_page {
+                let status_page_config = file_config.as_ref().and_then(|c| c.status_page.clone())
+                    .ok_or_else(|| anyhow::anyhow!("--status-page requires a [status_page] section in the config file"))?;
+                Some(status_page_config)
+            } else {
+                None
+            };
+            let notify_config = file_config.as_ref().and_then(|c| c.notify.clone());
+            if notify && notify_config.as_ref().is_none_or(|c| c.targets.is_empty()) {
+                anyhow::bail!("--notify requires targets in a [notify] section of the config file");
+            }
+            let notifier = integrations::notify::Notifier::new(notify_config.as_ref().and_then(|c| c.proxy.as_deref()))?;
+            flush_notifications(&notifier, &cli.state).await?;
+            let mut write_targets = vec![release_repo.clone()];
+            if backfill_links {
+                write_targets.extend(repos.iter().cloned());
+            }
+            let write_client = write_client(cli.write_token.as_deref(), &github_client, &write_targets).await?;
+
+            let config = aggregator::AggregatorConfig {
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                include_issues: false,
+                categorize_commits: true,
+                download_stats,
+                ..base_config
+            };
+
+            let state = state::store::StateStore::open(&cli.state)?;
+            let aggregator = aggregator::ReleaseAggregator::new(github_client, config)
+                .with_branch_history(state.branches)
+                .with_range_cache(state.ranges)
+                .with_known_issues(known_issue_label(include_known_issues, no_include_known_issues, features));
+            let mut release = aggregator.aggregate(&version, repos).await?;
+            state::store::save_range_cache(&cli.state, &aggregator)?;
+            release.pin(cli.deterministic, cli.generated_at);
+            state::store::record_release_shas(&cli.state, &release, true)?;
+
+            let generator = aggregator::changelog_generator::ChangelogGenerator::new(OutputFormat::Markdown, None)?
+                .with_entry_formats(entry_formats)
+                .with_links(aggregator.client().web_links())
+                .with_scaffold(scaffold);
+            let notes = generator.generate(&release)?;
+
+            let publisher = aggregator::publisher::Publisher::new(write_client.as_ref().unwrap_or(aggregator.client()));
+
+            // GitHub rejects oversize bodies outright, so shrink them before publishing
+            let mut body = notes.clone();
+            if body.chars().count() > aggregator::publisher::MAX_RELEASE_BODY {
+                if oversize == aggregator::publisher::OversizeStrategy::Details {
+                    body = generator.generate_collapsed(&release);
+                }
+                if body.chars().count() > aggregator::publisher::MAX_RELEASE_BODY {
+                    let full_notes_url = match full_notes_url {
+                        Some(url) => url,
+                        None => publisher.store_full_notes(&release_repo, &version, &notes).await?,
+                    };
+                    body = aggregator::publisher::truncate_body(&body, &full_notes_url);
+                }
+                eprintln!(
+                    "⚠ Notes are {} characters; published a {}-character body",
+                    notes.chars().count(),
+                    body.chars().count()
+                );
+            }
+
+            let previous = publisher.published_notes(&release_repo, &version).await?;
+            let published = publisher.publish_umbrella(&release_repo, &version, &body, draft, make_latest).await?;
+            let event = match previous {
+                Some(_) => integrations::notify::AnnouncementEvent::Updated,
+                None => integrations::notify::AnnouncementEvent::Published,
+            };
+            let mut announcement = integrations::notify::Announcement::from_release(event, &release, published.html_url.as_str());
+            match previous {
+                Some(previous) => {
+                    let diff = aggregator::notes_diff::NotesDiff::between(&previous, &body);
+                    println!(
+                        "Updated {} at {}: {} entries added, {} removed",
+                        version,
+                        published.html_url,
+                        diff.added(),
+                        diff.removed()
+                    );
+                    let diff = diff.to_markdown(&version);
+                    match &diff_output {
+                        Some(path) => std::fs::write(path, &diff)
+                            .with_context(|| format!("Failed to write the notes diff to {}", path.display()))?,
+                        None => println!("\n{}", diff.trim_end()),
+                    }
+                    announcement = announcement.with_diff(diff);
+                }
+                None => println!("Published {} to {}", version, published.html_url),
+            }
+
+            if backfill_links {
+                let updated = publisher.backfill_train_links(&release, published.html_url.as_str()).await?;
+                println!("Linked {} component release(s) to the train", updated.len());
+            }
+
+            if let Some(jira_config) = jira_config {
+                let mut issue_keys: Vec<String> = release.components.iter()
+                    .filter_map(|c| match &c.status {
+                        aggregator::release_fetcher::ComponentStatus::Released { commits, .. } => Some(commits),
+                        _ => None,
+                    })
+                    .flatten()
+                    .flat_map(|c| c.issue_keys.iter().cloned())
+                    .collect();
+                issue_keys.sort();
+                issue_keys.dedup();
+
+                let jira_client = integrations::jira::JiraClient::new(&jira_config)?;
+                let description = format!("Release train {}: {}", version, published.html_url);
+                for project in &jira_config.projects {
+                    let result = jira_client
+                        .publish_version(project, &version, &description, release.date.date_naive(), &issue_keys)
+                        .await?;
+                    println!(
+                        "Jira {}: version {} released with {} issue(s)",
+                        result.project,
+                        result.version.name,
+                        result.assigned.len()
+                    );
+                    for key in &result.failed {
+                        println!("  ✗ could not assign {}", key);
+                    }
+                }
+            }
+
+            if let Some(status_page_config) = status_page_config {
+                // Status pages are public, so they get the external view of the train
+                let redaction = file_config.as_ref().map(|c| c.redaction.clone()).unwrap_or_default();
+                let external = aggregator::redaction::Redactor::from_config(&redaction)?.apply(&release);
+                let start = window_start.unwrap_or_else(chrono::Utc::now);
+                let notice = integrations::status_page::MaintenanceNotice::from_release(&external, &status_page_config, start);
+
+                let client = integrations::status_page::StatusPageClient::new(&status_page_config)?;
+                match client.post_notice(&notice).await? {
+                    integrations::status_page::PostedNotice::Created { id } => println!(
+                        "Scheduled \"{}\" on the status page ({}) for {} affected component(s)",
+                        notice.name, id, notice.component_ids.len()
+                    ),
+                    integrations::status_page::PostedNotice::AlreadyScheduled { id } => {
+                        println!("\"{}\" is already scheduled on the status page ({})", notice.name, id)
+                    }
+                }
+            }
+
+            if let Some(notify_config) = notify_config.filter(|_| notify) {
+                let mut state = state::store::StateStore::open(&cli.state)?;
+                for delivery in notifier.announce(&notify_config.targets, &announcement, &mut state).await {
+                    match delivery.error {
+                        None => println!("Announced {} to {}", version, delivery.target),
+                        Some(error) => eprintln!("⚠ Could not announce {} to {}, queued for retry: {}", version, delivery.target, error),
+                    }
+                }
+                state.save()?;
+            }
+        }
+        Commands::Feedback { versions, release_repo, limit, format } => {
+            let release_repo = release_repo
+                .or_else(|| file_config.as_ref().and_then(|c| c.publish.repo.clone()))
+                .ok_or_else(|| anyhow::anyhow!("No release repository given; pass --release-repo or set publish.repo in the config"))?;
+            let report = aggregator::feedback::EngagementReport::collect(&github_client, &release_repo, &versions, limit).await?;
+
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+                _ => print!("{}", report.to_markdown()),
+            }
+        }
+        Commands::Prefill { version, repos, include_prs, no_include_prs } => {
+            let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+            github_client.preflight(&auth, &repos).await?;
+            let write_client = write_client(cli.write_token.as_deref(), &github_client, &repos).await?;
+            let config = aggregator::AggregatorConfig {
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                include_issues: false,
+                categorize_commits: true,
+                ..base_config
+            };
+
+            let aggregator = aggregator::ReleaseAggregator::new(github_client, config);
+            let generator = aggregator::changelog_generator::ChangelogGenerator::new(OutputFormat::Markdown, None)?
+                .with_entry_formats(entry_formats);
+            let publisher = aggregator::publisher::Publisher::new(write_client.as_ref().unwrap_or(aggregator.client()));
+
+            for repo in repos {
+                // Archived repositories are read-only, so there is nowhere to put a draft
+                if aggregator.client().get_repository(&repo).await?.is_some_and(|info| info.archived) {
+                    println!("- {}: archived, skipped", repo);
+                    continue;
+                }
+                let tag = aggregator.tag_for(&repo, &version);
+                if publisher.has_release(&repo, &tag).await? {
+                    println!("- {}: {} already drafted or released, skipped", repo, tag);
+                    continue;
+                }
+
+                let (since, commits) = aggregator.unreleased_commits(&repo).await?;
+                if commits.is_empty() {
+                    println!("- {}: no unreleased commits, skipped", repo);
+                    continue;
+                }
+
+                let body = generator.generate_draft_body(&commits);
+                let draft = publisher.prefill_draft(&repo, &tag, &body).await?;
+                println!(
+                    "✓ {}: drafted {} with {} commit(s) since {} — {}",
+                    repo,
+                    tag,
+                    commits.len(),
+                    since.as_deref().unwrap_or("the first commit"),
+                    draft.html_url
+                );
+            }
+        }
+        Commands::Lint { version, repos, format, owners, scaffold } => {
+            let scaffold = scaffold.resolve(file_config.as_ref(), &version)?;
+            let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+            github_client.preflight(&auth, &repos).await?;
+            let config = aggregator::AggregatorConfig {
+                include_prs: owners,
+                include_issues: false,
+                categorize_commits: true,
+                ..base_config
+            };
+
+            let state = state::store::StateStore::open(&cli.state)?;
+            let aggregator = aggregator::ReleaseAggregator::new(github_client, config)
+                .with_branch_history(state.branches)
+                .with_range_cache(state.ranges);
+            let release = aggregator.aggregate(&version, repos).await?;
+            state::store::save_range_cache(&cli.state, &aggregator)?;
+
+            let mut report = aggregator::lint::LintReport::from_release(&release);
+            report.unfilled_sections = scaffold.unfilled().into_iter().map(String::from).collect();
+            if owners {
+                report.assign_owners(aggregator.client()).await?;
+            }
+
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+                _ => print!("{}", report.to_markdown()),
+            }
+
+            if !report.is_clean() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Evidence { version, repos, output } => {
+            let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+            github_client.preflight(&auth, &repos).await?;
+            let config = aggregator::AggregatorConfig {
+                include_prs: true,
+                include_issues: false,
+                categorize_commits: true,
+                ..base_config
+            };
+
+            let state = state::store::StateStore::open(&cli.state)?;
+            let aggregator = aggregator::ReleaseAggregator::new(github_client, config)
+                .with_branch_history(state.branches)
+                .with_range_cache(state.ranges);
+            let mut release = aggregator.aggregate(&version, repos).await?;
+            state::store::save_range_cache(&cli.state, &aggregator)?;
+            release.pin(cli.deterministic, cli.generated_at);
+
+            let generator = aggregator::changelog_generator::ChangelogGenerator::new(OutputFormat::Markdown, None)?
+                .with_entry_formats(entry_formats)
+                .with_links(aggregator.client().web_links());
+            let notes = generator.generate(&release)?;
+
+            let mut bundle = aggregator::evidence::EvidenceCollector::collect(&aggregator, &release, &notes).await?;
+            if cli.deterministic || cli.generated_at.is_some() {
+                bundle.generated_at = release.date;
+            }
+            let json = serde_json::to_string_pretty(&bundle)?;
+
+            if let Some(output_dir) = output {
+                std::fs::create_dir_all(&output_dir)?;
+                std::fs::write(output_dir.join("evidence.json"), json)?;
+                std::fs::write(output_dir.join("evidence.md"), bundle.to_markdown())?;
+                std::fs::write(output_dir.join("release-notes.md"), notes)?;
+                println!("Evidence bundle written to {}", output_dir.display());
+            } else {
+                println!("{}", json);
+            }
+        }
+        Commands::WarmCache { repos, since, include_prs, no_include_prs } => {
+            let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+            github_client.preflight(&auth, &repos).await?;
+            let include_prs = feature_flag(include_prs, no_include_prs, features.is_some_and(|f| f.include_prs));
+            let warmer = aggregator::cache_warmer::CacheWarmer::new(&github_client, cli.date_source, cli.previous_strategy, include_prs);
+            let cutoff = chrono::Utc::now() - since;
+
+            for repo in repos {
+                match warmer.warm(&repo, base_config.repo_settings.get(&repo), cutoff).await {
+                    Ok(warmed) => println!(
+                        "✓ {}: {} release(s), {} commit(s), {} unreleased commit(s)",
+                        repo, warmed.releases, warmed.commits, warmed.unreleased_commits
+                    ),
+                    Err(e) => println!("✗ {}: {:#}", repo, e),
+                }
+            }
+            github_client.save_cache()?;
+        }
+        Commands::Bench { record, version, repos, include_prs, no_include_prs, .. } => {
+            let (Some(dir), Some(version)) = (record, version) else {
+                unreachable!("clap requires --record and --version when --replay isn't given");
+            };
+            let repos = config::types::select_repos(&github_client, file_config.as_ref(), repos).await?;
+            github_client.preflight(&auth, &repos).await?;
+            let config = aggregator::AggregatorConfig {
+                include_prs: feature_flag(include_prs, no_include_prs, base_config.include_prs),
+                include_issues: false,
+                categorize_commits: true,
+                ..base_config
+            };
+
+            let aggregator = aggregator::ReleaseAggregator::new(provider::replay::RecordingProvider::new(github_client), config);
+            aggregator.aggregate(&version, repos.clone()).await?;
+            let recording = provider::replay::Recording {
+                org: aggregator.client().org().to_string(),
+                version,
+                repos,
+                include_prs,
+                recorded_at: chrono::Utc::now(),
+                responses: aggregator.client().responses(),
+            };
+            recording.save(&dir)?;
+            aggregator.client().inner().save_cache()?;
+            println!("Recorded {} response(s) to {}", recording.responses.len(), dir.join(provider::replay::RECORDING_FILE).display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Aggregate and render recorded fixtures, and compare the timings and call counts with the
+/// baseline saved next to them.
+async fn run_bench_replay(
+    cli: &Cli,
+    dir: &std::path::Path,
+    iterations: usize,
+    save_baseline: bool,
+    tolerance: f64,
+    file_config: Option<&config::types::Config>,
+) -> Result<()> {
+    let recording = std::sync::Arc::new(provider::replay::Recording::load(dir)?);
+    let config = aggregator::AggregatorConfig {
+        include_prs: recording.include_prs,
+        include_issues: false,
+        categorize_commits: true,
+        ..aggregator_config(cli, file_config)?
+    };
+
+    let result = aggregator::bench::run(recording, config, iterations).await?;
+    let baseline = aggregator::bench::BenchResult::load_baseline(dir)?;
+    print!("{}", result.to_table(baseline.as_ref()));
+
+    let regressions = baseline.as_ref()
+        .map(|baseline| result.regressions(baseline, tolerance))
+        .unwrap_or_default();
+    if save_baseline || baseline.is_none() {
+        result.save_baseline(dir)?;
+        println!("\nSaved as the baseline in {}", dir.join(aggregator::bench::BASELINE_FILE).display());
+    }
+    if !regressions.is_empty() {
+        println!();
+        for regression in &regressions {
+            println!("✗ {}", regression);
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// The newest `limit` releases of `repo` that `filter` keeps, fetching more until filtering
+/// leaves enough or the repository runs out.
+async fn recent_releases(
+    client: &github::client::GitHubClient,
+    repo: &str,
+    limit: usize,
+    filter: aggregator::listing::ListFilter,
+    date_source: DateSource,
+) -> Result<Vec<aggregator::listing::ListedRelease>> {
+    let mut fetch = limit;
+    loop {
+        let releases = client.list_releases(repo, fetch).await?;
+        let exhausted = releases.len() < fetch;
+        let kept: Vec<_> = releases.iter().filter(|r| filter.keeps(r)).collect();
+        if kept.len() >= limit || exhausted {
+            return Ok(kept.into_iter()
+                .take(limit)
+                .map(|release| aggregator::listing::ListedRelease::new(repo, release, date_source))
+                .collect());
+        }
+        fetch = fetch.saturating_mul(2);
+    }
+}
+
+/// Label of the known issues to list: when `--include-known-issues` is passed, or the config
+/// turns them on and `--no-include-known-issues` isn't.
+fn known_issue_label(on: bool, off: bool, features: Option<&config::types::FeaturesConfig>) -> Option<String> {
+    match features {
+        _ if off => None,
+        Some(features) if on => Some(features.known_issue_label.clone()),
+        Some(features) => features.known_issues(),
+        None => on.then(|| aggregator::known_issues::DEFAULT_KNOWN_ISSUE_LABEL.to_string()),
+    }
+}
+
+/// Parse a timestamp given as Unix seconds (as in `SOURCE_DATE_EPOCH`) or RFC 3339.
+fn parse_timestamp(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(seconds) = value.trim().parse::<i64>() {
+        return chrono::DateTime::from_timestamp(seconds, 0).ok_or_else(|| format!("Timestamp out of range: {}", value));
+    }
+    chrono::DateTime::parse_from_rfc3339(value.trim())
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|e| format!("Invalid timestamp `{}`: {}", value, e))
+}
+
+/// Parse an age such as `90d`, `12w`, or `48h`.
+fn parse_age(value: &str) -> Result<chrono::Duration, String> {
+    let invalid = || format!("Invalid age `{}`; use a number followed by h, d, or w", value);
+    let trimmed = value.trim();
+    let unit = trimmed.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = trimmed[..trimmed.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    match unit {
+        'h' => Ok(chrono::Duration::hours(amount)),
+        'd' => Ok(chrono::Duration::days(amount)),
+        'w' => Ok(chrono::Duration::weeks(amount)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse an interval such as `100ms`, `2s`, or `0.5s`.
+fn parse_interval(value: &str) -> Result<std::time::Duration, String> {
+    let invalid = || format!("Invalid interval `{}`; use a number followed by ms or s", value);
+    let trimmed = value.trim();
+    let (amount, unit_seconds) = match trimmed.strip_suffix("ms") {
+        Some(amount) => (amount, 0.001),
+        None => (trimmed.strip_suffix('s').ok_or_else(invalid)?, 1.0),
+    };
+    let amount: f64 = amount.trim().parse().map_err(|_| invalid())?;
+    if !(amount.is_finite() && amount >= 0.0) {
+        return Err(invalid());
+    }
+    Ok(std::time::Duration::from_secs_f64(amount * unit_seconds))
+}
+
+/// Retry announcements queued by earlier runs, reporting what went out.
+async fn flush_notifications(notifier: &integrations::notify::Notifier, state_path: &std::path::Path) -> Result<()> {
+    let mut state = state::store::StateStore::open(state_path)?;
+    if state.notifications.is_empty() {
+        return Ok(());
+    }
+    let report = notifier.flush(&mut state).await;
+    state.save()?;
+    for delivered in &report.delivered {
+        println!("Announced {} to {} (queued since {})", delivered.announcement.version, delivered.target, delivered.queued_at.format("%Y-%m-%d %H:%M UTC"));
+    }
+    if report.remaining > 0 {
+        eprintln!("⚠ {} queued announcement(s) still failing; run `notify` to list them", report.remaining);
+    }
+    Ok(())
+}
+
+/// List the queued announcements, or retry them with `flush_queue`.
+async fn run_notify_command(state_path: &std::path::Path, flush_queue: bool, file_config: Option<&config::types::Config>) -> Result<()> {
+    if flush_queue {
+        let proxy = file_config.and_then(|c| c.notify.as_ref()).and_then(|n| n.proxy.as_deref());
+        let notifier = integrations::notify::Notifier::new(proxy)?;
+        flush_notifications(&notifier, state_path).await?;
+    }
+
+    let state = state::store::StateStore::open(state_path)?;
+    if state.notifications.is_empty() {
+        println!("No announcements queued");
+        return Ok(());
+    }
+    for queued in &state.notifications {
+        println!(
+            "{} to {}: {} attempt(s) since {}, last error: {}",
+            queued.announcement.version,
+            queued.target,
+            queued.attempts,
+            queued.queued_at.format("%Y-%m-%d %H:%M UTC"),
+            queued.last_error
+        );
+    }
+    Ok(())
+}
+
+/// Aggregation settings every command starts from: the config file's, with the global flags
+/// applied. Without a config file every feature is off.
+fn aggregator_config(cli: &Cli, file_config: Option<&config::types::Config>) -> Result<aggregator::AggregatorConfig> {
+    let configured = aggregator::AggregatorConfig::from_config(file_config.unwrap_or(&config::types::Config::default()))?;
+    Ok(aggregator::AggregatorConfig {
+        date_source: cli.date_source,
+        previous_strategy: cli.previous_strategy,
+        commit_sort: cli.sort.unwrap_or(configured.commit_sort),
+        include_archived: cli.include_archived,
+        concurrency: cli.concurrency,
+        ..configured
+    })
+}
+
+/// Registry credentials from the config file. GHCR falls back to the GitHub token, when there
+/// is one rather than a GitHub App's short-lived installation tokens.
+fn image_credentials(file_config: Option<&config::types::Config>, github_token: Option<&str>) -> Result<HashMap<String, integrations::oci::OciCredentials>> {
+    let mut credentials = HashMap::new();
+    if let Some(token) = github_token {
+        credentials.insert("ghcr.io".to_string(), integrations::oci::OciCredentials {
+            username: "token".to_string(),
+            password: token.to_string(),
+        });
+    }
+
+    if let Some(config) = file_config {
+        for (host, registry) in &config.image_registries {
+            let password = std::env::var(&registry.password_env)
+                .with_context(|| format!("Password for {} not found in ${}", host, registry.password_env))?;
+            credentials.insert(host.clone(), integrations::oci::OciCredentials {
+                username: registry.username.clone(),
+                password,
+            });
+        }
+    }
+
+    Ok(credentials)
+}
+
+/// A GitHub App installation when `--app-id` is given, otherwise the token, otherwise whatever
+/// the `gh` CLI is logged in to `host` with.
+fn github_auth(cli: &Cli, host: &github::host::GitHubHost) -> Option<github::auth::GitHubAuth> {
+    match (cli.app_id, &cli.private_key_path) {
+        (Some(app_id), Some(private_key_path)) => Some(github::auth::GitHubAuth::App {
+            app_id,
+            private_key_path: private_key_path.clone(),
+            installation_id: cli.installation_id,
+        }),
+        _ => cli.token.clone().map(github::auth::GitHubAuth::Token)
+            .or_else(|| github::auth::GitHubAuth::from_gh_cli(host)),
+    }
+}
+
+/// Credentials for writes: `--write-token`, else $GITHUB_WRITE_TOKEN. The variable is only read
+/// here, so commands that don't write never load it. `None` means writes use the read credentials.
+fn write_auth(write_token: Option<&str>) -> Option<github::auth::GitHubAuth> {
+    write_token.map(str::to_string)
+        .or_else(|| std::env::var("GITHUB_WRITE_TOKEN").ok())
+        .filter(|token| !token.is_empty())
+        .map(github::auth::GitHubAuth::Token)
+}
+
+/// Client for a command's writes when a write token is given, checked for push access to each
+/// of `targets`.
+async fn write_client(write_token: Option<&str>, reader: &github::client::GitHubClient, targets: &[String]) -> Result<Option<github::client::GitHubClient>> {
+    let Some(auth) = write_auth(write_token) else {
+        return Ok(None);
+    };
+    let client = reader.writer(&auth, targets).await?;
+    tracing::debug!("Writing with the write token");
+    Ok(Some(client))
+}
+
+/// Where the global flags point the clients, reading with `auth` from `org` on `host`.
+fn connection(cli: &Cli, auth: github::auth::GitHubAuth, org: String, host: github::host::GitHubHost, routing: ProviderRouting) -> provider::connection::Connection {
+    provider::connection::Connection {
+        auth,
+        org,
+        host,
+        routing,
+        gitlab_url: cli.gitlab_url.clone(),
+        repos_dir: cli.repos_dir.clone(),
+        invalid_text: cli.invalid_text,
+        max_pages: cli.max_pages,
+        source: cli.source,
+        api: cli.api,
+        throttle: cli.throttle,
+        state: cli.state.clone(),
+    }
+}
+
+/// Organization of clones in `repos_dir` when none is given: the directory's name, as in
+/// `~/src/acme`.
+fn local_org(repos_dir: &std::path::Path) -> Option<String> {
+    let dir = std::fs::canonicalize(repos_dir).ok()?;
+    dir.file_name()?.to_str().map(str::to_string)
+}
+
+/// The GitHub instance to talk to: `--github-url`, else `github.api_url`, else github.com.
+fn github_host(cli: &Cli, file_config: Option<&config::types::Config>) -> Result<github::host::GitHubHost> {
+    match cli.github_url.as_deref().or_else(|| file_config.and_then(|c| c.github.api_url.as_deref())) {
+        Some(url) => github::host::GitHubHost::parse(url),
+        None => Ok(github::host::GitHubHost::default()),
+    }
+}
+
+async fn run_config_command(action: &ConfigAction, cli: &Cli) -> Result<()> {
+    match action {
+        ConfigAction::Init { path, force } => {
+            if path.exists() && !force {
+                anyhow::bail!("{} already exists; pass --force to overwrite it", path.display());
+            }
+            std::fs::write(path, config::validate::starter_toml()?)?;
+            println!("Wrote starter config to {}", path.display());
+        }
+        ConfigAction::Validate { path } => {
+            let path = path.clone()
+                .or_else(|| cli.config.clone())
+                .or_else(config::types::Config::discover)
+                .ok_or_else(|| anyhow::anyhow!("No config file found; pass a path or run `config init`"))?;
+            let (file_config, unknown_keys) = config::types::Config::load_with_unknown_keys(&path)?;
+            let problems = config::validate::validate(&file_config);
+
+            for key in &unknown_keys {
+                println!("✗ {}", key);
+            }
+            for problem in &problems {
+                println!("✗ {}", problem);
+            }
+
+            // Expanding globs needs the org's repository list, so only do it when we can ask GitHub
+            let org = cli.org.clone().or_else(|| Some(file_config.github.org.clone()).filter(|o| !o.is_empty()));
+            let host = github_host(cli, Some(&file_config))?;
+            if let (Some(auth), Some(org)) = (github_auth(cli, &host), org) {
+                let client = github::client::GitHubClient::new(&auth, org, host).await?;
+                let repos = config::types::select_repos(&client, Some(&file_config), vec![]).await.unwrap_or_default();
+                println!("Repositories ({}): {}", repos.len(), repos.join(", "));
+            } else if file_config.has_repo_patterns() {
+                println!("- repo globs not expanded; pass --token and --org to resolve them");
+            }
+
+            if unknown_keys.is_empty() && problems.is_empty() {
+                println!("✓ {} is valid", path.display());
+            } else {
+                anyhow::bail!("{} has {} problem(s)", path.display(), unknown_keys.len() + problems.len());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the release calendar built from `data`'s exports, pushing it to Google Calendar if asked.
+async fn run_calendar_command(
+    data: &std::path::Path,
+    output: Option<&std::path::Path>,
+    notes_url: Option<String>,
+    google: bool,
+    file_config: Option<&config::types::Config>,
+) -> Result<()> {
+    let mut calendar = file_config.map(|c| c.calendar.clone()).unwrap_or_default();
+    if notes_url.is_some() {
+        calendar.notes_url = notes_url;
+    }
+    let trains = serve::store::TrainStore::new(data)?.trains()?;
+    let events = aggregator::calendar::train_events(&trains, &calendar);
+    let name = calendar.name.as_deref().unwrap_or(aggregator::calendar::DEFAULT_CALENDAR_NAME);
+    let ics = aggregator::calendar::to_ics(&events, name, chrono::Utc::now());
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, ics)?;
+            println!("Wrote {} train(s) to {}", events.len(), path.display());
+        }
+        None => print!("{}", ics),
+    }
+
+    if google {
+        let google_config = calendar.google.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("--google needs a [calendar.google] table in the config"))?;
+        let client = integrations::google_calendar::GoogleCalendarClient::new(google_config)?;
+        for event in &events {
+            client.upsert_event(event).await?;
+        }
+        eprintln!("✓ Pushed {} event(s) to Google Calendar {}", events.len(), google_config.calendar_id);
+    }
     Ok(())
 }
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::config::types::Config;
+use crate::github::auth::GitHubAuth;
+use crate::github::client::GitHubClient;
+use crate::github::host::GitHubHost;
+use crate::github::throttle::Throttle;
+use crate::github::types::{ApiBackend, ReleaseSource};
+use crate::gitlab::client::GitLabClient;
+use crate::local::client::LocalGitClient;
+use crate::local::encoding::InvalidTextPolicy;
+use super::forges::Forges;
+use super::{ProviderKind, ProviderRouting};
+
+/// How to reach the forges a run reads: the credentials, instances and client settings the
+/// global flags and the config file resolved to.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub auth: GitHubAuth,
+    pub org: String,
+    pub host: GitHubHost,
+    pub routing: ProviderRouting,
+    /// GitLab instance overriding `gitlab.url`
+    pub gitlab_url: Option<String>,
+    /// Directory holding the clones of repositories routed to `ProviderKind::Local`
+    pub repos_dir: PathBuf,
+    pub invalid_text: InvalidTextPolicy,
+    pub max_pages: usize,
+    pub source: ReleaseSource,
+    pub api: ApiBackend,
+    /// Spacing between GitHub requests, overriding `github.requests_per_second`
+    pub throttle: Option<Duration>,
+    /// State file; the ETag cache is kept next to it
+    pub state: PathBuf,
+}
+
+impl Connection {
+    /// Client for GitHub, reading releases from each repository's configured source.
+    pub async fn github_client(&self, config: Option<&Config>) -> Result<GitHubClient> {
+        let mut client = GitHubClient::new(&self.auth, self.org.clone(), self.host.clone()).await?
+            .with_max_pages(self.max_pages)
+            .with_etag_store(&self.state.with_file_name("etags.json"))?
+            .with_release_source(self.source, release_sources(config))
+            .with_api(self.api);
+        let throttle = match (self.throttle, config.and_then(|c| c.github.requests_per_second)) {
+            (Some(interval), _) => Some(Throttle::new(interval)),
+            (None, Some(rate)) if rate.is_finite() && rate > 0.0 => Some(Throttle::per_second(rate)),
+            _ => None,
+        };
+        if let Some(throttle) = throttle {
+            tracing::debug!("Spacing API requests {:?} apart", throttle.interval());
+            client = client.with_throttle(throttle);
+        }
+        Ok(client)
+    }
+
+    /// Client for the GitLab instance at `gitlab_url`, else `gitlab.url`, else gitlab.com. Bare
+    /// repository names belong to `gitlab.group`, defaulting to the organization.
+    pub fn gitlab_client(&self, config: Option<&Config>) -> Result<GitLabClient> {
+        let gitlab_config = config.and_then(|c| c.gitlab.clone()).unwrap_or_default();
+        let url = self.gitlab_url.clone().unwrap_or(gitlab_config.url);
+        let group = gitlab_config.group.unwrap_or_else(|| self.org.clone());
+        let token = std::env::var(&gitlab_config.token_env).ok().filter(|token| !token.is_empty());
+        if token.is_none() {
+            tracing::info!("${} is not set; reading GitLab anonymously (public projects only)", gitlab_config.token_env);
+        }
+        Ok(GitLabClient::new(&url, group, token)?
+            .with_max_pages(self.max_pages)
+            .with_release_source(self.source, release_sources(config)))
+    }
+
+    /// `github` together with GitLab and the local clones, when `routing` sends repositories there.
+    pub fn forges(&self, github: GitHubClient, config: Option<&Config>) -> Result<Forges> {
+        let gitlab = match self.routing.uses(ProviderKind::Gitlab) {
+            true => Some(self.gitlab_client(config)?),
+            false => None,
+        };
+        let mut forges = Forges::new(github, gitlab, self.routing.clone());
+        if self.routing.uses(ProviderKind::Local) {
+            let local = LocalGitClient::new(self.repos_dir.clone(), self.org.clone(), self.host.clone())
+                .with_text_policy(self.invalid_text);
+            forges = forges.with_local(local);
+        }
+        Ok(forges)
+    }
+}
+
+/// `source` in each repository's settings.
+fn release_sources(config: Option<&Config>) -> HashMap<String, ReleaseSource> {
+    config
+        .map(|c| c.repos.settings.iter()
+            .filter_map(|(repo, settings)| settings.source.map(|source| (repo.clone(), source)))
+            .collect())
+        .unwrap_or_default()
+}
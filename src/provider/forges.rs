@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::sync::RwLock;
+use crate::config::types::RepoSettings;
+use crate::github::client::GitHubClient;
+use crate::github::links::WebLinks;
+use crate::github::types::{CommitComparison, CommitInfo, DateSource, Issue, PreviousStrategy, PullRequest, Release, RepositoryInfo};
+use crate::gitlab::client::GitLabClient;
+use crate::local::client::LocalGitClient;
+use super::{ProviderKind, ProviderRouting, ReleaseProvider};
+
+/// GitHub and, optionally, a GitLab instance and local clones behind one provider, so a train
+/// can span them. Each repository is read from the provider `routing` assigns it.
+pub struct Forges {
+    github: GitHubClient,
+    gitlab: Option<GitLabClient>,
+    local: Option<LocalGitClient>,
+    routing: RwLock<ProviderRouting>,
+}
+
+impl Forges {
+    pub fn new(github: GitHubClient, gitlab: Option<GitLabClient>, routing: ProviderRouting) -> Self {
+        Self { github, gitlab, local: None, routing: RwLock::new(routing) }
+    }
+
+    /// Read the repositories routed to `ProviderKind::Local` from `local`.
+    pub fn with_local(mut self, local: LocalGitClient) -> Self {
+        self.local = Some(local);
+        self
+    }
+
+    pub fn github(&self) -> &GitHubClient {
+        &self.github
+    }
+
+    fn kind(&self, repo: &str) -> ProviderKind {
+        self.routing.read().expect("routing lock poisoned").provider(repo)
+    }
+
+    fn gitlab(&self) -> Result<&GitLabClient> {
+        self.gitlab.as_ref().context("Repositories are routed to GitLab but no GitLab instance is configured")
+    }
+
+    fn local(&self) -> Result<&LocalGitClient> {
+        self.local.as_ref().context("Repositories are routed to local clones but no clone directory is configured")
+    }
+
+    fn canonical_name_on(&self, kind: ProviderKind, info: &RepositoryInfo) -> String {
+        match (kind, &self.gitlab, &self.local) {
+            (ProviderKind::Gitlab, Some(gitlab), _) => gitlab.canonical_name(info),
+            (ProviderKind::Local, _, Some(local)) => local.canonical_name(info),
+            _ => self.github.canonical_name(info),
+        }
+    }
+
+    /// Links to each repository on the forge hosting it.
+    pub fn web_links(&self) -> WebLinks {
+        let links = self.github.web_links();
+        match &self.gitlab {
+            Some(gitlab) => {
+                let routing = self.routing.read().expect("routing lock poisoned").clone();
+                links.with_gitlab(gitlab.web_url(), gitlab.group(), routing)
+            }
+            None => links,
+        }
+    }
+}
+
+/// Run `$call` on whichever client hosts `$repo`.
+macro_rules! dispatch {
+    ($self:ident, $repo:expr, $client:ident => $call:expr) => {
+        match $self.kind($repo) {
+            ProviderKind::Github => {
+                let $client = &$self.github;
+                $call.await
+            }
+            ProviderKind::Gitlab => {
+                let $client = $self.gitlab()?;
+                $call.await
+            }
+            ProviderKind::Local => {
+                let $client = $self.local()?;
+                $call.await
+            }
+        }
+    };
+}
+
+impl ReleaseProvider for Forges {
+    /// The default provider's organization or group.
+    fn org(&self) -> &str {
+        match (self.routing.read().expect("routing lock poisoned").default, &self.gitlab, &self.local) {
+            (ProviderKind::Gitlab, Some(gitlab), _) => gitlab.group(),
+            (ProviderKind::Local, _, Some(local)) => local.org(),
+            _ => self.github.org(),
+        }
+    }
+
+    /// Only GitHub reads in bulk.
+    async fn prefetch(&self, repos: &[String]) -> Result<()> {
+        let github: Vec<String> = repos.iter().filter(|repo| self.kind(repo) == ProviderKind::Github).cloned().collect();
+        if github.is_empty() {
+            return Ok(());
+        }
+        self.github.prefetch(&github).await
+    }
+
+    /// Repositories of the default provider's organization.
+    async fn list_org_repos(&self) -> Result<Vec<String>> {
+        let default = self.routing.read().expect("routing lock poisoned").default;
+        match default {
+            ProviderKind::Github => ReleaseProvider::list_org_repos(&self.github).await,
+            ProviderKind::Gitlab => self.gitlab()?.list_org_repos().await,
+            ProviderKind::Local => self.local()?.list_org_repos().await,
+        }
+    }
+
+    /// Also routes the repository's canonical name to the same provider, since that is how
+    /// the aggregator addresses it after a rename or transfer.
+    async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        let kind = self.kind(repo);
+        let info = dispatch!(self, repo, client => ReleaseProvider::get_repository(client, repo))?;
+        if let Some(info) = &info {
+            let canonical = self.canonical_name_on(kind, info);
+            let mut routing = self.routing.write().expect("routing lock poisoned");
+            if routing.provider(&canonical) != kind {
+                routing.per_repo.insert(canonical, kind);
+            }
+        }
+        Ok(info)
+    }
+
+    fn canonical_name(&self, info: &RepositoryInfo) -> String {
+        let kind = match &self.gitlab {
+            Some(gitlab) if info.html_url.starts_with(gitlab.web_url()) => ProviderKind::Gitlab,
+            _ => ProviderKind::Github,
+        };
+        self.canonical_name_on(kind, info)
+    }
+
+    async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_release(client, repo, tag))
+    }
+
+    async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_latest_release(client, repo))
+    }
+
+    async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        dispatch!(self, repo, client => ReleaseProvider::list_all_releases(client, repo))
+    }
+
+    async fn get_previous_release(
+        &self,
+        repo: &str,
+        current_release: &Release,
+        settings: Option<&RepoSettings>,
+        date_source: DateSource,
+        strategy: PreviousStrategy,
+    ) -> Result<Option<Release>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_previous_release(client, repo, current_release, settings, date_source, strategy))
+    }
+
+    async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        dispatch!(self, repo, client => ReleaseProvider::resolve_ref_sha(client, repo, reference))
+    }
+
+    async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        dispatch!(self, repo, client => ReleaseProvider::get_changes_between(client, repo, from, to))
+    }
+
+    async fn get_commits_between(&self, repo: &str, from: &str, to: &str) -> Result<Vec<CommitInfo>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_commits_between(client, repo, from, to))
+    }
+
+    async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_all_commits_until(client, repo, until))
+    }
+
+    async fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> Result<Option<String>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_file_contents(client, repo, path, reference))
+    }
+
+    async fn list_directory(&self, repo: &str, path: &str, reference: &str) -> Result<Vec<String>> {
+        dispatch!(self, repo, client => ReleaseProvider::list_directory(client, repo, path, reference))
+    }
+
+    async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        dispatch!(self, repo, client => ReleaseProvider::has_commits_before(client, repo, author, until))
+    }
+
+    async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        dispatch!(self, repo, client => ReleaseProvider::is_empty_repository(client, repo))
+    }
+
+    async fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> Result<Vec<PullRequest>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_pull_requests_for_commits(client, repo, shas))
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        dispatch!(self, repo, client => ReleaseProvider::list_merged_pull_requests(client, repo, base, since, until))
+    }
+
+    async fn list_open_issues(&self, repo: &str, label: &str) -> Result<Vec<Issue>> {
+        dispatch!(self, repo, client => ReleaseProvider::list_open_issues(client, repo, label))
+    }
+
+    async fn get_issues(&self, repo: &str, numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        dispatch!(self, repo, client => ReleaseProvider::get_issues(client, repo, numbers))
+    }
+}
@@ -0,0 +1,305 @@
+//! The reads an aggregation needs from a forge, so backends other than GitHub can stand in for
+//! `GitHubClient` and tests can aggregate without one.
+pub mod connection;
+pub mod forges;
+pub mod replay;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use crate::config::types::{Config, RepoSettings};
+use crate::github::types::{tag_version, CommitComparison, CommitInfo, DateSource, Issue, PreviousStrategy, PullRequest, Release, RepositoryInfo};
+
+/// Where a repository is hosted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    #[default]
+    Github,
+    /// gitlab.com or a self-hosted GitLab; "org" means the group projects belong to
+    Gitlab,
+    /// Clones on disk, read with `git`
+    Local,
+}
+
+impl std::str::FromStr for ProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "github" => Ok(ProviderKind::Github),
+            "gitlab" => Ok(ProviderKind::Gitlab),
+            "local" => Ok(ProviderKind::Local),
+            _ => Err(format!("Unknown provider: {} (expected github, gitlab or local)", s)),
+        }
+    }
+}
+
+/// Which provider hosts each repository: `default` for every repository except those listed
+/// in `per_repo`.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderRouting {
+    pub default: ProviderKind,
+    pub per_repo: HashMap<String, ProviderKind>,
+}
+
+impl ProviderRouting {
+    /// `provider` in each repository's settings, else `default`.
+    pub fn from_config(default: ProviderKind, config: Option<&Config>) -> Self {
+        let per_repo = config
+            .map(|c| c.repos.settings.iter()
+                .filter_map(|(repo, settings)| settings.provider.map(|kind| (repo.clone(), kind)))
+                .collect())
+            .unwrap_or_default();
+        ProviderRouting { default, per_repo }
+    }
+
+    pub fn provider(&self, repo: &str) -> ProviderKind {
+        self.per_repo.get(repo).copied().unwrap_or(self.default)
+    }
+
+    /// Whether any repository is hosted on `kind`.
+    pub fn uses(&self, kind: ProviderKind) -> bool {
+        self.default == kind || self.per_repo.values().any(|k| *k == kind)
+    }
+}
+
+/// Releases, commits and pull requests of an organization's repositories.
+///
+/// Repositories are named as configured: a bare name belongs to `org()`, `owner/name` lives
+/// elsewhere. Every backend reports releases as `Release`; those without release objects build
+/// them from tags.
+pub trait ReleaseProvider: Send + Sync {
+    /// The organization (or group) bare repository names belong to.
+    fn org(&self) -> &str;
+
+    /// Read `repos` in bulk ahead of an aggregation, if the backend can. Lookups must still
+    /// work for anything this didn't fetch.
+    fn prefetch(&self, repos: &[String]) -> impl Future<Output = Result<()>> + Send {
+        let _ = repos;
+        async { Ok(()) }
+    }
+
+    /// Names of the organization's repositories, for expanding repository globs.
+    fn list_org_repos(&self) -> impl Future<Output = Result<Vec<String>>> + Send;
+
+    /// Repository metadata, following renames; `None` if it doesn't exist.
+    fn get_repository(&self, repo: &str) -> impl Future<Output = Result<Option<RepositoryInfo>>> + Send;
+
+    /// How a repository is named from now on: bare if it belongs to `org()`, otherwise by full
+    /// name, e.g. after being transferred out of the organization.
+    fn canonical_name(&self, info: &RepositoryInfo) -> String {
+        if info.owner() == self.org() {
+            info.name.clone()
+        } else {
+            info.full_name.clone()
+        }
+    }
+
+    /// Release tagged `tag`, or the same tag with the `v` prefix added or removed.
+    fn get_release(&self, repo: &str, tag: &str) -> impl Future<Output = Result<Option<Release>>> + Send;
+
+    fn get_latest_release(&self, repo: &str) -> impl Future<Output = Result<Option<Release>>> + Send;
+
+    /// Every release, newest first.
+    fn list_all_releases(&self, repo: &str) -> impl Future<Output = Result<Vec<Release>>> + Send;
+
+    /// The release `current_release` follows, chosen from `list_all_releases` by `strategy`
+    /// among those following the repository's tag scheme.
+    fn get_previous_release(
+        &self,
+        repo: &str,
+        current_release: &Release,
+        settings: Option<&RepoSettings>,
+        date_source: DateSource,
+        strategy: PreviousStrategy,
+    ) -> impl Future<Output = Result<Option<Release>>> + Send {
+        async move {
+            let releases = self.list_all_releases(repo).await?;
+            Ok(previous_release(repo, releases, current_release, settings, date_source, strategy))
+        }
+    }
+
+    /// Commit SHA a tag, branch or SHA points at; `None` if it doesn't resolve.
+    fn resolve_ref_sha(&self, repo: &str, reference: &str) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// Commits (newest first) and changed files between two refs.
+    fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> impl Future<Output = Result<CommitComparison>> + Send;
+
+    fn get_commits_between(&self, repo: &str, from: &str, to: &str) -> impl Future<Output = Result<Vec<CommitInfo>>> + Send {
+        async move { Ok(self.get_changes_between(repo, from, to).await?.commits) }
+    }
+
+    /// The whole history up to `until`, newest first; empty for an empty repository.
+    fn get_all_commits_until(&self, repo: &str, until: &str) -> impl Future<Output = Result<Vec<CommitInfo>>> + Send;
+
+    /// Text of the file at `path` as of `reference`; `None` if there is no such file.
+    fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// Paths of the files directly in directory `path` as of `reference`; empty if there is no
+    /// such directory.
+    fn list_directory(&self, repo: &str, path: &str, reference: &str) -> impl Future<Output = Result<Vec<String>>> + Send;
+
+    /// Whether `author` (a login or email) committed to the repository before `until`.
+    fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Whether the repository has no commits at all.
+    fn is_empty_repository(&self, repo: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Pull requests associated with each commit. Lookups that fail are skipped.
+    fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> impl Future<Output = Result<Vec<PullRequest>>> + Send;
+
+    /// Pull requests merged into `base` (any branch if `None`) after `since` and up to `until`
+    /// (unbounded if `None`), oldest first.
+    fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<Vec<PullRequest>>> + Send;
+
+    /// Open issues labeled `label`, oldest first; pull requests are left out.
+    fn list_open_issues(&self, repo: &str, label: &str) -> impl Future<Output = Result<Vec<Issue>>> + Send;
+
+    /// Issues with the given numbers; pull requests and numbers that can't be read are left out.
+    fn get_issues(&self, repo: &str, numbers: Vec<u64>) -> impl Future<Output = Result<Vec<Issue>>> + Send;
+}
+
+/// Percent-encode a path segment or query value; project paths and tags may contain `/`.
+pub fn encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// The release in `releases` that `current_release` follows: the highest version below it with
+/// `PreviousStrategy::Semver` (falling back to dates when its tag isn't a version), otherwise
+/// the latest one dated before it. Only releases whose tags follow `settings`' tag scheme are
+/// considered; drafts and undated releases are ignored, and so are prereleases when following
+/// a stable version by semver.
+pub fn previous_release(
+    repo: &str,
+    releases: Vec<Release>,
+    current_release: &Release,
+    settings: Option<&RepoSettings>,
+    date_source: DateSource,
+    strategy: PreviousStrategy,
+) -> Option<Release> {
+    // Versions under the tag scheme, so `service-a/v1.2.0` isn't compared with `service-b/` tags
+    let version_of = |tag: &str| match settings {
+        Some(settings) => settings.version_of(tag),
+        None => Some(tag.to_string()),
+    };
+    let releases = releases.into_iter()
+        .filter(|release| !release.draft)
+        .filter_map(|release| version_of(&release.tag_name).map(|version| (version, release)));
+    if strategy == PreviousStrategy::Semver {
+        let current = version_of(&current_release.tag_name).unwrap_or_else(|| current_release.tag_name.clone());
+        match tag_version(&current) {
+            Some(current_version) => {
+                // Highest version strictly below the current one; tags that aren't versions are
+                // ignored, and a stable version follows the last stable one
+                let stable = current_version.pre.is_empty();
+                return releases
+                    .filter_map(|(version, release)| tag_version(&version).map(|version| (version, release)))
+                    .filter(|(version, release)| !(stable && (release.prerelease || !version.pre.is_empty())))
+                    .filter(|(version, _)| *version < current_version)
+                    .max_by(|(a, _), (b, _)| a.cmp(b))
+                    .map(|(_, release)| release);
+            }
+            None => tracing::warn!(
+                "{}: {} is not a semantic version; finding the previous release by date",
+                repo, current_release.tag_name
+            ),
+        }
+    }
+
+    let current_date = date_source.resolve(current_release.created_at, current_release.published_at)?;
+
+    // Find the release immediately before the current one by date
+    let mut previous: Option<(DateTime<Utc>, Release)> = None;
+    for (_, release) in releases {
+        let Some(date) = date_source.resolve(release.created_at, release.published_at) else {
+            continue;
+        };
+        if date < current_date && previous.as_ref().is_none_or(|(prev_date, _)| date > *prev_date) {
+            previous = Some((date, release));
+        }
+    }
+
+    previous.map(|(_, release)| release)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::previous_release;
+    use crate::config::types::RepoSettings;
+    use crate::github::types::{DateSource, PreviousStrategy, Release};
+    use chrono::{TimeZone, Utc};
+
+    fn release(tag: &str, day: u32) -> Release {
+        let date = Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+        Release::from_tag(tag, Some(date), None, "main".to_string())
+    }
+
+    fn previous(releases: Vec<Release>, current: &Release, strategy: PreviousStrategy) -> Option<String> {
+        previous_release("acme/api", releases, current, None, DateSource::Published, strategy).map(|r| r.tag_name)
+    }
+
+    #[test]
+    fn semver_skips_drafts_and_prereleases_of_a_stable_version() {
+        let draft = Release { draft: true, ..release("v1.2.0", 9) };
+        let marked = Release { prerelease: true, ..release("v1.1.5", 8) };
+        let releases = vec![release("v1.0.0", 1), release("v1.1.0", 5), release("v1.2.0-rc.1", 7), marked, draft];
+
+        let current = release("v1.2.1", 10);
+        assert_eq!(previous(releases.clone(), &current, PreviousStrategy::Semver).as_deref(), Some("v1.1.0"));
+
+        // A prerelease follows whatever came before it, prereleases included
+        let current = release("v1.2.0-rc.2", 10);
+        assert_eq!(previous(releases, &current, PreviousStrategy::Semver).as_deref(), Some("v1.2.0-rc.1"));
+    }
+
+    #[test]
+    fn date_skips_drafts_and_takes_the_latest_earlier_release() {
+        let draft = Release { draft: true, ..release("v9.9.9", 9) };
+        let marked = Release { prerelease: true, ..release("v2.0.0-beta.1", 8) };
+        let releases = vec![release("v1.0.0", 1), release("v1.1.0", 5), marked, draft, release("v1.2.0", 12)];
+
+        let current = release("v1.1.1", 10);
+        assert_eq!(previous(releases.clone(), &current, PreviousStrategy::Date).as_deref(), Some("v2.0.0-beta.1"));
+
+        let current = release("v1.0.1", 3);
+        assert_eq!(previous(releases, &current, PreviousStrategy::Date).as_deref(), Some("v1.0.0"));
+    }
+
+    #[test]
+    fn follows_the_tag_scheme_of_the_component() {
+        let releases = vec![
+            release("service-a/v1.0.0", 1),
+            release("service-b/v1.4.0", 2),
+            release("service-a/v1.1.0", 3),
+            release("service-b/v1.5.0", 4),
+            release("v2.0.0", 5),
+        ];
+        let current = release("service-a/v1.2.0", 6);
+        let settings = RepoSettings { tag_prefix: Some("service-a/".to_string()), ..RepoSettings::default() };
+        let pattern = RepoSettings { tag_pattern: Some("service-a/v{version}".to_string()), ..RepoSettings::default() };
+
+        for strategy in [PreviousStrategy::Semver, PreviousStrategy::Date] {
+            for settings in [&settings, &pattern] {
+                let previous = previous_release("acme/monorepo", releases.clone(), &current, Some(settings), DateSource::Published, strategy);
+                assert_eq!(previous.map(|r| r.tag_name).as_deref(), Some("service-a/v1.1.0"), "{:?}", strategy);
+            }
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! Record what a provider answers during an aggregation and answer the same calls again
+//! offline, so `bench` can time the pipeline without the network.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use crate::config::types::RepoSettings;
+use crate::github::types::{CommitComparison, CommitInfo, DateSource, Issue, PreviousStrategy, PullRequest, Release, RepositoryInfo};
+use super::ReleaseProvider;
+
+/// File the recording is kept in, inside a fixtures directory.
+pub const RECORDING_FILE: &str = "recording.json";
+
+/// A train's provider responses, as `bench --record` captured them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Recording {
+    pub org: String,
+    pub version: String,
+    pub repos: Vec<String>,
+    /// Whether pull requests were looked up, which replays must repeat to make the same calls
+    #[serde(default)]
+    pub include_prs: bool,
+    pub recorded_at: DateTime<Utc>,
+    /// Each call's result, keyed by the call: a JSON array of its name and arguments
+    pub responses: BTreeMap<String, serde_json::Value>,
+}
+
+impl Recording {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(RECORDING_FILE);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}; record fixtures with `bench --record`", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(RECORDING_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn call_key(method: &str, args: &[&str]) -> String {
+    serde_json::json!([method, args]).to_string()
+}
+
+/// Passes every call through to `inner` and keeps what it returned. Failed calls aren't kept.
+pub struct RecordingProvider<P: ReleaseProvider> {
+    inner: P,
+    responses: Mutex<BTreeMap<String, serde_json::Value>>,
+}
+
+impl<P: ReleaseProvider> RecordingProvider<P> {
+    pub fn new(inner: P) -> Self {
+        Self { inner, responses: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// The responses recorded so far.
+    pub fn responses(&self) -> BTreeMap<String, serde_json::Value> {
+        self.responses.lock().expect("recording lock poisoned").clone()
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    fn keep<T: Serialize>(&self, method: &str, args: &[&str], result: Result<T>) -> Result<T> {
+        let value = result?;
+        let json = serde_json::to_value(&value)?;
+        self.responses.lock().expect("recording lock poisoned").insert(call_key(method, args), json);
+        Ok(value)
+    }
+}
+
+impl<P: ReleaseProvider> ReleaseProvider for RecordingProvider<P> {
+    fn org(&self) -> &str {
+        self.inner.org()
+    }
+
+    async fn prefetch(&self, repos: &[String]) -> Result<()> {
+        self.inner.prefetch(repos).await
+    }
+
+    async fn list_org_repos(&self) -> Result<Vec<String>> {
+        self.keep("list_org_repos", &[], self.inner.list_org_repos().await)
+    }
+
+    async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        self.keep("get_repository", &[repo], self.inner.get_repository(repo).await)
+    }
+
+    fn canonical_name(&self, info: &RepositoryInfo) -> String {
+        self.inner.canonical_name(info)
+    }
+
+    async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        self.keep("get_release", &[repo, tag], self.inner.get_release(repo, tag).await)
+    }
+
+    async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        self.keep("get_latest_release", &[repo], self.inner.get_latest_release(repo).await)
+    }
+
+    async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        self.keep("list_all_releases", &[repo], self.inner.list_all_releases(repo).await)
+    }
+
+    async fn get_previous_release(
+        &self,
+        repo: &str,
+        current_release: &Release,
+        settings: Option<&RepoSettings>,
+        date_source: DateSource,
+        strategy: PreviousStrategy,
+    ) -> Result<Option<Release>> {
+        let result = self.inner.get_previous_release(repo, current_release, settings, date_source, strategy).await;
+        let (date_source, strategy) = (format!("{:?}", date_source), format!("{:?}", strategy));
+        let scheme = settings.map_or_else(|| "{version}".to_string(), |settings| settings.tag_for("{version}"));
+        self.keep("get_previous_release", &[repo, &current_release.tag_name, &scheme, &date_source, &strategy], result)
+    }
+
+    async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        self.keep("resolve_ref_sha", &[repo, reference], self.inner.resolve_ref_sha(repo, reference).await)
+    }
+
+    async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        self.keep("get_changes_between", &[repo, from, to], self.inner.get_changes_between(repo, from, to).await)
+    }
+
+    async fn get_commits_between(&self, repo: &str, from: &str, to: &str) -> Result<Vec<CommitInfo>> {
+        self.keep("get_commits_between", &[repo, from, to], self.inner.get_commits_between(repo, from, to).await)
+    }
+
+    async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        self.keep("get_all_commits_until", &[repo, until], self.inner.get_all_commits_until(repo, until).await)
+    }
+
+    async fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> Result<Option<String>> {
+        self.keep("get_file_contents", &[repo, path, reference], self.inner.get_file_contents(repo, path, reference).await)
+    }
+
+    async fn list_directory(&self, repo: &str, path: &str, reference: &str) -> Result<Vec<String>> {
+        self.keep("list_directory", &[repo, path, reference], self.inner.list_directory(repo, path, reference).await)
+    }
+
+    async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        let result = self.inner.has_commits_before(repo, author, until).await;
+        self.keep("has_commits_before", &[repo, author, &until.to_rfc3339()], result)
+    }
+
+    async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        self.keep("is_empty_repository", &[repo], self.inner.is_empty_repository(repo).await)
+    }
+
+    async fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> Result<Vec<PullRequest>> {
+        let key = shas.join(",");
+        let result = self.inner.get_pull_requests_for_commits(repo, shas).await;
+        self.keep("get_pull_requests_for_commits", &[repo, &key], result)
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        let result = self.inner.list_merged_pull_requests(repo, base, since, until).await;
+        let since = since.map(|since| since.to_rfc3339()).unwrap_or_default();
+        let until = until.map(|until| until.to_rfc3339()).unwrap_or_default();
+        self.keep("list_merged_pull_requests", &[repo, base.unwrap_or_default(), &since, &until], result)
+    }
+
+    async fn list_open_issues(&self, repo: &str, label: &str) -> Result<Vec<Issue>> {
+        self.keep("list_open_issues", &[repo, label], self.inner.list_open_issues(repo, label).await)
+    }
+
+    async fn get_issues(&self, repo: &str, numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        let key = numbers.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+        let result = self.inner.get_issues(repo, numbers).await;
+        self.keep("get_issues", &[repo, &key], result)
+    }
+}
+
+/// Answers calls from a `Recording`, counting them by method. A call that wasn't recorded
+/// fails, since the pipeline now asks for something it didn't before.
+pub struct ReplayProvider {
+    recording: Arc<Recording>,
+    calls: Mutex<BTreeMap<String, u64>>,
+}
+
+impl ReplayProvider {
+    pub fn new(recording: Arc<Recording>) -> Self {
+        Self { recording, calls: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Calls answered so far, by method.
+    pub fn calls(&self) -> BTreeMap<String, u64> {
+        self.calls.lock().expect("call count lock poisoned").clone()
+    }
+
+    fn answer<T: DeserializeOwned>(&self, method: &str, args: &[&str]) -> Result<T> {
+        *self.calls.lock().expect("call count lock poisoned").entry(method.to_string()).or_default() += 1;
+        let key = call_key(method, args);
+        let value = self.recording.responses.get(&key)
+            .with_context(|| format!("No recorded response for {}; record the fixtures again", key))?;
+        T::deserialize(value).with_context(|| format!("Failed to read the recorded response for {}", key))
+    }
+}
+
+impl ReleaseProvider for ReplayProvider {
+    fn org(&self) -> &str {
+        &self.recording.org
+    }
+
+    async fn list_org_repos(&self) -> Result<Vec<String>> {
+        self.answer("list_org_repos", &[])
+    }
+
+    async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        self.answer("get_repository", &[repo])
+    }
+
+    async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        self.answer("get_release", &[repo, tag])
+    }
+
+    async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        self.answer("get_latest_release", &[repo])
+    }
+
+    async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        self.answer("list_all_releases", &[repo])
+    }
+
+    async fn get_previous_release(
+        &self,
+        repo: &str,
+        current_release: &Release,
+        settings: Option<&RepoSettings>,
+        date_source: DateSource,
+        strategy: PreviousStrategy,
+    ) -> Result<Option<Release>> {
+        let (date_source, strategy) = (format!("{:?}", date_source), format!("{:?}", strategy));
+        let scheme = settings.map_or_else(|| "{version}".to_string(), |settings| settings.tag_for("{version}"));
+        self.answer("get_previous_release", &[repo, &current_release.tag_name, &scheme, &date_source, &strategy])
+    }
+
+    async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        self.answer("resolve_ref_sha", &[repo, reference])
+    }
+
+    async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        self.answer("get_changes_between", &[repo, from, to])
+    }
+
+    async fn get_commits_between(&self, repo: &str, from: &str, to: &str) -> Result<Vec<CommitInfo>> {
+        self.answer("get_commits_between", &[repo, from, to])
+    }
+
+    async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        self.answer("get_all_commits_until", &[repo, until])
+    }
+
+    async fn get_file_contents(&self, repo: &str, path: &str, reference: &str) -> Result<Option<String>> {
+        self.answer("get_file_contents", &[repo, path, reference])
+    }
+
+    async fn list_directory(&self, repo: &str, path: &str, reference: &str) -> Result<Vec<String>> {
+        self.answer("list_directory", &[repo, path, reference])
+    }
+
+    async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        self.answer("has_commits_before", &[repo, author, &until.to_rfc3339()])
+    }
+
+    async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        self.answer("is_empty_repository", &[repo])
+    }
+
+    async fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> Result<Vec<PullRequest>> {
+        self.answer("get_pull_requests_for_commits", &[repo, &shas.join(",")])
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        let since = since.map(|since| since.to_rfc3339()).unwrap_or_default();
+        let until = until.map(|until| until.to_rfc3339()).unwrap_or_default();
+        self.answer("list_merged_pull_requests", &[repo, base.unwrap_or_default(), &since, &until])
+    }
+
+    async fn list_open_issues(&self, repo: &str, label: &str) -> Result<Vec<Issue>> {
+        self.answer("list_open_issues", &[repo, label])
+    }
+
+    async fn get_issues(&self, repo: &str, numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        let key = numbers.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+        self.answer("get_issues", &[repo, &key])
+    }
+}
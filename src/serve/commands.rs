@@ -0,0 +1,274 @@
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::path::Path;
+use std::sync::Arc;
+use crate::aggregator::changelog_generator::{ChangelogGenerator, OutputFormat};
+use crate::aggregator::entry_format::EntryFormats;
+use crate::aggregator::publisher;
+use crate::aggregator::release_fetcher::{AggregatedRelease, AggregatorConfig, ReleaseAggregator};
+use crate::config::types::{select_repos, CommandPermission, Config, SlashCommandConfig};
+use crate::github::client::GitHubClient;
+use crate::github::links::WebLinks;
+use crate::state::store::StateStore;
+use super::AppState;
+
+/// The comment command, followed by the train's version and optionally a section name.
+pub const COMMAND: &str = "/release-notes";
+
+/// GitHub rejects comments longer than this many characters.
+const MAX_COMMENT_BODY: usize = 65_536;
+
+/// What a chat command asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrainRequest {
+    pub version: String,
+    /// Name of a configured section to limit the train to; every repository otherwise
+    pub group: Option<String>,
+}
+
+/// A train generated for a command, with its notes rendered for a comment.
+pub struct GeneratedTrain {
+    pub release: AggregatedRelease,
+    /// Collapsed Markdown notes
+    pub notes: String,
+    pub links: WebLinks,
+}
+
+/// Aggregates and renders a requested train, the way `generate` would with the config's defaults.
+pub type Generate = Arc<dyn Fn(TrainRequest) -> BoxFuture<'static, Result<GeneratedTrain>> + Send + Sync>;
+
+/// Generate the train `request` asks for the way `generate` does with `config`'s defaults:
+/// the configured repositories, or only those of the section its group names.
+/// `aggregator_config` holds the settings to aggregate with; `state` is the state file whose
+/// branch history carries on from earlier trains.
+pub async fn generate_train(
+    client: GitHubClient,
+    config: &Config,
+    aggregator_config: AggregatorConfig,
+    state: &Path,
+    request: TrainRequest,
+) -> Result<GeneratedTrain> {
+    let (repos, sections) = match &request.group {
+        Some(group) => {
+            let section = config.sections.iter()
+                .find(|section| section.name.eq_ignore_ascii_case(group))
+                .ok_or_else(|| {
+                    let names: Vec<&str> = config.sections.iter().map(|s| s.name.as_str()).collect();
+                    anyhow::anyhow!("No section named {}; configured sections: {}", group, names.join(", "))
+                })?;
+            (select_repos(&client, Some(config), section.repos.clone()).await?, vec![section.clone()])
+        }
+        None => (select_repos(&client, Some(config), vec![]).await?, config.sections.clone()),
+    };
+    let aggregator = ReleaseAggregator::new(client, AggregatorConfig { sections, ..aggregator_config })
+        .with_branch_history(StateStore::open(state)?.branches)
+        .with_known_issues(config.features.known_issues());
+    let release = aggregator.aggregate(&request.version, repos).await?;
+
+    let links = aggregator.client().web_links();
+    let notes = ChangelogGenerator::new(OutputFormat::Markdown, None)?
+        .with_entry_formats(EntryFormats::from_config(&config.entry_formats)?)
+        .with_links(links.clone())
+        .generate_collapsed(&release);
+    Ok(GeneratedTrain { release, notes, links })
+}
+
+/// Answers `/release-notes <version>` comments on the configured repository's issues.
+pub struct SlashCommands {
+    config: SlashCommandConfig,
+    /// `owner/name` of `config.repo`
+    repo: String,
+    secret: String,
+    client: GitHubClient,
+    generate: Generate,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueCommentEvent {
+    action: String,
+    issue: EventIssue,
+    comment: EventComment,
+    repository: EventRepository,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventIssue {
+    number: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventComment {
+    body: String,
+    user: EventUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventUser {
+    login: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventRepository {
+    full_name: String,
+}
+
+/// A command found in a comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Generate(TrainRequest),
+    /// The command without a version, or with too many arguments
+    Usage,
+}
+
+impl SlashCommands {
+    /// Commands for `config`, checking permissions and replying with `client`. The webhook secret
+    /// is read from `config.secret_env`.
+    pub fn new(config: SlashCommandConfig, client: GitHubClient, generate: Generate) -> Result<Self> {
+        let secret = std::env::var(&config.secret_env)
+            .with_context(|| format!("serve.commands: webhook secret not found in ${}", config.secret_env))?;
+        let repo = match config.repo.split_once('/') {
+            Some(_) => config.repo.clone(),
+            None => format!("{}/{}", client.org(), config.repo),
+        };
+
+        Ok(Self {
+            config,
+            repo,
+            secret,
+            client,
+            generate,
+        })
+    }
+
+    pub fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    /// Whether `signature` (the `X-Hub-Signature-256` header) is the HMAC of `body` with the secret.
+    fn verify(&self, body: &[u8], signature: &str) -> bool {
+        let Some(signature) = signature.strip_prefix("sha256=").and_then(|hex| hex::decode(hex).ok()) else {
+            return false;
+        };
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Run `command` from `login` on issue `number` and reply there. Failures are reported in
+    /// the reply rather than returned, since nobody else is waiting on the webhook.
+    async fn run(&self, state: &AppState, number: u64, login: &str, command: Command) {
+        let reply = match self.reply(state, login, command).await {
+            Ok(reply) => reply,
+            Err(e) => format!("❌ @{} couldn't generate the release notes: {:#}", login, e),
+        };
+        let reply = publisher::truncate_with_notice(
+            &reply,
+            MAX_COMMENT_BODY,
+            "\n\n> ⚠️ Truncated to fit GitHub's comment size limit.\n",
+        );
+        if let Err(e) = self.client.create_issue_comment(&self.repo, number, &reply).await {
+            tracing::warn!("Failed to reply on #{} in {}: {:#}", number, self.repo, e);
+        }
+    }
+
+    async fn reply(&self, state: &AppState, login: &str, command: Command) -> Result<String> {
+        let permission = self.client.get_collaborator_permission(&self.repo, login).await?;
+        if CommandPermission::from_github(&permission).is_none_or(|level| level < self.config.permission) {
+            return Ok(format!(
+                "@{} generating release notes needs {} access to {}.",
+                login,
+                self.config.permission.as_str(),
+                self.repo
+            ));
+        }
+        let request = match command {
+            Command::Generate(request) => request,
+            Command::Usage => return Ok(format!("@{} usage: `{} <version> [section]`, e.g. `{} v1.4`", login, COMMAND, COMMAND)),
+        };
+
+        tracing::info!("{} requested release notes for {} on {}", login, request.version, self.repo);
+        let version = request.version.clone();
+        let generated = state.generate(&self.generate, request).await?;
+
+        let mut reply = format!("@{} here are the release notes for **{}**", login, version);
+        if let Some(url) = &self.config.public_url {
+            let url = url.trim_end_matches('/');
+            reply.push_str(&format!(
+                " ([JSON]({}/api/trains/{}), [components]({}/api/trains/{}/components))",
+                url, version, url, version
+            ));
+        }
+        reply.push_str(".\n\n");
+        reply.push_str(&generated.notes);
+        Ok(reply)
+    }
+}
+
+/// The command in a comment's first line, if it has one.
+fn parse_command(body: &str) -> Option<Command> {
+    let line = body.lines().next()?.trim();
+    let rest = line.strip_prefix(COMMAND)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        // e.g. /release-notes-draft
+        return None;
+    }
+    Some(match train_request(rest) {
+        Some(request) => Command::Generate(request),
+        None => Command::Usage,
+    })
+}
+
+/// `<version> [section]` from a command's arguments.
+pub fn train_request(arguments: &str) -> Option<TrainRequest> {
+    let arguments: Vec<&str> = arguments.split_whitespace().collect();
+    match arguments.as_slice() {
+        [version] => Some(TrainRequest { version: version.to_string(), group: None }),
+        [version, group] => Some(TrainRequest { version: version.to_string(), group: Some(group.to_string()) }),
+        _ => None,
+    }
+}
+
+/// `POST /webhooks/github`: issue comment events, authenticated by their signature rather
+/// than a bearer token. Commands run in the background; GitHub only waits 10 seconds for an answer.
+pub async fn webhook(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+    let Some(commands) = state.commands.clone() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    if !commands.verify(&body, header("x-hub-signature-256")) {
+        return (StatusCode::UNAUTHORIZED, "Invalid webhook signature").into_response();
+    }
+
+    match header("x-github-event") {
+        "ping" => return StatusCode::NO_CONTENT.into_response(),
+        "issue_comment" => {}
+        _ => return StatusCode::ACCEPTED.into_response(),
+    }
+    let event: IssueCommentEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Unreadable issue_comment event: {}", e)).into_response(),
+    };
+    // Bots are ignored, this server's own replies included
+    if event.action != "created" || event.comment.user.kind == "Bot" || !event.repository.full_name.eq_ignore_ascii_case(commands.repo()) {
+        return StatusCode::ACCEPTED.into_response();
+    }
+    let Some(command) = parse_command(&event.comment.body) else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    tokio::spawn(async move {
+        commands.run(&state, event.issue.number, &event.comment.user.login, command).await;
+    });
+    StatusCode::ACCEPTED.into_response()
+}
@@ -0,0 +1,295 @@
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Enum, Object, Schema, SimpleObject};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use crate::aggregator::commit_analyzer::{CommitType, EnrichedCommit};
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentRelease, ComponentStatus};
+use super::store::find_train;
+
+pub type ReleaseSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// Build the schema. Each request must carry the current trains as
+/// `Arc<Vec<AggregatedRelease>>` request data.
+pub fn schema() -> ReleaseSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(12)
+        .finish()
+}
+
+fn trains<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Arc<Vec<AggregatedRelease>>> {
+    ctx.data::<Arc<Vec<AggregatedRelease>>>()
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Stored trains, newest first.
+    async fn trains(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only trains generated at or after this time")] since: Option<DateTime<Utc>>,
+        #[graphql(default = 20)] limit: usize,
+    ) -> async_graphql::Result<Vec<Train>> {
+        Ok(trains(ctx)?
+            .iter()
+            .filter(|train| since.map_or(true, |since| train.date >= since))
+            .take(limit)
+            .cloned()
+            .map(Train)
+            .collect())
+    }
+
+    /// A train by version, including nested sub-trains.
+    async fn train(&self, ctx: &Context<'_>, version: String) -> async_graphql::Result<Option<Train>> {
+        Ok(find_train(trains(ctx)?, &version).cloned().map(Train))
+    }
+}
+
+pub struct Train(AggregatedRelease);
+
+#[Object]
+impl Train {
+    async fn version(&self) -> &str {
+        &self.0.version
+    }
+
+    /// When the train was aggregated
+    async fn date(&self) -> DateTime<Utc> {
+        self.0.date
+    }
+
+    async fn total_repos(&self) -> usize {
+        self.0.summary.total_repos
+    }
+
+    async fn updated_repos(&self) -> usize {
+        self.0.summary.updated_repos
+    }
+
+    async fn total_commits(&self) -> usize {
+        self.0.summary.total_commits
+    }
+
+    async fn contributors(&self) -> &[String] {
+        &self.0.summary.contributors
+    }
+
+    async fn skipped_archived(&self) -> &[String] {
+        &self.0.summary.skipped_archived
+    }
+
+    async fn sections(&self) -> Vec<Section> {
+        self.0.sections.iter()
+            .map(|s| Section { name: s.name.clone(), repositories: s.repositories.clone() })
+            .collect()
+    }
+
+    async fn components(&self, repository: Option<String>, status: Option<ComponentState>) -> Vec<Component> {
+        self.0.components.iter()
+            .filter(|c| repository.as_ref().map_or(true, |r| &c.repository == r))
+            .filter(|c| status.map_or(true, |s| ComponentState::of(&c.status) == s))
+            .cloned()
+            .map(Component)
+            .collect()
+    }
+
+    async fn sub_trains(&self) -> Vec<Train> {
+        self.0.sub_trains.iter().cloned().map(Train).collect()
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Section {
+    name: String,
+    repositories: Vec<String>,
+}
+
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ComponentState {
+    Released,
+    /// No release for this train; the repository's latest release is reported instead
+    Unchanged,
+    NotYetReleased,
+}
+
+impl ComponentState {
+    fn of(status: &ComponentStatus) -> Self {
+        match status {
+            ComponentStatus::Released { .. } => ComponentState::Released,
+            ComponentStatus::NoRelease { .. } => ComponentState::Unchanged,
+            ComponentStatus::NotYetReleased { .. } => ComponentState::NotYetReleased,
+        }
+    }
+}
+
+pub struct Component(ComponentRelease);
+
+#[Object]
+impl Component {
+    async fn repository(&self) -> &str {
+        &self.0.repository
+    }
+
+    async fn renamed_from(&self) -> Option<&str> {
+        self.0.renamed_from.as_deref()
+    }
+
+    async fn status(&self) -> ComponentState {
+        ComponentState::of(&self.0.status)
+    }
+
+    /// Released version, or the latest release of an unchanged component
+    async fn current_version(&self) -> Option<&str> {
+        match &self.0.status {
+            ComponentStatus::Released { current_version, .. } => Some(current_version),
+            ComponentStatus::NoRelease { latest_version, .. } => latest_version.as_deref(),
+            ComponentStatus::NotYetReleased { .. } => None,
+        }
+    }
+
+    async fn previous_version(&self) -> Option<&str> {
+        match &self.0.status {
+            ComponentStatus::Released { previous_version, .. } => previous_version.as_deref(),
+            _ => None,
+        }
+    }
+
+    async fn release_date(&self) -> Option<DateTime<Utc>> {
+        match &self.0.status {
+            ComponentStatus::Released { release_date, .. } => *release_date,
+            ComponentStatus::NoRelease { latest_date, .. } => *latest_date,
+            ComponentStatus::NotYetReleased { .. } => None,
+        }
+    }
+
+    async fn target_sha(&self) -> Option<&str> {
+        match &self.0.status {
+            ComponentStatus::Released { target_sha, .. } => target_sha.as_deref(),
+            _ => None,
+        }
+    }
+
+    async fn release_notes(&self) -> Option<&str> {
+        match &self.0.status {
+            ComponentStatus::Released { release_notes, .. } => release_notes.as_deref(),
+            _ => None,
+        }
+    }
+
+    async fn stats(&self) -> Option<Stats> {
+        match &self.0.status {
+            ComponentStatus::Released { stats, .. } => Some(Stats {
+                commit_count: stats.commit_count,
+                features: stats.features,
+                fixes: stats.fixes,
+                breaking_changes: stats.breaking_changes,
+                contributors: stats.contributors.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Releases rolled up into this component when a date range was aggregated, oldest first
+    async fn releases(&self) -> Vec<ReleaseEntry> {
+        match &self.0.status {
+            ComponentStatus::Released { releases, .. } => releases.iter()
+                .map(|r| ReleaseEntry {
+                    version: r.version.clone(),
+                    date: r.date,
+                    commit_count: r.commit_count,
+                    release_notes: r.release_notes.clone(),
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Commits shipped in this release, optionally narrowed by type keyword (`feat`, `fix`, ...)
+    /// or breaking flag
+    async fn commits(&self, r#type: Option<String>, breaking: Option<bool>) -> async_graphql::Result<Vec<Commit>> {
+        let ComponentStatus::Released { commits, .. } = &self.0.status else {
+            return Ok(vec![]);
+        };
+        let commit_type = match r#type {
+            Some(keyword) => Some(
+                CommitType::from_keyword(&keyword)
+                    .ok_or_else(|| async_graphql::Error::new(format!("Unknown commit type: {}", keyword)))?,
+            ),
+            None => None,
+        };
+
+        Ok(commits.iter()
+            .filter(|c| commit_type.as_ref().map_or(true, |t| c.commit_type.as_ref() == Some(t)))
+            .filter(|c| breaking.map_or(true, |b| c.breaking == b))
+            .cloned()
+            .map(Commit)
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ReleaseEntry {
+    version: String,
+    date: Option<DateTime<Utc>>,
+    commit_count: usize,
+    release_notes: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct Stats {
+    commit_count: usize,
+    features: usize,
+    fixes: usize,
+    breaking_changes: usize,
+    contributors: Vec<String>,
+}
+
+pub struct Commit(EnrichedCommit);
+
+#[Object]
+impl Commit {
+    async fn sha(&self) -> &str {
+        &self.0.sha
+    }
+
+    async fn message(&self) -> &str {
+        &self.0.message
+    }
+
+    async fn author(&self) -> &str {
+        &self.0.author
+    }
+
+    async fn date(&self) -> DateTime<Utc> {
+        self.0.date
+    }
+
+    /// Commit category, e.g. `Feature` or `Fix`; null when uncategorized
+    async fn r#type(&self) -> Option<String> {
+        self.0.commit_type.as_ref().map(|t| format!("{:?}", t))
+    }
+
+    async fn scope(&self) -> Option<&str> {
+        self.0.scope.as_deref()
+    }
+
+    async fn breaking(&self) -> bool {
+        self.0.breaking
+    }
+
+    async fn pr_number(&self) -> Option<u64> {
+        self.0.pr_number
+    }
+
+    /// Issue references, `#123` for GitHub issues and `API-123` for tracker keys
+    async fn issues(&self) -> Vec<String> {
+        self.0.issues.iter()
+            .map(|n| format!("#{}", n))
+            .chain(self.0.issue_keys.iter().cloned())
+            .collect()
+    }
+
+    async fn verified(&self) -> bool {
+        self.0.verified
+    }
+}
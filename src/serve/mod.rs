@@ -0,0 +1,232 @@
+pub mod commands;
+pub mod graphql;
+pub mod rest;
+pub mod slack;
+pub mod store;
+
+use anyhow::Result;
+use axum::extract::{Request, State};
+use axum::Extension;
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use std::path::Path;
+use std::sync::Arc;
+use crate::aggregator::calendar;
+use crate::aggregator::redaction::Redactor;
+use crate::aggregator::release_fetcher::{AggregatedRelease, AggregatorConfig};
+use crate::config::types::{Audience, CalendarConfig, Config};
+use crate::github::auth::GitHubAuth;
+use crate::github::client::GitHubClient;
+use crate::provider::connection::Connection;
+use self::commands::{Generate, GeneratedTrain, SlashCommands, TrainRequest};
+use self::graphql::ReleaseSchema;
+use self::slack::SlackCommands;
+use self::store::TrainStore;
+
+#[derive(Clone)]
+pub struct AppState {
+    store: Arc<TrainStore>,
+    schema: ReleaseSchema,
+    /// Accepted bearer tokens and the audience each one sees; empty leaves the server open
+    tokens: Arc<Vec<(String, Audience)>>,
+    redactor: Arc<Redactor>,
+    calendar: Arc<CalendarConfig>,
+    /// Issue comment commands, when `serve.commands` is configured
+    commands: Option<Arc<SlashCommands>>,
+    /// The Slack slash command, when `serve.slack` is configured
+    slack: Option<Arc<SlackCommands>>,
+    /// Held while a command generates a train, so they run one at a time
+    generating: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl AppState {
+    /// Stored trains as `audience` may see them.
+    fn trains(&self, audience: Audience) -> Result<Arc<Vec<AggregatedRelease>>> {
+        let trains = self.store.trains()?;
+        Ok(match audience {
+            Audience::Internal => trains,
+            Audience::External => Arc::new(trains.iter().map(|train| self.redactor.apply(train)).collect()),
+        })
+    }
+
+    /// Generate a train a command asked for and store it, so it's served right away.
+    async fn generate(&self, generate: &Generate, request: TrainRequest) -> Result<GeneratedTrain> {
+        let _generating = self.generating.lock().await;
+        let generated = generate(request).await?;
+        self.store.save(&generated.release)?;
+        Ok(generated)
+    }
+}
+
+/// Serve the trains exported to `data_dir` until the process is stopped. `api_token` grants
+/// internal access; `serve.tokens` in the config add more tokens, each with its own audience.
+/// With `commands`, GitHub's issue comment webhook is accepted at `/webhooks/github`; with
+/// `slack`, Slack's slash command at `/slack/commands`.
+pub async fn run(
+    data_dir: &Path,
+    bind: &str,
+    api_token: Option<String>,
+    config: Option<&Config>,
+    commands: Option<SlashCommands>,
+    slack: Option<SlackCommands>,
+) -> Result<()> {
+    let mut tokens: Vec<(String, Audience)> = api_token.into_iter().map(|t| (t, Audience::Internal)).collect();
+    for configured in config.map(|c| c.serve.tokens.as_slice()).unwrap_or_default() {
+        let token = std::env::var(&configured.token_env)
+            .map_err(|_| anyhow::anyhow!("serve.tokens: environment variable {} is not set", configured.token_env))?;
+        tokens.push((token, configured.audience));
+    }
+    let redaction = config.map(|c| c.redaction.clone()).unwrap_or_default();
+
+    let state = AppState {
+        store: Arc::new(TrainStore::new(data_dir)?),
+        schema: graphql::schema(),
+        tokens: Arc::new(tokens),
+        redactor: Arc::new(Redactor::from_config(&redaction)?),
+        calendar: Arc::new(config.map(|c| c.calendar.clone()).unwrap_or_default()),
+        commands: commands.map(Arc::new),
+        slack: slack.map(Arc::new),
+        generating: Arc::new(tokio::sync::Mutex::new(())),
+    };
+    if state.tokens.is_empty() {
+        eprintln!("⚠ No API token set; anyone who can reach {} can read the stored trains", bind);
+    }
+
+    let command_repo = state.commands.as_ref().map(|c| c.repo().to_string());
+    let slack = state.slack.is_some();
+    let app = Router::new()
+        .route("/graphql", get(graphiql).post(graphql_query))
+        .route("/calendar.ics", get(calendar_feed))
+        .merge(rest::routes())
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        // Webhook deliveries and Slack commands are signed instead of carrying a bearer token
+        .route("/webhooks/github", post(commands::webhook))
+        .route("/slack/commands", post(slack::slash_command))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    let addr = listener.local_addr()?;
+    println!("Serving {} on http://{} (GraphQL at /graphql, REST under /api, calendar at /calendar.ics)", data_dir.display(), addr);
+    if let Some(repo) = command_repo {
+        println!("Answering {} comments on {} issues via POST /webhooks/github", commands::COMMAND, repo);
+    }
+    if slack {
+        println!("Answering Slack slash commands via POST /slack/commands");
+    }
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// `serve.commands` and `serve.slack` from `config`, generating trains with a client of their own
+/// per request. Issue comments are answered with `reply_auth` when given, so replies can use a
+/// write token; otherwise with the connection's credentials.
+pub async fn chat_commands(
+    connection: Connection,
+    reply_auth: Option<GitHubAuth>,
+    config: &Config,
+    aggregator_config: AggregatorConfig,
+) -> Result<(Option<SlashCommands>, Option<SlackCommands>)> {
+    if config.serve.commands.is_none() && config.serve.slack.is_none() {
+        return Ok((None, None));
+    }
+    // Issue comment commands check permissions and reply with a client of their own
+    let comment_client = match &config.serve.commands {
+        Some(_) => {
+            let reply_auth = reply_auth.unwrap_or_else(|| connection.auth.clone());
+            Some(GitHubClient::new(&reply_auth, connection.org.clone(), connection.host.clone()).await?)
+        }
+        None => None,
+    };
+
+    let file_config = config.clone();
+    let generate: Generate = Arc::new(move |request: TrainRequest| {
+        let (connection, config, aggregator_config) = (connection.clone(), file_config.clone(), aggregator_config.clone());
+        Box::pin(async move {
+            let client = connection.github_client(Some(&config)).await?;
+            commands::generate_train(client, &config, aggregator_config, &connection.state, request).await
+        })
+    });
+
+    let commands = match (config.serve.commands.clone(), comment_client) {
+        (Some(commands), Some(client)) => Some(SlashCommands::new(commands, client, generate.clone())?),
+        _ => None,
+    };
+    let slack = match &config.serve.slack {
+        Some(slack) => Some(SlackCommands::new(slack, generate)?),
+        None => None,
+    };
+    Ok((commands, slack))
+}
+
+async fn graphiql() -> impl IntoResponse {
+    Html(async_graphql::http::GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+async fn graphql_query(
+    State(state): State<AppState>,
+    Extension(audience): Extension<Audience>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let response = match state.trains(audience) {
+        Ok(trains) => state.schema.execute(request.data(trains)).await,
+        Err(e) => async_graphql::Response::from_errors(vec![async_graphql::ServerError::new(format!("{:#}", e), None)]),
+    };
+    Json(response)
+}
+
+/// Past and scheduled trains as an iCalendar feed for calendar subscriptions.
+async fn calendar_feed(State(state): State<AppState>, Extension(audience): Extension<Audience>) -> Response {
+    let trains = match state.trains(audience) {
+        Ok(trains) => trains,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+    };
+    let events = calendar::train_events(&trains, &state.calendar);
+    let name = state.calendar.name.as_deref().unwrap_or(calendar::DEFAULT_CALENDAR_NAME);
+    let ics = calendar::to_ics(&events, name, chrono::Utc::now());
+    ([(header::CONTENT_TYPE, "text/calendar; charset=utf-8")], ics).into_response()
+}
+
+/// Compare a configured token with a presented one in time that depends only on their lengths,
+/// so response timing doesn't reveal how much of a guess was right.
+fn tokens_match(token: &str, presented: &str) -> bool {
+    token.len() == presented.len()
+        && token.bytes().zip(presented.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Resolve the caller's audience from their bearer token and attach it to the request.
+async fn require_token(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let audience = if state.tokens.is_empty() {
+        Some(Audience::Internal)
+    } else {
+        let presented = request.headers().get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        presented.and_then(|presented| {
+            state.tokens.iter().find(|(token, _)| tokens_match(token, presented)).map(|(_, audience)| *audience)
+        })
+    };
+
+    match audience {
+        Some(audience) => {
+            request.extensions_mut().insert(audience);
+            next.run(request).await
+        }
+        None => (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "Missing or invalid bearer token" }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tokens_match;
+
+    #[test]
+    fn matches_only_identical_tokens() {
+        assert!(tokens_match("s3cret", "s3cret"));
+        assert!(!tokens_match("s3cret", "s3creT"));
+        assert!(!tokens_match("s3cret", "s3cre"));
+        assert!(!tokens_match("s3cret", ""));
+    }
+}
@@ -0,0 +1,253 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::aggregator::commit_analyzer::{CommitType, EnrichedCommit};
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentRelease, ComponentStatus};
+use crate::config::types::Audience;
+use super::store::find_train;
+use super::AppState;
+
+const DEFAULT_PER_PAGE: usize = 50;
+const MAX_PER_PAGE: usize = 100;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/trains", get(list_trains))
+        .route("/api/trains/:version", get(get_train))
+        .route("/api/trains/:version/components", get(list_components))
+        .route("/api/components/:repo/commits", get(list_commits))
+}
+
+#[derive(Debug, Deserialize)]
+struct Pagination {
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+/// One page of results with enough context to request the next.
+#[derive(Debug, Serialize)]
+struct Paged<T> {
+    items: Vec<T>,
+    page: usize,
+    per_page: usize,
+    total: usize,
+}
+
+impl Pagination {
+    fn apply<T>(&self, items: Vec<T>) -> Paged<T> {
+        let page = self.page.unwrap_or(1).max(1);
+        let per_page = self.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+        let total = items.len();
+        let items = items.into_iter().skip((page - 1).saturating_mul(per_page)).take(per_page).collect();
+        Paged { items, page, per_page, total }
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+    }
+}
+
+fn not_found(what: String) -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, format!("{} not found", what))
+}
+
+#[derive(Debug, Serialize)]
+struct TrainSummary {
+    version: String,
+    date: DateTime<Utc>,
+    total_repos: usize,
+    updated_repos: usize,
+    total_commits: usize,
+    contributors: Vec<String>,
+    sub_trains: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrainFilter {
+    since: Option<DateTime<Utc>>,
+}
+
+async fn list_trains(
+    State(state): State<AppState>,
+    Extension(audience): Extension<Audience>,
+    Query(filter): Query<TrainFilter>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Paged<TrainSummary>>, ApiError> {
+    let trains = state.trains(audience)?;
+    let summaries = trains.iter()
+        .filter(|train| filter.since.map_or(true, |since| train.date >= since))
+        .map(|train| TrainSummary {
+            version: train.version.clone(),
+            date: train.date,
+            total_repos: train.summary.total_repos,
+            updated_repos: train.summary.updated_repos,
+            total_commits: train.summary.total_commits,
+            contributors: train.summary.contributors.clone(),
+            sub_trains: train.sub_trains.iter().map(|t| t.version.clone()).collect(),
+        })
+        .collect();
+    Ok(Json(pagination.apply(summaries)))
+}
+
+async fn get_train(
+    State(state): State<AppState>,
+    Extension(audience): Extension<Audience>,
+    Path(version): Path<String>,
+) -> Result<Json<AggregatedRelease>, ApiError> {
+    let trains = state.trains(audience)?;
+    let train = find_train(&trains, &version).ok_or_else(|| not_found(format!("Train {}", version)))?;
+    Ok(Json(train.clone()))
+}
+
+#[derive(Debug, Deserialize)]
+struct ComponentFilter {
+    /// `released`, `unchanged`, or `not_yet_released`
+    status: Option<String>,
+}
+
+fn status_name(status: &ComponentStatus) -> &'static str {
+    match status {
+        ComponentStatus::Released { .. } => "released",
+        ComponentStatus::NoRelease { .. } => "unchanged",
+        ComponentStatus::NotYetReleased { .. } => "not_yet_released",
+    }
+}
+
+async fn list_components(
+    State(state): State<AppState>,
+    Extension(audience): Extension<Audience>,
+    Path(version): Path<String>,
+    Query(filter): Query<ComponentFilter>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Paged<ComponentRelease>>, ApiError> {
+    let trains = state.trains(audience)?;
+    let train = find_train(&trains, &version).ok_or_else(|| not_found(format!("Train {}", version)))?;
+    let components = train.components.iter()
+        .filter(|c| filter.status.as_deref().map_or(true, |s| status_name(&c.status) == s))
+        .cloned()
+        .collect();
+    Ok(Json(pagination.apply(components)))
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitFilter {
+    /// Commit type keyword such as `feat` or `fix`
+    #[serde(rename = "type")]
+    commit_type: Option<String>,
+    breaking: Option<bool>,
+    /// Only commits shipped in this train
+    train: Option<String>,
+}
+
+/// A commit with the train and component release it shipped in.
+#[derive(Debug, Serialize)]
+struct ShippedCommit {
+    train: String,
+    component_version: String,
+    #[serde(flatten)]
+    commit: EnrichedCommit,
+}
+
+async fn list_commits(
+    State(state): State<AppState>,
+    Extension(audience): Extension<Audience>,
+    Path(repo): Path<String>,
+    Query(filter): Query<CommitFilter>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Paged<ShippedCommit>>, ApiError> {
+    let commit_type = match &filter.commit_type {
+        Some(keyword) => Some(CommitType::from_keyword(keyword).ok_or_else(|| {
+            ApiError(StatusCode::BAD_REQUEST, format!("Unknown commit type: {}", keyword))
+        })?),
+        None => None,
+    };
+
+    let trains = state.trains(audience)?;
+    let mut all_trains = Vec::new();
+    flatten(&trains, &mut all_trains);
+
+    let mut commits = Vec::new();
+    for train in all_trains {
+        if filter.train.as_ref().is_some_and(|version| version != &train.version) {
+            continue;
+        }
+        for component in train.components.iter().filter(|c| c.repository == repo) {
+            let ComponentStatus::Released { current_version, commits: shipped, .. } = &component.status else {
+                continue;
+            };
+            commits.extend(shipped.iter()
+                .filter(|c| commit_type.as_ref().map_or(true, |t| c.commit_type.as_ref() == Some(t)))
+                .filter(|c| filter.breaking.map_or(true, |b| c.breaking == b))
+                .map(|c| ShippedCommit {
+                    train: train.version.clone(),
+                    component_version: current_version.clone(),
+                    commit: c.clone(),
+                }));
+        }
+    }
+    commits.sort_by(|a, b| b.commit.date.cmp(&a.commit.date));
+
+    Ok(Json(pagination.apply(commits)))
+}
+
+fn flatten<'a>(trains: &'a [AggregatedRelease], out: &mut Vec<&'a AggregatedRelease>) {
+    for train in trains {
+        out.push(train);
+        flatten(&train.sub_trains, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pagination(page: Option<usize>, per_page: Option<usize>) -> Pagination {
+        Pagination { page, per_page }
+    }
+
+    #[test]
+    fn pages_through_items() {
+        let paged = pagination(Some(2), Some(3)).apply((1..=8).collect());
+        assert_eq!(paged.items, vec![4, 5, 6]);
+        assert_eq!((paged.page, paged.per_page, paged.total), (2, 3, 8));
+
+        let last = pagination(Some(3), Some(3)).apply((1..=8).collect());
+        assert_eq!(last.items, vec![7, 8]);
+    }
+
+    #[test]
+    fn clamps_page_and_per_page() {
+        let paged = pagination(Some(0), Some(1000)).apply((1..=150).collect::<Vec<_>>());
+        assert_eq!((paged.page, paged.per_page), (1, MAX_PER_PAGE));
+        assert_eq!(paged.items.len(), MAX_PER_PAGE);
+
+        let defaults = pagination(None, Some(0)).apply(vec![1, 2]);
+        assert_eq!((defaults.page, defaults.per_page, defaults.items), (1, 1, vec![1]));
+    }
+
+    #[test]
+    fn out_of_range_page_is_empty() {
+        let past_end = pagination(Some(5), Some(10)).apply((1..=8).collect::<Vec<_>>());
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.total, 8);
+
+        let huge = pagination(Some(usize::MAX), Some(MAX_PER_PAGE)).apply((1..=8).collect::<Vec<_>>());
+        assert!(huge.items.is_empty());
+        assert_eq!(huge.page, usize::MAX);
+    }
+}
@@ -0,0 +1,185 @@
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use crate::aggregator::publisher;
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentStatus};
+use crate::config::types::SlackCommandConfig;
+use crate::github::links::WebLinks;
+use super::commands::{self, Generate, TrainRequest};
+use super::AppState;
+
+/// Requests signed longer ago than this are refused, so captured ones can't be replayed.
+const MAX_REQUEST_AGE_SECS: i64 = 5 * 60;
+
+/// Slack truncates message text beyond this many characters.
+const MAX_MESSAGE_TEXT: usize = 40_000;
+
+/// Answers a Slack slash command such as `/release v1.4 backend`, in the channel it was used in.
+pub struct SlackCommands {
+    signing_secret: String,
+    http: reqwest::Client,
+    generate: Generate,
+}
+
+/// The fields of a slash command invocation that matter here.
+#[derive(Debug, Deserialize)]
+struct SlashCommand {
+    #[serde(default)]
+    command: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    user_name: String,
+    #[serde(default)]
+    response_url: String,
+    /// Set when Slack is only verifying the server's certificate
+    #[serde(default)]
+    ssl_check: Option<String>,
+}
+
+impl SlackCommands {
+    /// Commands generating trains with `generate`; the signing secret is read from `config.signing_secret_env`.
+    pub fn new(config: &SlackCommandConfig, generate: Generate) -> Result<Self> {
+        let signing_secret = std::env::var(&config.signing_secret_env)
+            .with_context(|| format!("serve.slack: signing secret not found in ${}", config.signing_secret_env))?;
+        Ok(Self { signing_secret, http: reqwest::Client::new(), generate })
+    }
+
+    /// Whether `signature` (`X-Slack-Signature`) signs `body` sent at `timestamp`
+    /// (`X-Slack-Request-Timestamp`), and the request is recent.
+    fn verify(&self, body: &[u8], timestamp: &str, signature: &str) -> bool {
+        let Ok(sent) = timestamp.parse::<i64>() else {
+            return false;
+        };
+        if (chrono::Utc::now().timestamp() - sent).abs() > MAX_REQUEST_AGE_SECS {
+            return false;
+        }
+        let Some(signature) = signature.strip_prefix("v0=").and_then(|hex| hex::decode(hex).ok()) else {
+            return false;
+        };
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(self.signing_secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(format!("v0:{}:", timestamp).as_bytes());
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Generate the requested train and post its summary to `response_url`, or the error only
+    /// to the user who asked.
+    async fn run(&self, state: &AppState, command: SlashCommand, request: TrainRequest) {
+        tracing::info!("{} requested release notes for {} from Slack", command.user_name, request.version);
+        let message = match state.generate(&self.generate, request).await {
+            Ok(generated) => json!({
+                "response_type": "in_channel",
+                "text": publisher::truncate_with_notice(
+                    &summary(&generated.release, &generated.links),
+                    MAX_MESSAGE_TEXT,
+                    "\n_…truncated_",
+                ),
+            }),
+            Err(e) => json!({
+                "response_type": "ephemeral",
+                "text": format!("❌ Couldn't generate the release notes: {:#}", e),
+            }),
+        };
+
+        let posted = self.http.post(&command.response_url)
+            .json(&message)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = posted {
+            tracing::warn!("Failed to answer {} in Slack: {}", command.command, e);
+        }
+    }
+}
+
+/// The train as Slack `mrkdwn`: totals, then a line per released component linking to its release.
+pub fn summary(release: &AggregatedRelease, links: &WebLinks) -> String {
+    let mut text = format!(
+        "*Release {}*\n{} of {} components updated · {} commits · {} contributors\n",
+        escape(&release.version),
+        release.summary.updated_repos,
+        release.summary.total_repos,
+        release.summary.total_commits,
+        release.summary.contributors.len()
+    );
+
+    let mut unchanged = Vec::new();
+    for component in &release.components {
+        let ComponentStatus::Released { current_version, previous_version, commits, .. } = &component.status else {
+            unchanged.push(escape(&component.repository));
+            continue;
+        };
+        let repo = links.repo(&component.repository);
+        text.push_str(&format!(
+            "• *{}* <{}|{}>",
+            escape(&component.repository),
+            repo.release(current_version),
+            escape(current_version)
+        ));
+        if let Some(previous) = previous_version {
+            text.push_str(&format!(" (from {})", escape(previous)));
+        }
+        text.push_str(&format!(" · {} commits", commits.len()));
+        let breaking = commits.iter().filter(|c| c.breaking).count();
+        if breaking > 0 {
+            text.push_str(&format!(" · ⚠️ {} breaking", breaking));
+        }
+        text.push('\n');
+    }
+    if !unchanged.is_empty() {
+        text.push_str(&format!("Unchanged: {}\n", unchanged.join(", ")));
+    }
+    text
+}
+
+/// Escape the characters Slack treats as markup in message text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// `POST /slack/commands`: slash command invocations, authenticated by Slack's request signature.
+/// Slack wants an answer within 3 seconds, so this acknowledges the command only to its sender
+/// and posts the summary in the channel once the train is generated.
+pub async fn slash_command(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+    let Some(slack) = state.slack.clone() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    if !slack.verify(&body, header("x-slack-request-timestamp"), header("x-slack-signature")) {
+        return (StatusCode::UNAUTHORIZED, "Invalid request signature").into_response();
+    }
+
+    let command: SlashCommand = match serde_urlencoded::from_bytes(&body) {
+        Ok(command) => command,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Unreadable slash command: {}", e)).into_response(),
+    };
+    if command.ssl_check.is_some() {
+        return StatusCode::OK.into_response();
+    }
+    let Some(request) = commands::train_request(&command.text) else {
+        return Json(json!({
+            "response_type": "ephemeral",
+            "text": format!("Usage: `{} <version> [section]`, e.g. `{} v1.4 backend`", command.command, command.command),
+        }))
+        .into_response();
+    };
+
+    let acknowledgement = match &request.group {
+        Some(group) => format!("⏳ Generating {} for {}…", request.version, group),
+        None => format!("⏳ Generating {}…", request.version),
+    };
+    tokio::spawn(async move {
+        slack.run(&state, command, request).await;
+    });
+    Json(json!({ "response_type": "ephemeral", "text": acknowledgement })).into_response()
+}
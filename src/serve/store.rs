@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use crate::aggregator::release_fetcher::AggregatedRelease;
+
+/// Trains exported with `generate -f json`, read from a directory and reloaded whenever a file
+/// in it is added, removed, or modified.
+pub struct TrainStore {
+    dir: PathBuf,
+    cached: RwLock<Option<(Fingerprint, Arc<Vec<AggregatedRelease>>)>>,
+}
+
+/// Number of JSON files and the newest modification time among them.
+type Fingerprint = (usize, Option<SystemTime>);
+
+impl TrainStore {
+    pub fn new(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            anyhow::bail!("{} is not a directory", dir.display());
+        }
+        Ok(Self { dir: dir.to_path_buf(), cached: RwLock::new(None) })
+    }
+
+    /// All trains, newest first, reloading from disk if the directory changed.
+    pub fn trains(&self) -> Result<Arc<Vec<AggregatedRelease>>> {
+        let files = self.json_files()?;
+        let fingerprint: Fingerprint = (
+            files.len(),
+            files.iter().filter_map(|path| path.metadata().and_then(|m| m.modified()).ok()).max(),
+        );
+
+        if let Some((cached_fingerprint, trains)) = self.cached.read().unwrap().as_ref() {
+            if *cached_fingerprint == fingerprint {
+                return Ok(trains.clone());
+            }
+        }
+
+        let mut trains = Vec::new();
+        for path in &files {
+            // Other JSON (search indexes, evidence bundles) may live alongside the exports
+            match AggregatedRelease::load(path) {
+                Ok(release) => trains.push(release),
+                Err(e) => tracing::debug!("Skipping {}: {:#}", path.display(), e),
+            }
+        }
+        trains.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.version.cmp(&b.version)));
+
+        let trains = Arc::new(trains);
+        *self.cached.write().unwrap() = Some((fingerprint, trains.clone()));
+        Ok(trains)
+    }
+
+    /// Write `release` as `<version>.json`, replacing an earlier export of the same version.
+    pub fn save(&self, release: &AggregatedRelease) -> Result<PathBuf> {
+        let path = self.dir.join(format!("{}.json", release.version.replace(['/', '\\'], "-")));
+        std::fs::write(&path, serde_json::to_string_pretty(release)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    fn json_files(&self) -> Result<Vec<PathBuf>> {
+        let entries = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Find a train by version, searching nested sub-trains too.
+pub fn find_train<'a>(trains: &'a [AggregatedRelease], version: &str) -> Option<&'a AggregatedRelease> {
+    trains.iter().find_map(|train| {
+        if train.version == version {
+            Some(train)
+        } else {
+            find_train(&train.sub_trains, version)
+        }
+    })
+}
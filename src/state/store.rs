@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentStatus, ReleaseAggregator};
+use crate::config::types::NotifyTarget;
+use crate::github::types::CommitComparison;
+use crate::integrations::notify::Announcement;
+use crate::provider::ReleaseProvider;
+
+pub const DEFAULT_STATE_PATH: &str = ".release-inator/state.json";
+
+/// Cached commit ranges no run has used for this long are dropped when the state is saved.
+const RANGE_CACHE_DAYS: i64 = 90;
+
+/// Small JSON-backed record of what previous runs observed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StateStore {
+    #[serde(skip)]
+    path: PathBuf,
+    /// Keyed by `repo@tag`
+    #[serde(default)]
+    pub releases: BTreeMap<String, SeenRelease>,
+    /// Branch heads released by each train, for repositories that deploy from a branch.
+    /// Keyed by repository
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub branches: BTreeMap<String, BranchHistory>,
+    /// Announcements that could not be delivered, oldest first, retried by the next `publish`
+    /// or `notify --flush-queue`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifications: Vec<QueuedNotification>,
+    /// Commits and changed files between two refs, as resolved by earlier runs. Keyed by
+    /// `range_key`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ranges: BTreeMap<String, CachedRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeenRelease {
+    pub repository: String,
+    pub tag: String,
+    pub sha: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_shas: Vec<String>,
+}
+
+/// Trains a branch-deployed repository shipped in, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchHistory {
+    pub branch: String,
+    pub trains: Vec<BranchTrain>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchTrain {
+    /// Train version the branch head was released in
+    pub version: String,
+    pub sha: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl BranchHistory {
+    /// The commit window of train `version`: the head released by the train before it, and the
+    /// head recorded for `version` itself when it was generated before (so regenerating a train
+    /// reproduces it rather than picking up later commits).
+    pub fn window(&self, version: &str) -> (Option<&BranchTrain>, Option<&BranchTrain>) {
+        match self.trains.iter().position(|t| t.version == version) {
+            Some(index) => (index.checked_sub(1).map(|i| &self.trains[i]), Some(&self.trains[index])),
+            None => (self.trains.last(), None),
+        }
+    }
+}
+
+/// A commit range resolved by an earlier run. It only answers for the same refs while both
+/// still point at the commits they did then.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedRange {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub last_used: DateTime<Utc>,
+    pub comparison: CommitComparison,
+}
+
+/// Key of the commit range from `base` to `head` in `repository`.
+pub fn range_key(repository: &str, base: &str, head: &str) -> String {
+    format!("{}:{}...{}", repository, base, head)
+}
+
+/// An announcement waiting to be delivered to one target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedNotification {
+    pub target: NotifyTarget,
+    pub announcement: Announcement,
+    pub queued_at: DateTime<Utc>,
+    pub last_attempt: DateTime<Utc>,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+/// A version that now resolves to different content than when it was first recorded.
+#[derive(Debug, Clone)]
+pub struct TagDrift {
+    pub repository: String,
+    pub tag: String,
+    pub previous_sha: String,
+    pub current_sha: String,
+}
+
+impl std::fmt::Display for TagDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} was re-tagged ({} -> {})",
+            self.repository,
+            self.tag,
+            short_sha(&self.previous_sha),
+            short_sha(&self.current_sha)
+        )
+    }
+}
+
+impl StateStore {
+    /// Open the store at `path`, starting empty if the file does not exist yet.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut store: StateStore = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read state file {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse state file {}", path.display()))?
+        } else {
+            StateStore::default()
+        };
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write state file {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Record the SHA a release tag points at, returning the drift if it changed since last seen.
+    pub fn record_release(&mut self, repository: &str, tag: &str, sha: &str) -> Option<TagDrift> {
+        let now = Utc::now();
+        let key = format!("{}@{}", repository, tag);
+
+        match self.releases.get_mut(&key) {
+            Some(seen) if seen.sha != sha => {
+                let drift = TagDrift {
+                    repository: repository.to_string(),
+                    tag: tag.to_string(),
+                    previous_sha: seen.sha.clone(),
+                    current_sha: sha.to_string(),
+                };
+                seen.previous_shas.push(std::mem::replace(&mut seen.sha, sha.to_string()));
+                seen.last_seen = now;
+                Some(drift)
+            }
+            Some(seen) => {
+                seen.last_seen = now;
+                None
+            }
+            None => {
+                self.releases.insert(key, SeenRelease {
+                    repository: repository.to_string(),
+                    tag: tag.to_string(),
+                    sha: sha.to_string(),
+                    first_seen: now,
+                    last_seen: now,
+                    previous_shas: vec![],
+                });
+                None
+            }
+        }
+    }
+
+    /// Record the SHA each released component of `release` was tagged at, returning the tags
+    /// that moved. With `record_branches`, also record the head each branch-deployed component
+    /// released, so the next train counts commits from there.
+    pub fn record_train(&mut self, release: &AggregatedRelease, record_branches: bool) -> Vec<TagDrift> {
+        let mut drifts = Vec::new();
+        for component in &release.components {
+            if let ComponentStatus::Released { current_version, target_sha: Some(sha), branch, .. } = &component.status {
+                match branch {
+                    Some(branch) if record_branches => {
+                        self.record_branch_release(&component.repository, branch, &release.version, sha);
+                    }
+                    Some(_) => {}
+                    None => drifts.extend(self.record_release(&component.repository, current_version, sha)),
+                }
+            }
+        }
+        drifts
+    }
+
+    /// Record that train `version` released `branch` of `repository` at `sha`. Regenerating a
+    /// train updates its entry instead of adding another.
+    pub fn record_branch_release(&mut self, repository: &str, branch: &str, version: &str, sha: &str) {
+        let history = self.branches.entry(repository.to_string()).or_insert_with(|| BranchHistory {
+            branch: branch.to_string(),
+            trains: Vec::new(),
+        });
+        history.branch = branch.to_string();
+        match history.trains.iter_mut().find(|t| t.version == version) {
+            Some(train) => train.sha = sha.to_string(),
+            None => history.trains.push(BranchTrain {
+                version: version.to_string(),
+                sha: sha.to_string(),
+                recorded_at: Utc::now(),
+            }),
+        }
+    }
+
+    /// Replace the cached commit ranges with `ranges`, dropping any unused for
+    /// `RANGE_CACHE_DAYS`.
+    pub fn record_ranges(&mut self, mut ranges: BTreeMap<String, CachedRange>) {
+        let cutoff = Utc::now() - chrono::Duration::days(RANGE_CACHE_DAYS);
+        ranges.retain(|_, range| range.last_used >= cutoff);
+        self.ranges = ranges;
+    }
+
+    /// Queue a failed delivery of `announcement` to `target`, replacing one of the same
+    /// announcement already waiting for it.
+    pub fn queue_notification(&mut self, target: NotifyTarget, announcement: Announcement, error: &str) {
+        let now = Utc::now();
+        match self.notifications.iter_mut().find(|n| n.target == target && n.announcement.version == announcement.version) {
+            Some(queued) => {
+                queued.announcement = announcement;
+                queued.attempts += 1;
+                queued.last_attempt = now;
+                queued.last_error = error.to_string();
+            }
+            None => self.notifications.push(QueuedNotification {
+                target,
+                announcement,
+                queued_at: now,
+                last_attempt: now,
+                attempts: 1,
+                last_error: error.to_string(),
+            }),
+        }
+    }
+}
+
+/// Remember which SHA each released component's tag pointed at and warn about re-tagged versions.
+pub fn record_release_shas(state_path: &Path, release: &AggregatedRelease, record_branches: bool) -> Result<()> {
+    let mut state = StateStore::open(state_path)?;
+    for drift in state.record_train(release, record_branches) {
+        tracing::warn!("{}", drift);
+        eprintln!("⚠ {}", drift);
+    }
+    state.save()
+}
+
+/// Keep the commit ranges `aggregator` resolved, so the next run needn't compare them again.
+pub fn save_range_cache<P: ReleaseProvider>(state_path: &Path, aggregator: &ReleaseAggregator<P>) -> Result<()> {
+    let mut state = StateStore::open(state_path)?;
+    state.record_ranges(aggregator.range_cache());
+    state.save()
+}
+
+pub fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
@@ -0,0 +1,305 @@
+use chrono::{DateTime, TimeZone, Utc};
+use crate::aggregator::commit_analyzer::{CommitAnalyzer, EnrichedCommit, IssueDetails};
+use crate::aggregator::release_fetcher::{
+    AggregatedRelease, ComponentRelease, ComponentStatus, ReleaseSection, ReleaseStats, ReleaseSummary,
+};
+use crate::github::types::{CommitAuthor, CommitInfo};
+
+/// Date every fixture carries unless told otherwise, so rendered output is stable.
+pub fn fixture_date() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Builds an `EnrichedCommit` from a commit message, parsed the same way a real run parses
+/// conventional commits, so type, scope, issues and PR number come from the message.
+#[derive(Debug, Clone)]
+pub struct CommitBuilder {
+    commit: EnrichedCommit,
+}
+
+impl CommitBuilder {
+    pub fn new(message: &str) -> Self {
+        let info = CommitInfo {
+            sha: fixture_sha(message),
+            message: message.to_string(),
+            author: CommitAuthor {
+                name: "octocat".to_string(),
+                email: "octocat@example.com".to_string(),
+                username: Some("octocat".to_string()),
+            },
+            date: fixture_date(),
+            verified: true,
+        };
+        let commit = CommitAnalyzer::analyze_commits(vec![info]).remove(0);
+        Self { commit }
+    }
+
+    pub fn author(mut self, author: &str) -> Self {
+        self.commit.author = author.to_string();
+        self
+    }
+
+    pub fn date(mut self, date: DateTime<Utc>) -> Self {
+        self.commit.date = date;
+        self
+    }
+
+    pub fn sha(mut self, sha: &str) -> Self {
+        self.commit.sha = sha.to_string();
+        self
+    }
+
+    pub fn pr(mut self, number: u64) -> Self {
+        self.commit.pr_number = Some(number);
+        self
+    }
+
+    pub fn issue(mut self, number: u64) -> Self {
+        self.commit.issues.push(number);
+        self
+    }
+
+    /// Attach a resolved issue, as `--include-issues` would; `state` is `open` or `closed`.
+    pub fn resolved_issue(mut self, number: u64, title: &str, state: &str) -> Self {
+        if !self.commit.issues.contains(&number) {
+            self.commit.issues.push(number);
+        }
+        self.commit.resolved_issues.push(IssueDetails {
+            number,
+            title: title.to_string(),
+            state: state.to_string(),
+            url: format!("https://github.com/acme/repo/issues/{}", number),
+        });
+        self
+    }
+
+    /// List the commit under a section, as a pull request label mapped under `[labels]` would.
+    pub fn section(mut self, title: &str) -> Self {
+        self.commit.section = Some(title.to_string());
+        self
+    }
+
+    pub fn breaking(mut self) -> Self {
+        self.commit.breaking = true;
+        self
+    }
+
+    pub fn unverified(mut self) -> Self {
+        self.commit.verified = false;
+        self
+    }
+
+    pub fn build(self) -> EnrichedCommit {
+        self.commit
+    }
+}
+
+/// Builds a `ComponentRelease` in any of its states. Stats of a released component are
+/// computed from its commits.
+#[derive(Debug, Clone)]
+pub struct ComponentBuilder {
+    repository: String,
+    renamed_from: Option<String>,
+    kind: ComponentKind,
+}
+
+#[derive(Debug, Clone)]
+enum ComponentKind {
+    Released {
+        version: String,
+        previous: Option<String>,
+        date: DateTime<Utc>,
+        notes: Option<String>,
+        commits: Vec<EnrichedCommit>,
+    },
+    Unchanged { latest: Option<String> },
+    NotYetReleased,
+}
+
+impl ComponentBuilder {
+    pub fn released(repository: &str, version: &str) -> Self {
+        Self::with_kind(repository, ComponentKind::Released {
+            version: version.to_string(),
+            previous: None,
+            date: fixture_date(),
+            notes: None,
+            commits: Vec::new(),
+        })
+    }
+
+    /// A component with no release in the train; `latest` is its most recent release, if any.
+    pub fn unchanged(repository: &str, latest: Option<&str>) -> Self {
+        Self::with_kind(repository, ComponentKind::Unchanged { latest: latest.map(str::to_string) })
+    }
+
+    pub fn not_yet_released(repository: &str) -> Self {
+        Self::with_kind(repository, ComponentKind::NotYetReleased)
+    }
+
+    fn with_kind(repository: &str, kind: ComponentKind) -> Self {
+        Self { repository: repository.to_string(), renamed_from: None, kind }
+    }
+
+    pub fn renamed_from(mut self, old_name: &str) -> Self {
+        self.renamed_from = Some(old_name.to_string());
+        self
+    }
+
+    /// Previous version of a released component; ignored for the other states.
+    pub fn previous(mut self, version: &str) -> Self {
+        if let ComponentKind::Released { previous, .. } = &mut self.kind {
+            *previous = Some(version.to_string());
+        }
+        self
+    }
+
+    pub fn date(mut self, release_date: DateTime<Utc>) -> Self {
+        if let ComponentKind::Released { date, .. } = &mut self.kind {
+            *date = release_date;
+        }
+        self
+    }
+
+    pub fn notes(mut self, release_notes: &str) -> Self {
+        if let ComponentKind::Released { notes, .. } = &mut self.kind {
+            *notes = Some(release_notes.to_string());
+        }
+        self
+    }
+
+    /// Add a commit parsed from `message`; see `CommitBuilder` for anything more specific.
+    pub fn commit(self, message: &str) -> Self {
+        self.enriched_commit(CommitBuilder::new(message).build())
+    }
+
+    pub fn enriched_commit(mut self, commit: EnrichedCommit) -> Self {
+        if let ComponentKind::Released { commits, .. } = &mut self.kind {
+            commits.push(commit);
+        }
+        self
+    }
+
+    pub fn commits<I>(self, commits: I) -> Self
+    where
+        I: IntoIterator<Item = EnrichedCommit>,
+    {
+        commits.into_iter().fold(self, |builder, commit| builder.enriched_commit(commit))
+    }
+
+    pub fn build(self) -> ComponentRelease {
+        let status = match self.kind {
+            ComponentKind::Released { version, previous, date, notes, commits } => ComponentStatus::Released {
+                current_version: version,
+                previous_version: previous,
+                release_date: Some(date),
+                created_at: Some(date),
+                published_at: Some(date),
+                target_sha: None,
+                stats: ReleaseStats::from_commits(&commits),
+                commits,
+                release_notes: notes,
+                previous_downloads: None,
+                releases: Vec::new(),
+                branch: None,
+                risk: None,
+                is_latest: None,
+                known_issues: Vec::new(),
+            },
+            ComponentKind::Unchanged { latest } => ComponentStatus::NoRelease {
+                latest_date: latest.as_ref().map(|_| fixture_date()),
+                latest_version: latest,
+            },
+            ComponentKind::NotYetReleased => ComponentStatus::NotYetReleased { empty: false },
+        };
+        ComponentRelease { repository: self.repository, renamed_from: self.renamed_from, status }
+    }
+}
+
+impl From<ComponentBuilder> for ComponentRelease {
+    fn from(builder: ComponentBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builds an `AggregatedRelease` with a summary computed from its components.
+#[derive(Debug, Clone)]
+pub struct ReleaseBuilder {
+    version: String,
+    date: DateTime<Utc>,
+    components: Vec<ComponentRelease>,
+    sections: Vec<ReleaseSection>,
+    skipped_archived: Vec<String>,
+    sub_trains: Vec<AggregatedRelease>,
+}
+
+impl ReleaseBuilder {
+    pub fn new(version: &str) -> Self {
+        Self {
+            version: version.to_string(),
+            date: fixture_date(),
+            components: Vec::new(),
+            sections: Vec::new(),
+            skipped_archived: Vec::new(),
+            sub_trains: Vec::new(),
+        }
+    }
+
+    pub fn date(mut self, date: DateTime<Utc>) -> Self {
+        self.date = date;
+        self
+    }
+
+    pub fn component(mut self, component: impl Into<ComponentRelease>) -> Self {
+        self.components.push(component.into());
+        self
+    }
+
+    pub fn section(mut self, name: &str, repositories: &[&str]) -> Self {
+        self.sections.push(ReleaseSection {
+            name: name.to_string(),
+            repositories: repositories.iter().map(|r| r.to_string()).collect(),
+        });
+        self
+    }
+
+    pub fn skipped_archived(mut self, repository: &str) -> Self {
+        self.skipped_archived.push(repository.to_string());
+        self
+    }
+
+    pub fn sub_train(mut self, train: ReleaseBuilder) -> Self {
+        self.sub_trains.push(train.build());
+        self
+    }
+
+    pub fn build(self) -> AggregatedRelease {
+        let mut release = AggregatedRelease {
+            version: self.version,
+            date: self.date,
+            summary: ReleaseSummary::from_components(&self.components, self.skipped_archived),
+            components: self.components,
+            sections: self.sections,
+            sub_trains: Vec::new(),
+        };
+        for sub_train in self.sub_trains {
+            release.add_sub_train(sub_train);
+        }
+        release
+    }
+}
+
+/// 40-character hex SHA derived from `seed` (FNV-1a, repeated), stable across runs and
+/// toolchains so short SHAs in golden files don't churn.
+pub(crate) fn fixture_sha(seed: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut sha = String::with_capacity(40);
+    while sha.len() < 40 {
+        for byte in seed.bytes().chain(sha.len().to_le_bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        sha.push_str(&format!("{:016x}", hash));
+    }
+    sha.truncate(40);
+    sha
+}
@@ -0,0 +1,58 @@
+use serde::Serialize;
+use std::path::Path;
+
+/// Environment variable that makes `assert_golden` rewrite golden files instead of comparing.
+pub const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+/// Compare `actual` against the golden file at `path`, panicking with the first differing line.
+/// Run with `UPDATE_GOLDEN=1` to write `actual` to `path` instead (creating it if missing).
+/// Line endings are normalized so goldens survive a Windows checkout.
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_ENV).is_some_and(|v| v != "0") {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("Failed to create {}: {}", parent.display(), e));
+        }
+        std::fs::write(path, actual)
+            .unwrap_or_else(|e| panic!("Failed to write golden file {}: {}", path.display(), e));
+        return;
+    }
+
+    let expected = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!("Failed to read golden file {}: {} (run with {}=1 to create it)", path.display(), e, UPDATE_ENV)
+    });
+    let expected = expected.replace("\r\n", "\n");
+    let actual = actual.replace("\r\n", "\n");
+    if expected == actual {
+        return;
+    }
+
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (None, None) => panic!(
+                "Output differs from golden file {} only in its trailing newline (run with {}=1 to update it)",
+                path.display(),
+                UPDATE_ENV,
+            ),
+            (e, a) => panic!(
+                "Output differs from golden file {} at line {}\n  expected: {}\n    actual: {}\n(run with {}=1 to update it)",
+                path.display(),
+                line,
+                e.unwrap_or("<end of file>"),
+                a.unwrap_or("<end of output>"),
+                UPDATE_ENV,
+            ),
+        }
+    }
+}
+
+/// `assert_golden` for a value's pretty-printed JSON, e.g. an `AggregatedRelease`.
+pub fn assert_golden_json<T: Serialize>(path: impl AsRef<Path>, value: &T) {
+    let json = serde_json::to_string_pretty(value).expect("Failed to serialize value for golden comparison");
+    assert_golden(path, &format!("{}\n", json));
+}
@@ -0,0 +1,10 @@
+//! Fixtures for testing code built on this crate (and our own templates) without writing out
+//! the deep release structs by hand. Enabled with the `test-utils` feature.
+pub mod builders;
+pub mod golden;
+pub mod provider;
+pub mod strategies;
+
+pub use builders::{fixture_date, CommitBuilder, ComponentBuilder, ReleaseBuilder};
+pub use golden::assert_golden;
+pub use provider::MockProvider;
@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+use crate::github::host::GitHubHost;
+use crate::github::types::{toggle_v_prefix, CommitAuthor, CommitComparison, CommitInfo, Issue, PullRequest, Release, RepositoryInfo, UserRef};
+use crate::provider::ReleaseProvider;
+use super::builders::{fixture_date, fixture_sha};
+
+/// Branch every mock repository's unreleased commits are on.
+pub const MOCK_DEFAULT_BRANCH: &str = "main";
+
+/// An in-memory `ReleaseProvider`, so `ReleaseAggregator` can run without GitHub. Each
+/// repository is a straight line of history: releases in the order they were added, each
+/// shipping the commits added with it, then any unreleased commits on `main`.
+///
+/// Release `n` of a repository (counting from 0) is dated `n` days after `fixture_date()`,
+/// as are its commits, so the default previous-release lookups find the release added before it.
+#[derive(Debug, Clone, Default)]
+pub struct MockProvider {
+    org: String,
+    repos: BTreeMap<String, MockRepository>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct MockRepository {
+    archived: bool,
+    releases: Vec<MockRelease>,
+    /// Every commit, oldest first
+    commits: Vec<CommitInfo>,
+    pull_requests: Vec<PullRequest>,
+}
+
+#[derive(Debug, Clone)]
+struct MockRelease {
+    tag: String,
+    date: DateTime<Utc>,
+    /// Number of commits in `MockRepository::commits` up to and including this release
+    position: usize,
+}
+
+impl MockProvider {
+    pub fn new(org: &str) -> Self {
+        Self { org: org.to_string(), repos: BTreeMap::new() }
+    }
+
+    /// Release `repo` as `tag`, shipping a commit per message. Messages are parsed like any
+    /// other commit's, so conventional commit types and `(#42)` references apply.
+    pub fn release(mut self, repo: &str, tag: &str, messages: &[&str]) -> Self {
+        let repository = self.repos.entry(repo.to_string()).or_default();
+        let date = fixture_date() + Duration::days(repository.releases.len() as i64);
+        for message in messages {
+            repository.commits.push(mock_commit(repo, tag, message, date));
+        }
+        let position = repository.commits.len();
+        repository.releases.push(MockRelease { tag: tag.to_string(), date, position });
+        self
+    }
+
+    /// Commits on `main` after the latest release, as `--unreleased` reports them.
+    pub fn unreleased(mut self, repo: &str, messages: &[&str]) -> Self {
+        let repository = self.repos.entry(repo.to_string()).or_default();
+        let date = fixture_date() + Duration::days(repository.releases.len() as i64);
+        for message in messages {
+            repository.commits.push(mock_commit(repo, MOCK_DEFAULT_BRANCH, message, date));
+        }
+        self
+    }
+
+    /// A repository with no commits at all.
+    pub fn empty_repository(mut self, repo: &str) -> Self {
+        self.repos.entry(repo.to_string()).or_default();
+        self
+    }
+
+    pub fn archived(mut self, repo: &str) -> Self {
+        self.repos.entry(repo.to_string()).or_default().archived = true;
+        self
+    }
+
+    /// Pull request `number` titled `title`, merged as the commit with `message` in `repo`.
+    ///
+    /// # Panics
+    ///
+    /// If `repo` has no commit with that message yet.
+    pub fn pull_request(mut self, repo: &str, message: &str, number: u64, title: &str) -> Self {
+        let repository = self.repos.entry(repo.to_string()).or_default();
+        let commit = repository.commits.iter()
+            .find(|commit| commit.message == message)
+            .unwrap_or_else(|| panic!("{} has no commit {:?}", repo, message));
+        repository.pull_requests.push(PullRequest {
+            number,
+            title: title.to_string(),
+            body: None,
+            merged_at: Some(commit.date),
+            merge_commit_sha: Some(commit.sha.clone()),
+            updated_at: Some(commit.date),
+            user: commit.author.username.clone().map(|login| UserRef { login }),
+            labels: vec![],
+        });
+        self
+    }
+
+    fn repository(&self, repo: &str) -> Result<&MockRepository> {
+        self.repos.get(repo).with_context(|| format!("MockProvider has no repository {}", repo))
+    }
+
+    fn find_release<'a>(&self, repository: &'a MockRepository, tag: &str) -> Option<&'a MockRelease> {
+        let toggled = toggle_v_prefix(tag);
+        repository.releases.iter().find(|release| release.tag == tag)
+            .or_else(|| repository.releases.iter().find(|release| Some(&release.tag) == toggled.as_ref()))
+    }
+
+    /// How many commits `reference` (a tag, `main`, or a commit SHA) includes.
+    fn position(&self, repository: &MockRepository, reference: &str) -> Option<usize> {
+        if reference == MOCK_DEFAULT_BRANCH {
+            return Some(repository.commits.len());
+        }
+        if let Some(release) = repository.releases.iter().find(|release| release.tag == reference) {
+            return Some(release.position);
+        }
+        repository.commits.iter().position(|commit| commit.sha == reference).map(|i| i + 1)
+    }
+
+    fn to_release(&self, repo: &str, repository: &MockRepository, release: &MockRelease) -> Release {
+        let (owner, name) = repo.split_once('/').unwrap_or((&self.org, repo));
+        let target = match release.position.checked_sub(1) {
+            Some(last) => repository.commits[last].sha.clone(),
+            None => fixture_sha(&format!("{}@{}", repo, release.tag)),
+        };
+        Release::from_tag(&release.tag, Some(release.date), None, target)
+            .with_html_url(GitHubHost::default().tag_url(owner, name, &release.tag))
+    }
+}
+
+fn mock_commit(repo: &str, reference: &str, message: &str, date: DateTime<Utc>) -> CommitInfo {
+    CommitInfo {
+        sha: fixture_sha(&format!("{}@{}:{}", repo, reference, message)),
+        message: message.to_string(),
+        author: CommitAuthor {
+            name: "octocat".to_string(),
+            email: "octocat@example.com".to_string(),
+            username: Some("octocat".to_string()),
+        },
+        date,
+        verified: true,
+    }
+}
+
+impl ReleaseProvider for MockProvider {
+    fn org(&self) -> &str {
+        &self.org
+    }
+
+    async fn list_org_repos(&self) -> Result<Vec<String>> {
+        Ok(self.repos.keys().filter(|repo| !repo.contains('/')).cloned().collect())
+    }
+
+    async fn get_repository(&self, repo: &str) -> Result<Option<RepositoryInfo>> {
+        let Some(repository) = self.repos.get(repo) else {
+            return Ok(None);
+        };
+        let full_name = match repo.split_once('/') {
+            Some(_) => repo.to_string(),
+            None => format!("{}/{}", self.org, repo),
+        };
+        Ok(Some(RepositoryInfo {
+            name: full_name.rsplit('/').next().unwrap_or(repo).to_string(),
+            html_url: format!("https://github.com/{}", full_name),
+            full_name,
+            archived: repository.archived,
+            default_branch: Some(MOCK_DEFAULT_BRANCH.to_string()),
+        }))
+    }
+
+    async fn get_release(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        let Some(repository) = self.repos.get(repo) else {
+            return Ok(None);
+        };
+        Ok(self.find_release(repository, tag).map(|release| self.to_release(repo, repository, release)))
+    }
+
+    async fn get_latest_release(&self, repo: &str) -> Result<Option<Release>> {
+        let Some(repository) = self.repos.get(repo) else {
+            return Ok(None);
+        };
+        Ok(repository.releases.last().map(|release| self.to_release(repo, repository, release)))
+    }
+
+    async fn list_all_releases(&self, repo: &str) -> Result<Vec<Release>> {
+        let Some(repository) = self.repos.get(repo) else {
+            return Ok(vec![]);
+        };
+        Ok(repository.releases.iter().rev().map(|release| self.to_release(repo, repository, release)).collect())
+    }
+
+    async fn resolve_ref_sha(&self, repo: &str, reference: &str) -> Result<Option<String>> {
+        let Some(repository) = self.repos.get(repo) else {
+            return Ok(None);
+        };
+        Ok(self.position(repository, reference)
+            .and_then(|position| position.checked_sub(1))
+            .map(|last| repository.commits[last].sha.clone()))
+    }
+
+    async fn get_changes_between(&self, repo: &str, from: &str, to: &str) -> Result<CommitComparison> {
+        let repository = self.repository(repo)?;
+        let position = |reference: &str| {
+            self.position(repository, reference).with_context(|| format!("{} has no ref {}", repo, reference))
+        };
+        let (from, to) = (position(from)?, position(to)?);
+        let commits: Vec<CommitInfo> = repository.commits[from.min(to)..to].iter().rev().cloned().collect();
+        Ok(CommitComparison {
+            status: if commits.is_empty() { "identical" } else { "ahead" }.to_string(),
+            ahead_by: commits.len() as u64,
+            behind_by: from.saturating_sub(to) as u64,
+            commits,
+            files: vec![],
+        })
+    }
+
+    async fn get_all_commits_until(&self, repo: &str, until: &str) -> Result<Vec<CommitInfo>> {
+        let repository = self.repository(repo)?;
+        let until = self.position(repository, until).with_context(|| format!("{} has no ref {}", repo, until))?;
+        Ok(repository.commits[..until].iter().rev().cloned().collect())
+    }
+
+    /// Mock repositories have no files.
+    async fn get_file_contents(&self, repo: &str, _path: &str, _reference: &str) -> Result<Option<String>> {
+        self.repository(repo)?;
+        Ok(None)
+    }
+
+    async fn list_directory(&self, repo: &str, _path: &str, _reference: &str) -> Result<Vec<String>> {
+        self.repository(repo)?;
+        Ok(vec![])
+    }
+
+    async fn has_commits_before(&self, repo: &str, author: &str, until: DateTime<Utc>) -> Result<bool> {
+        Ok(self.repository(repo)?.commits.iter().any(|commit| {
+            commit.date < until && (commit.author.email == author || commit.author.username.as_deref() == Some(author))
+        }))
+    }
+
+    async fn is_empty_repository(&self, repo: &str) -> Result<bool> {
+        Ok(self.repository(repo)?.commits.is_empty())
+    }
+
+    async fn get_pull_requests_for_commits(&self, repo: &str, shas: Vec<String>) -> Result<Vec<PullRequest>> {
+        Ok(self.repository(repo)?.pull_requests.iter()
+            .filter(|pr| pr.merge_commit_sha.as_ref().is_some_and(|sha| shas.contains(sha)))
+            .cloned()
+            .collect())
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        repo: &str,
+        _base: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PullRequest>> {
+        let mut merged: Vec<PullRequest> = self.repository(repo)?.pull_requests.iter()
+            .filter(|pr| pr.merged_at.is_some_and(|merged_at| since.is_none_or(|since| merged_at > since) && until.is_none_or(|until| merged_at <= until)))
+            .cloned()
+            .collect();
+        merged.sort_by_key(|pr| pr.merged_at);
+        Ok(merged)
+    }
+
+    /// Mock repositories have no issues.
+    async fn list_open_issues(&self, _repo: &str, _label: &str) -> Result<Vec<Issue>> {
+        Ok(vec![])
+    }
+
+    async fn get_issues(&self, _repo: &str, _numbers: Vec<u64>) -> Result<Vec<Issue>> {
+        Ok(vec![])
+    }
+}
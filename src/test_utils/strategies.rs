@@ -0,0 +1,77 @@
+//! Proptest strategies for property-based tests over releases, e.g. "every train renders" or
+//! "redaction never increases the commit count".
+use proptest::prelude::*;
+use crate::aggregator::commit_analyzer::EnrichedCommit;
+use crate::aggregator::release_fetcher::{AggregatedRelease, ComponentRelease};
+use super::builders::{CommitBuilder, ComponentBuilder, ReleaseBuilder};
+
+const TYPES: &[&str] = &["feat", "fix", "docs", "perf", "refactor", "test", "build", "ci", "chore", "style"];
+
+/// Commit message, conventional or not, optionally scoped, breaking, and referencing a PR.
+pub fn arb_message() -> impl Strategy<Value = String> {
+    (
+        prop::option::weighted(0.9, prop::sample::select(TYPES)),
+        prop::option::of("[a-z]{2,8}"),
+        any::<bool>(),
+        "[A-Za-z][A-Za-z0-9 ,.'-]{0,60}",
+        prop::option::of(1u64..10_000),
+    )
+        .prop_map(|(commit_type, scope, breaking, subject, pr)| {
+            let mut message = match commit_type {
+                Some(commit_type) => {
+                    let scope = scope.map(|s| format!("({})", s)).unwrap_or_default();
+                    format!("{}{}{}: {}", commit_type, scope, if breaking { "!" } else { "" }, subject)
+                }
+                None => subject,
+            };
+            if let Some(pr) = pr {
+                message.push_str(&format!(" (#{})", pr));
+            }
+            message
+        })
+}
+
+pub fn arb_commit() -> impl Strategy<Value = EnrichedCommit> {
+    (arb_message(), "[a-z][a-z0-9-]{0,15}")
+        .prop_map(|(message, author)| CommitBuilder::new(&message).author(&author).build())
+}
+
+/// Version string such as `v1.4.2`.
+pub fn arb_version() -> impl Strategy<Value = String> {
+    (0u32..20, 0u32..50, 0u32..100).prop_map(|(major, minor, patch)| format!("v{}.{}.{}", major, minor, patch))
+}
+
+/// Component named `repository` in any of its states.
+pub fn arb_component(repository: String) -> impl Strategy<Value = ComponentRelease> {
+    let (released, unchanged) = (repository.clone(), repository.clone());
+    prop_oneof![
+        3 => (arb_version(), prop::option::of(arb_version()), prop::option::of(".{0,200}"), prop::collection::vec(arb_commit(), 0..20))
+            .prop_map(move |(version, previous, notes, commits)| {
+                let mut builder = ComponentBuilder::released(&released, &version).commits(commits);
+                if let Some(previous) = previous {
+                    builder = builder.previous(&previous);
+                }
+                if let Some(notes) = notes {
+                    builder = builder.notes(&notes);
+                }
+                builder.build()
+            }),
+        1 => prop::option::of(arb_version())
+            .prop_map(move |latest| ComponentBuilder::unchanged(&unchanged, latest.as_deref()).build()),
+        1 => Just(ComponentBuilder::not_yet_released(&repository).build()),
+    ]
+}
+
+/// Train of up to `max_components` components with distinct `org/repo` names.
+pub fn arb_release(max_components: usize) -> impl Strategy<Value = AggregatedRelease> {
+    (arb_version(), prop::collection::btree_set("[a-z]{1,6}/[a-z][a-z-]{0,10}", 0..=max_components))
+        .prop_flat_map(|(version, repositories)| {
+            let components: Vec<_> = repositories.into_iter().map(arb_component).collect();
+            (Just(version), components)
+        })
+        .prop_map(|(version, components)| {
+            components.into_iter()
+                .fold(ReleaseBuilder::new(&version), |builder, component| builder.component(component))
+                .build()
+        })
+}
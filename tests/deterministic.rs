@@ -0,0 +1,56 @@
+//! `--deterministic` output: the same train aggregated twice renders byte-for-byte the same
+//! Markdown and JSON, matching the checked-in golden files.
+use release_aggregator::github::links::WebLinks;
+use release_aggregator::test_utils::{assert_golden, MockProvider};
+use release_aggregator::{AggregatorConfig, ChangelogGenerator, OutputFormat, ReleaseAggregator};
+
+fn golden(name: &str) -> String {
+    format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn provider() -> MockProvider {
+    MockProvider::new("acme")
+        .release("api", "v1.0.0", &["feat: accounts"])
+        .release("api", "v1.1.0", &["feat: invoices (#42)", "fix: rounding closes #7", "docs: billing guide"])
+        .pull_request("api", "fix: rounding closes #7", 43, "Round invoice totals")
+        .release("web", "v2.0.0", &["feat: landing page"])
+        .release("worker", "v0.3.0", &["perf: batch jobs"])
+        .release("worker", "v0.4.0", &["feat!: new queue format", "chore: bump deps"])
+        .empty_repository("docs")
+}
+
+/// Markdown and JSON of the `v1.1.0` train, aggregated and rendered from scratch.
+async fn render() -> (String, String) {
+    let config = AggregatorConfig { include_prs: true, ..AggregatorConfig::default() };
+    let aggregator = ReleaseAggregator::new(provider(), config);
+    let repos = ["api", "web", "worker", "docs"].map(String::from).to_vec();
+    let mut release = aggregator.aggregate("v1.1.0", repos).await.unwrap();
+    release.pin(true, None);
+
+    let links = WebLinks::new("https://github.com", "acme");
+    let markdown = ChangelogGenerator::new(OutputFormat::Markdown, None).unwrap()
+        .with_links(links.clone())
+        .generate(&release)
+        .unwrap();
+    let json = ChangelogGenerator::new(OutputFormat::Json, None).unwrap()
+        .with_links(links)
+        .generate(&release)
+        .unwrap();
+    (markdown, json)
+}
+
+#[tokio::test]
+async fn deterministic_markdown_is_stable() {
+    let (first, _) = render().await;
+    let (second, _) = render().await;
+    assert_eq!(first, second);
+    assert_golden(golden("deterministic.md"), &first);
+}
+
+#[tokio::test]
+async fn deterministic_json_is_stable() {
+    let (_, first) = render().await;
+    let (_, second) = render().await;
+    assert_eq!(first, second);
+    assert_golden(golden("deterministic.json"), &first);
+}
@@ -0,0 +1,70 @@
+//! The `test-utils` fixtures as a downstream crate uses them: a `ReleaseBuilder` train rendered
+//! against golden files, and the proptest strategies fed through every output format.
+use proptest::prelude::*;
+use release_aggregator::aggregator::changelog_generator::{ChangelogGenerator, OutputFormat};
+use release_aggregator::aggregator::release_fetcher::AggregatedRelease;
+use release_aggregator::test_utils::golden::assert_golden_json;
+use release_aggregator::test_utils::strategies::{arb_message, arb_release};
+use release_aggregator::test_utils::{assert_golden, CommitBuilder, ComponentBuilder, ReleaseBuilder};
+
+fn golden(name: &str) -> String {
+    format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn train() -> AggregatedRelease {
+    ReleaseBuilder::new("v2024.06")
+        .component(ComponentBuilder::released("acme/api", "v1.4.0")
+            .previous("v1.3.2")
+            .commit("feat(auth): add SSO login (#42)")
+            .commit("fix: reject expired sessions")
+            .enriched_commit(CommitBuilder::new("fix!: drop legacy tokens").author("bob").issue(17).build()))
+        .component(ComponentBuilder::released("acme/worker", "v0.8.0")
+            .notes("Queue priorities land in this release.")
+            .commit("perf(queue): batch acknowledgements"))
+        .component(ComponentBuilder::unchanged("acme/web", Some("v0.9.1")))
+        .component(ComponentBuilder::not_yet_released("acme/cli"))
+        .skipped_archived("acme/legacy")
+        .build()
+}
+
+#[test]
+fn builder_train_matches_golden_markdown() {
+    let markdown = ChangelogGenerator::new(OutputFormat::Markdown, None).unwrap().generate(&train()).unwrap();
+    assert_golden(golden("builder_train.md"), &markdown);
+}
+
+#[test]
+fn builder_train_matches_golden_json() {
+    assert_golden_json(golden("builder_train.json"), &train());
+}
+
+proptest! {
+    // Rendering is slow in debug builds; a few dozen trains cover every component state.
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn every_train_renders(release in arb_release(6)) {
+        for format in [OutputFormat::Markdown, OutputFormat::Json, OutputFormat::Html] {
+            ChangelogGenerator::new(format, None).unwrap().generate(&release).unwrap();
+        }
+    }
+
+    #[test]
+    fn json_output_round_trips(release in arb_release(6)) {
+        let json = ChangelogGenerator::new(OutputFormat::Json, None).unwrap().generate(&release).unwrap();
+        let parsed: AggregatedRelease = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&release).unwrap());
+    }
+
+    #[test]
+    fn generated_messages_parse_their_markers(message in arb_message()) {
+        let commit = CommitBuilder::new(&message).build();
+        if message.contains("!:") {
+            prop_assert!(commit.breaking);
+        }
+        let pr = message.strip_suffix(')').and_then(|m| m.rsplit_once(" (#")).and_then(|(_, n)| n.parse::<u64>().ok());
+        if pr.is_some() {
+            prop_assert_eq!(commit.pr_number, pr);
+        }
+    }
+}